@@ -0,0 +1,54 @@
+//! Compares the allocating `normalize_from_value` path against the
+//! borrowing `normalize_ingest_event_ref` path on a realistic full event
+//! payload — see the doc comment on `NormalizedEventRef` in
+//! `contracts::validation` for what each path allocates.
+//!
+//! Requires a `criterion` dev-dependency and a
+//! `[[bench]] name = "validation_benchmark" harness = false` entry in
+//! Cargo.toml.
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use serde_json::json;
+
+use agentmonitor_rs::contracts::validation::{
+    IngestValidation, normalize_from_value, normalize_ingest_event_ref,
+};
+
+fn full_event_payload() -> serde_json::Value {
+    json!({
+        "event_id": "evt-123",
+        "session_id": "sess-1",
+        "agent_type": "claude_code",
+        "event_type": "tool_use",
+        "tool_name": "Read",
+        "status": "success",
+        "tokens_in": 100,
+        "tokens_out": 200,
+        "branch": "main",
+        "project": "myapp",
+        "duration_ms": 500,
+        "model": "claude-sonnet-4-5-20250514",
+        "cost_usd": 0.05,
+        "cache_read_tokens": 10,
+        "cache_write_tokens": 5,
+        "client_timestamp": "2026-02-24T12:00:00Z",
+        "source": "hook",
+        "metadata": {"command": "cat foo.txt", "args": ["foo.txt"]}
+    })
+}
+
+fn bench_normalize(c: &mut Criterion) {
+    let validation = IngestValidation::default();
+    let payload = full_event_payload();
+
+    c.bench_function("normalize_from_value (owned)", |b| {
+        b.iter(|| normalize_from_value(black_box(payload.clone()), black_box(&validation)))
+    });
+
+    c.bench_function("normalize_ingest_event_ref (borrowed)", |b| {
+        b.iter(|| normalize_ingest_event_ref(black_box(&payload), black_box(&validation)))
+    });
+}
+
+criterion_group!(benches, bench_normalize);
+criterion_main!(benches);
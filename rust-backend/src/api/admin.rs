@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use axum::Extension;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::auth::{KeyScope, TenantId, hash_key};
+use crate::db::queries;
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct CreateKeyRequest {
+    key: String,
+    label: Option<String>,
+    scope: String,
+}
+
+#[derive(Serialize)]
+struct CreateKeyResponse {
+    id: i64,
+    label: Option<String>,
+    scope: String,
+    created_at: String,
+    tenant_id: String,
+}
+
+#[derive(Serialize)]
+struct AdminErrorResponse {
+    error: &'static str,
+}
+
+/// POST /api/admin/keys — register a new API key. The caller picks the raw
+/// key value (e.g. a generated UUID); only its hash is stored. The new key
+/// always authenticates as the caller's own tenant (from its
+/// `Extension<TenantId>`, set by `auth::require_admin`) — there is no
+/// client-supplied `tenant_id` to mint a key for some other tenant with.
+pub async fn create_key_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
+    Json(body): Json<CreateKeyRequest>,
+) -> impl IntoResponse {
+    let Some(_) = KeyScope::parse(&body.scope) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(AdminErrorResponse { error: "scope must be \"read\", \"write\", or \"admin\"" }),
+        )
+            .into_response();
+    };
+
+    let hash = hash_key(&body.key);
+    let db = match state.write_conn_blocking().await {
+        Ok(db) => db,
+        Err(e) => {
+            warn!("failed to check out a database connection: {e}");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(AdminErrorResponse { error: "internal server error" }),
+            )
+                .into_response();
+        }
+    };
+    match queries::create_api_key(&db, &hash, body.label.as_deref(), &body.scope, &tenant.0) {
+        Ok(row) => {
+            if let Err(e) = state.auth.reload(&db).await {
+                warn!("failed to reload auth store after key creation: {e}");
+            }
+            (
+                StatusCode::CREATED,
+                Json(CreateKeyResponse {
+                    id: row.id,
+                    label: row.label,
+                    scope: row.scope,
+                    created_at: row.created_at,
+                    tenant_id: row.tenant_id,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            warn!("create_api_key error: {e}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(AdminErrorResponse { error: "internal server error" }),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// DELETE /api/admin/keys/{id} — revoke a key so it can no longer
+/// authenticate. Scoped to the caller's own tenant (from its
+/// `Extension<TenantId>`), so one tenant's admin key can only ever revoke
+/// that same tenant's keys — never another tenant's, and never the whole
+/// deployment's.
+pub async fn revoke_key_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let db = match state.write_conn_blocking().await {
+        Ok(db) => db,
+        Err(e) => {
+            warn!("failed to check out a database connection: {e}");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(AdminErrorResponse { error: "internal server error" }),
+            )
+                .into_response();
+        }
+    };
+    match queries::revoke_api_key(&db, id, &tenant.0) {
+        Ok(true) => {
+            if let Err(e) = state.auth.reload(&db).await {
+                warn!("failed to reload auth store after key revocation: {e}");
+            }
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(AdminErrorResponse { error: "key not found or already revoked" }),
+        )
+            .into_response(),
+        Err(e) => {
+            warn!("revoke_api_key error: {e}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(AdminErrorResponse { error: "internal server error" }),
+            )
+                .into_response()
+        }
+    }
+}
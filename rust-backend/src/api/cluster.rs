@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::Serialize;
+
+use crate::cluster::ClusterMessage;
+use crate::state::AppState;
+
+#[derive(Serialize)]
+struct ClusterBroadcastResponse {
+    /// `false` when this message had already been seen (forwarded earlier
+    /// by this node, or received once already) and was therefore dropped
+    /// rather than re-fanned into `sse_hub` — see `cluster::Broadcasting::receive`.
+    accepted: bool,
+}
+
+/// POST /api/cluster/broadcast — entry point for a peer node's
+/// `cluster::Broadcasting::forward_local`/`receive` re-forward. Requires a
+/// write-scoped key like every other ingest route.
+pub async fn cluster_broadcast_handler(
+    State(state): State<Arc<AppState>>,
+    Json(message): Json<ClusterMessage>,
+) -> impl IntoResponse {
+    let accepted = state.cluster.receive(&state, message).await;
+    (StatusCode::OK, Json(ClusterBroadcastResponse { accepted }))
+}
@@ -1,18 +1,108 @@
 use std::sync::Arc;
 
+use axum::Extension;
 use axum::Json;
-use axum::extract::State;
+use axum::extract::{Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use serde::Serialize;
 use serde_json::Value;
 use tracing::warn;
 
-use crate::contracts::validation::normalize_from_value;
-use crate::contracts::event::{NormalizeResult, ValidationError};
+use crate::auth::TenantId;
+use crate::contracts::validation::{NormalizedEventRef, normalize_ingest_event_ref};
+use crate::contracts::event::{NormalizedEvent, ValidationError};
+use crate::db;
 use crate::db::queries::{self, InsertEventParams};
+use crate::ingest_metrics::RejectReason;
 use crate::state::AppState;
-use crate::util::truncate::truncate_metadata;
+use crate::util::truncate::{MetadataStorage, store_or_truncate_metadata};
+
+/// Build the `insert_event` params for a validated event, given the
+/// already-resolved metadata column value (inline, truncated, compressed,
+/// or a `MetadataStorage::Chunked` marker — see `util::truncate`), whether
+/// it should be flagged `payload_truncated`, and the tenant it was ingested
+/// under (from the caller's `TenantId`, never the client payload — see the
+/// `source` field just below for the precedent). Used by the WebSocket
+/// ingest path; the single and batch paths use `insert_params_ref` below,
+/// which maps the same fields off a `NormalizedEventRef`.
+pub(crate) fn insert_params<'a>(
+    event: &'a NormalizedEvent,
+    metadata: &'a str,
+    payload_truncated: bool,
+    tenant_id: &'a str,
+) -> InsertEventParams<'a> {
+    InsertEventParams {
+        event_id: event.event_id.as_deref(),
+        session_id: &event.session_id,
+        agent_type: &event.agent_type,
+        event_type: &event.event_type,
+        tool_name: event.tool_name.as_deref(),
+        status: &event.status,
+        tokens_in: event.tokens_in,
+        tokens_out: event.tokens_out,
+        branch: event.branch.as_deref(),
+        project: event.project.as_deref(),
+        duration_ms: event.duration_ms,
+        client_timestamp: event.client_timestamp.as_deref(),
+        metadata,
+        payload_truncated,
+        model: event.model.as_deref(),
+        cost_usd: event.cost_usd,
+        cache_read_tokens: event.cache_read_tokens,
+        cache_write_tokens: event.cache_write_tokens,
+        source: event.source.as_deref().unwrap_or("api"),
+        tenant_id,
+    }
+}
+
+/// Ref-borrowing counterpart to `insert_params`, for the high-throughput
+/// path (`normalize_ingest_event_ref`/`NormalizedEventRef`) — reads straight
+/// off the `Cow`s instead of requiring a fully-materialized `NormalizedEvent`
+/// first, so a payload whose string fields didn't need normalizing never
+/// allocates just to reach the DB layer.
+pub(crate) fn insert_params_ref<'a>(
+    event: &'a NormalizedEventRef<'a>,
+    metadata: &'a str,
+    payload_truncated: bool,
+    tenant_id: &'a str,
+) -> InsertEventParams<'a> {
+    InsertEventParams {
+        event_id: event.event_id.as_deref(),
+        session_id: event.session_id.as_ref(),
+        agent_type: event.agent_type.as_ref(),
+        event_type: event.event_type.as_ref(),
+        tool_name: event.tool_name.as_deref(),
+        status: event.status.as_ref(),
+        tokens_in: event.tokens_in,
+        tokens_out: event.tokens_out,
+        branch: event.branch.as_deref(),
+        project: event.project.as_deref(),
+        duration_ms: event.duration_ms,
+        client_timestamp: event.client_timestamp.as_deref(),
+        metadata,
+        payload_truncated,
+        model: event.model.as_deref(),
+        cost_usd: event.cost_usd,
+        cache_read_tokens: event.cache_read_tokens,
+        cache_write_tokens: event.cache_write_tokens,
+        source: event.source.as_deref().unwrap_or("api"),
+        tenant_id,
+    }
+}
+
+/// Republish an accepted event to NATS in the background, so a slow or
+/// unreachable broker never adds latency to the ingest response. A no-op
+/// when NATS isn't configured (`state.nats_client()` is `None`).
+fn spawn_nats_publish(state: &Arc<AppState>, event: NormalizedEvent) {
+    let state = Arc::clone(state);
+    tokio::spawn(async move {
+        if let Some(client) = state.nats_client().await {
+            crate::nats::publish_event(&client, &state.config.nats_publish_subject_prefix, &event)
+                .await;
+        }
+    });
+}
 
 // --- Response types ---
 
@@ -35,12 +125,33 @@ struct BatchRejection {
     errors: Vec<String>,
 }
 
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum BatchItemOutcome {
+    Inserted { id: i64, event_id: Option<String> },
+    Deduplicated { event_id: Option<String> },
+    Error { event_id: Option<String>, message: String },
+}
+
+#[derive(Serialize)]
+struct BatchItemResult {
+    index: usize,
+    #[serde(flatten)]
+    outcome: BatchItemOutcome,
+}
+
 #[derive(Serialize)]
 struct BatchResponse {
+    // Aggregate counts, kept for existing callers.
     received: usize,
     ids: Vec<i64>,
     duplicates: usize,
     rejected: Vec<BatchRejection>,
+    // Per-item outcomes so a client can retry only what actually failed.
+    results: Vec<BatchItemResult>,
+    inserted_count: usize,
+    deduplicated_count: usize,
+    error_count: usize,
 }
 
 #[derive(Serialize)]
@@ -48,17 +159,55 @@ struct BatchFormatError {
     error: &'static str,
 }
 
+#[derive(Serialize)]
+struct BatchTooLargeError {
+    error: String,
+    max_batch_size: usize,
+    received: usize,
+}
+
+#[derive(serde::Deserialize)]
+pub struct BatchIngestQuery {
+    atomic: Option<bool>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct PollQuery {
+    since: i64,
+    timeout_ms: Option<u64>,
+}
+
+/// Long-poll requests wait at most this long, regardless of what the client
+/// asks for — long enough to avoid most clients' busy-polling, short enough
+/// that a load balancer or reverse proxy timeout doesn't cut the response
+/// off first.
+const POLL_MAX_TIMEOUT_MS: u64 = 60_000;
+const POLL_DEFAULT_TIMEOUT_MS: u64 = 30_000;
+/// Bounds how many rows one poll response can carry, so a client that's
+/// fallen far behind `since` gets a bounded page instead of the whole
+/// backlog — see `queries::events_since`.
+const POLL_MAX_EVENTS: usize = 500;
+
+#[derive(Serialize)]
+struct PollResponse {
+    events: Vec<queries::EventRow>,
+    next_cursor: i64,
+}
+
 // --- Handlers ---
 
 /// POST /api/events — single event ingest.
 pub async fn ingest_single(
     State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
     Json(body): Json<Value>,
 ) -> impl IntoResponse {
-    let result = normalize_from_value(body);
+    let parsed = normalize_ingest_event_ref(&body, &state.config.ingest_validation);
+    state.ingest_counters.record_received();
 
-    match result {
-        NormalizeResult::Err { errors } => {
+    match parsed {
+        Err(errors) => {
+            state.ingest_counters.record_rejected(RejectReason::Validation);
             (StatusCode::BAD_REQUEST, Json(Value::from(serde_json::to_value(
                 IngestErrorResponse {
                     error: "Invalid event payload",
@@ -66,48 +215,55 @@ pub async fn ingest_single(
                 },
             ).unwrap()))).into_response()
         }
-        NormalizeResult::Ok { event } => {
+        Ok(event) => {
             let max_kb = state.config.max_payload_kb;
-            let truncated = truncate_metadata(&event.metadata, max_kb);
-
-            let params = InsertEventParams {
-                event_id: event.event_id.as_deref(),
-                session_id: &event.session_id,
-                agent_type: &event.agent_type,
-                event_type: &event.event_type,
-                tool_name: event.tool_name.as_deref(),
-                status: &event.status,
-                tokens_in: event.tokens_in,
-                tokens_out: event.tokens_out,
-                branch: event.branch.as_deref(),
-                project: event.project.as_deref(),
-                duration_ms: event.duration_ms,
-                client_timestamp: event.client_timestamp.as_deref(),
-                metadata: &truncated.value,
-                payload_truncated: truncated.truncated,
-                model: event.model.as_deref(),
-                cost_usd: event.cost_usd,
-                cache_read_tokens: event.cache_read_tokens,
-                cache_write_tokens: event.cache_write_tokens,
-                source: event.source.as_deref().unwrap_or("api"),
-            };
+            let empty_metadata = Value::Object(serde_json::Map::new());
+            let metadata_value = event.metadata.unwrap_or(&empty_metadata);
+            let metadata_storage =
+                store_or_truncate_metadata(metadata_value, max_kb, state.config.store_large_payloads);
+            if metadata_storage.payload_truncated() {
+                state.ingest_counters.record_truncated();
+            }
+            let params = insert_params_ref(
+                &event,
+                metadata_storage.column_value(),
+                metadata_storage.payload_truncated(),
+                &tenant.0,
+            );
 
-            let db = state.db.lock().await;
+            let db = match state.write_conn_blocking().await {
+                Ok(db) => db,
+                Err(e) => {
+                    warn!("failed to check out a database connection: {e}");
+                    return (StatusCode::INTERNAL_SERVER_ERROR, Json(Value::from(
+                        serde_json::json!({"error": "internal server error"}),
+                    ))).into_response();
+                }
+            };
             match queries::insert_event(&db, &params) {
                 Ok(Some(row)) => {
-                    // TODO: broadcast "event" + "session_update" to SSE hub (Task 6)
+                    if let MetadataStorage::Chunked(chunks) = &metadata_storage {
+                        if let Err(e) = queries::persist_chunked_metadata(&db, row.id, chunks) {
+                            warn!("failed to persist chunked payload for event {}: {e}", row.id);
+                        }
+                    }
+                    let row_value = serde_json::to_value(&row).unwrap_or_else(|_| Value::from(serde_json::json!({})));
+                    state.sse_hub.broadcast("event", &row_value);
+                    state.notify_new_events.notify_waiters();
+                    spawn_nats_publish(&state, event.into_owned());
                     (StatusCode::CREATED, Json(Value::from(serde_json::to_value(
                         IngestResponse { received: 1, ids: vec![row.id], duplicates: 0 },
                     ).unwrap()))).into_response()
                 }
                 Ok(None) => {
-                    // Deduplicated
+                    state.ingest_counters.record_duplicate();
                     (StatusCode::OK, Json(Value::from(serde_json::to_value(
                         IngestResponse { received: 0, ids: vec![], duplicates: 1 },
                     ).unwrap()))).into_response()
                 }
                 Err(e) => {
                     warn!("insert_event error: {e}");
+                    state.ingest_counters.record_rejected(RejectReason::InternalError);
                     (StatusCode::INTERNAL_SERVER_ERROR, Json(Value::from(
                         serde_json::json!({"error": "internal server error"}),
                     ))).into_response()
@@ -117,32 +273,93 @@ pub async fn ingest_single(
     }
 }
 
-/// POST /api/events/batch — batch event ingest.
+/// POST /api/events/batch — batch event ingest. The body is either a bare
+/// JSON array of event payloads or `{ "events": [...] }`; every item is
+/// inserted in a single transaction and the response reports a per-item
+/// outcome (`inserted`/`deduplicated`/`error`) alongside the existing
+/// aggregate counts. Batches larger than `Config::max_batch_size` are
+/// rejected outright; a successful batch emits one aggregated
+/// `session_update` broadcast rather than one per inserted row.
+///
+/// By default one item failing doesn't stop the rest from being committed.
+/// Set `"atomic": true` in the body, or pass `?atomic=true` as a query
+/// param, to roll the whole transaction back (and report every item,
+/// including ones that inserted cleanly, as an error) if any item fails.
 pub async fn ingest_batch(
     State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
+    Query(query): Query<BatchIngestQuery>,
     Json(body): Json<Value>,
 ) -> impl IntoResponse {
-    let events_array = match body.get("events").and_then(|v| v.as_array()) {
-        Some(arr) => arr.clone(),
+    let events_array = match body.as_array().cloned().or_else(|| {
+        body.get("events").and_then(|v| v.as_array()).cloned()
+    }) {
+        Some(arr) => arr,
         None => {
             return (StatusCode::BAD_REQUEST, Json(Value::from(serde_json::to_value(
                 BatchFormatError { error: "Expected { events: [...] }" },
             ).unwrap()))).into_response();
         }
     };
+    let atomic = query.atomic.unwrap_or(false)
+        || body.get("atomic").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    let max_batch_size = state.config.max_batch_size;
+    if events_array.len() > max_batch_size {
+        state.ingest_counters.record_rejected(RejectReason::BatchTooLarge);
+        return (StatusCode::PAYLOAD_TOO_LARGE, Json(Value::from(serde_json::to_value(
+            BatchTooLargeError {
+                error: format!("batch exceeds the maximum of {max_batch_size} events"),
+                max_batch_size,
+                received: events_array.len(),
+            },
+        ).unwrap()))).into_response();
+    }
 
     let max_kb = state.config.max_payload_kb;
     let mut ids: Vec<i64> = Vec::new();
     let mut duplicates: usize = 0;
     let mut rejected: Vec<BatchRejection> = Vec::new();
+    let events_array_len = events_array.len();
+    let mut results: Vec<BatchItemResult> = Vec::with_capacity(events_array_len);
+    let mut inserted_count: usize = 0;
+    let mut deduplicated_count: usize = 0;
+    let mut error_count: usize = 0;
+    let mut inserted_events: Vec<NormalizedEvent> = Vec::new();
 
-    let db = state.db.lock().await;
+    let mut db = match state.write_conn_blocking().await {
+        Ok(db) => db,
+        Err(e) => {
+            warn!("failed to check out a database connection: {e}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(Value::from(
+                serde_json::json!({"error": "internal server error"}),
+            ))).into_response();
+        }
+    };
+    let tx = match db.transaction() {
+        Ok(tx) => tx,
+        Err(e) => {
+            warn!("failed to start batch transaction: {e}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(Value::from(
+                serde_json::json!({"error": "internal server error"}),
+            ))).into_response();
+        }
+    };
 
     for (i, item) in events_array.into_iter().enumerate() {
-        let result = normalize_from_value(item);
+        state.ingest_counters.record_received();
+        let parsed = normalize_ingest_event_ref(&item, &state.config.ingest_validation);
 
-        match result {
-            NormalizeResult::Err { errors } => {
+        match parsed {
+            Err(errors) => {
+                error_count += 1;
+                state.ingest_counters.record_rejected(RejectReason::Validation);
+                let event_id = None;
+                let message = errors
+                    .iter()
+                    .map(|e| format!("{}: {}", e.field, e.message))
+                    .collect::<Vec<_>>()
+                    .join("; ");
                 rejected.push(BatchRejection {
                     index: i,
                     errors: errors
@@ -150,53 +367,220 @@ pub async fn ingest_batch(
                         .map(|e| format!("{}: {}", e.field, e.message))
                         .collect(),
                 });
+                results.push(BatchItemResult {
+                    index: i,
+                    outcome: BatchItemOutcome::Error { event_id, message },
+                });
             }
-            NormalizeResult::Ok { event } => {
-                let truncated = truncate_metadata(&event.metadata, max_kb);
-
-                let params = InsertEventParams {
-                    event_id: event.event_id.as_deref(),
-                    session_id: &event.session_id,
-                    agent_type: &event.agent_type,
-                    event_type: &event.event_type,
-                    tool_name: event.tool_name.as_deref(),
-                    status: &event.status,
-                    tokens_in: event.tokens_in,
-                    tokens_out: event.tokens_out,
-                    branch: event.branch.as_deref(),
-                    project: event.project.as_deref(),
-                    duration_ms: event.duration_ms,
-                    client_timestamp: event.client_timestamp.as_deref(),
-                    metadata: &truncated.value,
-                    payload_truncated: truncated.truncated,
-                    model: event.model.as_deref(),
-                    cost_usd: event.cost_usd,
-                    cache_read_tokens: event.cache_read_tokens,
-                    cache_write_tokens: event.cache_write_tokens,
-                    source: event.source.as_deref().unwrap_or("api"),
-                };
+            Ok(event) => {
+                let empty_metadata = Value::Object(serde_json::Map::new());
+                let metadata_value = event.metadata.unwrap_or(&empty_metadata);
+                let metadata_storage =
+                    store_or_truncate_metadata(metadata_value, max_kb, state.config.store_large_payloads);
+                if metadata_storage.payload_truncated() {
+                    state.ingest_counters.record_truncated();
+                }
+                let event_id = event.event_id.as_deref().map(str::to_string);
+                let params = insert_params_ref(
+                    &event,
+                    metadata_storage.column_value(),
+                    metadata_storage.payload_truncated(),
+                    &tenant.0,
+                );
 
-                match queries::insert_event(&db, &params) {
+                match queries::insert_event(&tx, &params) {
                     Ok(Some(row)) => {
-                        // TODO: broadcast "event" to SSE hub (Task 6)
+                        if let MetadataStorage::Chunked(chunks) = &metadata_storage {
+                            if let Err(e) = queries::persist_chunked_metadata(&tx, row.id, chunks) {
+                                warn!("failed to persist chunked payload for event {}: {e}", row.id);
+                            }
+                        }
                         ids.push(row.id);
+                        inserted_count += 1;
+                        inserted_events.push(event.into_owned());
+                        results.push(BatchItemResult {
+                            index: i,
+                            outcome: BatchItemOutcome::Inserted { id: row.id, event_id },
+                        });
                     }
                     Ok(None) => {
                         duplicates += 1;
+                        deduplicated_count += 1;
+                        state.ingest_counters.record_duplicate();
+                        results.push(BatchItemResult {
+                            index: i,
+                            outcome: BatchItemOutcome::Deduplicated { event_id },
+                        });
                     }
                     Err(e) => {
                         warn!("batch insert_event error at index {i}: {e}");
+                        error_count += 1;
+                        state.ingest_counters.record_rejected(RejectReason::InternalError);
                         rejected.push(BatchRejection {
                             index: i,
                             errors: vec!["internal server error".into()],
                         });
+                        results.push(BatchItemResult {
+                            index: i,
+                            outcome: BatchItemOutcome::Error {
+                                event_id,
+                                message: "internal server error".into(),
+                            },
+                        });
                     }
                 }
             }
         }
     }
 
+    if atomic && error_count > 0 {
+        if let Err(e) = tx.rollback() {
+            warn!("failed to roll back atomic batch transaction: {e}");
+        }
+
+        // Nothing the transaction touched actually landed, so every item
+        // that looked inserted/deduplicated above is really an error now.
+        for result in &mut results {
+            if !matches!(result.outcome, BatchItemOutcome::Error { .. }) {
+                result.outcome = BatchItemOutcome::Error {
+                    event_id: None,
+                    message: "rolled back: batch is atomic and another item failed".into(),
+                };
+            }
+        }
+
+        return (StatusCode::OK, Json(Value::from(serde_json::to_value(
+            BatchResponse {
+                received: 0,
+                ids: Vec::new(),
+                duplicates: 0,
+                rejected,
+                results,
+                inserted_count: 0,
+                deduplicated_count: 0,
+                error_count: events_array_len,
+            },
+        ).unwrap()))).into_response();
+    }
+
+    if let Err(e) = tx.commit() {
+        warn!("failed to commit batch transaction: {e}");
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(Value::from(
+            serde_json::json!({"error": "internal server error"}),
+        ))).into_response();
+    }
+
+    // Roll up session_stats for the sessions this batch just touched, so a
+    // client that re-fetches the session list right after a batch import
+    // sees fresh counts instead of waiting on the periodic rollup task.
+    if inserted_count > 0 {
+        if let Err(e) = db::rollup::rollup_session_stats(&db) {
+            warn!("session stats rollup after batch insert failed: {e}");
+        }
+        state.notify_new_events.notify_waiters();
+    }
+
+    // One aggregated broadcast for the whole batch rather than one per row —
+    // batches can be hundreds of events and clients only care that the feed
+    // changed, not about replaying every insert individually.
+    if inserted_count > 0 && state.sse_hub.client_count() > 0 {
+        state.sse_hub.broadcast(
+            "session_update",
+            &serde_json::json!({
+                "type": "batch_import",
+                "imported": inserted_count,
+                "tenant_id": tenant.0,
+            }),
+        );
+    }
+
+    // Republish to NATS only once the transaction has actually committed,
+    // so a rolled-back batch never gets federated.
+    for event in inserted_events {
+        spawn_nats_publish(&state, event);
+    }
+
     (StatusCode::CREATED, Json(Value::from(serde_json::to_value(
-        BatchResponse { received: ids.len(), ids, duplicates, rejected },
+        BatchResponse {
+            received: ids.len(),
+            ids,
+            duplicates,
+            rejected,
+            results,
+            inserted_count,
+            deduplicated_count,
+            error_count,
+        },
     ).unwrap()))).into_response()
 }
+
+/// GET /api/events/poll?since=<id>&timeout_ms=<ms> — long-poll catch-up for
+/// clients that don't want to hold an SSE stream open. Returns immediately
+/// with every event `id > since` (oldest first, capped at
+/// `POLL_MAX_EVENTS`); if none exist yet, parks on `state.notify_new_events`
+/// until one lands or `timeout_ms` elapses, whichever comes first. A timed
+/// out poll still returns `200` with an empty `events` array and
+/// `next_cursor` set to the current max id, so the client's next call
+/// starts from there instead of re-scanning the same empty range.
+pub async fn poll_events(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PollQuery>,
+) -> impl IntoResponse {
+    let timeout_ms = query.timeout_ms.unwrap_or(POLL_DEFAULT_TIMEOUT_MS).min(POLL_MAX_TIMEOUT_MS);
+
+    loop {
+        // Registered before the query runs, so an insert that lands between
+        // the query and the `await` below still wakes us instead of being
+        // missed.
+        let notified = state.notify_new_events.notified();
+
+        let db = match state.read_conn_blocking().await {
+            Ok(db) => db,
+            Err(e) => {
+                warn!("failed to check out a database connection for poll: {e}");
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(Value::from(
+                    serde_json::json!({"error": "internal server error"}),
+                ))).into_response();
+            }
+        };
+        let events = match queries::events_since(&db, query.since, POLL_MAX_EVENTS) {
+            Ok(events) => events,
+            Err(e) => {
+                warn!("poll events_since error: {e}");
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(Value::from(
+                    serde_json::json!({"error": "internal server error"}),
+                ))).into_response();
+            }
+        };
+        drop(db);
+
+        if !events.is_empty() {
+            let next_cursor = events.last().expect("just checked non-empty").id;
+            return (StatusCode::OK, Json(Value::from(serde_json::to_value(
+                PollResponse { events, next_cursor },
+            ).unwrap()))).into_response();
+        }
+
+        if tokio::time::timeout(std::time::Duration::from_millis(timeout_ms), notified)
+            .await
+            .is_err()
+        {
+            let db = match state.read_conn_blocking().await {
+                Ok(db) => db,
+                Err(e) => {
+                    warn!("failed to check out a database connection for poll: {e}");
+                    return (StatusCode::INTERNAL_SERVER_ERROR, Json(Value::from(
+                        serde_json::json!({"error": "internal server error"}),
+                    ))).into_response();
+                }
+            };
+            let next_cursor = queries::max_event_id(&db).unwrap_or(query.since);
+            return (StatusCode::OK, Json(Value::from(serde_json::to_value(
+                PollResponse { events: Vec::new(), next_cursor },
+            ).unwrap()))).into_response();
+        }
+        // Woken by a new insert — loop around and re-check rather than
+        // trusting the notification alone, since another poller could have
+        // already consumed the same row in a batch.
+    }
+}
@@ -10,7 +10,16 @@ use crate::state::AppState;
 
 /// GET /api/filter-options — distinct values used by dashboard filters.
 pub async fn filter_options_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let db = state.db.lock().await;
+    let db = match state.read_conn_blocking().await {
+        Ok(db) => db,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "internal server error" })),
+            )
+                .into_response();
+        }
+    };
     match queries::get_filter_options(&db) {
         Ok(options) => (StatusCode::OK, Json(options)).into_response(),
         Err(_) => (
@@ -8,21 +8,36 @@ use crate::state::AppState;
 
 #[derive(Serialize)]
 pub struct HealthResponse {
-    status: &'static str,
-    uptime: u64,
-    db_size_bytes: u64,
-    sse_clients: usize,
+    pub status: &'static str,
+    pub uptime: u64,
+    pub db_size_bytes: u64,
+    /// Connected live-feed clients, SSE and WebSocket combined — both
+    /// transports share one `SseHub`, so this field isn't renamed per-transport.
+    pub sse_clients: usize,
+    /// Whether the server has finished startup and is serving traffic.
+    /// Mirrors the optional systemd `READY=1` notification so non-systemd
+    /// consumers (e.g. a dashboard splash screen) have the same signal.
+    pub ready: bool,
 }
 
-pub async fn health_handler(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
+/// Build the health snapshot directly from shared state. Pulled out of
+/// `health_handler` so in-process callers (the desktop shell's IPC commands —
+/// see `agentmonitor_tauri_lib::ipc`) can get the same data without a round
+/// trip through the HTTP listener.
+pub fn build(state: &AppState) -> HealthResponse {
     let db_size = std::fs::metadata(&state.config.db_path)
         .map(|m| m.len())
         .unwrap_or(0);
 
-    Json(HealthResponse {
+    HealthResponse {
         status: "ok",
         uptime: state.start_time.elapsed().as_secs(),
         db_size_bytes: db_size,
-        sse_clients: 0, // placeholder until SSE hub is wired
-    })
+        sse_clients: state.sse_hub.client_count(),
+        ready: state.is_ready(),
+    }
+}
+
+pub async fn health_handler(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
+    Json(build(&state))
 }
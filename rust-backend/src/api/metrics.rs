@@ -0,0 +1,391 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use serde::Serialize;
+
+use crate::db::queries;
+use crate::state::AppState;
+
+/// Escape a Prometheus label value per the text exposition format.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// GET /metrics, GET /api/metrics — Prometheus/OpenMetrics text exposition format.
+pub async fn metrics_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let conn = state.read_conn_blocking().await.ok();
+    let stats = conn
+        .as_ref()
+        .and_then(|db| queries::get_stats(db, None).ok())
+        .unwrap_or_else(|| queries::Stats {
+            total_events: 0,
+            active_sessions: 0,
+            total_sessions: 0,
+            total_tokens_in: 0,
+            total_tokens_out: 0,
+            total_cost_usd: 0.0,
+        });
+    let by_agent_type = conn
+        .as_ref()
+        .and_then(|db| queries::get_metrics_by_agent_type(db).ok())
+        .unwrap_or_default();
+    let detailed = conn
+        .as_ref()
+        .and_then(|db| queries::get_detailed_metrics(db).ok())
+        .unwrap_or_default();
+    let tool_analytics = conn
+        .as_ref()
+        .and_then(|db| queries::get_tool_analytics(db, &queries::AnalyticsFilters::default(), None).ok())
+        .unwrap_or_default();
+    let tool_status_counts = conn
+        .as_ref()
+        .and_then(|db| queries::get_tool_status_counts(db, &queries::AnalyticsFilters::default()).ok())
+        .unwrap_or_default();
+    let usage = conn
+        .as_ref()
+        .and_then(|db| queries::get_usage_monitor(db, &state.config.usage_monitor, None).ok())
+        .unwrap_or_default();
+    let db_size_bytes = std::fs::metadata(&state.config.db_path)
+        .map(|m| m.len())
+        .unwrap_or(0);
+    drop(conn);
+
+    let mut body = String::new();
+
+    let ingest = state.ingest_counters.snapshot();
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_ingest_events_received_total Total ingest requests/items seen across /api/events, /api/events/batch, and /api/otel/v1/*."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_ingest_events_received_total counter");
+    let _ = writeln!(body, "agentmonitor_ingest_events_received_total {}", ingest.events_received);
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_ingest_duplicates_total Total ingested items deduplicated against an existing event_id."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_ingest_duplicates_total counter");
+    let _ = writeln!(body, "agentmonitor_ingest_duplicates_total {}", ingest.duplicates);
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_ingest_rejected_total Total ingested items rejected, labeled by reason."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_ingest_rejected_total counter");
+    for (reason, count) in &ingest.rejected {
+        let _ = writeln!(body, "agentmonitor_ingest_rejected_total{{reason=\"{reason}\"}} {count}");
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_ingest_payloads_truncated_total Total ingested payloads whose metadata was truncated by max_payload_kb."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_ingest_payloads_truncated_total counter");
+    let _ = writeln!(body, "agentmonitor_ingest_payloads_truncated_total {}", ingest.payloads_truncated);
+
+    let _ = writeln!(body, "# HELP agentmonitor_sse_clients Current number of connected SSE clients.");
+    let _ = writeln!(body, "# TYPE agentmonitor_sse_clients gauge");
+    let _ = writeln!(body, "agentmonitor_sse_clients {}", state.sse_hub.client_count());
+
+    let _ = writeln!(body, "# HELP agentmonitor_uptime_seconds Seconds since the process started.");
+    let _ = writeln!(body, "# TYPE agentmonitor_uptime_seconds gauge");
+    let _ = writeln!(body, "agentmonitor_uptime_seconds {}", state.start_time.elapsed().as_secs());
+
+    let _ = writeln!(body, "# HELP agentmonitor_db_size_bytes Size of the SQLite database file in bytes.");
+    let _ = writeln!(body, "# TYPE agentmonitor_db_size_bytes gauge");
+    let _ = writeln!(body, "agentmonitor_db_size_bytes {db_size_bytes}");
+
+    let _ = writeln!(body, "# HELP agentmonitor_sessions_active Number of sessions currently active.");
+    let _ = writeln!(body, "# TYPE agentmonitor_sessions_active gauge");
+    let _ = writeln!(body, "agentmonitor_sessions_active {}", stats.active_sessions);
+
+    let _ = writeln!(body, "# HELP agentmonitor_sessions_total Total number of sessions ever seen.");
+    let _ = writeln!(body, "# TYPE agentmonitor_sessions_total gauge");
+    let _ = writeln!(body, "agentmonitor_sessions_total {}", stats.total_sessions);
+
+    let _ = writeln!(body, "# HELP agentmonitor_events_total Total events ingested, labeled by agent_type.");
+    let _ = writeln!(body, "# TYPE agentmonitor_events_total counter");
+    for row in &by_agent_type {
+        let label = escape_label_value(&row.agent_type);
+        let _ = writeln!(
+            body,
+            "agentmonitor_events_total{{agent_type=\"{label}\"}} {}",
+            row.total_events
+        );
+    }
+
+    let _ = writeln!(body, "# HELP agentmonitor_tokens_in_total Total input tokens, labeled by agent_type.");
+    let _ = writeln!(body, "# TYPE agentmonitor_tokens_in_total counter");
+    for row in &by_agent_type {
+        let label = escape_label_value(&row.agent_type);
+        let _ = writeln!(
+            body,
+            "agentmonitor_tokens_in_total{{agent_type=\"{label}\"}} {}",
+            row.total_tokens_in
+        );
+    }
+
+    let _ = writeln!(body, "# HELP agentmonitor_tokens_out_total Total output tokens, labeled by agent_type.");
+    let _ = writeln!(body, "# TYPE agentmonitor_tokens_out_total counter");
+    for row in &by_agent_type {
+        let label = escape_label_value(&row.agent_type);
+        let _ = writeln!(
+            body,
+            "agentmonitor_tokens_out_total{{agent_type=\"{label}\"}} {}",
+            row.total_tokens_out
+        );
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_cost_usd_total Total estimated cost in USD, labeled by agent_type, model, and event_type."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_cost_usd_total counter");
+    for row in &detailed {
+        let agent_label = escape_label_value(&row.agent_type);
+        let model_label = escape_label_value(row.model.as_deref().unwrap_or("unknown"));
+        let event_label = escape_label_value(&row.event_type);
+        let _ = writeln!(
+            body,
+            "agentmonitor_cost_usd_total{{agent_type=\"{agent_label}\",model=\"{model_label}\",event_type=\"{event_label}\"}} {}",
+            row.total_cost_usd
+        );
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_input_tokens_total Total input tokens, labeled by agent_type, model, and event_type."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_input_tokens_total counter");
+    for row in &detailed {
+        let agent_label = escape_label_value(&row.agent_type);
+        let model_label = escape_label_value(row.model.as_deref().unwrap_or("unknown"));
+        let event_label = escape_label_value(&row.event_type);
+        let _ = writeln!(
+            body,
+            "agentmonitor_input_tokens_total{{agent_type=\"{agent_label}\",model=\"{model_label}\",event_type=\"{event_label}\"}} {}",
+            row.total_tokens_in
+        );
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_output_tokens_total Total output tokens, labeled by agent_type, model, and event_type."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_output_tokens_total counter");
+    for row in &detailed {
+        let agent_label = escape_label_value(&row.agent_type);
+        let model_label = escape_label_value(row.model.as_deref().unwrap_or("unknown"));
+        let event_label = escape_label_value(&row.event_type);
+        let _ = writeln!(
+            body,
+            "agentmonitor_output_tokens_total{{agent_type=\"{agent_label}\",model=\"{model_label}\",event_type=\"{event_label}\"}} {}",
+            row.total_tokens_out
+        );
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_tool_invocations_total Total tool calls, labeled by tool_name and status."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_tool_invocations_total counter");
+    for row in &tool_status_counts {
+        let tool_label = escape_label_value(&row.tool_name);
+        let status_label = escape_label_value(&row.status);
+        let _ = writeln!(
+            body,
+            "agentmonitor_tool_invocations_total{{tool_name=\"{tool_label}\",status=\"{status_label}\"}} {}",
+            row.count
+        );
+    }
+
+    // get_tool_analytics only computes error_rate/avg_duration_ms per
+    // tool_name, not per (tool_name, agent_type) — by_agent only carries call
+    // counts. Label by both anyway, repeating the tool-level rate across
+    // every agent_type that called it, rather than adding a second query.
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_tool_error_rate Error rate (0-1) for a tool, labeled by tool_name and agent_type."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_tool_error_rate gauge");
+    for row in &tool_analytics {
+        let tool_label = escape_label_value(&row.tool_name);
+        for agent_type in row.by_agent.keys() {
+            let agent_label = escape_label_value(agent_type);
+            let _ = writeln!(
+                body,
+                "agentmonitor_tool_error_rate{{tool_name=\"{tool_label}\",agent_type=\"{agent_label}\"}} {}",
+                row.error_rate
+            );
+        }
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_tool_avg_duration_ms Average tool call duration in milliseconds, labeled by tool_name and agent_type."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_tool_avg_duration_ms gauge");
+    for row in &tool_analytics {
+        let Some(avg_duration_ms) = row.avg_duration_ms else {
+            continue;
+        };
+        let tool_label = escape_label_value(&row.tool_name);
+        for agent_type in row.by_agent.keys() {
+            let agent_label = escape_label_value(agent_type);
+            let _ = writeln!(
+                body,
+                "agentmonitor_tool_avg_duration_ms{{tool_name=\"{tool_label}\",agent_type=\"{agent_label}\"}} {avg_duration_ms}"
+            );
+        }
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_usage_window_used Usage consumed in the current session window, labeled by agent_type and limit_type."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_usage_window_used gauge");
+    for row in &usage {
+        let agent_label = escape_label_value(&row.agent_type);
+        let limit_label = escape_label_value(&row.limit_type);
+        let _ = writeln!(
+            body,
+            "agentmonitor_usage_window_used{{agent_type=\"{agent_label}\",limit_type=\"{limit_label}\"}} {}",
+            row.session.used
+        );
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_usage_window_limit Usage limit for the current session window, labeled by agent_type and limit_type."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_usage_window_limit gauge");
+    for row in &usage {
+        let agent_label = escape_label_value(&row.agent_type);
+        let limit_label = escape_label_value(&row.limit_type);
+        let _ = writeln!(
+            body,
+            "agentmonitor_usage_window_limit{{agent_type=\"{agent_label}\",limit_type=\"{limit_label}\"}} {}",
+            row.session.limit
+        );
+    }
+
+    let task_health = state.task_health.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_task_runs_total Total completed runs of a supervised background task, labeled by task."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_task_runs_total counter");
+    for (name, health) in task_health.iter() {
+        let _ = writeln!(body, "agentmonitor_task_runs_total{{task=\"{name}\"}} {}", health.total_runs);
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_task_consecutive_failures Consecutive panicking runs of a supervised background task, labeled by task."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_task_consecutive_failures gauge");
+    for (name, health) in task_health.iter() {
+        let _ = writeln!(
+            body,
+            "agentmonitor_task_consecutive_failures{{task=\"{name}\"}} {}",
+            health.consecutive_failures
+        );
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_task_last_run_duration_ms Wall-clock duration of a supervised background task's most recent run, labeled by task."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_task_last_run_duration_ms gauge");
+    for (name, health) in task_health.iter() {
+        let _ = writeln!(
+            body,
+            "agentmonitor_task_last_run_duration_ms{{task=\"{name}\"}} {}",
+            health.last_duration_ms
+        );
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_task_avg_run_duration_ms Average wall-clock duration across every run of a supervised background task, labeled by task."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_task_avg_run_duration_ms gauge");
+    for (name, health) in task_health.iter() {
+        let avg_ms = if health.total_runs > 0 {
+            health.total_duration_ms / health.total_runs
+        } else {
+            0
+        };
+        let _ = writeln!(body, "agentmonitor_task_avg_run_duration_ms{{task=\"{name}\"}} {avg_ms}");
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP agentmonitor_task_last_success_timestamp_seconds Unix timestamp of a supervised background task's last successful (non-panicking) run, labeled by task."
+    );
+    let _ = writeln!(body, "# TYPE agentmonitor_task_last_success_timestamp_seconds gauge");
+    for (name, health) in task_health.iter() {
+        if let Some(last_success) = health.last_success {
+            let _ = writeln!(
+                body,
+                "agentmonitor_task_last_success_timestamp_seconds{{task=\"{name}\"}} {}",
+                last_success.timestamp()
+            );
+        }
+    }
+
+    drop(task_health);
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+/// Lightweight per-session rollup returned by `/summary` — a trimmed-down
+/// `queries::SessionRow` for monitoring stacks that just want totals, not
+/// the full `/api/sessions` payload (metadata, file/line counts, etc.).
+#[derive(Debug, Serialize)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub agent_type: String,
+    pub status: String,
+    pub event_count: i64,
+    pub tokens_in: i64,
+    pub tokens_out: i64,
+    pub cost_usd: f64,
+}
+
+/// GET /summary, GET /api/summary — per-session token/cost rollups in JSON,
+/// read-only against the same DB `run_import` writes to. Lists the sessions
+/// of `auth::DEFAULT_TENANT` only.
+pub async fn summary_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let conn = state.read_conn_blocking().await.ok();
+    let sessions = conn
+        .as_ref()
+        .and_then(|db| {
+            queries::get_sessions(db, &queries::SessionFilters::default(), crate::auth::DEFAULT_TENANT).ok()
+        })
+        .unwrap_or_default();
+
+    let summary: Vec<SessionSummary> = sessions
+        .into_iter()
+        .map(|s| SessionSummary {
+            session_id: s.id,
+            agent_type: s.agent_type,
+            status: s.status,
+            event_count: s.event_count,
+            tokens_in: s.tokens_in,
+            tokens_out: s.tokens_out,
+            cost_usd: s.total_cost_usd,
+        })
+        .collect();
+
+    Json(summary)
+}
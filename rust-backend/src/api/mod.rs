@@ -1,15 +1,39 @@
+mod admin;
+mod cluster;
 mod events;
 mod filter_options;
 mod health;
+mod metrics;
+mod otel;
+mod runtime_admin;
+mod search;
 mod sessions;
 mod stats;
 mod stream;
 mod transcripts;
+mod upstreams;
+mod ws;
 
-pub use events::{ingest_batch, ingest_single};
+pub use admin::{create_key_handler, revoke_key_handler};
+pub use cluster::cluster_broadcast_handler;
+pub use events::{ingest_batch, ingest_single, poll_events};
+pub(crate) use events::insert_params;
 pub use filter_options::filter_options_handler;
-pub use health::health_handler;
+pub use health::{health_handler, HealthResponse};
+pub use health::build as health_snapshot;
+pub use metrics::{metrics_handler, summary_handler};
+pub use otel::{otel_logs_handler, otel_metrics_handler, otel_traces_handler};
+pub use runtime_admin::{
+    get_settings_handler, trigger_idle_check_handler, trigger_stats_broadcast_handler,
+    update_settings_handler,
+};
+pub use search::search_events_handler;
 pub use sessions::{session_detail_handler, sessions_list_handler};
-pub use stats::{stats_cost_handler, stats_handler, stats_tools_handler, usage_monitor_handler};
+pub use stats::{
+    analytics_handler, stats_activity_handler, stats_cost_handler, stats_handler,
+    stats_tools_handler, usage_monitor_handler,
+};
 pub use stream::stream_handler;
 pub use transcripts::session_transcript_handler;
+pub use upstreams::upstreams_handler;
+pub use ws::ws_handler;
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use axum::Extension;
 use axum::Json;
 use axum::body::Bytes;
 use axum::extract::State;
@@ -8,33 +9,21 @@ use axum::response::IntoResponse;
 use serde_json::{Value, json};
 use tracing::warn;
 
+use crate::auth::TenantId;
 use crate::db::queries::{self, InsertEventParams};
-use crate::otel::parser::{parse_otel_logs, parse_otel_metrics};
+use crate::ingest_metrics::RejectReason;
+use crate::otel::parser::{parse_otel_logs, parse_otel_metrics, parse_otel_traces};
+use crate::otel::protobuf;
 use crate::state::AppState;
 use crate::util::truncate::truncate_metadata;
 
-fn reject_protobuf_if_needed(headers: &HeaderMap) -> Option<axum::response::Response> {
+fn is_protobuf_content_type(headers: &HeaderMap) -> bool {
     let content_type = headers
         .get("content-type")
         .and_then(|v| v.to_str().ok())
         .unwrap_or_default();
 
-    if content_type.contains("application/x-protobuf")
-        || content_type.contains("application/protobuf")
-    {
-        return Some(
-            (
-                StatusCode::UNSUPPORTED_MEDIA_TYPE,
-                Json(json!({
-                    "error": "Protobuf not supported yet. Use JSON format.",
-                    "hint": "Set OTEL_EXPORTER_OTLP_PROTOCOL=http/json",
-                })),
-            )
-                .into_response(),
-        );
-    }
-
-    None
+    content_type.contains("application/x-protobuf") || content_type.contains("application/protobuf")
 }
 
 fn parse_json_body(body: Bytes) -> Result<Value, axum::response::Response> {
@@ -50,27 +39,52 @@ fn parse_json_body(body: Bytes) -> Result<Value, axum::response::Response> {
     })
 }
 
+/// Parse an OTLP request body, dispatching on `Content-Type`: protobuf-encoded
+/// requests (the default wire format for most OTel exporters) are decoded via
+/// `otel::protobuf` into the same OTLP/JSON shape the JSON path produces;
+/// everything else is parsed as JSON directly.
+fn parse_otlp_body(
+    body: Bytes,
+    headers: &HeaderMap,
+    decode_protobuf: fn(&[u8]) -> Value,
+) -> Result<Value, axum::response::Response> {
+    if is_protobuf_content_type(headers) {
+        if body.is_empty() {
+            return Ok(json!({}));
+        }
+        return Ok(decode_protobuf(&body));
+    }
+    parse_json_body(body)
+}
+
 /// POST /api/otel/v1/logs
 pub async fn otel_logs_handler(
     State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
     headers: HeaderMap,
     body: Bytes,
 ) -> impl IntoResponse {
-    if let Some(resp) = reject_protobuf_if_needed(&headers) {
-        return resp;
-    }
-
-    let payload = match parse_json_body(body) {
+    let payload = match parse_otlp_body(body, &headers, protobuf::decode_export_logs_request) {
         Ok(v) => v,
         Err(resp) => return resp,
     };
 
     let parsed = parse_otel_logs(&payload);
     let max_kb = state.config.max_payload_kb;
-    let db = state.db.lock().await;
+    let db = match state.write_conn_blocking().await {
+        Ok(db) => db,
+        Err(e) => {
+            warn!("failed to check out a database connection: {e}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "internal server error"}))).into_response();
+        }
+    };
 
     for event in parsed {
+        state.ingest_counters.record_received();
         let truncated = truncate_metadata(&event.metadata, max_kb);
+        if truncated.truncated {
+            state.ingest_counters.record_truncated();
+        }
         let params = InsertEventParams {
             event_id: None,
             session_id: &event.session_id,
@@ -91,15 +105,20 @@ pub async fn otel_logs_handler(
             cache_read_tokens: event.cache_read_tokens,
             cache_write_tokens: event.cache_write_tokens,
             source: "otel",
+            tenant_id: &tenant.0,
         };
 
         match queries::insert_event(&db, &params) {
             Ok(Some(row)) => {
                 let row_value = serde_json::to_value(&row).unwrap_or_else(|_| json!({}));
                 state.sse_hub.broadcast("event", &row_value);
+                state.notify_new_events.notify_waiters();
+            }
+            Ok(None) => state.ingest_counters.record_duplicate(),
+            Err(e) => {
+                warn!("otel logs insert_event error: {e}");
+                state.ingest_counters.record_rejected(RejectReason::InternalError);
             }
-            Ok(None) => {}
-            Err(e) => warn!("otel logs insert_event error: {e}"),
         }
     }
 
@@ -109,26 +128,27 @@ pub async fn otel_logs_handler(
 /// POST /api/otel/v1/metrics
 pub async fn otel_metrics_handler(
     State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
     headers: HeaderMap,
     body: Bytes,
 ) -> impl IntoResponse {
-    if let Some(resp) = reject_protobuf_if_needed(&headers) {
-        return resp;
-    }
-
-    let payload = match parse_json_body(body) {
+    let payload = match parse_otlp_body(body, &headers, protobuf::decode_export_metrics_request) {
         Ok(v) => v,
         Err(resp) => return resp,
     };
 
-    let mut cumulative = state.otel_cumulative_state.lock().await;
-    let deltas = parse_otel_metrics(&payload, &mut cumulative);
-    drop(cumulative);
+    let parsed = parse_otel_metrics(&payload, state.otel_cumulative_state.as_ref());
 
     let max_kb = state.config.max_payload_kb;
-    let db = state.db.lock().await;
+    let db = match state.write_conn_blocking().await {
+        Ok(db) => db,
+        Err(e) => {
+            warn!("failed to check out a database connection: {e}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "internal server error"}))).into_response();
+        }
+    };
 
-    for delta in deltas {
+    for delta in parsed.deltas {
         let has_tokens = delta.tokens_in_delta > 0
             || delta.tokens_out_delta > 0
             || delta.cache_read_delta > 0
@@ -137,12 +157,16 @@ pub async fn otel_metrics_handler(
         if !has_tokens && !has_cost {
             continue;
         }
+        state.ingest_counters.record_received();
 
         let metadata = json!({
             "_synthetic": true,
             "_source": "otel_metric",
         });
         let truncated = truncate_metadata(&metadata, max_kb);
+        if truncated.truncated {
+            state.ingest_counters.record_truncated();
+        }
 
         let params = InsertEventParams {
             event_id: None,
@@ -168,25 +192,160 @@ pub async fn otel_metrics_handler(
             cache_read_tokens: delta.cache_read_delta,
             cache_write_tokens: delta.cache_write_delta,
             source: "otel",
+            tenant_id: &tenant.0,
         };
 
         match queries::insert_event(&db, &params) {
             Ok(Some(row)) => {
                 let row_value = serde_json::to_value(&row).unwrap_or_else(|_| json!({}));
                 state.sse_hub.broadcast("event", &row_value);
+                state.notify_new_events.notify_waiters();
+            }
+            Ok(None) => state.ingest_counters.record_duplicate(),
+            Err(e) => {
+                warn!("otel metrics insert_event error: {e}");
+                state.ingest_counters.record_rejected(RejectReason::InternalError);
+            }
+        }
+    }
+
+    for histogram in parsed.histograms {
+        if histogram.count_delta <= 0 {
+            continue;
+        }
+        state.ingest_counters.record_received();
+
+        // duration_ms is the mean over the interval, not a single
+        // observation — buckets stay in metadata so the dashboard can do
+        // its own percentile estimation instead of trusting the mean alone.
+        let duration_ms = (histogram.sum_delta / histogram.count_delta as f64).round() as i64;
+        let buckets: Vec<Value> = histogram
+            .buckets
+            .iter()
+            .map(|b| {
+                json!({
+                    "upper_bound": b.upper_bound,
+                    "count_delta": b.count_delta,
+                })
+            })
+            .collect();
+        let metadata = json!({
+            "_synthetic": true,
+            "_source": "otel_metric",
+            "metric_name": histogram.metric_name,
+            "count_delta": histogram.count_delta,
+            "sum_delta": histogram.sum_delta,
+            "buckets": buckets,
+        });
+        let truncated = truncate_metadata(&metadata, max_kb);
+        if truncated.truncated {
+            state.ingest_counters.record_truncated();
+        }
+
+        let params = InsertEventParams {
+            event_id: None,
+            session_id: &histogram.session_id,
+            agent_type: &histogram.agent_type,
+            event_type: "llm_response",
+            tool_name: None,
+            status: "success",
+            tokens_in: 0,
+            tokens_out: 0,
+            branch: None,
+            project: None,
+            duration_ms: Some(duration_ms),
+            client_timestamp: None,
+            metadata: &truncated.value,
+            payload_truncated: truncated.truncated,
+            model: histogram.model.as_deref(),
+            cost_usd: None,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            source: "otel",
+            tenant_id: &tenant.0,
+        };
+
+        match queries::insert_event(&db, &params) {
+            Ok(Some(row)) => {
+                let row_value = serde_json::to_value(&row).unwrap_or_else(|_| json!({}));
+                state.sse_hub.broadcast("event", &row_value);
+                state.notify_new_events.notify_waiters();
+            }
+            Ok(None) => state.ingest_counters.record_duplicate(),
+            Err(e) => {
+                warn!("otel histogram insert_event error: {e}");
+                state.ingest_counters.record_rejected(RejectReason::InternalError);
             }
-            Ok(None) => {}
-            Err(e) => warn!("otel metrics insert_event error: {e}"),
         }
     }
 
     (StatusCode::OK, Json(json!({}))).into_response()
 }
 
-/// POST /api/otel/v1/traces â€” accepted stub.
-pub async fn otel_traces_handler(headers: HeaderMap, _body: Bytes) -> impl IntoResponse {
-    if let Some(resp) = reject_protobuf_if_needed(&headers) {
-        return resp;
+/// POST /api/otel/v1/traces
+pub async fn otel_traces_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let payload = match parse_otlp_body(body, &headers, protobuf::decode_export_trace_request) {
+        Ok(v) => v,
+        Err(resp) => return resp,
+    };
+
+    let parsed = parse_otel_traces(&payload);
+    let max_kb = state.config.max_payload_kb;
+    let db = match state.write_conn_blocking().await {
+        Ok(db) => db,
+        Err(e) => {
+            warn!("failed to check out a database connection: {e}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "internal server error"}))).into_response();
+        }
+    };
+
+    for span in parsed {
+        state.ingest_counters.record_received();
+        let truncated = truncate_metadata(&span.metadata, max_kb);
+        if truncated.truncated {
+            state.ingest_counters.record_truncated();
+        }
+        let params = InsertEventParams {
+            event_id: None,
+            session_id: &span.session_id,
+            agent_type: &span.agent_type,
+            event_type: &span.event_type,
+            tool_name: span.tool_name.as_deref(),
+            status: &span.status,
+            tokens_in: span.tokens_in,
+            tokens_out: span.tokens_out,
+            branch: span.branch.as_deref(),
+            project: span.project.as_deref(),
+            duration_ms: span.duration_ms,
+            client_timestamp: span.client_timestamp.as_deref(),
+            metadata: &truncated.value,
+            payload_truncated: truncated.truncated,
+            model: span.model.as_deref(),
+            cost_usd: span.cost_usd,
+            cache_read_tokens: span.cache_read_tokens,
+            cache_write_tokens: span.cache_write_tokens,
+            source: "otel",
+            tenant_id: &tenant.0,
+        };
+
+        match queries::insert_event(&db, &params) {
+            Ok(Some(row)) => {
+                let row_value = serde_json::to_value(&row).unwrap_or_else(|_| json!({}));
+                state.sse_hub.broadcast("event", &row_value);
+                state.notify_new_events.notify_waiters();
+            }
+            Ok(None) => state.ingest_counters.record_duplicate(),
+            Err(e) => {
+                warn!("otel traces insert_event error: {e}");
+                state.ingest_counters.record_rejected(RejectReason::InternalError);
+            }
+        }
     }
+
     (StatusCode::OK, Json(json!({}))).into_response()
 }
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+
+use crate::runtime_tasks::{run_idle_check_once, run_stats_broadcast_once};
+use crate::state::AppState;
+
+#[derive(Serialize)]
+struct NotifierChannelState {
+    name: &'static str,
+    enabled: bool,
+}
+
+#[derive(Serialize)]
+struct RuntimeSettingsResponse {
+    idle_timeout_minutes: u64,
+    stats_interval_ms: u64,
+    notifier_channels: Vec<NotifierChannelState>,
+}
+
+fn current_settings(state: &AppState) -> RuntimeSettingsResponse {
+    RuntimeSettingsResponse {
+        idle_timeout_minutes: state.runtime_settings.idle_timeout_minutes(),
+        stats_interval_ms: state.runtime_settings.stats_interval_ms(),
+        notifier_channels: state
+            .notifier
+            .channel_states()
+            .into_iter()
+            .map(|(name, enabled)| NotifierChannelState { name, enabled })
+            .collect(),
+    }
+}
+
+#[derive(Serialize)]
+struct RuntimeAdminErrorResponse {
+    error: String,
+}
+
+fn error_response(status: StatusCode, message: impl Into<String>) -> axum::response::Response {
+    (status, Json(RuntimeAdminErrorResponse { error: message.into() })).into_response()
+}
+
+/// GET /runtime/settings — current idle timeout, stats broadcast interval,
+/// and notifier channel on/off state.
+pub async fn get_settings_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(current_settings(&state)).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct NotifierChannelUpdate {
+    name: String,
+    enabled: bool,
+}
+
+/// Body for `PUT /runtime/settings`. Every field is optional — an absent
+/// field leaves that knob untouched, so a caller can retune just the idle
+/// timeout without having to also resend the current stats interval.
+#[derive(Deserialize)]
+pub struct UpdateSettingsRequest {
+    idle_timeout_minutes: Option<u64>,
+    stats_interval_ms: Option<u64>,
+    notifier_channels: Option<Vec<NotifierChannelUpdate>>,
+}
+
+/// PUT /runtime/settings — live-update the idle timeout, stats broadcast
+/// interval, and/or notifier channel on/off state. `run_idle_check_once` and
+/// `spawn_stats_task` read these values fresh every tick (see
+/// `runtime_settings::RuntimeSettings`), so a change here takes effect
+/// without a restart.
+pub async fn update_settings_handler(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<UpdateSettingsRequest>,
+) -> impl IntoResponse {
+    if let Some(minutes) = body.idle_timeout_minutes {
+        if minutes == 0 {
+            return error_response(StatusCode::BAD_REQUEST, "idle_timeout_minutes must be greater than 0");
+        }
+        state.runtime_settings.set_idle_timeout_minutes(minutes);
+    }
+    if let Some(ms) = body.stats_interval_ms {
+        if ms == 0 {
+            return error_response(StatusCode::BAD_REQUEST, "stats_interval_ms must be greater than 0");
+        }
+        state.runtime_settings.set_stats_interval_ms(ms);
+    }
+    if let Some(channels) = body.notifier_channels {
+        for update in channels {
+            if !state.notifier.set_channel_enabled(&update.name, update.enabled) {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("unknown notifier channel {:?}", update.name),
+                );
+            }
+        }
+    }
+    Json(current_settings(&state)).into_response()
+}
+
+#[derive(Serialize)]
+struct StatsBroadcastResponse {
+    sent: bool,
+}
+
+/// POST /runtime/stats-broadcast — run one stats broadcast cycle immediately
+/// instead of waiting for the next scheduled tick, and report whether it was
+/// actually sent (see `run_stats_broadcast_once`).
+pub async fn trigger_stats_broadcast_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let sent = run_stats_broadcast_once(state).await;
+    Json(StatsBroadcastResponse { sent })
+}
+
+#[derive(Serialize)]
+struct IdleCheckResponse {
+    idled: usize,
+}
+
+/// POST /runtime/idle-check — run one idle-session sweep immediately and
+/// report how many sessions transitioned from active -> idle.
+pub async fn trigger_idle_check_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let idled = run_idle_check_once(state).await;
+    Json(IdleCheckResponse { idled })
+}
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use axum::Extension;
+use axum::Json;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::auth::TenantId;
+use crate::db::queries::{self, SessionFilters};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    q: Option<String>,
+    agent_type: Option<String>,
+    project: Option<String>,
+    branch: Option<String>,
+    since: Option<String>,
+    limit: Option<String>,
+}
+
+fn parse_i64(input: Option<&str>) -> Option<i64> {
+    input.and_then(|raw| raw.parse::<i64>().ok())
+}
+
+/// GET /api/search/events — full-text search over event metadata/tool_name
+/// (see `queries::search_events`). `q` is an FTS5 MATCH expression, so
+/// `"apply_patch auth.rs"` (phrase) and `tool_name:Edit` (column-scoped) both
+/// work as-is. Scoped to the caller's tenant.
+pub async fn search_events_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
+    Query(query): Query<SearchQuery>,
+) -> impl IntoResponse {
+    let Some(q) = query.q.filter(|q| !q.trim().is_empty()) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "q is required" })),
+        )
+            .into_response();
+    };
+
+    let filters = SessionFilters {
+        agent_type: query.agent_type,
+        project: query.project,
+        branch: query.branch,
+        since: query.since,
+        limit: parse_i64(query.limit.as_deref()),
+        ..SessionFilters::default()
+    };
+
+    let db = match state.read_conn_blocking().await {
+        Ok(db) => db,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "internal server error" })),
+            )
+                .into_response();
+        }
+    };
+    match queries::search_events(&db, &q, &filters, &tenant.0) {
+        Ok(events) => (StatusCode::OK, Json(serde_json::json!({ "events": events }))).into_response(),
+        Err(e) => {
+            warn!("search_events error: {e}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "internal server error" })),
+            )
+                .into_response()
+        }
+    }
+}
@@ -1,11 +1,13 @@
 use std::sync::Arc;
 
+use axum::Extension;
 use axum::Json;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use serde::{Deserialize, Serialize};
 
+use crate::auth::TenantId;
 use crate::db::queries::{self, SessionFilters};
 use crate::state::AppState;
 
@@ -16,6 +18,8 @@ pub struct SessionsQuery {
     agent_type: Option<String>,
     since: Option<String>,
     limit: Option<String>,
+    project: Option<String>,
+    branch: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -39,9 +43,11 @@ fn parse_i64(input: Option<&str>) -> Option<i64> {
     input.and_then(|raw| raw.parse::<i64>().ok())
 }
 
-/// GET /api/sessions — list sessions with optional filters.
+/// GET /api/sessions — list sessions with optional filters, scoped to the
+/// caller's tenant.
 pub async fn sessions_list_handler(
     State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
     Query(query): Query<SessionsQuery>,
 ) -> impl IntoResponse {
     let filters = SessionFilters {
@@ -50,10 +56,21 @@ pub async fn sessions_list_handler(
         agent_type: query.agent_type,
         since: query.since,
         limit: parse_i64(query.limit.as_deref()),
+        project: query.project,
+        branch: query.branch,
     };
 
-    let db = state.db.lock().await;
-    match queries::get_sessions(&db, &filters) {
+    let db = match state.read_conn_blocking().await {
+        Ok(db) => db,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "internal server error" })),
+            )
+                .into_response();
+        }
+    };
+    match queries::get_sessions(&db, &filters, &tenant.0) {
         Ok(sessions) => {
             let total = sessions.len();
             (StatusCode::OK, Json(SessionsResponse { sessions, total })).into_response()
@@ -66,9 +83,11 @@ pub async fn sessions_list_handler(
     }
 }
 
-/// GET /api/sessions/:id — session detail plus most recent events.
+/// GET /api/sessions/:id — session detail plus most recent events, scoped
+/// to the caller's tenant.
 pub async fn session_detail_handler(
     State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
     Path(session_id): Path<String>,
     Query(query): Query<SessionDetailQuery>,
 ) -> impl IntoResponse {
@@ -76,8 +95,17 @@ pub async fn session_detail_handler(
         .or_else(|| parse_i64(query.limit.as_deref()))
         .unwrap_or(10);
 
-    let db = state.db.lock().await;
-    match queries::get_session_with_events(&db, &session_id, event_limit) {
+    let db = match state.read_conn_blocking().await {
+        Ok(db) => db,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "internal server error" })),
+            )
+                .into_response();
+        }
+    };
+    match queries::get_session_with_events(&db, &session_id, event_limit, &tenant.0) {
         Ok((Some(session), events)) => (
             StatusCode::OK,
             Json(serde_json::json!({
@@ -1,11 +1,13 @@
 use std::sync::Arc;
 
+use axum::Extension;
 use axum::Json;
 use axum::extract::{Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use serde::Deserialize;
 
+use crate::auth::TenantId;
 use crate::db::queries;
 use crate::state::AppState;
 
@@ -14,6 +16,12 @@ pub struct StatsQuery {
     agent_type: Option<String>,
     since: Option<String>,
     limit: Option<String>,
+    project: Option<String>,
+    branch: Option<String>,
+    granularity: Option<String>,
+    group_by: Option<String>,
+    utc_offset_minutes: Option<String>,
+    interval: Option<String>,
 }
 
 fn parse_i64(input: Option<&str>) -> Option<i64> {
@@ -24,33 +32,50 @@ fn to_filters(query: &StatsQuery) -> queries::AnalyticsFilters {
     queries::AnalyticsFilters {
         agent_type: query.agent_type.clone(),
         since: query.since.clone(),
+        project: query.project.clone(),
+        branch: query.branch.clone(),
     }
 }
 
-/// GET /api/stats — aggregated statistics.
+/// GET /api/stats — aggregated statistics, scoped to the caller's tenant.
 pub async fn stats_handler(
     State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
 ) -> Json<queries::Stats> {
-    let db = state.db.lock().await;
-    let stats = queries::get_stats(&db).unwrap_or_else(|_| queries::Stats {
-        total_events: 0,
-        active_sessions: 0,
-        total_sessions: 0,
-        total_tokens_in: 0,
-        total_tokens_out: 0,
-        total_cost_usd: 0.0,
-    });
+    let stats = state
+        .read_conn_blocking()
+        .await
+        .ok()
+        .and_then(|db| queries::get_stats(&db, Some(&tenant.0)).ok())
+        .unwrap_or_else(|| queries::Stats {
+            total_events: 0,
+            active_sessions: 0,
+            total_sessions: 0,
+            total_tokens_in: 0,
+            total_tokens_out: 0,
+            total_cost_usd: 0.0,
+        });
     Json(stats)
 }
 
-/// GET /api/stats/tools — tool analytics.
+/// GET /api/stats/tools — tool analytics, scoped to the caller's tenant.
 pub async fn stats_tools_handler(
     State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
     Query(query): Query<StatsQuery>,
 ) -> impl IntoResponse {
     let filters = to_filters(&query);
-    let db = state.db.lock().await;
-    match queries::get_tool_analytics(&db, &filters) {
+    let db = match state.read_conn_blocking().await {
+        Ok(db) => db,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "internal server error" })),
+            )
+                .into_response();
+        }
+    };
+    match queries::get_tool_analytics(&db, &filters, Some(&tenant.0)) {
         Ok(tools) => (StatusCode::OK, Json(serde_json::json!({ "tools": tools }))).into_response(),
         Err(_) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -60,18 +85,35 @@ pub async fn stats_tools_handler(
     }
 }
 
-/// GET /api/stats/cost — cost timeline + breakdowns.
+/// GET /api/stats/cost — cost timeline + breakdowns, scoped to the
+/// caller's tenant.
 pub async fn stats_cost_handler(
     State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
     Query(query): Query<StatsQuery>,
 ) -> impl IntoResponse {
     let filters = to_filters(&query);
     let limit = parse_i64(query.limit.as_deref()).unwrap_or(10).max(1);
+    let granularity = query
+        .granularity
+        .as_deref()
+        .and_then(queries::TimeGranularity::parse)
+        .unwrap_or_default();
+    let group_by = query.group_by.as_deref().and_then(queries::CostGroupBy::parse);
 
-    let db = state.db.lock().await;
-    let timeline = queries::get_cost_over_time(&db, &filters);
-    let by_project = queries::get_cost_by_project(&db, limit, &filters);
-    let by_model = queries::get_cost_by_model(&db, &filters);
+    let db = match state.read_conn_blocking().await {
+        Ok(db) => db,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "internal server error" })),
+            )
+                .into_response();
+        }
+    };
+    let timeline = queries::get_cost_over_time(&db, &filters, granularity, group_by, &tenant.0);
+    let by_project = queries::get_cost_by_project(&db, limit, &filters, &tenant.0);
+    let by_model = queries::get_cost_by_model(&db, &filters, &tenant.0);
 
     match (timeline, by_project, by_model) {
         (Ok(timeline), Ok(by_project), Ok(by_model)) => (
@@ -91,12 +133,104 @@ pub async fn stats_cost_handler(
     }
 }
 
-/// GET /api/stats/usage-monitor — rolling usage by agent type.
+/// GET /api/stats/activity — time-bucketed activity trends (tokens, cost,
+/// events, edit volume) for charting, scoped to the caller's tenant.
+/// `utc_offset_minutes` defaults to `0` (UTC buckets); pass e.g. `-300` so
+/// `day`/`week` boundaries land on a caller in US Eastern rather than UTC
+/// midnight.
+pub async fn stats_activity_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
+    Query(query): Query<StatsQuery>,
+) -> impl IntoResponse {
+    let filters = to_filters(&query);
+    let granularity = query
+        .granularity
+        .as_deref()
+        .and_then(queries::TimeGranularity::parse)
+        .unwrap_or_default();
+    let utc_offset_minutes = parse_i64(query.utc_offset_minutes.as_deref()).unwrap_or(0);
+
+    let db = match state.read_conn_blocking().await {
+        Ok(db) => db,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "internal server error" })),
+            )
+                .into_response();
+        }
+    };
+    match queries::get_activity_stats(&db, &filters, granularity, utc_offset_minutes, &tenant.0) {
+        Ok(buckets) => (StatusCode::OK, Json(serde_json::json!({ "buckets": buckets }))).into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "internal server error" })),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/analytics — server-side aggregation over events for dashboard
+/// charts: per-bucket (and optionally per-`group_by`) token/cost sums and
+/// average duration, so the frontend can render cost-over-time and
+/// per-model breakdowns directly instead of aggregating raw events
+/// client-side. `group_by` is one of `model`/`project`/`branch`/
+/// `agent_type`/`event_type`; `interval` is `hour` (default) or `day`.
+/// Scoped to the caller's tenant.
+pub async fn analytics_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
+    Query(query): Query<StatsQuery>,
+) -> impl IntoResponse {
+    let filters = to_filters(&query);
+    let interval = query
+        .interval
+        .as_deref()
+        .and_then(queries::AnalyticsInterval::parse)
+        .unwrap_or_default();
+    let group_by = query
+        .group_by
+        .as_deref()
+        .and_then(queries::AnalyticsGroupBy::parse);
+
+    let db = match state.read_conn_blocking().await {
+        Ok(db) => db,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "internal server error" })),
+            )
+                .into_response();
+        }
+    };
+    match queries::get_analytics(&db, &filters, interval, group_by, &tenant.0) {
+        Ok(buckets) => (StatusCode::OK, Json(serde_json::json!({ "buckets": buckets }))).into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "internal server error" })),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/stats/usage-monitor — rolling usage by agent type, scoped to
+/// the caller's tenant.
 pub async fn usage_monitor_handler(
     State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
 ) -> impl IntoResponse {
-    let db = state.db.lock().await;
-    match queries::get_usage_monitor(&db, &state.config.usage_monitor) {
+    let db = match state.read_conn_blocking().await {
+        Ok(db) => db,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "internal server error" })),
+            )
+                .into_response();
+        }
+    };
+    match queries::get_usage_monitor(&db, &state.config.usage_monitor, Some(&tenant.0)) {
         Ok(data) => (StatusCode::OK, Json(data)).into_response(),
         Err(_) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -1,13 +1,16 @@
 use std::sync::Arc;
 
+use axum::Extension;
 use axum::Json;
-use axum::extract::State;
-use axum::http::StatusCode;
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::IntoResponse;
 use axum::response::sse::{Event, KeepAlive, Sse};
 use futures_util::stream::Stream;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
+use crate::auth::TenantId;
+use crate::sse::hub::{BroadcastEvent, SseFilter};
 use crate::state::AppState;
 
 #[derive(Serialize)]
@@ -16,11 +19,60 @@ struct SseError {
     max_clients: usize,
 }
 
-/// GET /api/stream — SSE endpoint.
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    session_id: Option<String>,
+    agent_type: Option<String>,
+    event_type: Option<String>,
+    since_seq: Option<u64>,
+    from_id: Option<u64>,
+}
+
+impl StreamQuery {
+    /// Layer this query's narrowing on top of a tenant-scoped base filter
+    /// (see `SseFilter::for_tenant`) rather than building a fresh one, so
+    /// the tenant can't be dropped in the process.
+    fn narrow(self, mut filter: SseFilter) -> SseFilter {
+        filter.session_id = self.session_id;
+        filter.agent_type = self.agent_type;
+        filter.event_type = self.event_type;
+        filter
+    }
+}
+
+/// Parse the standard `Last-Event-ID` request header as a hub broadcast id,
+/// falling back to the `?since_seq=`/`?from_id=` query params so non-browser
+/// clients can resume too.
+fn last_event_id(headers: &HeaderMap, query: &StreamQuery) -> Option<u64> {
+    headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .or(query.since_seq)
+        .or(query.from_id)
+}
+
+/// GET /api/stream — SSE endpoint. Accepts `?session_id=`, `?agent_type=`,
+/// and `?event_type=` query params so a client only receives matching events.
+/// On reconnect, pass the `Last-Event-ID` header (or `?since_seq=`/`?from_id=`)
+/// to replay buffered broadcasts missed while disconnected before attaching
+/// to the live stream. Every frame — `event`, `stats`, and `session_update`
+/// alike — shares one monotonically increasing id from `SseHub`, so a
+/// reconnect resumes correctly no matter which kind of frame the client last
+/// saw. If the requested id is older than everything still buffered, a
+/// `{"type":"gap","from":<oldest>}` frame is sent instead of a partial
+/// replay, so the client knows to re-sync via `/api/stats`/`/api/sessions`.
+/// The same `{"type":"gap",...}` frame is sent if the client ever falls far
+/// enough behind the live broadcast channel to get `RecvError::Lagged` —
+/// that's a gap mid-stream rather than at reconnect, but the client's
+/// recovery is identical either way.
 pub async fn stream_handler(
     State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
+    Query(query): Query<StreamQuery>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
-    let client = state.sse_hub.subscribe();
+    let client = state.sse_hub.subscribe(tenant.0);
 
     match client {
         None => {
@@ -31,12 +83,26 @@ pub async fn stream_handler(
             })).into_response()
         }
         Some(client) => {
-            let stream = sse_stream(client);
+            let since = last_event_id(&headers, &query);
+            let filter = query.narrow(client.filter.clone());
+            // A gap means the buffer already evicted frames older than what
+            // this client is asking to resume from — tell it to re-sync via
+            // the regular read endpoints instead of replaying a partial history.
+            let gap = since.and_then(|since| {
+                state
+                    .sse_hub
+                    .oldest_id()
+                    .filter(|&oldest| since + 1 < oldest)
+            });
+            let replay = since
+                .map(|since| state.sse_hub.replay_since(since))
+                .unwrap_or_default();
+            let stream = sse_stream(client, filter, gap, replay);
             Sse::new(stream)
                 .keep_alive(
                     KeepAlive::new()
                         .interval(std::time::Duration::from_millis(state.config.sse_heartbeat_ms))
-                        .text("heartbeat"),
+                        .text("ping"),
                 )
                 .into_response()
         }
@@ -45,6 +111,9 @@ pub async fn stream_handler(
 
 fn sse_stream(
     client: crate::sse::hub::SseClient,
+    filter: SseFilter,
+    gap: Option<u64>,
+    replay: Vec<BroadcastEvent>,
 ) -> impl Stream<Item = Result<Event, std::convert::Infallible>> {
     let connected = serde_json::json!({
         "type": "connected",
@@ -59,23 +128,43 @@ fn sse_stream(
         let _guard = guard;
         let mut rx = rx;
 
-        // Send connected message
+        // Send connected message unconditionally, regardless of filter.
         yield Ok(Event::default().data(connected_data));
 
-        // Relay broadcast messages
+        // The client asked to resume from an id older than anything still
+        // buffered — tell it outright rather than silently replaying a
+        // partial (and therefore misleading) history.
+        if let Some(oldest) = gap {
+            let gap_data = serde_json::json!({ "type": "gap", "from": oldest }).to_string();
+            yield Ok(Event::default().data(gap_data));
+        }
+
+        // Replay broadcasts missed while this client was disconnected, in the
+        // same envelope shape as the live stream below.
+        for event in replay {
+            if !event.matches(&filter) {
+                continue;
+            }
+            yield Ok(to_sse_event(&event));
+        }
+
+        // Relay broadcast messages that match this client's subscription filter.
         loop {
             match rx.recv().await {
-                Ok(msg) => {
-                    // The hub formats as "data: ...\n\n", but axum's Sse
-                    // wraps Event::data() itself. Strip the hub's framing.
-                    let content = msg
-                        .strip_prefix("data: ")
-                        .and_then(|s| s.strip_suffix("\n\n"))
-                        .unwrap_or(&msg);
-                    yield Ok(Event::default().data(content));
+                Ok(event) => {
+                    if !event.matches(&filter) {
+                        continue;
+                    }
+                    yield Ok(to_sse_event(&event));
                 }
-                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
-                    continue;
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    // The broadcast channel (capacity 256) dropped frames
+                    // this client couldn't keep up with — same situation as
+                    // a `Last-Event-ID` older than the replay buffer, so it
+                    // gets the same gap frame rather than silently resuming
+                    // mid-stream with a hole in it.
+                    let gap_data = serde_json::json!({ "type": "gap", "skipped": skipped }).to_string();
+                    yield Ok(Event::default().data(gap_data));
                 }
                 Err(tokio::sync::broadcast::error::RecvError::Closed) => {
                     break;
@@ -85,3 +174,13 @@ fn sse_stream(
         // _guard drops here, decrementing the client count.
     }
 }
+
+fn to_sse_event(event: &BroadcastEvent) -> Event {
+    let frame = serde_json::json!({
+        "type": event.kind,
+        "payload": &*event.payload,
+    });
+    Event::default()
+        .id(event.id.to_string())
+        .data(frame.to_string())
+}
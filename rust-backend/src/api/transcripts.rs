@@ -1,19 +1,60 @@
 use std::sync::Arc;
 
+use axum::Extension;
 use axum::Json;
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use serde::Serialize;
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::db::queries::{self, TranscriptEvent};
+use crate::auth::TenantId;
+use crate::contracts::event::EVENT_TYPES;
+use crate::db::queries::{self, TranscriptEvent, TranscriptOrder, TranscriptPageFilters};
 use crate::state::AppState;
+use crate::util::truncate::inflate_metadata;
+
+#[derive(Debug, Deserialize)]
+pub struct TranscriptQuery {
+    cursor: Option<String>,
+    limit: Option<String>,
+    order: Option<String>,
+    role: Option<String>,
+    event_type: Option<String>,
+}
+
+/// Opaque `(created_at, id)` cursor: URL-safe, unpadded base64 of
+/// `"{created_at}|{id}"` so it round-trips through a query string without
+/// percent-encoding.
+fn encode_cursor(created_at: &str, id: i64) -> String {
+    BASE64.encode(format!("{created_at}|{id}"))
+}
+
+fn decode_cursor(cursor: &str) -> Option<(String, i64)> {
+    let decoded = BASE64.decode(cursor).ok()?;
+    let raw = String::from_utf8(decoded).ok()?;
+    let (created_at, id) = raw.rsplit_once('|')?;
+    Some((created_at.to_string(), id.parse().ok()?))
+}
+
+/// `event_type`s that map to `role` under `map_role`, so the `?role=` filter
+/// can push down into the SQL `WHERE` clause instead of being applied after
+/// `LIMIT` already capped the page.
+fn event_types_for_role(role: &str) -> Vec<String> {
+    EVENT_TYPES
+        .iter()
+        .filter(|event_type| map_role(event_type) == role)
+        .map(|event_type| event_type.to_string())
+        .collect()
+}
 
 #[derive(Debug, Serialize)]
 struct TranscriptResponse {
     session_id: String,
     entries: Vec<TranscriptEntry>,
+    next_cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -64,7 +105,9 @@ fn scalar_to_string(value: &Value) -> Option<String> {
 }
 
 fn extract_detail(event: &TranscriptEvent) -> Option<String> {
-    let meta: Value = serde_json::from_str(&event.metadata).ok()?;
+    // `inflate_metadata` transparently reverses a compressed-metadata wrapper
+    // so a truncated payload doesn't lose detail extraction for readers.
+    let meta = inflate_metadata(&event.metadata);
     let meta_obj = meta.as_object()?;
 
     if event.event_type == "user_prompt"
@@ -135,14 +178,82 @@ fn to_entry(event: TranscriptEvent) -> TranscriptEntry {
     }
 }
 
-/// GET /api/sessions/:id/transcript
+fn parse_i64(input: Option<&str>) -> Option<i64> {
+    input.and_then(|raw| raw.parse::<i64>().ok())
+}
+
+/// GET /api/sessions/:id/transcript — cursor-paginated; `?cursor=` resumes
+/// from the opaque token returned as `next_cursor` on the previous page,
+/// `?limit=` bounds the page size (default/max `Config::max_feed`),
+/// `?order=asc|desc` picks direction, and `?role=`/`?event_type=` filter
+/// which entries come back. A first page (no `?cursor=`) that comes back
+/// empty (404) means no event of the session passed the filters — with no
+/// `?role=`/`?event_type=`, that the session has no events; an empty later
+/// page just means pagination reached the end. Scoped to the caller's tenant — a
+/// `session_id` belonging to another tenant reads back as an empty first
+/// page, same as a session with no events.
 pub async fn session_transcript_handler(
     State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
     Path(session_id): Path<String>,
+    Query(query): Query<TranscriptQuery>,
 ) -> impl IntoResponse {
-    let db = state.db.lock().await;
-    match queries::get_session_transcript(&db, &session_id) {
-        Ok(events) if events.is_empty() => (
+    let order = match query.order.as_deref() {
+        Some("desc") => TranscriptOrder::Desc,
+        _ => TranscriptOrder::Asc,
+    };
+    let after = match query.cursor.as_deref().map(decode_cursor) {
+        Some(Some(cursor)) => Some(cursor),
+        Some(None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Invalid cursor" })),
+            )
+                .into_response();
+        }
+        None => None,
+    };
+    let event_types = match (&query.role, &query.event_type) {
+        (None, None) => None,
+        (role, event_type) => {
+            let mut allowed = role.as_deref().map(event_types_for_role);
+            if let Some(event_type) = event_type {
+                allowed = Some(match allowed {
+                    Some(from_role) => from_role
+                        .into_iter()
+                        .filter(|t| t == event_type)
+                        .collect(),
+                    None => vec![event_type.clone()],
+                });
+            }
+            allowed
+        }
+    };
+
+    let max_feed = state.config.max_feed as i64;
+    let limit = parse_i64(query.limit.as_deref())
+        .filter(|&l| l > 0)
+        .unwrap_or(max_feed)
+        .min(max_feed);
+
+    let db = match state.read_conn_blocking().await {
+        Ok(db) => db,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "internal server error" })),
+            )
+                .into_response();
+        }
+    };
+
+    let page_filters = TranscriptPageFilters {
+        after,
+        order,
+        event_types,
+    };
+    match queries::get_session_transcript_page(&db, &session_id, &page_filters, limit, &tenant.0) {
+        Ok(events) if events.is_empty() && page_filters.after.is_none() => (
             StatusCode::NOT_FOUND,
             Json(TranscriptError {
                 error: "No transcript data for this session",
@@ -150,12 +261,18 @@ pub async fn session_transcript_handler(
         )
             .into_response(),
         Ok(events) => {
+            let next_cursor = if events.len() == limit as usize {
+                events.last().map(|e| encode_cursor(&e.created_at, e.id))
+            } else {
+                None
+            };
             let entries = events.into_iter().map(to_entry).collect();
             (
                 StatusCode::OK,
                 Json(TranscriptResponse {
                     session_id,
                     entries,
+                    next_cursor,
                 }),
             )
                 .into_response()
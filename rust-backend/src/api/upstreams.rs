@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::State;
+use serde::Serialize;
+
+use crate::state::AppState;
+
+#[derive(Serialize)]
+pub struct UpstreamHealth {
+    pub url: String,
+    pub connected: bool,
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct UpstreamsResponse {
+    pub upstreams: Vec<UpstreamHealth>,
+}
+
+/// GET /api/upstreams — relay mode's configured upstreams and whether each
+/// one's `relay::run_relay_client` task is currently connected. Empty
+/// `upstreams` means relay mode isn't configured, same as an empty
+/// `Config::upstreams`.
+pub async fn upstreams_handler(State(state): State<Arc<AppState>>) -> Json<UpstreamsResponse> {
+    let upstreams = state
+        .relay_status
+        .iter()
+        .map(|status| UpstreamHealth {
+            url: status.url.clone(),
+            connected: status.is_connected(),
+            last_error: status.last_error(),
+        })
+        .collect();
+    Json(UpstreamsResponse { upstreams })
+}
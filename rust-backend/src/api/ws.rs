@@ -0,0 +1,176 @@
+use std::sync::Arc;
+
+use axum::Extension;
+use axum::extract::State;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::IntoResponse;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tracing::warn;
+
+use crate::auth::TenantId;
+use crate::contracts::event::NormalizeResult;
+use crate::contracts::validation::normalize_from_value;
+use crate::db::queries;
+use crate::sse::hub::SseFilter;
+use crate::state::AppState;
+use crate::util::truncate::truncate_metadata;
+
+/// Inbound control messages a WS client can send alongside (or instead of)
+/// plain HTTP ingest. Matches the same externally-tagged shape the dashboard
+/// already uses for its two cases: `{"subscribe": {...}}` narrows the live
+/// feed to this socket's own `SseFilter`, `{"event": {...}}` ingests a single
+/// event over the socket rather than POSTing `/api/events`.
+#[derive(Debug, Deserialize)]
+enum ClientMessage {
+    #[serde(rename = "subscribe")]
+    Subscribe(WsSubscribe),
+    #[serde(rename = "event")]
+    Event(Value),
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct WsSubscribe {
+    session_id: Option<String>,
+    agent_type: Option<String>,
+    event_type: Option<String>,
+}
+
+/// GET /api/ws — WebSocket counterpart to `/api/stream`. Pushes the same
+/// `{"type": ..., "payload": ...}` frames as JSON text messages and accepts
+/// the same subscription filter, but over a persistent socket instead of an
+/// SSE response. Reuses `SseHub::subscribe` so the `sse_clients` health
+/// counter covers both transports without any renaming.
+pub async fn ws_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(tenant): Extension<TenantId>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_socket(socket, state, tenant))
+}
+
+async fn handle_socket(socket: WebSocket, state: Arc<AppState>, tenant: TenantId) {
+    let Some(client) = state.sse_hub.subscribe(tenant.0.clone()) else {
+        // Max clients reached — there's no way to reject with a status code
+        // after the upgrade has already completed, so just close the socket.
+        let _ = socket.close().await;
+        return;
+    };
+
+    let (mut sink, mut source) = socket.split();
+    let filter_base = client.filter.clone();
+    let (mut rx, _guard) = client.into_parts();
+    let mut filter = filter_base;
+
+    let connected = json!({
+        "type": "connected",
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    });
+    if sink.send(Message::Text(connected.to_string())).await.is_err() {
+        return;
+    }
+
+    let mut keepalive = tokio::time::interval(std::time::Duration::from_millis(
+        state.config.sse_heartbeat_ms,
+    ));
+    keepalive.tick().await; // first tick fires immediately; skip it
+
+    loop {
+        tokio::select! {
+            _ = keepalive.tick() => {
+                if sink.send(Message::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+            }
+            broadcast = rx.recv() => {
+                match broadcast {
+                    Ok(event) => {
+                        if !event.matches(&filter) {
+                            continue;
+                        }
+                        let frame = json!({ "type": event.kind, "payload": &*event.payload });
+                        if sink.send(Message::Text(frame.to_string())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            incoming = source.next() => {
+                match incoming {
+                    Some(Ok(Message::Text(text))) => {
+                        handle_client_message(&state, &tenant, &text, &mut filter).await;
+                    }
+                    Some(Ok(Message::Ping(payload))) => {
+                        if sink.send(Message::Pong(payload)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        warn!("websocket receive error: {e}");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    // _guard drops here, decrementing the shared client count.
+}
+
+/// Apply one inbound text message: either update the socket's filter or
+/// validate and insert an event into the same `events` table `POST
+/// /api/events` writes to. Unlike that route, a socket-delivered event is
+/// not broadcast on `sse_hub`, counted in `ingest_counters`, republished to
+/// NATS, or stored in chunks when oversized (it is truncated). A `subscribe`
+/// message only ever touches the narrowing fields, not `filter.tenant_id` —
+/// a client can't widen its subscription past the tenant its key was
+/// authenticated for.
+async fn handle_client_message(
+    state: &Arc<AppState>,
+    tenant: &TenantId,
+    text: &str,
+    filter: &mut SseFilter,
+) {
+    let parsed: Result<ClientMessage, _> = serde_json::from_str(text);
+    match parsed {
+        Ok(ClientMessage::Subscribe(sub)) => {
+            filter.session_id = sub.session_id;
+            filter.agent_type = sub.agent_type;
+            filter.event_type = sub.event_type;
+        }
+        Ok(ClientMessage::Event(body)) => {
+            ingest_event_over_ws(state, tenant, body).await;
+        }
+        Err(e) => {
+            warn!("ignoring malformed websocket message: {e}");
+        }
+    }
+}
+
+async fn ingest_event_over_ws(state: &Arc<AppState>, tenant: &TenantId, body: Value) {
+    let NormalizeResult::Ok { event } = normalize_from_value(body, &state.config.ingest_validation) else {
+        return;
+    };
+
+    let max_kb = state.config.max_payload_kb;
+    let truncated = truncate_metadata(&event.metadata, max_kb);
+    let params =
+        super::events::insert_params(&event, &truncated.value, truncated.truncated, &tenant.0);
+
+    let db = match state.write_conn_blocking().await {
+        Ok(db) => db,
+        Err(e) => {
+            warn!("failed to check out a database connection for websocket ingest: {e}");
+            return;
+        }
+    };
+    match queries::insert_event(&db, &params) {
+        Ok(Some(_row)) => state.notify_new_events.notify_waiters(),
+        Ok(None) => {}
+        Err(e) => warn!("websocket insert_event error: {e}"),
+    }
+}
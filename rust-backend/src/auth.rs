@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use rusqlite::Connection;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+
+use crate::db::pool::PoolError;
+use crate::db::queries;
+use crate::state::AppState;
+
+/// The tenant every request is attributed to when no multi-tenant auth is
+/// configured — either because no keys exist at all (auth is fully open) or
+/// because a key was registered without an explicit tenant. Every
+/// deployment that predates multi-tenancy has every session, event, and key
+/// tagged with this one tenant, so its `/api/stats`/`/api/stream`/etc.
+/// behavior is unchanged by tenant scoping existing at all. See the scope
+/// note on `db::store::Store`.
+pub const DEFAULT_TENANT: &str = "default";
+
+/// The tenant a request's API key is attributed to, inserted into the
+/// request extensions by `require_read`/`require_write` and read back out
+/// by ingest and SSE/WebSocket subscribe handlers — see
+/// `api::events::insert_params`, `api::stream::stream_handler`,
+/// `api::ws::ws_handler`. `Clone` rather than `Copy` since it owns the
+/// tenant string rather than borrowing it.
+#[derive(Debug, Clone)]
+pub struct TenantId(pub String);
+
+impl Default for TenantId {
+    fn default() -> Self {
+        Self(DEFAULT_TENANT.to_string())
+    }
+}
+
+/// Access level granted by an API key. A `Write` key also satisfies a
+/// `Read` requirement; a `Read` key only satisfies `Read`. `Admin` is its
+/// own, stricter tier — a plain `Write` key (the kind every ingest
+/// integration holds) must not be able to mint or revoke API keys, so
+/// `Admin` satisfies an `Admin` or a `Read` requirement, but not `Write`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyScope {
+    Read,
+    Write,
+    Admin,
+}
+
+impl KeyScope {
+    fn permits(self, required: KeyScope) -> bool {
+        match required {
+            KeyScope::Read => true,
+            KeyScope::Write => self == KeyScope::Write,
+            KeyScope::Admin => self == KeyScope::Admin,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            KeyScope::Read => "read",
+            KeyScope::Write => "write",
+            KeyScope::Admin => "admin",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "read" => Some(Self::Read),
+            "write" => Some(Self::Write),
+            "admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+}
+
+/// In-memory lookup of hashed API key -> (scope, tenant). Loaded from the
+/// `api_keys` table at startup (seeded from any `AGENTMONITOR_API_KEYS`-
+/// configured keys, see `Config::from_env`) and refreshed after every admin
+/// mutation, so a request never has to wait on the `db` mutex just to check
+/// a key.
+pub struct AuthStore {
+    keys: RwLock<HashMap<String, (KeyScope, String)>>,
+}
+
+impl AuthStore {
+    pub fn empty() -> Self {
+        Self { keys: RwLock::new(HashMap::new()) }
+    }
+
+    pub async fn reload(&self, conn: &Connection) -> rusqlite::Result<()> {
+        let active = queries::list_active_api_keys(conn)?;
+        let mut keys = self.keys.write().await;
+        keys.clear();
+        for (hash, row) in active {
+            if let Some(scope) = KeyScope::parse(&row.scope) {
+                keys.insert(hash, (scope, row.tenant_id));
+            }
+        }
+        Ok(())
+    }
+
+    /// No keys configured means auth is opt-in and not yet turned on for
+    /// this deployment (the default for local/dev use) — every request is
+    /// allowed and attributed to `DEFAULT_TENANT`. As soon as one key
+    /// exists, only a matching, non-revoked key is accepted, and the
+    /// request is attributed to that key's own tenant.
+    async fn authorize(&self, presented_key: &str, required: KeyScope) -> Option<TenantId> {
+        let keys = self.keys.read().await;
+        if keys.is_empty() {
+            return Some(TenantId::default());
+        }
+        let hash = hash_key(presented_key);
+        keys.get(&hash).and_then(|(scope, tenant_id)| {
+            scope.permits(required).then(|| TenantId(tenant_id.clone()))
+        })
+    }
+}
+
+/// Seed the `api_keys` table with any keys configured via
+/// `AGENTMONITOR_API_KEYS`, then load the in-memory lookup from it. Safe to
+/// call on every startup: existing hashes hit the `key_hash` UNIQUE
+/// constraint and are ignored.
+pub async fn bootstrap(state: &Arc<AppState>) -> Result<(), PoolError> {
+    {
+        let conn = state.write_conn().map_err(PoolError::Checkout)?;
+        for (key, scope, tenant_id) in &state.config.api_keys {
+            let hash = hash_key(key);
+            let _ = queries::create_api_key(&conn, &hash, None, scope.as_str(), tenant_id);
+        }
+    }
+    let conn = state.read_conn().map_err(PoolError::Checkout)?;
+    state.auth.reload(&conn).await.map_err(PoolError::Query)
+}
+
+pub fn hash_key(raw: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(raw.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+#[derive(Serialize)]
+struct AuthErrorBody {
+    error: &'static str,
+}
+
+fn unauthorized() -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        axum::Json(AuthErrorBody { error: "missing or invalid API key" }),
+    )
+        .into_response()
+}
+
+/// Pull the presented key out of `Authorization: Bearer <key>` or
+/// `X-API-Key: <key>`, whichever is present.
+fn extract_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| headers.get("x-api-key").and_then(|v| v.to_str().ok()))
+}
+
+async fn require_scope(
+    state: &Arc<AppState>,
+    headers: &HeaderMap,
+    required: KeyScope,
+) -> Result<TenantId, Response> {
+    let presented = extract_key(headers).unwrap_or("");
+    state.auth.authorize(presented, required).await.ok_or_else(unauthorized)
+}
+
+/// Middleware for ingest routes: only a write-scoped key is accepted. The
+/// key's tenant is inserted as a `TenantId` request extension so handlers
+/// (`api::ingest_single`, `api::ingest_batch`, ...) know which tenant to
+/// attribute the write to without re-deriving it themselves.
+pub async fn require_write(
+    State(state): State<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    match require_scope(&state, req.headers(), KeyScope::Write).await {
+        Ok(tenant) => {
+            req.extensions_mut().insert(tenant);
+            next.run(req).await
+        }
+        Err(resp) => resp,
+    }
+}
+
+/// Middleware for dashboard/read routes: a key of any scope works.
+/// Same `TenantId` extension as `require_write`, so `api::stream_handler`
+/// and `api::ws_handler` only ever see their own tenant's events.
+pub async fn require_read(
+    State(state): State<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    match require_scope(&state, req.headers(), KeyScope::Read).await {
+        Ok(tenant) => {
+            req.extensions_mut().insert(tenant);
+            next.run(req).await
+        }
+        Err(resp) => resp,
+    }
+}
+
+/// Middleware for `/api/admin/*`: only an admin-scoped key is accepted — a
+/// plain write key (the kind every ingest integration holds) must not be
+/// able to mint or revoke API keys. Same `TenantId` extension as
+/// `require_write`/`require_read`, so `create_key_handler`/
+/// `revoke_key_handler` scope their mutations to the caller's own tenant
+/// instead of trusting client-supplied tenant data.
+pub async fn require_admin(
+    State(state): State<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    match require_scope(&state, req.headers(), KeyScope::Admin).await {
+        Ok(tenant) => {
+            req.extensions_mut().insert(tenant);
+            next.run(req).await
+        }
+        Err(resp) => resp,
+    }
+}
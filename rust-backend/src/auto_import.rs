@@ -27,13 +27,29 @@ pub async fn run_auto_import_once_with_dirs(
         force: false,
         claude_dir,
         codex_dir,
+        s3: None,
         max_payload_kb,
+        watch: false,
+        watch_debounce_ms: 500,
+        export_url: None,
+        export_flush_threshold: 100,
+        sampled_hash: false,
     };
 
     let task_state = Arc::clone(&state);
-    let result = tokio::task::spawn_blocking(move || {
-        let db = task_state.db.blocking_lock();
-        run_import(&db, &options)
+    let result = tokio::task::spawn_blocking(move || match task_state.write_conn() {
+        Ok(db) => run_import(&db, &options),
+        Err(err) => {
+            error!("auto-import couldn't check out a database connection: {err}");
+            ImportResult {
+                files: vec![],
+                total_files: 0,
+                total_events_found: 0,
+                total_events_imported: 0,
+                total_duplicates: 0,
+                skipped_files: 0,
+            }
+        }
     })
     .await;
 
@@ -68,6 +84,7 @@ pub async fn run_auto_import_once_with_dirs(
                 }),
             );
         }
+        state.notify_new_events.notify_waiters();
     }
 
     result
@@ -1,26 +1,45 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, NaiveDate, Utc};
 
 use agentmonitor_rs::config::Config;
 use agentmonitor_rs::db;
-use agentmonitor_rs::importer::{ImportOptions, ImportSource, run_import};
+use agentmonitor_rs::db::backup;
+use agentmonitor_rs::db::queries;
+use agentmonitor_rs::importer::s3::S3Source;
+use agentmonitor_rs::importer::{ImportOptions, ImportSource, run_import_parallel, run_watch};
 
 fn main() {
     let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.iter().any(|a| a == "--recompute-costs") {
+        run_recompute_costs();
+        return;
+    }
+    if let Some(path) = flag_value(&args, "--export-backup") {
+        run_export_backup(&path);
+        return;
+    }
+    if let Some(path) = flag_value(&args, "--import-backup") {
+        run_import_backup(&path);
+        return;
+    }
+    if args.iter().any(|a| a == "--rekey") {
+        run_rekey();
+        return;
+    }
+
     match parse_cli(&args) {
         Ok(mut options) => {
             let config = Config::from_env();
-            let conn = match db::initialize(&config.db_path) {
-                Ok(conn) => conn,
-                Err(err) => {
-                    eprintln!("failed to initialize DB: {err}");
-                    std::process::exit(1);
-                }
-            };
+            let conn = open_writer(&config);
 
             options.max_payload_kb = config.max_payload_kb;
-            let result = run_import(&conn, &options);
+            // Worker threads open their own connections against `db_path`
+            // directly (see `run_import_parallel`); `conn` above exists to
+            // make sure the database is created and migrated first, and is
+            // kept around for `--watch` below.
+            let result = run_import_parallel(&config.db_path, config.db_passphrase.as_deref(), &options);
 
             println!("Import complete.");
             println!("  Files processed:   {}", result.total_files);
@@ -28,6 +47,10 @@ fn main() {
             println!("  Events found:      {}", result.total_events_found);
             println!("  Events imported:   {}", result.total_events_imported);
             println!("  Duplicates skipped: {}", result.total_duplicates);
+
+            if options.watch {
+                run_watch(&conn, &options);
+            }
         }
         Err(err) => {
             eprintln!("{err}");
@@ -37,6 +60,114 @@ fn main() {
     }
 }
 
+/// Returns the value following `flag` in `args`, e.g. `flag_value(args,
+/// "--export-backup")` for `... --export-backup ./out.db ...`. Passphrases
+/// are deliberately never accepted this way — only read from env vars below
+/// — so they never land in argv or shell history.
+fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).cloned()
+}
+
+/// Writes a passphrase-encrypted copy of the live database to `dest_path`
+/// (see `db::backup::export_encrypted_backup`), keyed with
+/// `AGENTMONITOR_DB_BACKUP_PASSPHRASE`.
+fn run_export_backup(dest_path: &str) {
+    let config = Config::from_env();
+    let dest_passphrase = require_env("AGENTMONITOR_DB_BACKUP_PASSPHRASE");
+    let conn = open_writer(&config);
+
+    match backup::export_encrypted_backup(&conn, Path::new(dest_path), &dest_passphrase) {
+        Ok(()) => println!("Encrypted backup written to {dest_path}"),
+        Err(err) => {
+            eprintln!("failed to export encrypted backup: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Restores `agents`/`sessions`/`events` from a passphrase-encrypted archive
+/// at `src_path` (see `db::backup::import_encrypted_backup`), keyed with
+/// `AGENTMONITOR_DB_BACKUP_PASSPHRASE`. Replaces the live database's rows in
+/// those three tables.
+fn run_import_backup(src_path: &str) {
+    let config = Config::from_env();
+    let src_passphrase = require_env("AGENTMONITOR_DB_BACKUP_PASSPHRASE");
+    let conn = open_writer(&config);
+
+    match backup::import_encrypted_backup(&conn, Path::new(src_path), &src_passphrase) {
+        Ok(()) => println!("Restored from encrypted backup at {src_path}"),
+        Err(err) => {
+            eprintln!("failed to import encrypted backup: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Rotates the live database's SQLCipher passphrase from
+/// `AGENTMONITOR_DB_PASSPHRASE` to `AGENTMONITOR_DB_NEW_PASSPHRASE` (see
+/// `db::backup::rekey`). The caller is responsible for updating
+/// `AGENTMONITOR_DB_PASSPHRASE` in the server's environment afterward.
+fn run_rekey() {
+    let config = Config::from_env();
+    let new_passphrase = require_env("AGENTMONITOR_DB_NEW_PASSPHRASE");
+    let conn = open_writer(&config);
+
+    match backup::rekey(&conn, &new_passphrase) {
+        Ok(()) => println!("Passphrase rotated. Update AGENTMONITOR_DB_PASSPHRASE before the next start."),
+        Err(err) => {
+            eprintln!("failed to rekey database: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn require_env(key: &str) -> String {
+    std::env::var(key).unwrap_or_else(|_| {
+        eprintln!("{key} must be set");
+        std::process::exit(1);
+    })
+}
+
+fn open_writer(config: &Config) -> db::pool::PooledConn {
+    let pool = match db::pool::initialize(&config.db_path, config.db_pool_size, config.db_passphrase.as_deref()) {
+        Ok(pool) => pool,
+        Err(err) => {
+            eprintln!("failed to initialize DB: {err}");
+            std::process::exit(1);
+        }
+    };
+    match pool.writer.get() {
+        Ok(conn) => conn,
+        Err(err) => {
+            eprintln!("failed to check out a database connection: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Re-derive `cost_usd` for already-imported rows under the current pricing
+/// table (see `queries::recompute_costs`), instead of running an import.
+/// Idempotent: a row already stamped with the active pricing version is
+/// left alone, so this is safe to rerun after editing `AGENTMONITOR_PRICING_FILE`
+/// or on every deploy.
+fn run_recompute_costs() {
+    let config = Config::from_env();
+    let conn = open_writer(&config);
+
+    match queries::recompute_costs(&conn) {
+        Ok(summary) => {
+            println!("Recompute complete.");
+            println!("  Pricing version: {}", summary.pricing_version);
+            println!("  Rows scanned:    {}", summary.rows_scanned);
+            println!("  Rows updated:    {}", summary.rows_updated);
+        }
+        Err(err) => {
+            eprintln!("failed to recompute costs: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
 fn parse_cli(args: &[String]) -> Result<ImportOptions, String> {
     let mut source = ImportSource::All;
     let mut from: Option<DateTime<Utc>> = None;
@@ -45,6 +176,12 @@ fn parse_cli(args: &[String]) -> Result<ImportOptions, String> {
     let mut force = false;
     let mut claude_dir: Option<PathBuf> = None;
     let mut codex_dir: Option<PathBuf> = None;
+    let mut s3: Option<S3Source> = None;
+    let mut watch = false;
+    let mut watch_debounce_ms = 500u64;
+    let mut export_url: Option<String> = None;
+    let mut export_flush_threshold = 100usize;
+    let mut sampled_hash = false;
 
     let mut i = 0usize;
     while i < args.len() {
@@ -59,6 +196,9 @@ fn parse_cli(args: &[String]) -> Result<ImportOptions, String> {
                     "claude-code" => ImportSource::ClaudeCode,
                     "codex" => ImportSource::Codex,
                     "all" => ImportSource::All,
+                    other if agentmonitor_rs::importer::available_source_ids().contains(&other) => {
+                        ImportSource::Other(other.to_string())
+                    }
                     _ => return Err(format!("unsupported --source value: {value}")),
                 };
                 i += 1;
@@ -75,6 +215,15 @@ fn parse_cli(args: &[String]) -> Result<ImportOptions, String> {
             }
             "--dry-run" => dry_run = true,
             "--force" => force = true,
+            "--sampled-hash" => sampled_hash = true,
+            "--watch" => watch = true,
+            "--watch-debounce-ms" => {
+                let value = args.get(i + 1).ok_or("--watch-debounce-ms requires a value")?;
+                watch_debounce_ms = value
+                    .parse()
+                    .map_err(|_| format!("invalid --watch-debounce-ms value: {value}"))?;
+                i += 1;
+            }
             "--claude-dir" => {
                 let value = args.get(i + 1).ok_or("--claude-dir requires a path")?;
                 claude_dir = Some(PathBuf::from(value));
@@ -85,6 +234,30 @@ fn parse_cli(args: &[String]) -> Result<ImportOptions, String> {
                 codex_dir = Some(PathBuf::from(value));
                 i += 1;
             }
+            "--s3" => {
+                let value = args.get(i + 1).ok_or("--s3 requires an s3://bucket/prefix URL")?;
+                s3 = Some(S3Source::parse(value).ok_or_else(|| {
+                    format!(
+                        "invalid --s3 value: {value} (expected s3://bucket/prefix, with \
+                         AGENTMONITOR_S3_ACCESS_KEY_ID/AGENTMONITOR_S3_SECRET_ACCESS_KEY set)"
+                    )
+                })?);
+                i += 1;
+            }
+            "--export-url" => {
+                let value = args.get(i + 1).ok_or("--export-url requires a URL")?;
+                export_url = Some(value.clone());
+                i += 1;
+            }
+            "--export-flush-threshold" => {
+                let value = args
+                    .get(i + 1)
+                    .ok_or("--export-flush-threshold requires a value")?;
+                export_flush_threshold = value
+                    .parse()
+                    .map_err(|_| format!("invalid --export-flush-threshold value: {value}"))?;
+                i += 1;
+            }
             unknown => return Err(format!("unknown argument: {unknown}")),
         }
         i += 1;
@@ -98,7 +271,13 @@ fn parse_cli(args: &[String]) -> Result<ImportOptions, String> {
         force,
         claude_dir,
         codex_dir,
+        s3,
         max_payload_kb: 10,
+        sampled_hash,
+        watch,
+        watch_debounce_ms,
+        export_url,
+        export_flush_threshold,
     })
 }
 
@@ -123,7 +302,30 @@ fn print_usage() {
     println!("  --to <ISO timestamp>                Import events before this time");
     println!("  --dry-run                           Parse only, do not write DB");
     println!("  --force                             Re-import files even if unchanged");
+    println!("  --sampled-hash                       Fingerprint large files by sampling a few");
+    println!("                                       windows instead of hashing the whole file");
     println!("  --claude-dir <path>                 Override Claude logs root");
     println!("  --codex-dir <path>                  Override Codex home root");
+    println!("  --s3 <s3://bucket/prefix>            Also import Claude Code JSONL objects from this");
+    println!("                                       bucket/prefix (requires AGENTMONITOR_S3_ACCESS_KEY_ID/");
+    println!("                                       AGENTMONITOR_S3_SECRET_ACCESS_KEY; optional");
+    println!("                                       AGENTMONITOR_S3_ENDPOINT/AGENTMONITOR_S3_REGION)");
+    println!("  --watch                             After the initial import, keep running");
+    println!("                                       and re-import as log files change");
+    println!("  --watch-debounce-ms <ms>             Coalescing window for --watch (default: 500)");
+    println!("  --export-url <url>                  Also forward imported events to a remote");
+    println!("                                       collector as newline-delimited JSON");
+    println!("  --export-flush-threshold <n>         Events buffered before POSTing (default: 100)");
+    println!("  --recompute-costs                    Re-derive cost_usd for already-imported rows");
+    println!("                                       under the current pricing table, then exit");
+    println!("                                       (set AGENTMONITOR_PRICING_FILE to override rates)");
+    println!("  --export-backup <path>               Write a passphrase-encrypted copy of the DB to");
+    println!("                                       <path>, then exit (requires AGENTMONITOR_DB_BACKUP_PASSPHRASE)");
+    println!("  --import-backup <path>               Restore agents/sessions/events from an encrypted");
+    println!("                                       backup at <path>, then exit (requires AGENTMONITOR_DB_BACKUP_PASSPHRASE)");
+    println!("  --rekey                              Rotate the DB's SQLCipher passphrase, then exit");
+    println!("                                       (requires AGENTMONITOR_DB_NEW_PASSPHRASE)");
+    println!("                                       (all three require AGENTMONITOR_DB_PASSPHRASE to already");
+    println!("                                       be set so the live DB is keyed correctly first)");
     println!("  --help                              Show this help");
 }
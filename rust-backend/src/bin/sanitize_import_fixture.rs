@@ -0,0 +1,117 @@
+//! Turns a real Claude Code or Codex session log into a sanitized fixture
+//! safe to commit under `tests/fixtures/import/`: free-text fields (shell
+//! commands, file contents/paths, tool output, error messages) are replaced
+//! with deterministic placeholders, while everything the parsers in
+//! `importer` key their behavior on — `type`, timestamps, token/cost
+//! numbers, and line counts within multi-line string fields — is left
+//! untouched. Run it once against a real log, review the output, then check
+//! the result in; this isn't run as part of the test suite itself.
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use serde_json::Value;
+
+/// Fields known to carry free text in one format or the other. Each is
+/// replaced with a placeholder that preserves its line count (so Edit/Write
+/// line-count extraction still has something to count) but nothing of the
+/// original content.
+const TEXT_FIELDS: &[&str] = &[
+    "command",
+    "pattern",
+    "query",
+    "file_path",
+    "old_string",
+    "new_string",
+    "content",
+    "output",
+    "error",
+    "message",
+];
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (Some(input), Some(output)) = (args.first(), args.get(1)) else {
+        eprintln!(
+            "Usage: cargo run --manifest-path rust-backend/Cargo.toml --bin sanitize_import_fixture -- <input.jsonl> <output.jsonl>"
+        );
+        return ExitCode::FAILURE;
+    };
+
+    let raw = match fs::read_to_string(input) {
+        Ok(raw) => raw,
+        Err(err) => {
+            eprintln!("failed to read {input}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut sanitized_lines = Vec::new();
+    for (i, line) in raw.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Value>(line) {
+            Ok(mut value) => {
+                sanitize_value(&mut value);
+                sanitized_lines.push(value.to_string());
+            }
+            Err(err) => eprintln!("skipping unparseable line {}: {err}", i + 1),
+        }
+    }
+
+    if let Err(err) = fs::write(PathBuf::from(output), sanitized_lines.join("\n") + "\n") {
+        eprintln!("failed to write {output}: {err}");
+        return ExitCode::FAILURE;
+    }
+
+    println!("Wrote {} sanitized line(s) to {output}", sanitized_lines.len());
+    ExitCode::SUCCESS
+}
+
+/// Recursively replaces known free-text fields in place; every other field
+/// (type, timestamps, usage counts, costUSD, ids, ...) passes through
+/// unchanged so the parsers' structural/numeric behavior is still exercised.
+fn sanitize_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, entry) in map.iter_mut() {
+                if TEXT_FIELDS.contains(&key.as_str()) && entry.is_string() {
+                    sanitize_text_field(key, entry);
+                } else if key == "cwd" && entry.is_string() {
+                    *entry = Value::String("/home/dev/sanitized-project".to_string());
+                } else {
+                    sanitize_value(entry);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                sanitize_value(item);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replaces a free-text string with a placeholder that keeps the same
+/// number of lines (so `lines_added`/`lines_removed`/line-based patch
+/// parsing still has realistic material to count), numbered so a diff
+/// against the original's shape is still visible if a reviewer wants one.
+fn sanitize_text_field(key: &str, entry: &mut Value) {
+    let Some(text) = entry.as_str() else {
+        return;
+    };
+    let placeholder = text
+        .lines()
+        .enumerate()
+        .map(|(i, _)| format!("placeholder-{key}-line-{}", i + 1))
+        .collect::<Vec<_>>()
+        .join("\n");
+    *entry = Value::String(if placeholder.is_empty() {
+        format!("placeholder-{key}")
+    } else {
+        placeholder
+    });
+}
@@ -0,0 +1,186 @@
+//! Optional cluster broadcasting: when `Config::peer_urls` is configured,
+//! the `stats`/`session_update` envelope a local `run_stats_broadcast_once`/
+//! `run_idle_check_once` already fans out over this node's own `sse_hub` is
+//! also forwarded to every peer's `POST /api/cluster/broadcast`, so a
+//! dashboard connected anywhere in the cluster sees the same updates
+//! regardless of which node the agent reporting them actually talked to.
+//! Each node still keeps its own `sse_hub` — `Broadcasting` only decides
+//! what gets fanned into it from elsewhere. A message-id dedupe set (same
+//! `Mutex<HashMap<_, Instant>>` TTL shape as `notifier::Notifier`'s) stops a
+//! broadcast from looping forever in a cluster whose peer lists overlap:
+//! a message this node has already forwarded or received is skipped, not
+//! re-sent.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::state::AppState;
+
+/// How long a message id is remembered in the dedupe set before it's
+/// forgotten — long enough to absorb reasonable delay across every hop in
+/// a small peer mesh, short enough that the set doesn't grow unbounded.
+const DEDUPE_WINDOW: Duration = Duration::from_secs(300);
+
+#[derive(Debug)]
+pub enum BroadcastError {
+    Send(reqwest::Error),
+    Status(reqwest::StatusCode),
+}
+
+impl fmt::Display for BroadcastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Send(err) => write!(f, "forward failed: {err}"),
+            Self::Status(status) => write!(f, "peer returned {status}"),
+        }
+    }
+}
+
+impl std::error::Error for BroadcastError {}
+
+/// One `stats`/`session_update` broadcast gossiped between nodes.
+/// `message_id` is `<node_id>:<sequence>` from whichever node first
+/// generated it, carried unchanged through every re-forward so the dedupe
+/// set recognizes it no matter how many hops it's taken.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterMessage {
+    pub message_id: String,
+    pub event_type: String,
+    pub payload: Value,
+}
+
+/// Forwards local broadcasts to `Config::peer_urls` and re-forwards
+/// whatever a peer sends this node, deduping by `message_id` so a message
+/// is never fanned into this node's own `sse_hub` (or sent onward) twice.
+pub struct Broadcasting {
+    node_id: String,
+    peers: Vec<String>,
+    api_key: Option<String>,
+    client: reqwest::Client,
+    seq: AtomicU64,
+    seen: Mutex<HashMap<String, Instant>>,
+}
+
+impl Broadcasting {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            node_id: config.node_id.clone(),
+            peers: config.peer_urls.clone(),
+            api_key: config.cluster_api_key.clone(),
+            client: reqwest::Client::new(),
+            seq: AtomicU64::new(0),
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// True when no peers are configured — lets a call site skip building
+    /// the forwarded message entirely.
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    /// Forward a broadcast this node originated (generating a fresh
+    /// `message_id`) to every configured peer. Call after fanning `payload`
+    /// out over the local `sse_hub` — unlike `receive`, this never touches
+    /// `sse_hub` itself, since the local fan-out already happened.
+    pub async fn forward_local(&self, event_type: &str, payload: &Value) {
+        if self.is_empty() {
+            return;
+        }
+        let message_id = format!("{}:{}", self.node_id, self.seq.fetch_add(1, Ordering::Relaxed));
+        self.mark_seen(&message_id);
+        let message = ClusterMessage {
+            message_id,
+            event_type: event_type.to_string(),
+            payload: payload.clone(),
+        };
+        self.send_to_peers(&message).await;
+    }
+
+    /// Handle a `ClusterMessage` a peer posted to `/api/cluster/broadcast`:
+    /// fan it into this node's own `sse_hub` unless it's already been seen
+    /// (either forwarded by this node earlier or received once already),
+    /// then re-forward it so a peer mesh that isn't fully connected still
+    /// reaches every node. Returns `false` for a message already seen, so
+    /// the handler can skip touching `sse_hub` a second time.
+    pub async fn receive(&self, state: &Arc<AppState>, message: ClusterMessage) -> bool {
+        if self.is_seen(&message.message_id) {
+            return false;
+        }
+        self.mark_seen(&message.message_id);
+        state.sse_hub.broadcast(&message.event_type, &message.payload);
+        self.send_to_peers(&message).await;
+        true
+    }
+
+    fn is_seen(&self, message_id: &str) -> bool {
+        let seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.contains_key(message_id)
+    }
+
+    fn mark_seen(&self, message_id: &str) {
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.retain(|_, seen_at| now.duration_since(*seen_at) < DEDUPE_WINDOW);
+        seen.insert(message_id.to_string(), now);
+    }
+
+    async fn send_to_peers(&self, message: &ClusterMessage) {
+        for peer in &self.peers {
+            if let Err(err) = self.send_to_peer(peer, message).await {
+                warn!("cluster: forward to {peer} failed: {err}");
+            }
+        }
+    }
+
+    async fn send_to_peer(&self, peer: &str, message: &ClusterMessage) -> Result<(), BroadcastError> {
+        let url = format!("{}/api/cluster/broadcast", peer.trim_end_matches('/'));
+        let mut request = self.client.post(&url).json(message);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("x-api-key", api_key);
+        }
+        let response = request.send().await.map_err(BroadcastError::Send)?;
+        if !response.status().is_success() {
+            return Err(BroadcastError::Status(response.status()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_broadcasting(peers: Vec<String>) -> Broadcasting {
+        Broadcasting {
+            node_id: "node-a".to_string(),
+            peers,
+            api_key: None,
+            client: reqwest::Client::new(),
+            seq: AtomicU64::new(0),
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[test]
+    fn is_empty_reflects_configured_peers() {
+        assert!(test_broadcasting(Vec::new()).is_empty());
+        assert!(!test_broadcasting(vec!["http://peer-b:3142".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn dedupe_recognizes_a_message_already_marked_seen() {
+        let broadcasting = test_broadcasting(Vec::new());
+        assert!(!broadcasting.is_seen("node-b:1"));
+        broadcasting.mark_seen("node-b:1");
+        assert!(broadcasting.is_seen("node-b:1"));
+    }
+}
@@ -1,27 +1,280 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
 use serde_json::Value;
 
 use super::event::{
     EVENT_STATUSES, EVENT_TYPES, NormalizeResult, NormalizedEvent, RawIngestEvent, ValidationError,
 };
 
+/// Ingest validation policy: which `event_type`/`status` values are accepted,
+/// which `agent_type`s are allowed, which otherwise-optional fields become
+/// mandatory for a given `event_type`, and the upper bounds a payload's
+/// tokens/cost must stay under. `normalize_ingest_event`/`normalize_from_value`
+/// take one by reference instead of consulting `EVENT_TYPES`/`EVENT_STATUSES`
+/// directly, so a deployment that wants a stricter (or looser) contract than
+/// the built-in one doesn't have to fork this module.
+///
+/// `IngestValidation::default()` accepts every `EVENT_TYPES`/`EVENT_STATUSES`
+/// entry and any non-empty `agent_type`, with no upper bounds on tokens/cost
+/// — except for the built-in `required_fields` entries (`tool_use` needs
+/// `tool_name`, completion events carrying a `model` need it present), which
+/// close gaps the validator otherwise missed silently. Every call site that
+/// doesn't build its own policy should pass `&IngestValidation::default()`.
+#[derive(Debug, Clone)]
+pub struct IngestValidation {
+    event_types: Vec<String>,
+    event_statuses: Vec<String>,
+    /// `None` means any non-empty `agent_type` is accepted, matching today's
+    /// behavior. `Some` restricts to the given set.
+    agent_types: Option<Vec<String>>,
+    max_tokens: Option<i64>,
+    max_cost_usd: Option<f64>,
+    /// Keyed by `event_type`; names the otherwise-optional fields that
+    /// become mandatory for that type. Borrows the "required claims" idea
+    /// from `jsonwebtoken`'s `required_spec_claims` — field names here must
+    /// match one of the optional `NormalizedEvent` fields `normalize_ingest_event`
+    /// computes (`tool_name`, `branch`, `project`, `model`, `duration_ms`,
+    /// `cost_usd`, `client_timestamp`, `source`, `event_id`); any other name
+    /// is silently treated as present, so it never rejects an event.
+    required_fields: HashMap<String, HashSet<String>>,
+    /// How many seconds of clock skew to tolerate before a `client_timestamp`
+    /// in the future is rejected — mirrors the `leeway` field on
+    /// `jsonwebtoken`'s `Validation`. Defaults to 60.
+    timestamp_leeway_secs: i64,
+    /// Reject a `client_timestamp` older than this many seconds. `None`
+    /// (the default) accepts events of any age.
+    max_timestamp_age_secs: Option<i64>,
+}
+
+impl Default for IngestValidation {
+    fn default() -> Self {
+        Self {
+            event_types: EVENT_TYPES.iter().map(|s| s.to_string()).collect(),
+            event_statuses: EVENT_STATUSES.iter().map(|s| s.to_string()).collect(),
+            agent_types: None,
+            max_tokens: None,
+            max_cost_usd: None,
+            required_fields: HashMap::from([
+                ("tool_use".to_string(), HashSet::from(["tool_name".to_string()])),
+                ("llm_response".to_string(), HashSet::from(["model".to_string()])),
+                ("response".to_string(), HashSet::from(["model".to_string()])),
+            ]),
+            timestamp_leeway_secs: 60,
+            max_timestamp_age_secs: None,
+        }
+    }
+}
+
+impl IngestValidation {
+    /// Restrict `agent_type` to exactly this set instead of accepting any
+    /// non-empty string.
+    pub fn with_agent_types(mut self, agent_types: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.agent_types = Some(agent_types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Add extra accepted `event_type` values on top of `EVENT_TYPES`.
+    pub fn with_extra_event_types(mut self, event_types: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.event_types.extend(event_types.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add extra accepted `status` values on top of `EVENT_STATUSES`.
+    pub fn with_extra_statuses(mut self, statuses: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.event_statuses.extend(statuses.into_iter().map(Into::into));
+        self
+    }
+
+    /// Reject payloads whose `tokens_in`/`tokens_out` exceed this bound.
+    pub fn with_max_tokens(mut self, max_tokens: i64) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Reject payloads whose `cost_usd` exceeds this bound.
+    pub fn with_max_cost_usd(mut self, max_cost_usd: f64) -> Self {
+        self.max_cost_usd = Some(max_cost_usd);
+        self
+    }
+
+    /// Mark `fields` as mandatory for events of `event_type`, on top of the
+    /// built-in `tool_use`/`llm_response`/`response` entries: they are added
+    /// to whatever that type already requires, never replacing it.
+    /// Field names must match one of the optional `NormalizedEvent` fields —
+    /// see the `required_fields` doc comment above.
+    pub fn with_required_fields(
+        mut self,
+        event_type: impl Into<String>,
+        fields: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.required_fields
+            .entry(event_type.into())
+            .or_default()
+            .extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    /// Override the default 60s clock-skew leeway for future-dated
+    /// `client_timestamp`s.
+    pub fn with_timestamp_leeway_secs(mut self, leeway_secs: i64) -> Self {
+        self.timestamp_leeway_secs = leeway_secs;
+        self
+    }
+
+    /// Reject `client_timestamp`s older than `max_age_secs`.
+    pub fn with_max_timestamp_age_secs(mut self, max_age_secs: i64) -> Self {
+        self.max_timestamp_age_secs = Some(max_age_secs);
+        self
+    }
+
+    fn check_agent_type(&self, agent_type: &str, errors: &mut Vec<ValidationError>) {
+        if let Some(allowed) = &self.agent_types {
+            if !agent_type.is_empty() && !allowed.iter().any(|a| a == agent_type) {
+                errors.push(ValidationError {
+                    field: "agent_type".into(),
+                    message: format!("must be one of: {}", allowed.join(", ")),
+                });
+            }
+        }
+    }
+
+    fn check_event_type(&self, event_type: &str, errors: &mut Vec<ValidationError>) {
+        if !event_type.is_empty() && !self.event_types.iter().any(|t| t == event_type) {
+            errors.push(ValidationError {
+                field: "event_type".into(),
+                message: format!("must be one of: {}", self.event_types.join(", ")),
+            });
+        }
+    }
+
+    /// `present` is the set of otherwise-optional fields this payload
+    /// actually supplied, as `(field_name, is_some)` pairs — see the
+    /// `required_fields` doc comment for which names are recognized.
+    fn check_required_fields(
+        &self,
+        event_type: &str,
+        present: &[(&str, bool)],
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let Some(required) = self.required_fields.get(event_type) else {
+            return;
+        };
+        for field in required {
+            let is_present = present
+                .iter()
+                .find(|(name, _)| name == field)
+                .map(|(_, is_some)| *is_some)
+                .unwrap_or(true);
+            if !is_present {
+                errors.push(ValidationError {
+                    field: field.clone(),
+                    message: format!("is required for event_type \"{event_type}\""),
+                });
+            }
+        }
+    }
+
+    fn check_status(&self, status: &str, errors: &mut Vec<ValidationError>) {
+        if !self.event_statuses.iter().any(|s| s == status) {
+            errors.push(ValidationError {
+                field: "status".into(),
+                message: format!("must be one of: {}", self.event_statuses.join(", ")),
+            });
+        }
+    }
+
+    fn check_tokens(&self, field: &str, tokens: i64, errors: &mut Vec<ValidationError>) {
+        if let Some(max) = self.max_tokens {
+            if tokens > max {
+                errors.push(ValidationError {
+                    field: field.into(),
+                    message: format!("must not exceed {max}"),
+                });
+            }
+        }
+    }
+
+    fn check_cost(&self, cost_usd: f64, errors: &mut Vec<ValidationError>) {
+        if let Some(max) = self.max_cost_usd {
+            if cost_usd > max {
+                errors.push(ValidationError {
+                    field: "cost_usd".into(),
+                    message: format!("must not exceed {max}"),
+                });
+            }
+        }
+    }
+
+    /// Parse `value` as RFC 3339, reject it relative to `timestamp_leeway_secs`/
+    /// `max_timestamp_age_secs`, and re-serialize to a canonical UTC RFC 3339
+    /// string for storage.
+    fn check_client_timestamp(
+        &self,
+        value: &Option<Value>,
+        errors: &mut Vec<ValidationError>,
+    ) -> Option<String> {
+        match value {
+            None | Some(Value::Null) => None,
+            Some(Value::String(s)) => {
+                let Ok(parsed) = DateTime::parse_from_rfc3339(s) else {
+                    errors.push(ValidationError {
+                        field: "client_timestamp".into(),
+                        message: "unparseable: must be an RFC 3339 timestamp".into(),
+                    });
+                    return None;
+                };
+                let parsed = parsed.with_timezone(&Utc);
+                let now = Utc::now();
+
+                if (parsed - now).num_seconds() > self.timestamp_leeway_secs {
+                    errors.push(ValidationError {
+                        field: "client_timestamp".into(),
+                        message: format!(
+                            "too far in future: exceeds {}s clock-skew leeway",
+                            self.timestamp_leeway_secs
+                        ),
+                    });
+                    return None;
+                }
+
+                if let Some(max_age_secs) = self.max_timestamp_age_secs {
+                    if (now - parsed).num_seconds() > max_age_secs {
+                        errors.push(ValidationError {
+                            field: "client_timestamp".into(),
+                            message: format!("too old: exceeds max age of {max_age_secs}s"),
+                        });
+                        return None;
+                    }
+                }
+
+                Some(parsed.to_rfc3339())
+            }
+            Some(_) => {
+                errors.push(ValidationError {
+                    field: "client_timestamp".into(),
+                    message: "must be an RFC 3339 timestamp string when provided".into(),
+                });
+                None
+            }
+        }
+    }
+}
+
 /// Normalize and validate a raw ingest payload, mirroring TypeScript normalizeIngestEvent().
-pub fn normalize_ingest_event(raw: RawIngestEvent) -> NormalizeResult {
+pub fn normalize_ingest_event(raw: RawIngestEvent, validation: &IngestValidation) -> NormalizeResult {
     let mut errors = Vec::new();
 
     let session_id = get_required_string(&raw.session_id, "session_id", &mut errors);
     let agent_type = get_required_string(&raw.agent_type, "agent_type", &mut errors);
     let event_type_raw = get_required_string(&raw.event_type, "event_type", &mut errors);
 
-    // Validate event_type enum
-    if !event_type_raw.is_empty() && !EVENT_TYPES.contains(&event_type_raw.as_str()) {
-        errors.push(ValidationError {
-            field: "event_type".into(),
-            message: format!("must be one of: {}", EVENT_TYPES.join(", ")),
-        });
-    }
+    validation.check_agent_type(&agent_type, &mut errors);
+    validation.check_event_type(&event_type_raw, &mut errors);
 
     // Validate and default status
-    let status = normalize_status(&raw.status, &event_type_raw, &mut errors);
+    let status = normalize_status(&raw.status, &event_type_raw, validation, &mut errors);
 
     let event_id = get_optional_string(&raw.event_id, "event_id", &mut errors);
     let tool_name = get_optional_string(&raw.tool_name, "tool_name", &mut errors);
@@ -40,9 +293,30 @@ pub fn normalize_ingest_event(raw: RawIngestEvent) -> NormalizeResult {
         get_optional_non_negative_int(&raw.cache_write_tokens, "cache_write_tokens", &mut errors)
             .unwrap_or(0);
     let cost_usd = get_optional_non_negative_f64(&raw.cost_usd, "cost_usd", &mut errors);
-    let client_timestamp = normalize_client_timestamp(&raw.client_timestamp, &mut errors);
+    let client_timestamp = validation.check_client_timestamp(&raw.client_timestamp, &mut errors);
     let source = get_optional_string(&raw.source, "source", &mut errors);
 
+    validation.check_tokens("tokens_in", tokens_in, &mut errors);
+    validation.check_tokens("tokens_out", tokens_out, &mut errors);
+    if let Some(cost_usd) = cost_usd {
+        validation.check_cost(cost_usd, &mut errors);
+    }
+    validation.check_required_fields(
+        &event_type_raw,
+        &[
+            ("event_id", event_id.is_some()),
+            ("tool_name", tool_name.is_some()),
+            ("branch", branch.is_some()),
+            ("project", project.is_some()),
+            ("model", model.is_some()),
+            ("duration_ms", duration_ms.is_some()),
+            ("cost_usd", cost_usd.is_some()),
+            ("client_timestamp", client_timestamp.is_some()),
+            ("source", source.is_some()),
+        ],
+        &mut errors,
+    );
+
     if !errors.is_empty() {
         return NormalizeResult::Err { errors };
     }
@@ -73,7 +347,7 @@ pub fn normalize_ingest_event(raw: RawIngestEvent) -> NormalizeResult {
 
 /// Validate a raw JSON body (not yet deserialized into RawIngestEvent).
 /// Returns NormalizeResult::Err if the body is not a JSON object.
-pub fn normalize_from_value(value: Value) -> NormalizeResult {
+pub fn normalize_from_value(value: Value, validation: &IngestValidation) -> NormalizeResult {
     if !value.is_object() {
         return NormalizeResult::Err {
             errors: vec![ValidationError {
@@ -84,7 +358,7 @@ pub fn normalize_from_value(value: Value) -> NormalizeResult {
     }
 
     match serde_json::from_value::<RawIngestEvent>(value) {
-        Ok(raw) => normalize_ingest_event(raw),
+        Ok(raw) => normalize_ingest_event(raw, validation),
         Err(e) => NormalizeResult::Err {
             errors: vec![ValidationError {
                 field: "body".into(),
@@ -94,6 +368,304 @@ pub fn normalize_from_value(value: Value) -> NormalizeResult {
     }
 }
 
+/// Ceiling on the number of items `normalize_batch` accepts in one call.
+/// Independent of the HTTP-layer `Config::max_batch_size` enforced by
+/// `api::events::ingest_batch` (which also owns the atomic-transaction
+/// behavior around it) — this is the floor every caller of the pure
+/// normalization entry point gets, even one that skips that handler.
+const MAX_BATCH_SIZE: usize = 500;
+
+/// Normalized events plus per-item failures from [`normalize_batch`]. Unlike
+/// `NormalizeResult`, a batch's individual item failures don't invalidate
+/// the whole call — `events` holds everything that passed, `failures` holds
+/// `(index, errors)` for everything that didn't, indexed into the original
+/// array.
+#[derive(Debug)]
+pub struct BatchNormalizeResult {
+    pub events: Vec<NormalizedEvent>,
+    pub failures: Vec<(usize, Vec<ValidationError>)>,
+}
+
+/// Normalize a top-level JSON array of event objects, running each element
+/// through [`normalize_ingest_event`] independently — one malformed event
+/// doesn't reject the rest of the batch. Rejects outright (before looking at
+/// any element) if `value` isn't an array, or if it exceeds `MAX_BATCH_SIZE`.
+/// `normalize_from_value` is unchanged and still rejects arrays for the
+/// single-event ingest path.
+pub fn normalize_batch(value: Value, validation: &IngestValidation) -> Result<BatchNormalizeResult, ValidationError> {
+    let Value::Array(items) = value else {
+        return Err(ValidationError {
+            field: "body".into(),
+            message: "must be a JSON array of event objects".into(),
+        });
+    };
+
+    if items.len() > MAX_BATCH_SIZE {
+        return Err(ValidationError {
+            field: "body".into(),
+            message: format!("batch exceeds the maximum of {MAX_BATCH_SIZE} events"),
+        });
+    }
+
+    let mut events = Vec::new();
+    let mut failures = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        match normalize_from_value(item, validation) {
+            NormalizeResult::Ok { event } => events.push(event),
+            NormalizeResult::Err { errors } => failures.push((index, errors)),
+        }
+    }
+
+    Ok(BatchNormalizeResult { events, failures })
+}
+
+/// Borrowing counterpart to `NormalizedEvent` for the high-throughput ingest
+/// path — see [`normalize_ingest_event_ref`]. String fields are `Cow<'a, str>`
+/// borrowing straight from the input `Value` (trimming a `&str` is already a
+/// zero-allocation subslice, unlike `normalize_ingest_event`'s
+/// `.trim().to_string()`). Only `client_timestamp`, which is re-serialized to
+/// its canonical UTC form, always allocates.
+#[derive(Debug)]
+pub struct NormalizedEventRef<'a> {
+    pub event_id: Option<Cow<'a, str>>,
+    pub session_id: Cow<'a, str>,
+    pub agent_type: Cow<'a, str>,
+    pub event_type: Cow<'a, str>,
+    pub tool_name: Option<Cow<'a, str>>,
+    pub status: Cow<'a, str>,
+    pub tokens_in: i64,
+    pub tokens_out: i64,
+    pub branch: Option<Cow<'a, str>>,
+    pub project: Option<Cow<'a, str>>,
+    pub duration_ms: Option<i64>,
+    pub metadata: Option<&'a Value>,
+    pub client_timestamp: Option<Cow<'a, str>>,
+    pub model: Option<Cow<'a, str>>,
+    pub cost_usd: Option<f64>,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub source: Option<Cow<'a, str>>,
+}
+
+impl<'a> NormalizedEventRef<'a> {
+    /// Materialize into today's fully-owned `NormalizedEvent`, allocating
+    /// every borrowed field.
+    pub fn into_owned(self) -> NormalizedEvent {
+        NormalizedEvent {
+            event_id: self.event_id.map(Cow::into_owned),
+            session_id: self.session_id.into_owned(),
+            agent_type: self.agent_type.into_owned(),
+            event_type: self.event_type.into_owned(),
+            tool_name: self.tool_name.map(Cow::into_owned),
+            status: self.status.into_owned(),
+            tokens_in: self.tokens_in,
+            tokens_out: self.tokens_out,
+            branch: self.branch.map(Cow::into_owned),
+            project: self.project.map(Cow::into_owned),
+            duration_ms: self.duration_ms,
+            metadata: self
+                .metadata
+                .cloned()
+                .unwrap_or_else(|| Value::Object(serde_json::Map::new())),
+            client_timestamp: self.client_timestamp.map(Cow::into_owned),
+            model: self.model.map(Cow::into_owned),
+            cost_usd: self.cost_usd,
+            cache_read_tokens: self.cache_read_tokens,
+            cache_write_tokens: self.cache_write_tokens,
+            source: self.source.map(Cow::into_owned),
+        }
+    }
+}
+
+/// Borrowing counterpart to `normalize_ingest_event`. Reads fields directly
+/// off `value` instead of deserializing into an owned `RawIngestEvent`
+/// first, so string fields can borrow from the input instead of each
+/// allocating a `String` — see `NormalizedEventRef`. Validation rules
+/// (including `IngestValidation`'s policy) are identical to the owned path;
+/// only the representation differs. This is what `api::events::ingest_single`/
+/// `ingest_batch` call for the real HTTP ingest path; relay/NATS/MQTT/WS
+/// still go through the owned `normalize_from_value`, since those paths
+/// already hold an owned `Value` they don't need back afterward.
+pub fn normalize_ingest_event_ref<'a>(
+    value: &'a Value,
+    validation: &IngestValidation,
+) -> Result<NormalizedEventRef<'a>, Vec<ValidationError>> {
+    if !value.is_object() {
+        return Err(vec![ValidationError {
+            field: "body".into(),
+            message: "must be a JSON object".into(),
+        }]);
+    }
+
+    let mut errors = Vec::new();
+
+    let session_id = get_required_str(value, "session_id", &mut errors);
+    let agent_type = get_required_str(value, "agent_type", &mut errors);
+    let event_type_raw = get_required_str(value, "event_type", &mut errors);
+
+    validation.check_agent_type(&agent_type, &mut errors);
+    validation.check_event_type(&event_type_raw, &mut errors);
+
+    let status = normalize_status_ref(value, &event_type_raw, validation, &mut errors);
+
+    let event_id = get_optional_str(value, "event_id", &mut errors);
+    let tool_name = get_optional_str(value, "tool_name", &mut errors);
+    let branch = get_optional_str(value, "branch", &mut errors);
+    let project = get_optional_str(value, "project", &mut errors);
+    let model = get_optional_str(value, "model", &mut errors);
+    let duration_ms =
+        get_optional_non_negative_int(&value.get("duration_ms").cloned(), "duration_ms", &mut errors);
+    let tokens_in =
+        get_optional_non_negative_int(&value.get("tokens_in").cloned(), "tokens_in", &mut errors)
+            .unwrap_or(0);
+    let tokens_out =
+        get_optional_non_negative_int(&value.get("tokens_out").cloned(), "tokens_out", &mut errors)
+            .unwrap_or(0);
+    let cache_read_tokens = get_optional_non_negative_int(
+        &value.get("cache_read_tokens").cloned(),
+        "cache_read_tokens",
+        &mut errors,
+    )
+    .unwrap_or(0);
+    let cache_write_tokens = get_optional_non_negative_int(
+        &value.get("cache_write_tokens").cloned(),
+        "cache_write_tokens",
+        &mut errors,
+    )
+    .unwrap_or(0);
+    let cost_usd =
+        get_optional_non_negative_f64(&value.get("cost_usd").cloned(), "cost_usd", &mut errors);
+    let client_timestamp = validation
+        .check_client_timestamp(&value.get("client_timestamp").cloned(), &mut errors)
+        .map(Cow::Owned);
+    let source = get_optional_str(value, "source", &mut errors);
+    let metadata = value.get("metadata");
+
+    validation.check_tokens("tokens_in", tokens_in, &mut errors);
+    validation.check_tokens("tokens_out", tokens_out, &mut errors);
+    if let Some(cost_usd) = cost_usd {
+        validation.check_cost(cost_usd, &mut errors);
+    }
+    validation.check_required_fields(
+        &event_type_raw,
+        &[
+            ("event_id", event_id.is_some()),
+            ("tool_name", tool_name.is_some()),
+            ("branch", branch.is_some()),
+            ("project", project.is_some()),
+            ("model", model.is_some()),
+            ("duration_ms", duration_ms.is_some()),
+            ("cost_usd", cost_usd.is_some()),
+            ("client_timestamp", client_timestamp.is_some()),
+            ("source", source.is_some()),
+        ],
+        &mut errors,
+    );
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(NormalizedEventRef {
+        event_id,
+        session_id,
+        agent_type,
+        event_type: event_type_raw,
+        tool_name,
+        status,
+        tokens_in,
+        tokens_out,
+        branch,
+        project,
+        duration_ms,
+        metadata,
+        client_timestamp,
+        model,
+        cost_usd,
+        cache_read_tokens,
+        cache_write_tokens,
+        source,
+    })
+}
+
+fn get_required_str<'a>(value: &'a Value, field: &str, errors: &mut Vec<ValidationError>) -> Cow<'a, str> {
+    match value.get(field) {
+        Some(Value::String(s)) => {
+            let trimmed = s.trim();
+            if trimmed.is_empty() {
+                errors.push(ValidationError {
+                    field: field.into(),
+                    message: "must be a non-empty string".into(),
+                });
+            }
+            Cow::Borrowed(trimmed)
+        }
+        Some(_) => {
+            errors.push(ValidationError {
+                field: field.into(),
+                message: "must be a string".into(),
+            });
+            Cow::Borrowed("")
+        }
+        None => {
+            errors.push(ValidationError {
+                field: field.into(),
+                message: "must be a string".into(),
+            });
+            Cow::Borrowed("")
+        }
+    }
+}
+
+fn get_optional_str<'a>(
+    value: &'a Value,
+    field: &str,
+    errors: &mut Vec<ValidationError>,
+) -> Option<Cow<'a, str>> {
+    match value.get(field) {
+        None | Some(Value::Null) => None,
+        Some(Value::String(s)) => {
+            let trimmed = s.trim();
+            if trimmed.is_empty() {
+                None
+            } else {
+                Some(Cow::Borrowed(trimmed))
+            }
+        }
+        Some(_) => {
+            errors.push(ValidationError {
+                field: field.into(),
+                message: "must be a string when provided".into(),
+            });
+            None
+        }
+    }
+}
+
+fn normalize_status_ref<'a>(
+    value: &'a Value,
+    event_type: &str,
+    validation: &IngestValidation,
+    errors: &mut Vec<ValidationError>,
+) -> Cow<'a, str> {
+    let default: &'static str = if event_type == "error" { "error" } else { "success" };
+
+    match value.get("status") {
+        None | Some(Value::Null) => Cow::Borrowed(default),
+        Some(Value::String(s)) => {
+            validation.check_status(s, errors);
+            Cow::Borrowed(s.as_str())
+        }
+        Some(_) => {
+            errors.push(ValidationError {
+                field: "status".into(),
+                message: "must be a string when provided".into(),
+            });
+            Cow::Borrowed(default)
+        }
+    }
+}
+
 // --- Helper extractors mirroring TypeScript contract helpers ---
 
 fn get_required_string(
@@ -221,6 +793,7 @@ fn get_optional_non_negative_f64(
 fn normalize_status(
     value: &Option<Value>,
     event_type: &str,
+    validation: &IngestValidation,
     errors: &mut Vec<ValidationError>,
 ) -> String {
     let default = if event_type == "error" {
@@ -232,12 +805,7 @@ fn normalize_status(
     match value {
         None | Some(Value::Null) => default.into(),
         Some(Value::String(s)) => {
-            if !EVENT_STATUSES.contains(&s.as_str()) {
-                errors.push(ValidationError {
-                    field: "status".into(),
-                    message: format!("must be one of: {}", EVENT_STATUSES.join(", ")),
-                });
-            }
+            validation.check_status(s, errors);
             s.clone()
         }
         Some(_) => {
@@ -250,43 +818,13 @@ fn normalize_status(
     }
 }
 
-fn normalize_client_timestamp(
-    value: &Option<Value>,
-    errors: &mut Vec<ValidationError>,
-) -> Option<String> {
-    match value {
-        None | Some(Value::Null) => None,
-        Some(Value::String(s)) => {
-            // Basic ISO 8601 validation â€” accept strings that look like timestamps.
-            // Full chrono parsing is available but heavyweight for a spike;
-            // we do a length + prefix check that catches obvious garbage.
-            if s.len() >= 10 && s.chars().nth(4) == Some('-') {
-                Some(s.clone())
-            } else {
-                errors.push(ValidationError {
-                    field: "client_timestamp".into(),
-                    message: "must be a valid timestamp".into(),
-                });
-                None
-            }
-        }
-        Some(_) => {
-            errors.push(ValidationError {
-                field: "client_timestamp".into(),
-                message: "must be an ISO timestamp string when provided".into(),
-            });
-            None
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
     fn norm(v: Value) -> NormalizeResult {
-        normalize_from_value(v)
+        normalize_from_value(v, &IngestValidation::default())
     }
 
     // --- Required fields ---
@@ -296,7 +834,8 @@ mod tests {
         let result = norm(json!({
             "session_id": "sess-1",
             "agent_type": "claude_code",
-            "event_type": "tool_use"
+            "event_type": "tool_use",
+            "tool_name": "Read"
         }));
         assert!(result.is_ok());
         let evt = result.unwrap_event();
@@ -308,6 +847,57 @@ mod tests {
         assert_eq!(evt.tokens_out, 0);
     }
 
+    #[test]
+    fn tool_use_without_tool_name_rejected() {
+        let result = norm(json!({
+            "session_id": "sess-1",
+            "agent_type": "claude_code",
+            "event_type": "tool_use"
+        }));
+        assert!(!result.is_ok());
+        let errors = result.unwrap_errors();
+        assert!(errors.iter().any(|e| e.field == "tool_name"));
+    }
+
+    #[test]
+    fn llm_response_without_model_rejected() {
+        let result = norm(json!({
+            "session_id": "sess-1",
+            "agent_type": "claude_code",
+            "event_type": "llm_response"
+        }));
+        assert!(!result.is_ok());
+        let errors = result.unwrap_errors();
+        assert!(errors.iter().any(|e| e.field == "model"));
+    }
+
+    #[test]
+    fn llm_response_with_model_accepted() {
+        let result = norm(json!({
+            "session_id": "sess-1",
+            "agent_type": "claude_code",
+            "event_type": "llm_response",
+            "model": "claude-sonnet-4-5-20250514"
+        }));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn custom_required_field_overridable() {
+        let validation = IngestValidation::default().with_required_fields("session_start", ["project"]);
+        let result = normalize_from_value(
+            json!({
+                "session_id": "sess-1",
+                "agent_type": "claude_code",
+                "event_type": "session_start"
+            }),
+            &validation,
+        );
+        assert!(!result.is_ok());
+        let errors = result.unwrap_errors();
+        assert!(errors.iter().any(|e| e.field == "project"));
+    }
+
     #[test]
     fn missing_session_id_rejected() {
         let result = norm(json!({
@@ -383,7 +973,9 @@ mod tests {
             let result = norm(json!({
                 "session_id": "sess-1",
                 "agent_type": "claude_code",
-                "event_type": et
+                "event_type": et,
+                "tool_name": "Read",
+                "model": "claude-sonnet-4-5-20250514"
             }));
             assert!(result.is_ok(), "event_type '{et}' should be accepted");
         }
@@ -409,6 +1001,7 @@ mod tests {
                 "session_id": "sess-1",
                 "agent_type": "claude_code",
                 "event_type": "tool_use",
+                "tool_name": "Read",
                 "status": s
             }));
             assert!(result.is_ok(), "status '{s}' should be accepted");
@@ -422,7 +1015,8 @@ mod tests {
         let evt = norm(json!({
             "session_id": "sess-1",
             "agent_type": "claude_code",
-            "event_type": "tool_use"
+            "event_type": "tool_use",
+            "tool_name": "Read"
         })).unwrap_event();
         assert_eq!(evt.status, "success");
     }
@@ -455,7 +1049,8 @@ mod tests {
         let evt = norm(json!({
             "session_id": "sess-1",
             "agent_type": "claude_code",
-            "event_type": "tool_use"
+            "event_type": "tool_use",
+            "tool_name": "Read"
         })).unwrap_event();
         assert_eq!(evt.tokens_in, 0);
         assert_eq!(evt.tokens_out, 0);
@@ -469,6 +1064,7 @@ mod tests {
             "session_id": "sess-1",
             "agent_type": "claude_code",
             "event_type": "tool_use",
+            "tool_name": "Read",
             "tokens_in": 100,
             "tokens_out": 50,
             "cache_read_tokens": 25,
@@ -599,7 +1195,7 @@ mod tests {
         let result = norm(json!({
             "session_id": "sess-1",
             "agent_type": "claude_code",
-            "event_type": "tool_use",
+            "event_type": "session_start",
             "tool_name": null,
             "model": null,
             "cost_usd": null,
@@ -622,7 +1218,7 @@ mod tests {
         let evt = norm(json!({
             "session_id": "sess-1",
             "agent_type": "claude_code",
-            "event_type": "tool_use"
+            "event_type": "session_start"
         })).unwrap_event();
         assert!(evt.metadata.is_object());
         assert!(evt.metadata.as_object().unwrap().is_empty());
@@ -635,10 +1231,82 @@ mod tests {
         let evt = norm(json!({
             "session_id": "sess-1",
             "agent_type": "claude_code",
-            "event_type": "tool_use",
+            "event_type": "session_start",
             "client_timestamp": "2026-02-24T12:00:00Z"
         })).unwrap_event();
-        assert_eq!(evt.client_timestamp.as_deref(), Some("2026-02-24T12:00:00Z"));
+        // Canonicalized to chrono's UTC RFC 3339 form ("+00:00", not "Z").
+        assert_eq!(evt.client_timestamp.as_deref(), Some("2026-02-24T12:00:00+00:00"));
+    }
+
+    #[test]
+    fn timestamp_canonicalizes_non_utc_offset_to_utc() {
+        let evt = norm(json!({
+            "session_id": "sess-1",
+            "agent_type": "claude_code",
+            "event_type": "session_start",
+            "client_timestamp": "2026-02-24T07:00:00-05:00"
+        })).unwrap_event();
+        assert_eq!(evt.client_timestamp.as_deref(), Some("2026-02-24T12:00:00+00:00"));
+    }
+
+    #[test]
+    fn future_timestamp_beyond_leeway_rejected() {
+        let far_future = (chrono::Utc::now() + chrono::Duration::days(365)).to_rfc3339();
+        let result = norm(json!({
+            "session_id": "sess-1",
+            "agent_type": "claude_code",
+            "event_type": "session_start",
+            "client_timestamp": far_future
+        }));
+        assert!(!result.is_ok());
+        let errors = result.unwrap_errors();
+        assert!(errors.iter().any(|e| e.field == "client_timestamp" && e.message.contains("future")));
+    }
+
+    #[test]
+    fn future_timestamp_within_leeway_accepted() {
+        let near_future = (chrono::Utc::now() + chrono::Duration::seconds(10)).to_rfc3339();
+        let result = norm(json!({
+            "session_id": "sess-1",
+            "agent_type": "claude_code",
+            "event_type": "session_start",
+            "client_timestamp": near_future
+        }));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn timestamp_older_than_max_age_rejected() {
+        let validation = IngestValidation::default().with_max_timestamp_age_secs(60);
+        let old = (chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339();
+        let result = normalize_from_value(
+            json!({
+                "session_id": "sess-1",
+                "agent_type": "claude_code",
+                "event_type": "session_start",
+                "client_timestamp": old
+            }),
+            &validation,
+        );
+        assert!(!result.is_ok());
+        let errors = result.unwrap_errors();
+        assert!(errors.iter().any(|e| e.field == "client_timestamp" && e.message.contains("old")));
+    }
+
+    #[test]
+    fn timestamp_within_max_age_accepted() {
+        let validation = IngestValidation::default().with_max_timestamp_age_secs(3600);
+        let recent = (chrono::Utc::now() - chrono::Duration::seconds(5)).to_rfc3339();
+        let result = normalize_from_value(
+            json!({
+                "session_id": "sess-1",
+                "agent_type": "claude_code",
+                "event_type": "session_start",
+                "client_timestamp": recent
+            }),
+            &validation,
+        );
+        assert!(result.is_ok());
     }
 
     #[test]
@@ -706,7 +1374,7 @@ mod tests {
         let evt = norm(json!({
             "session_id": "sess-1",
             "agent_type": "claude_code",
-            "event_type": "tool_use",
+            "event_type": "session_start",
             "tool_name": "   "
         })).unwrap_event();
         assert!(evt.tool_name.is_none());
@@ -725,4 +1393,244 @@ mod tests {
         let errors = result.unwrap_errors();
         assert!(errors.len() >= 3, "Expected at least 3 errors, got {}", errors.len());
     }
+
+    // --- IngestValidation ---
+
+    #[test]
+    fn restricted_agent_types_rejects_unknown_agent() {
+        let validation = IngestValidation::default().with_agent_types(["claude_code"]);
+        let result = normalize_from_value(
+            json!({
+                "session_id": "sess-1",
+                "agent_type": "some_other_agent",
+                "event_type": "tool_use"
+            }),
+            &validation,
+        );
+        assert!(!result.is_ok());
+        let errors = result.unwrap_errors();
+        assert!(errors.iter().any(|e| e.field == "agent_type"));
+    }
+
+    #[test]
+    fn restricted_agent_types_accepts_listed_agent() {
+        let validation = IngestValidation::default().with_agent_types(["claude_code"]);
+        let result = normalize_from_value(
+            json!({
+                "session_id": "sess-1",
+                "agent_type": "claude_code",
+                "event_type": "session_start"
+            }),
+            &validation,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn extra_event_types_accepted() {
+        let validation = IngestValidation::default().with_extra_event_types(["custom_event"]);
+        let result = normalize_from_value(
+            json!({
+                "session_id": "sess-1",
+                "agent_type": "claude_code",
+                "event_type": "custom_event"
+            }),
+            &validation,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn extra_statuses_accepted() {
+        let validation = IngestValidation::default().with_extra_statuses(["retrying"]);
+        let result = normalize_from_value(
+            json!({
+                "session_id": "sess-1",
+                "agent_type": "claude_code",
+                "event_type": "session_start",
+                "status": "retrying"
+            }),
+            &validation,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn max_tokens_rejects_over_bound() {
+        let validation = IngestValidation::default().with_max_tokens(100);
+        let result = normalize_from_value(
+            json!({
+                "session_id": "sess-1",
+                "agent_type": "claude_code",
+                "event_type": "tool_use",
+                "tokens_in": 1000
+            }),
+            &validation,
+        );
+        assert!(!result.is_ok());
+        let errors = result.unwrap_errors();
+        assert!(errors.iter().any(|e| e.field == "tokens_in"));
+    }
+
+    #[test]
+    fn max_cost_usd_rejects_over_bound() {
+        let validation = IngestValidation::default().with_max_cost_usd(1.0);
+        let result = normalize_from_value(
+            json!({
+                "session_id": "sess-1",
+                "agent_type": "claude_code",
+                "event_type": "tool_use",
+                "cost_usd": 5.0
+            }),
+            &validation,
+        );
+        assert!(!result.is_ok());
+        let errors = result.unwrap_errors();
+        assert!(errors.iter().any(|e| e.field == "cost_usd"));
+    }
+
+    #[test]
+    fn default_validation_has_no_bounds() {
+        let evt = norm(json!({
+            "session_id": "sess-1",
+            "agent_type": "anything_goes",
+            "event_type": "session_start",
+            "tokens_in": 1_000_000,
+            "cost_usd": 999.0
+        }))
+        .unwrap_event();
+        assert_eq!(evt.tokens_in, 1_000_000);
+    }
+
+    // --- normalize_batch ---
+
+    #[test]
+    fn normalize_batch_rejects_non_array() {
+        let err = normalize_batch(json!({"session_id": "sess-1"}), &IngestValidation::default())
+            .unwrap_err();
+        assert_eq!(err.field, "body");
+    }
+
+    #[test]
+    fn normalize_batch_rejects_oversized_batch() {
+        let items: Vec<Value> = (0..MAX_BATCH_SIZE + 1)
+            .map(|_| json!({"session_id": "s", "agent_type": "a", "event_type": "session_start"}))
+            .collect();
+        let err = normalize_batch(Value::Array(items), &IngestValidation::default()).unwrap_err();
+        assert!(err.message.contains("maximum"));
+    }
+
+    #[test]
+    fn normalize_batch_accepts_batch_at_the_cap() {
+        let items: Vec<Value> = (0..MAX_BATCH_SIZE)
+            .map(|_| json!({"session_id": "s", "agent_type": "a", "event_type": "session_start"}))
+            .collect();
+        let result = normalize_batch(Value::Array(items), &IngestValidation::default()).unwrap();
+        assert_eq!(result.events.len(), MAX_BATCH_SIZE);
+        assert!(result.failures.is_empty());
+    }
+
+    #[test]
+    fn normalize_batch_isolates_per_item_failures() {
+        let items = vec![
+            json!({"session_id": "sess-1", "agent_type": "claude_code", "event_type": "session_start"}),
+            json!({"agent_type": "claude_code", "event_type": "session_start"}),
+            json!({"session_id": "sess-3", "agent_type": "claude_code", "event_type": "session_start"}),
+        ];
+        let result = normalize_batch(Value::Array(items), &IngestValidation::default()).unwrap();
+        assert_eq!(result.events.len(), 2);
+        assert_eq!(result.failures.len(), 1);
+        let (index, errors) = &result.failures[0];
+        assert_eq!(*index, 1);
+        assert!(errors.iter().any(|e| e.field == "session_id"));
+    }
+
+    #[test]
+    fn normalize_batch_empty_array_accepted() {
+        let result = normalize_batch(Value::Array(vec![]), &IngestValidation::default()).unwrap();
+        assert!(result.events.is_empty());
+        assert!(result.failures.is_empty());
+    }
+
+    // --- normalize_ingest_event_ref ---
+
+    #[test]
+    fn ref_path_matches_owned_path_for_a_full_event() {
+        let payload = json!({
+            "event_id": "evt-123",
+            "session_id": "sess-1",
+            "agent_type": "claude_code",
+            "event_type": "tool_use",
+            "tool_name": "Read",
+            "status": "success",
+            "tokens_in": 100,
+            "tokens_out": 200,
+            "branch": "main",
+            "project": "myapp",
+            "duration_ms": 500,
+            "model": "claude-sonnet-4-5-20250514",
+            "cost_usd": 0.05,
+            "cache_read_tokens": 10,
+            "cache_write_tokens": 5,
+            "client_timestamp": "2026-02-24T12:00:00Z",
+            "source": "hook",
+            "metadata": {"command": "cat foo.txt"}
+        });
+        let validation = IngestValidation::default();
+
+        let owned = normalize_from_value(payload.clone(), &validation).unwrap_event();
+        let borrowed = normalize_ingest_event_ref(&payload, &validation).unwrap().into_owned();
+
+        assert_eq!(owned.event_id, borrowed.event_id);
+        assert_eq!(owned.session_id, borrowed.session_id);
+        assert_eq!(owned.agent_type, borrowed.agent_type);
+        assert_eq!(owned.event_type, borrowed.event_type);
+        assert_eq!(owned.tool_name, borrowed.tool_name);
+        assert_eq!(owned.status, borrowed.status);
+        assert_eq!(owned.tokens_in, borrowed.tokens_in);
+        assert_eq!(owned.tokens_out, borrowed.tokens_out);
+        assert_eq!(owned.branch, borrowed.branch);
+        assert_eq!(owned.project, borrowed.project);
+        assert_eq!(owned.duration_ms, borrowed.duration_ms);
+        assert_eq!(owned.model, borrowed.model);
+        assert_eq!(owned.cost_usd, borrowed.cost_usd);
+        assert_eq!(owned.cache_read_tokens, borrowed.cache_read_tokens);
+        assert_eq!(owned.cache_write_tokens, borrowed.cache_write_tokens);
+        assert_eq!(owned.client_timestamp, borrowed.client_timestamp);
+        assert_eq!(owned.source, borrowed.source);
+        assert_eq!(owned.metadata, borrowed.metadata);
+    }
+
+    #[test]
+    fn ref_path_borrows_unmodified_strings() {
+        let payload = json!({
+            "session_id": "sess-1",
+            "agent_type": "claude_code",
+            "event_type": "session_start"
+        });
+        let event = normalize_ingest_event_ref(&payload, &IngestValidation::default()).unwrap();
+        assert!(matches!(event.session_id, Cow::Borrowed(_)));
+        assert!(matches!(event.agent_type, Cow::Borrowed(_)));
+        assert!(matches!(event.event_type, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn ref_path_rejects_same_invalid_payloads_as_owned_path() {
+        let payload = json!({
+            "agent_type": "claude_code",
+            "event_type": "tool_use"
+        });
+        let errors =
+            normalize_ingest_event_ref(&payload, &IngestValidation::default()).unwrap_err();
+        assert!(errors.iter().any(|e| e.field == "session_id"));
+        assert!(errors.iter().any(|e| e.field == "tool_name"));
+    }
+
+    #[test]
+    fn ref_path_rejects_non_object_body() {
+        let payload = json!("just a string");
+        let errors =
+            normalize_ingest_event_ref(&payload, &IngestValidation::default()).unwrap_err();
+        assert!(errors.iter().any(|e| e.field == "body"));
+    }
 }
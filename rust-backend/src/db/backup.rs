@@ -0,0 +1,66 @@
+use std::path::Path;
+
+use rusqlite::{Connection, Result, params};
+
+/// Write a full, passphrase-encrypted copy of `conn`'s database to
+/// `dest_path` via SQLCipher's `sqlcipher_export()` — the same routine
+/// SQLCipher-based wallet stores use to move an encrypted database to a new
+/// file or passphrase without a plaintext copy ever touching disk. Requires
+/// this crate built against a SQLCipher `libsqlite3` (see
+/// `db::pool::initialize`'s `PRAGMA key` on checkout).
+pub fn export_encrypted_backup(conn: &Connection, dest_path: &Path, dest_passphrase: &str) -> Result<()> {
+    conn.execute(
+        "ATTACH DATABASE ?1 AS export_target KEY ?2",
+        params![dest_path.to_string_lossy(), dest_passphrase],
+    )?;
+    let result = conn.execute("SELECT sqlcipher_export('export_target')", []);
+    conn.execute("DETACH DATABASE export_target", [])?;
+    result.map(|_| ())
+}
+
+/// Restore `agents`/`sessions`/`events` from a passphrase-encrypted archive
+/// written by `export_encrypted_backup`, replacing whatever is currently in
+/// those three tables on `conn`. Only those tables are pulled across —
+/// `schema_migrations` and anything else the archive carries is left alone,
+/// since `conn` has already run `migrate` against the current schema and the
+/// archive may predate it.
+pub fn import_encrypted_backup(conn: &Connection, src_path: &Path, src_passphrase: &str) -> Result<()> {
+    conn.execute(
+        "ATTACH DATABASE ?1 AS import_source KEY ?2",
+        params![src_path.to_string_lossy(), src_passphrase],
+    )?;
+    let result: Result<()> = (|| {
+        conn.execute("DELETE FROM events", [])?;
+        conn.execute("DELETE FROM sessions", [])?;
+        conn.execute("DELETE FROM agents", [])?;
+        conn.execute("INSERT INTO agents SELECT * FROM import_source.agents", [])?;
+        conn.execute("INSERT INTO sessions SELECT * FROM import_source.sessions", [])?;
+        conn.execute(
+            "INSERT INTO events (
+                event_id, session_id, agent_type, event_type, tool_name, status,
+                tokens_in, tokens_out, branch, project, duration_ms, created_at,
+                client_timestamp, metadata, payload_truncated, model, cost_usd,
+                cache_read_tokens, cache_write_tokens, source, pricing_version
+             )
+             SELECT
+                event_id, session_id, agent_type, event_type, tool_name, status,
+                tokens_in, tokens_out, branch, project, duration_ms, created_at,
+                client_timestamp, metadata, payload_truncated, model, cost_usd,
+                cache_read_tokens, cache_write_tokens, source, pricing_version
+             FROM import_source.events",
+            [],
+        )?;
+        Ok(())
+    })();
+    conn.execute("DETACH DATABASE import_source", [])?;
+    result
+}
+
+/// Rotate `conn`'s passphrase in place via SQLCipher's `PRAGMA rekey`. The
+/// connection must already be correctly keyed — `db::pool::initialize`
+/// issues `PRAGMA key` on checkout — `rekey` only re-encrypts an
+/// already-decrypted connection, it can't recover one opened with the wrong
+/// passphrase.
+pub fn rekey(conn: &Connection, new_passphrase: &str) -> Result<()> {
+    conn.pragma_update(None, "rekey", new_passphrase)
+}
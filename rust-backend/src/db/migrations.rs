@@ -0,0 +1,538 @@
+use rusqlite::{Connection, Result};
+use tracing::info;
+
+/// One forward-only schema change. `up_sql` runs inside its own transaction
+/// when the migration is applied. Once a migration has shipped, its
+/// `up_sql` must never be edited or reordered — ship a new migration with
+/// the next version instead.
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub up_sql: &'static str,
+}
+
+/// Base schema, now migration 1. Exact mirror of the TypeScript schema from
+/// src/db/schema.ts — column names, types, defaults, and constraints match
+/// 1:1.
+const BASE_SCHEMA_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS agents (
+    id TEXT PRIMARY KEY,
+    agent_type TEXT NOT NULL,
+    name TEXT,
+    registered_at TEXT NOT NULL DEFAULT (datetime('now')),
+    last_seen_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY,
+    agent_id TEXT NOT NULL,
+    agent_type TEXT NOT NULL,
+    project TEXT,
+    branch TEXT,
+    status TEXT NOT NULL DEFAULT 'active',
+    started_at TEXT NOT NULL DEFAULT (datetime('now')),
+    ended_at TEXT,
+    last_event_at TEXT NOT NULL DEFAULT (datetime('now')),
+    metadata TEXT DEFAULT '{}'
+);
+
+CREATE TABLE IF NOT EXISTS events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id TEXT UNIQUE,
+    schema_version INTEGER NOT NULL DEFAULT 1,
+    session_id TEXT NOT NULL,
+    agent_type TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    tool_name TEXT,
+    status TEXT NOT NULL DEFAULT 'success' CHECK (status IN ('success', 'error', 'timeout')),
+    tokens_in INTEGER DEFAULT 0,
+    tokens_out INTEGER DEFAULT 0,
+    branch TEXT,
+    project TEXT,
+    duration_ms INTEGER,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    client_timestamp TEXT,
+    metadata TEXT DEFAULT '{}',
+    payload_truncated INTEGER NOT NULL DEFAULT 0 CHECK (payload_truncated IN (0, 1)),
+    model TEXT,
+    cost_usd REAL,
+    cache_read_tokens INTEGER DEFAULT 0,
+    cache_write_tokens INTEGER DEFAULT 0,
+    source TEXT DEFAULT 'api'
+);
+
+CREATE INDEX IF NOT EXISTS idx_events_created_at ON events(created_at);
+CREATE INDEX IF NOT EXISTS idx_events_session_id ON events(session_id);
+CREATE INDEX IF NOT EXISTS idx_events_event_type ON events(event_type);
+CREATE INDEX IF NOT EXISTS idx_events_tool_name ON events(tool_name);
+CREATE INDEX IF NOT EXISTS idx_events_agent_type ON events(agent_type);
+CREATE INDEX IF NOT EXISTS idx_events_model ON events(model);
+CREATE INDEX IF NOT EXISTS idx_sessions_status ON sessions(status);
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    key_hash TEXT NOT NULL UNIQUE,
+    label TEXT,
+    scope TEXT NOT NULL CHECK (scope IN ('read', 'write')),
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    revoked_at TEXT
+);
+"#;
+
+/// Tracks per-file import progress for `importer::run_import` so a repeated
+/// run over an unchanged file can skip it, and a run over a file that has
+/// only grown (an open Claude Code/Codex session) can resume from where it
+/// left off instead of reparsing from byte zero. `byte_offset`/`line_offset`/
+/// `cost_state` are importer-internal resume state (see `importer::ResumeState`);
+/// `header_hash` lets the importer cheaply confirm a grown file's earlier
+/// bytes are still the ones it already parsed before trusting that offset.
+const IMPORT_STATE_SCHEMA_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS import_state (
+    file_path TEXT PRIMARY KEY,
+    file_hash TEXT NOT NULL,
+    file_size INTEGER NOT NULL DEFAULT 0,
+    source TEXT NOT NULL,
+    events_imported INTEGER NOT NULL DEFAULT 0,
+    imported_at TEXT NOT NULL DEFAULT (datetime('now')),
+    byte_offset INTEGER NOT NULL DEFAULT 0,
+    line_offset INTEGER NOT NULL DEFAULT 0,
+    cost_state REAL NOT NULL DEFAULT 0,
+    header_hash TEXT
+);
+"#;
+
+/// Tags each event's `cost_usd` with the pricing rate table (see
+/// `pricing::pricing_version`) that computed it, whenever the source log
+/// didn't carry its own cost and we derived one from token counts. NULL
+/// means the cost either came straight from the source or was never priced
+/// at all — both left alone by `queries::recompute_costs`.
+const EVENT_PRICING_VERSION_SQL: &str = r#"
+ALTER TABLE events ADD COLUMN pricing_version TEXT;
+"#;
+
+/// Rollup tables `rollup::rollup_session_stats` maintains incrementally, so
+/// `queries::get_sessions`/`get_session_with_events` can join a single row
+/// per session instead of running the correlated subqueries those two used
+/// to run directly against `events`. `last_rolled_event_id` is the
+/// watermark: the rollup only rescans events with `id` past it.
+/// `session_edited_files` backs `files_edited` with a real `COUNT(*)`
+/// instead of `COUNT(DISTINCT ...)` over the whole table every time.
+const SESSION_STATS_ROLLUP_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS session_stats (
+    session_id TEXT PRIMARY KEY,
+    event_count INTEGER NOT NULL DEFAULT 0,
+    tokens_in INTEGER NOT NULL DEFAULT 0,
+    tokens_out INTEGER NOT NULL DEFAULT 0,
+    total_cost_usd REAL NOT NULL DEFAULT 0,
+    files_edited INTEGER NOT NULL DEFAULT 0,
+    lines_added INTEGER NOT NULL DEFAULT 0,
+    lines_removed INTEGER NOT NULL DEFAULT 0,
+    last_rolled_event_id INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS session_edited_files (
+    session_id TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    UNIQUE(session_id, file_path)
+);
+"#;
+
+/// `events_fts` is an "external content" FTS5 index over `events` — it
+/// stores no text of its own, just the inverted index, and leans on
+/// `content_rowid` to map back to `events.id`. The three triggers below are
+/// FTS5's documented pattern for keeping an external-content table in sync;
+/// the final `INSERT ... SELECT` backfills rows that existed before this
+/// migration ran, since the triggers only see writes from here on.
+/// `queries::search_events` is the one reader; see its doc comment for how
+/// column-scoped and phrase queries work.
+const EVENTS_FTS_SQL: &str = r#"
+CREATE VIRTUAL TABLE IF NOT EXISTS events_fts USING fts5(
+    metadata,
+    tool_name,
+    content='events',
+    content_rowid='id'
+);
+
+CREATE TRIGGER IF NOT EXISTS events_fts_ai AFTER INSERT ON events BEGIN
+    INSERT INTO events_fts(rowid, metadata, tool_name) VALUES (new.id, new.metadata, new.tool_name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS events_fts_ad AFTER DELETE ON events BEGIN
+    INSERT INTO events_fts(events_fts, rowid, metadata, tool_name) VALUES ('delete', old.id, old.metadata, old.tool_name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS events_fts_au AFTER UPDATE ON events BEGIN
+    INSERT INTO events_fts(events_fts, rowid, metadata, tool_name) VALUES ('delete', old.id, old.metadata, old.tool_name);
+    INSERT INTO events_fts(rowid, metadata, tool_name) VALUES (new.id, new.metadata, new.tool_name);
+END;
+
+INSERT INTO events_fts(rowid, metadata, tool_name) SELECT id, metadata, tool_name FROM events;
+"#;
+
+/// Content-hash dedup index for the importer (see
+/// `importer::content_fingerprint`). Keyed by session rather than globally:
+/// two different sessions are allowed to contain an event that happens to
+/// fingerprint identically (e.g. two empty `session_start`s), but the same
+/// session seeing the same fingerprint twice means a re-import saw the same
+/// underlying event again, however its position-derived `event_id` came out
+/// this time.
+const EVENT_FINGERPRINTS_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS event_fingerprints (
+    session_id TEXT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    PRIMARY KEY (session_id, fingerprint)
+);
+"#;
+
+/// Backing store for `util::chunking`-split event payloads (see
+/// `queries::persist_chunked_metadata`) — an opt-in alternative to
+/// `truncate_metadata` that keeps the full payload instead of discarding
+/// whatever doesn't fit. `payload_chunks` holds each unique chunk once,
+/// content-addressed by its blake3 hash; `event_payload_chunks` records the
+/// ordered chunk list for a given event so the metadata can be reassembled.
+const PAYLOAD_CHUNKS_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS payload_chunks (
+    content_hash TEXT PRIMARY KEY,
+    bytes BLOB NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS event_payload_chunks (
+    event_id INTEGER NOT NULL REFERENCES events(id),
+    chunk_index INTEGER NOT NULL,
+    content_hash TEXT NOT NULL REFERENCES payload_chunks(content_hash),
+    PRIMARY KEY (event_id, chunk_index)
+);
+"#;
+
+/// Adds a `tenant_id` column to every table a tenant's data lives in, so one
+/// AgentMonitor instance can serve more than one team with their activity
+/// kept apart in queries and live streams — see `auth::DEFAULT_TENANT` and
+/// the scope note on `db::store::Store`. Every existing row backfills to
+/// `'default'`, which is also what a deployment with no multi-tenant setup
+/// (no per-key `tenant_id`, i.e. every deployment before this migration)
+/// keeps using forever, so this ships with no behavior change for them.
+const TENANT_PARTITIONING_SQL: &str = r#"
+ALTER TABLE sessions ADD COLUMN tenant_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE events ADD COLUMN tenant_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE api_keys ADD COLUMN tenant_id TEXT NOT NULL DEFAULT 'default';
+
+CREATE INDEX IF NOT EXISTS idx_sessions_tenant_id ON sessions(tenant_id);
+CREATE INDEX IF NOT EXISTS idx_events_tenant_id ON events(tenant_id);
+"#;
+
+/// Backs `db::settings`'s typed `get`/`set` accessors — user configuration
+/// (retention window, default project filter, backend port, idle-timeout
+/// threshold) that needs to survive restarts without a separate config
+/// file. `value` is JSON-encoded by the caller; this table has no opinion on
+/// what's inside it.
+const SETTINGS_SCHEMA_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+"#;
+
+/// Widens `api_keys.scope`'s CHECK constraint to admit `'admin'`, a stricter
+/// tier than `'write'` reserved for `/api/admin/*` (see
+/// `auth::KeyScope::Admin`/`auth::require_admin`) so an ordinary ingest key
+/// can't mint or revoke API keys. SQLite has no `ALTER TABLE ... DROP
+/// CONSTRAINT`, so loosening a CHECK means rebuilding the table: copy every
+/// row into a new one with the wider constraint, drop the old table, and
+/// rename the new one into its place.
+const ADMIN_KEY_SCOPE_SQL: &str = r#"
+CREATE TABLE api_keys_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    key_hash TEXT NOT NULL UNIQUE,
+    label TEXT,
+    scope TEXT NOT NULL CHECK (scope IN ('read', 'write', 'admin')),
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    revoked_at TEXT,
+    tenant_id TEXT NOT NULL DEFAULT 'default'
+);
+
+INSERT INTO api_keys_new (id, key_hash, label, scope, created_at, revoked_at, tenant_id)
+    SELECT id, key_hash, label, scope, created_at, revoked_at, tenant_id FROM api_keys;
+
+DROP TABLE api_keys;
+ALTER TABLE api_keys_new RENAME TO api_keys;
+"#;
+
+/// `sessions.id`/`events.event_id` were globally unique before
+/// `tenant_partitioning` introduced the tenant concept, so two tenants whose
+/// clients independently generate the same session/event id collide:
+/// `upsert_session`'s `ON CONFLICT(id)` silently merges a second tenant's
+/// session into the first's row, and a second tenant's event with the same
+/// `event_id` is dropped as a false "duplicate". Rebuilds both tables so
+/// uniqueness is scoped per tenant instead — `sessions`' primary key becomes
+/// `(id, tenant_id)`, and `events.event_id`'s column-level `UNIQUE` becomes a
+/// `(tenant_id, event_id)` composite index (still allowing any number of
+/// `NULL` `event_id`s per tenant, the same as the column-level constraint
+/// did). Rebuilding `events` drops its `events_fts_*` triggers along with
+/// the table, so this migration recreates them verbatim from `EVENTS_FTS_SQL`
+/// — `events_fts` itself (and its indexed content, keyed by `events.id`,
+/// which this rebuild preserves exactly) is untouched.
+const TENANT_SCOPED_UNIQUENESS_SQL: &str = r#"
+CREATE TABLE sessions_new (
+    id TEXT NOT NULL,
+    agent_id TEXT NOT NULL,
+    agent_type TEXT NOT NULL,
+    project TEXT,
+    branch TEXT,
+    status TEXT NOT NULL DEFAULT 'active',
+    started_at TEXT NOT NULL DEFAULT (datetime('now')),
+    ended_at TEXT,
+    last_event_at TEXT NOT NULL DEFAULT (datetime('now')),
+    metadata TEXT DEFAULT '{}',
+    tenant_id TEXT NOT NULL DEFAULT 'default',
+    PRIMARY KEY (id, tenant_id)
+);
+
+INSERT INTO sessions_new (
+    id, agent_id, agent_type, project, branch, status,
+    started_at, ended_at, last_event_at, metadata, tenant_id
+)
+SELECT id, agent_id, agent_type, project, branch, status,
+    started_at, ended_at, last_event_at, metadata, tenant_id
+FROM sessions;
+
+DROP TABLE sessions;
+ALTER TABLE sessions_new RENAME TO sessions;
+
+CREATE INDEX IF NOT EXISTS idx_sessions_status ON sessions(status);
+CREATE INDEX IF NOT EXISTS idx_sessions_tenant_id ON sessions(tenant_id);
+
+CREATE TABLE events_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id TEXT,
+    schema_version INTEGER NOT NULL DEFAULT 1,
+    session_id TEXT NOT NULL,
+    agent_type TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    tool_name TEXT,
+    status TEXT NOT NULL DEFAULT 'success' CHECK (status IN ('success', 'error', 'timeout')),
+    tokens_in INTEGER DEFAULT 0,
+    tokens_out INTEGER DEFAULT 0,
+    branch TEXT,
+    project TEXT,
+    duration_ms INTEGER,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    client_timestamp TEXT,
+    metadata TEXT DEFAULT '{}',
+    payload_truncated INTEGER NOT NULL DEFAULT 0 CHECK (payload_truncated IN (0, 1)),
+    model TEXT,
+    cost_usd REAL,
+    cache_read_tokens INTEGER DEFAULT 0,
+    cache_write_tokens INTEGER DEFAULT 0,
+    source TEXT DEFAULT 'api',
+    pricing_version TEXT,
+    tenant_id TEXT NOT NULL DEFAULT 'default'
+);
+
+INSERT INTO events_new (
+    id, event_id, schema_version, session_id, agent_type, event_type, tool_name,
+    status, tokens_in, tokens_out, branch, project, duration_ms, created_at,
+    client_timestamp, metadata, payload_truncated, model, cost_usd,
+    cache_read_tokens, cache_write_tokens, source, pricing_version, tenant_id
+)
+SELECT id, event_id, schema_version, session_id, agent_type, event_type, tool_name,
+    status, tokens_in, tokens_out, branch, project, duration_ms, created_at,
+    client_timestamp, metadata, payload_truncated, model, cost_usd,
+    cache_read_tokens, cache_write_tokens, source, pricing_version, tenant_id
+FROM events;
+
+DROP TABLE events;
+ALTER TABLE events_new RENAME TO events;
+
+CREATE UNIQUE INDEX idx_events_event_id_tenant ON events(tenant_id, event_id);
+CREATE INDEX IF NOT EXISTS idx_events_created_at ON events(created_at);
+CREATE INDEX IF NOT EXISTS idx_events_session_id ON events(session_id);
+CREATE INDEX IF NOT EXISTS idx_events_event_type ON events(event_type);
+CREATE INDEX IF NOT EXISTS idx_events_tool_name ON events(tool_name);
+CREATE INDEX IF NOT EXISTS idx_events_agent_type ON events(agent_type);
+CREATE INDEX IF NOT EXISTS idx_events_model ON events(model);
+CREATE INDEX IF NOT EXISTS idx_events_tenant_id ON events(tenant_id);
+
+CREATE TRIGGER IF NOT EXISTS events_fts_ai AFTER INSERT ON events BEGIN
+    INSERT INTO events_fts(rowid, metadata, tool_name) VALUES (new.id, new.metadata, new.tool_name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS events_fts_ad AFTER DELETE ON events BEGIN
+    INSERT INTO events_fts(events_fts, rowid, metadata, tool_name) VALUES ('delete', old.id, old.metadata, old.tool_name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS events_fts_au AFTER UPDATE ON events BEGIN
+    INSERT INTO events_fts(events_fts, rowid, metadata, tool_name) VALUES ('delete', old.id, old.metadata, old.tool_name);
+    INSERT INTO events_fts(rowid, metadata, tool_name) VALUES (new.id, new.metadata, new.tool_name);
+END;
+"#;
+
+/// Ordered, append-only list of migrations. Never reorder or edit an entry
+/// that has already shipped — add a new one with the next version instead.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "base_schema",
+        up_sql: BASE_SCHEMA_SQL,
+    },
+    Migration {
+        version: 2,
+        name: "import_state",
+        up_sql: IMPORT_STATE_SCHEMA_SQL,
+    },
+    Migration {
+        version: 3,
+        name: "event_pricing_version",
+        up_sql: EVENT_PRICING_VERSION_SQL,
+    },
+    Migration {
+        version: 4,
+        name: "session_stats_rollup",
+        up_sql: SESSION_STATS_ROLLUP_SQL,
+    },
+    Migration {
+        version: 5,
+        name: "events_fts",
+        up_sql: EVENTS_FTS_SQL,
+    },
+    Migration {
+        version: 6,
+        name: "event_fingerprints",
+        up_sql: EVENT_FINGERPRINTS_SQL,
+    },
+    Migration {
+        version: 7,
+        name: "payload_chunks",
+        up_sql: PAYLOAD_CHUNKS_SQL,
+    },
+    Migration {
+        version: 8,
+        name: "tenant_partitioning",
+        up_sql: TENANT_PARTITIONING_SQL,
+    },
+    Migration {
+        version: 9,
+        name: "settings_table",
+        up_sql: SETTINGS_SCHEMA_SQL,
+    },
+    Migration {
+        version: 10,
+        name: "admin_key_scope",
+        up_sql: ADMIN_KEY_SCOPE_SQL,
+    },
+    Migration {
+        version: 11,
+        name: "tenant_scoped_uniqueness",
+        up_sql: TENANT_SCOPED_UNIQUENESS_SQL,
+    },
+];
+
+/// The version of the last migration applied to `conn`, per `PRAGMA
+/// user_version`. Zero on a fresh, never-migrated database.
+pub fn current_version(conn: &Connection) -> Result<u32> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+/// Apply every migration whose version exceeds `conn`'s current
+/// `user_version`, in order. Each migration runs inside its own
+/// transaction: its `up_sql`, a `schema_migrations` row recording it, and
+/// the `user_version` bump all commit together, so a failure partway
+/// through rolls back that migration and leaves `user_version` (and every
+/// migration after it) unapplied. Returns the resulting version.
+pub fn migrate(conn: &Connection) -> Result<u32> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+    )?;
+
+    let mut version = current_version(conn)?;
+    let starting_version = version;
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+        info!("applying migration {} ({})", migration.version, migration.name);
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(migration.up_sql)?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version, name) VALUES (?1, ?2)",
+            rusqlite::params![migration.version, migration.name],
+        )?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+        version = migration.version;
+    }
+
+    if version != starting_version {
+        info!("database migrated from version {starting_version} to {version}");
+    }
+
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open() -> Connection {
+        Connection::open_in_memory().unwrap()
+    }
+
+    #[test]
+    fn migrate_applies_every_shipped_migration_in_order() {
+        let conn = open();
+        let version = migrate(&conn).unwrap();
+        let last = MIGRATIONS.last().unwrap().version;
+        assert_eq!(version, last);
+        assert_eq!(current_version(&conn).unwrap(), last);
+
+        let applied: i64 = conn
+            .query_row("SELECT COUNT(*) FROM schema_migrations", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(applied, MIGRATIONS.len() as i64);
+    }
+
+    #[test]
+    fn migrate_is_a_no_op_on_an_already_current_database() {
+        let conn = open();
+        migrate(&conn).unwrap();
+        let applied_before: i64 = conn
+            .query_row("SELECT COUNT(*) FROM schema_migrations", [], |r| r.get(0))
+            .unwrap();
+
+        let version = migrate(&conn).unwrap();
+
+        let applied_after: i64 = conn
+            .query_row("SELECT COUNT(*) FROM schema_migrations", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.last().unwrap().version);
+        assert_eq!(applied_before, applied_after, "re-running migrate must not re-apply anything");
+    }
+
+    /// A migration whose `up_sql` fails partway through must leave neither a
+    /// half-applied schema nor a bumped `user_version` behind — the same
+    /// guarantee `migrate` gives each real entry in `MIGRATIONS`, exercised
+    /// here against a SQL statement engineered to fail.
+    #[test]
+    fn a_failing_migration_rolls_back_without_touching_user_version() {
+        let conn = open();
+        migrate(&conn).unwrap();
+        let version_before = current_version(&conn).unwrap();
+
+        let tx = conn.unchecked_transaction().unwrap();
+        let result = tx.execute_batch("CREATE TABLE scratch (id INTEGER); NOT VALID SQL;");
+        assert!(result.is_err());
+        drop(tx);
+
+        assert_eq!(current_version(&conn).unwrap(), version_before);
+        let scratch_exists: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'scratch'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(scratch_exists, 0, "the half-run statement's table must not survive rollback");
+    }
+}
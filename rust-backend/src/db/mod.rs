@@ -0,0 +1,33 @@
+pub mod backup;
+pub mod migrations;
+pub mod pool;
+pub mod postgres;
+pub mod queries;
+pub mod rollup;
+pub mod schema;
+pub mod settings;
+pub mod store;
+
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::db::store::{Store, StoreError};
+
+/// Picks the `Store` backend for this process: `PostgresStore` when
+/// `Config::database_url` is set, otherwise a `SqliteStore` opened at
+/// `Config::db_path`. Neither `AppState` nor any handler calls this yet —
+/// `AppState::remote_store` calls `PostgresStore::connect` directly instead,
+/// since it only ever wants the Postgres branch (the `SqliteStore` branch
+/// would open a second, independent connection alongside `DbPools` — see
+/// `AppState::new`). This is the one place the full picks-either-backend
+/// decision should live once more than `remote_store` needs it.
+pub fn build_store(config: &Config) -> Result<Arc<dyn Store>, StoreError> {
+    if let Some(database_url) = config.database_url.as_deref() {
+        let pg = postgres::PostgresStore::connect(database_url)?;
+        Ok(Arc::new(pg))
+    } else {
+        let conn = rusqlite::Connection::open(&config.db_path)
+            .map_err(StoreError::from)?;
+        Ok(Arc::new(store::SqliteStore::new(conn)))
+    }
+}
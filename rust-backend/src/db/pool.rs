@@ -0,0 +1,120 @@
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::OpenFlags;
+
+use crate::db::migrations;
+
+pub type DbPool = Pool<SqliteConnectionManager>;
+pub type PooledConn = r2d2::PooledConnection<SqliteConnectionManager>;
+
+/// The two pools a live dashboard needs to not serialize on SQLite's single
+/// writer lock: `writer` is capped at one connection (SQLite only allows one
+/// writer at a time anyway, so a bigger pool would just move the queueing
+/// from the OS file lock to the pool), while `reader` is a `max_size`-sized
+/// pool of `SQLITE_OPEN_READ_ONLY` connections analytics queries check out
+/// of instead, so a long-running `get_cost_over_time` scan never blocks
+/// `insert_event`. Both point at the same on-disk file in WAL mode, which is
+/// what actually makes the two pools able to proceed concurrently.
+pub struct DbPools {
+    pub writer: DbPool,
+    pub reader: DbPool,
+    /// `PRAGMA user_version` after `initialize` ran `migrations::migrate` —
+    /// the schema version every connection either pool hands out is on.
+    pub schema_version: u32,
+}
+
+/// Error setting up the pooled connection manager.
+#[derive(Debug)]
+pub enum PoolError {
+    Build(r2d2::Error),
+    Checkout(r2d2::Error),
+    Migrate(rusqlite::Error),
+    Query(rusqlite::Error),
+    Join(tokio::task::JoinError),
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolError::Build(e) => write!(f, "failed to build connection pool: {e}"),
+            PoolError::Checkout(e) => write!(f, "failed to check out a connection: {e}"),
+            PoolError::Migrate(e) => write!(f, "failed to migrate database: {e}"),
+            PoolError::Query(e) => write!(f, "database query failed: {e}"),
+            PoolError::Join(e) => write!(f, "connection checkout task panicked: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PoolError {}
+
+/// Open `db_path` as a pair of pooled, WAL-mode SQLite connection managers —
+/// a single writer plus a `max_read_pool_size`-sized set of read-only
+/// connections — and run any pending migrations once up front through the
+/// writer, so every connection either pool hands out is already on the
+/// current schema.
+///
+/// `:memory:` is special-cased to a single shared connection reused for both
+/// `writer` and `reader`: SQLite's in-memory databases are private to the
+/// connection that created them, so a second pool would silently hand some
+/// callers back an empty database.
+///
+/// `passphrase`, when set, requires this crate built against a SQLCipher
+/// `libsqlite3` — every connection the pool hands out issues `PRAGMA key`
+/// before anything else runs, per `with_init`, including before the
+/// migration check so `migrate` sees the decrypted schema rather than
+/// garbage. `db::backup::rekey` rotates the passphrase on an already-open
+/// database; `initialize` always opens with whatever passphrase is current.
+pub fn initialize(
+    db_path: &Path,
+    max_read_pool_size: u32,
+    passphrase: Option<&str>,
+) -> Result<DbPools, PoolError> {
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+
+    let is_memory = db_path.as_os_str() == ":memory:";
+    let key_init = passphrase.map(|p| p.to_string());
+    let writer_manager = SqliteConnectionManager::file(db_path).with_init(move |conn| {
+        if let Some(key) = &key_init {
+            conn.pragma_update(None, "key", key)?;
+        }
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.busy_timeout(Duration::from_millis(5000))?;
+        Ok(())
+    });
+
+    let writer = Pool::builder()
+        .max_size(1)
+        .build(writer_manager)
+        .map_err(PoolError::Build)?;
+
+    let conn = writer.get().map_err(PoolError::Checkout)?;
+    let schema_version = migrations::migrate(&conn).map_err(PoolError::Migrate)?;
+    drop(conn);
+
+    let reader = if is_memory {
+        writer.clone()
+    } else {
+        let key_init = passphrase.map(|p| p.to_string());
+        let reader_manager = SqliteConnectionManager::file(db_path)
+            .with_flags(OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+            .with_init(move |conn| {
+                if let Some(key) = &key_init {
+                    conn.pragma_update(None, "key", key)?;
+                }
+                conn.busy_timeout(Duration::from_millis(5000))?;
+                Ok(())
+            });
+        Pool::builder()
+            .max_size(max_read_pool_size.max(1))
+            .build(reader_manager)
+            .map_err(PoolError::Build)?
+    };
+
+    Ok(DbPools { writer, reader, schema_version })
+}
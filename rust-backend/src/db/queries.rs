@@ -20,6 +20,13 @@ pub fn upsert_agent(conn: &Connection, id: &str, agent_type: &str) -> rusqlite::
 
 // --- Sessions ---
 
+/// `tenant_id` is only set on insert — a session's tenant is fixed at
+/// creation and never reassigned by a later event for the same `id`, the
+/// same way `agent_id`/`agent_type` aren't touched by the `ON CONFLICT`
+/// clause either. The conflict target is the composite `(id, tenant_id)`
+/// primary key (see the `tenant_scoped_uniqueness` migration), not `id`
+/// alone — otherwise two tenants whose clients independently generate the
+/// same session id would collide into one row.
 pub fn upsert_session(
     conn: &Connection,
     id: &str,
@@ -27,16 +34,17 @@ pub fn upsert_session(
     agent_type: &str,
     project: Option<&str>,
     branch: Option<&str>,
+    tenant_id: &str,
 ) -> rusqlite::Result<()> {
     conn.execute(
-        "INSERT INTO sessions (id, agent_id, agent_type, project, branch)
-         VALUES (?1, ?2, ?3, ?4, ?5)
-         ON CONFLICT(id) DO UPDATE SET
+        "INSERT INTO sessions (id, agent_id, agent_type, project, branch, tenant_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(id, tenant_id) DO UPDATE SET
            last_event_at = datetime('now'),
            status = 'active',
            project = COALESCE(excluded.project, sessions.project),
            branch = COALESCE(excluded.branch, sessions.branch)",
-        params![id, agent_id, agent_type, project, branch],
+        params![id, agent_id, agent_type, project, branch, tenant_id],
     )?;
     Ok(())
 }
@@ -71,26 +79,86 @@ pub fn end_session(conn: &Connection, session_id: &str) -> rusqlite::Result<()>
     Ok(())
 }
 
-pub fn update_idle_sessions(conn: &Connection, timeout_minutes: u64) -> rusqlite::Result<usize> {
+/// `tenant_id` narrows both transitions to one tenant's sessions —
+/// `runtime_tasks::run_idle_check_once` calls this once per tenant returned
+/// by `list_tenants` rather than once globally, so one tenant's idle sweep
+/// never touches another's session rows.
+pub fn update_idle_sessions(
+    conn: &Connection,
+    timeout_minutes: u64,
+    tenant_id: &str,
+) -> rusqlite::Result<usize> {
     let neg = format!("-{timeout_minutes}");
     let idled = conn.execute(
         "UPDATE sessions SET status = 'idle'
-         WHERE status = 'active'
-         AND last_event_at < datetime('now', ?1 || ' minutes')",
-        params![neg],
+         WHERE status = 'active' AND tenant_id = ?1
+         AND last_event_at < datetime('now', ?2 || ' minutes')",
+        params![tenant_id, neg],
     )?;
 
     let neg_double = format!("-{}", timeout_minutes * 2);
     conn.execute(
         "UPDATE sessions SET status = 'ended', ended_at = datetime('now')
-         WHERE status = 'idle' AND ended_at IS NULL
-         AND last_event_at < datetime('now', ?1 || ' minutes')",
-        params![neg_double],
+         WHERE status = 'idle' AND ended_at IS NULL AND tenant_id = ?1
+         AND last_event_at < datetime('now', ?2 || ' minutes')",
+        params![tenant_id, neg_double],
     )?;
 
     Ok(idled)
 }
 
+/// Desktop-only counterpart to `update_idle_sessions` that additionally
+/// returns the ids that transitioned all the way to `ended` this call, so
+/// `idle_sweep::sweep_once` can emit a `session-idle-closed` event per
+/// session instead of only a count. Same `active` -> `idle` -> `ended`
+/// staging and tenant scoping as `update_idle_sessions`; kept as a separate
+/// function rather than changing that one's return type because its other
+/// callers (`runtime_tasks::run_idle_check_once`, directly or through
+/// `SqliteStore`) have no use for the ids and broadcast a tenant-scoped
+/// count instead.
+pub fn update_idle_sessions_returning_ended(
+    conn: &Connection,
+    timeout_minutes: u64,
+    tenant_id: &str,
+) -> rusqlite::Result<Vec<String>> {
+    let neg = format!("-{timeout_minutes}");
+    conn.execute(
+        "UPDATE sessions SET status = 'idle'
+         WHERE status = 'active' AND tenant_id = ?1
+         AND last_event_at < datetime('now', ?2 || ' minutes')",
+        params![tenant_id, neg],
+    )?;
+
+    let neg_double = format!("-{}", timeout_minutes * 2);
+    let ended_ids: Vec<String> = {
+        let mut stmt = conn.prepare(
+            "SELECT id FROM sessions WHERE status = 'idle' AND ended_at IS NULL AND tenant_id = ?1
+             AND last_event_at < datetime('now', ?2 || ' minutes')",
+        )?;
+        let rows = stmt.query_map(params![tenant_id, neg_double], |row| row.get(0))?;
+        rows.filter_map(Result::ok).collect()
+    };
+
+    conn.execute(
+        "UPDATE sessions SET status = 'ended', ended_at = datetime('now')
+         WHERE status = 'idle' AND ended_at IS NULL AND tenant_id = ?1
+         AND last_event_at < datetime('now', ?2 || ' minutes')",
+        params![tenant_id, neg_double],
+    )?;
+
+    Ok(ended_ids)
+}
+
+/// Distinct tenants with at least one session, for iterating the idle scan
+/// and stats rollup per tenant — see `runtime_tasks`. A fresh single-tenant
+/// deployment with no sessions yet returns an empty list, same as it always
+/// has implicitly run its one (now `'default'`) tenant's cycle.
+pub fn list_tenants(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare_cached("SELECT DISTINCT tenant_id FROM sessions")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    rows.collect()
+}
+
 // --- Events ---
 
 #[derive(Debug, Clone, Serialize)]
@@ -116,6 +184,8 @@ pub struct EventRow {
     pub cache_read_tokens: i64,
     pub cache_write_tokens: i64,
     pub source: String,
+    pub pricing_version: Option<String>,
+    pub tenant_id: String,
 }
 
 impl EventRow {
@@ -142,6 +212,8 @@ impl EventRow {
             cache_read_tokens: row.get("cache_read_tokens")?,
             cache_write_tokens: row.get("cache_write_tokens")?,
             source: row.get("source")?,
+            pricing_version: row.get("pricing_version")?,
+            tenant_id: row.get("tenant_id")?,
         })
     }
 }
@@ -166,6 +238,13 @@ pub struct InsertEventParams<'a> {
     pub cache_read_tokens: i64,
     pub cache_write_tokens: i64,
     pub source: &'a str,
+    /// Which tenant this event belongs to — see `auth::DEFAULT_TENANT` and
+    /// the scope note on `db::store::Store`. Assigned by the ingest call
+    /// site from the authenticated key's tenant (or `DEFAULT_TENANT` for
+    /// paths with no per-key auth, e.g. relay/NATS/MQTT/the importer), never
+    /// parsed out of the client payload — the same precedent `source`
+    /// already set.
+    pub tenant_id: &'a str,
 }
 
 /// Insert an event. Returns the inserted row, or None if deduplicated (event_id conflict).
@@ -182,6 +261,7 @@ pub fn insert_event(
         p.agent_type,
         p.project,
         p.branch,
+        p.tenant_id,
     )?;
 
     // Handle session lifecycle
@@ -193,8 +273,10 @@ pub fn insert_event(
         }
     }
 
-    let computed_cost = if p.cost_usd.is_none() && (p.tokens_in > 0 || p.tokens_out > 0) {
-        p.model.and_then(|model| {
+    let mut computed_cost = p.cost_usd;
+    let mut pricing_version = None;
+    if p.cost_usd.is_none() && (p.tokens_in > 0 || p.tokens_out > 0) {
+        computed_cost = p.model.and_then(|model| {
             calculate_cost(
                 model,
                 TokenCounts {
@@ -204,45 +286,57 @@ pub fn insert_event(
                     cache_write: p.cache_write_tokens,
                 },
             )
-        })
-    } else {
-        p.cost_usd
-    };
+        });
+        if computed_cost.is_some() {
+            pricing_version = Some(crate::pricing::pricing_version());
+        }
+    }
 
-    let result = conn.execute(
-        "INSERT INTO events (
-            event_id, session_id, agent_type, event_type, tool_name, status,
-            tokens_in, tokens_out, branch, project, duration_ms,
-            created_at, client_timestamp, metadata, payload_truncated,
-            model, cost_usd, cache_read_tokens, cache_write_tokens, source
-         ) VALUES (
-            ?1, ?2, ?3, ?4, ?5, ?6,
-            ?7, ?8, ?9, ?10, ?11,
-            datetime('now'), ?12, ?13, ?14,
-            ?15, ?16, ?17, ?18, ?19
-         )",
-        params![
-            p.event_id,
-            p.session_id,
-            p.agent_type,
-            p.event_type,
-            p.tool_name,
-            p.status,
-            p.tokens_in,
-            p.tokens_out,
-            p.branch,
-            p.project,
-            p.duration_ms,
-            p.client_timestamp,
-            p.metadata,
-            p.payload_truncated as i64,
-            p.model,
-            computed_cost,
-            p.cache_read_tokens,
-            p.cache_write_tokens,
-            p.source,
-        ],
-    );
+    // `prepare_cached` rather than `conn.execute` so a batch ingest sharing
+    // one transaction across many rows (see `api::events::ingest_batch`)
+    // compiles this statement once and reuses it, instead of re-parsing the
+    // same SQL text on every row.
+    let result = conn
+        .prepare_cached(
+            "INSERT INTO events (
+                event_id, session_id, agent_type, event_type, tool_name, status,
+                tokens_in, tokens_out, branch, project, duration_ms,
+                created_at, client_timestamp, metadata, payload_truncated,
+                model, cost_usd, cache_read_tokens, cache_write_tokens, source,
+                pricing_version, tenant_id
+             ) VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6,
+                ?7, ?8, ?9, ?10, ?11,
+                datetime('now'), ?12, ?13, ?14,
+                ?15, ?16, ?17, ?18, ?19,
+                ?20, ?21
+             )",
+        )
+        .and_then(|mut stmt| {
+            stmt.execute(params![
+                p.event_id,
+                p.session_id,
+                p.agent_type,
+                p.event_type,
+                p.tool_name,
+                p.status,
+                p.tokens_in,
+                p.tokens_out,
+                p.branch,
+                p.project,
+                p.duration_ms,
+                p.client_timestamp,
+                p.metadata,
+                p.payload_truncated as i64,
+                p.model,
+                computed_cost,
+                p.cache_read_tokens,
+                p.cache_write_tokens,
+                p.source,
+                pricing_version,
+                p.tenant_id,
+            ])
+        });
 
     match result {
         Ok(0) => Ok(None), // no rows changed (shouldn't happen with INSERT, but defensive)
@@ -253,10 +347,12 @@ pub fn insert_event(
             Ok(Some(row))
         }
         Err(e) => {
-            // UNIQUE constraint violation on event_id = deduplicated
-            if e.to_string()
-                .contains("UNIQUE constraint failed: events.event_id")
-            {
+            // UNIQUE constraint violation on (tenant_id, event_id) = deduplicated.
+            // Substring rather than an exact prefix match since the
+            // `tenant_scoped_uniqueness` migration made this a composite
+            // index, so SQLite's error lists `events.tenant_id` ahead of
+            // `events.event_id` in the violated-columns list.
+            if e.to_string().contains("UNIQUE constraint failed") && e.to_string().contains("event_id") {
                 Ok(None)
             } else {
                 Err(e)
@@ -265,6 +361,172 @@ pub fn insert_event(
     }
 }
 
+/// Events with `id > since`, oldest first, for `api::events::poll_events`'s
+/// catch-up response. Capped at `limit` rows so a client that's fallen far
+/// behind gets a bounded response instead of the whole backlog at once —
+/// its next poll just picks up from the last id in this page.
+pub fn events_since(conn: &Connection, since: i64, limit: usize) -> rusqlite::Result<Vec<EventRow>> {
+    let mut stmt = conn.prepare_cached("SELECT * FROM events WHERE id > ?1 ORDER BY id ASC LIMIT ?2")?;
+    let rows = stmt.query_map(params![since, limit as i64], EventRow::from_row)?;
+    rows.collect()
+}
+
+/// The current highest `events.id`, or 0 if the table is empty. Used as the
+/// next cursor when a poll times out with nothing new, so a client that
+/// polls again doesn't re-scan rows it already knows don't match.
+pub fn max_event_id(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.query_row("SELECT COALESCE(MAX(id), 0) FROM events", [], |row| row.get(0))
+}
+
+// --- Import content-hash dedup ---
+
+/// Whether `fingerprint` (see `importer::content_fingerprint`) has already
+/// been recorded for `session_id`.
+pub fn fingerprint_exists(
+    conn: &Connection,
+    session_id: &str,
+    fingerprint: &str,
+) -> rusqlite::Result<bool> {
+    Ok(conn
+        .query_row(
+            "SELECT 1 FROM event_fingerprints WHERE session_id = ?1 AND fingerprint = ?2",
+            params![session_id, fingerprint],
+            |_| Ok(()),
+        )
+        .ok()
+        .is_some())
+}
+
+/// Records that `fingerprint` has been imported for `session_id`.
+/// `INSERT OR IGNORE` because the importer only calls this after a
+/// successful insert, but a re-import racing a still-running one could see
+/// the same fingerprint land twice.
+pub fn record_fingerprint(
+    conn: &Connection,
+    session_id: &str,
+    fingerprint: &str,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO event_fingerprints (session_id, fingerprint) VALUES (?1, ?2)",
+        params![session_id, fingerprint],
+    )?;
+    Ok(())
+}
+
+// --- Chunked payload storage ---
+
+/// Persists `chunks` (see `util::chunking::chunk`) for `event_id`, in order.
+/// Each unique chunk is stored once in `payload_chunks` — `INSERT OR IGNORE`
+/// since the same content-addressed chunk recurring across events (or
+/// within the same payload) is the point of content-defined chunking, not
+/// an error.
+pub fn persist_chunked_metadata(conn: &Connection, event_id: i64, chunks: &[crate::util::chunking::Chunk]) -> rusqlite::Result<()> {
+    let mut insert_chunk = conn.prepare_cached(
+        "INSERT OR IGNORE INTO payload_chunks (content_hash, bytes) VALUES (?1, ?2)",
+    )?;
+    let mut insert_link = conn.prepare_cached(
+        "INSERT INTO event_payload_chunks (event_id, chunk_index, content_hash) VALUES (?1, ?2, ?3)",
+    )?;
+    for (index, chunk) in chunks.iter().enumerate() {
+        insert_chunk.execute(params![chunk.content_hash, chunk.bytes])?;
+        insert_link.execute(params![event_id, index as i64, chunk.content_hash])?;
+    }
+    Ok(())
+}
+
+/// Reassembles a chunked payload stored by [`persist_chunked_metadata`] back
+/// into the original metadata `Value` — the read-side counterpart to
+/// `util::truncate::MetadataStorage::Chunked`. Returns `Ok(None)` if
+/// `event_id` has no recorded chunks (it wasn't stored chunked).
+pub fn reassemble_chunked_metadata(conn: &Connection, event_id: i64) -> rusqlite::Result<Option<serde_json::Value>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT pc.bytes FROM event_payload_chunks epc
+         JOIN payload_chunks pc ON pc.content_hash = epc.content_hash
+         WHERE epc.event_id = ?1
+         ORDER BY epc.chunk_index",
+    )?;
+    let mut bytes = Vec::new();
+    let mut found = false;
+    let rows = stmt.query_map(params![event_id], |row| row.get::<_, Vec<u8>>(0))?;
+    for chunk_bytes in rows {
+        bytes.extend(chunk_bytes?);
+        found = true;
+    }
+    if !found {
+        return Ok(None);
+    }
+    Ok(Some(
+        serde_json::from_slice(&bytes).unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(&bytes).into_owned())),
+    ))
+}
+
+// --- Pricing maintenance ---
+
+#[derive(Debug, Serialize)]
+pub struct RecomputeSummary {
+    pub rows_scanned: usize,
+    pub rows_updated: usize,
+    pub pricing_version: String,
+}
+
+/// Re-derive `cost_usd` for rows whose cost our pricing table owns — either
+/// never successfully priced (`cost_usd IS NULL`) or priced under an older
+/// rate table (`pricing_version` doesn't match the one currently loaded) —
+/// and leaves everything else untouched, in particular rows whose cost came
+/// straight from the source log (`pricing_version IS NULL AND cost_usd IS
+/// NOT NULL`). Comparing against `pricing::pricing_version()` up front makes
+/// a repeat run with unchanged rates a no-op, so this is safe to run on a
+/// schedule.
+pub fn recompute_costs(conn: &Connection) -> rusqlite::Result<RecomputeSummary> {
+    let current_version = crate::pricing::pricing_version();
+
+    let mut stmt = conn.prepare(
+        "SELECT id, model, tokens_in, tokens_out, cache_read_tokens, cache_write_tokens
+         FROM events
+         WHERE model IS NOT NULL
+           AND (tokens_in > 0 OR tokens_out > 0)
+           AND (pricing_version IS NOT NULL OR cost_usd IS NULL)
+           AND (pricing_version IS NULL OR pricing_version <> ?1)",
+    )?;
+    let candidates: Vec<(i64, String, i64, i64, i64, i64)> = stmt
+        .query_map(params![current_version], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut rows_updated = 0usize;
+    for (id, model, tokens_in, tokens_out, cache_read_tokens, cache_write_tokens) in &candidates {
+        if let Some(cost) = calculate_cost(
+            model,
+            TokenCounts {
+                input: *tokens_in,
+                output: *tokens_out,
+                cache_read: *cache_read_tokens,
+                cache_write: *cache_write_tokens,
+            },
+        ) {
+            conn.execute(
+                "UPDATE events SET cost_usd = ?1, pricing_version = ?2 WHERE id = ?3",
+                params![cost, current_version, id],
+            )?;
+            rows_updated += 1;
+        }
+    }
+
+    Ok(RecomputeSummary {
+        rows_scanned: candidates.len(),
+        rows_updated,
+        pricing_version: current_version.to_string(),
+    })
+}
+
 // --- Stats ---
 
 #[derive(Debug, Serialize)]
@@ -277,17 +539,28 @@ pub struct Stats {
     pub total_cost_usd: f64,
 }
 
-pub fn get_stats(conn: &Connection) -> rusqlite::Result<Stats> {
-    let mut stmt = conn.prepare_cached(
+/// `tenant_id` narrows every count to one tenant's rows; `None` reports
+/// across all tenants — only the unauthenticated `/metrics` endpoint passes
+/// that. A deployment with no multi-tenant auth configured passes
+/// `Some(DEFAULT_TENANT)`, the tenant every one of its rows carries, so its
+/// numbers are unchanged by this filter existing at all.
+pub fn get_stats(conn: &Connection, tenant_id: Option<&str>) -> rusqlite::Result<Stats> {
+    let events_where = tenant_id.map(|_| "WHERE tenant_id = ?1").unwrap_or("");
+    let sessions_where = tenant_id.map(|_| "WHERE tenant_id = ?1").unwrap_or("");
+    let active_sessions_where = tenant_id
+        .map(|_| "WHERE status = 'active' AND tenant_id = ?1")
+        .unwrap_or("WHERE status = 'active'");
+
+    let mut stmt = conn.prepare_cached(&format!(
         "SELECT
             COUNT(*) as total_events,
             COALESCE(SUM(tokens_in), 0) as total_tokens_in,
             COALESCE(SUM(tokens_out), 0) as total_tokens_out,
             COALESCE(SUM(cost_usd), 0) as total_cost_usd
-         FROM events",
-    )?;
+         FROM events {events_where}"
+    ))?;
     let (total_events, total_tokens_in, total_tokens_out, total_cost_usd) =
-        stmt.query_row([], |row| {
+        stmt.query_row(params_from_iter(tenant_id), |row| {
             Ok((
                 row.get::<_, i64>(0)?,
                 row.get::<_, i64>(1)?,
@@ -297,13 +570,16 @@ pub fn get_stats(conn: &Connection) -> rusqlite::Result<Stats> {
         })?;
 
     let active_sessions: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM sessions WHERE status = 'active'",
-        [],
+        &format!("SELECT COUNT(*) FROM sessions {active_sessions_where}"),
+        params_from_iter(tenant_id),
         |row| row.get(0),
     )?;
 
-    let total_sessions: i64 =
-        conn.query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))?;
+    let total_sessions: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM sessions {sessions_where}"),
+        params_from_iter(tenant_id),
+        |row| row.get(0),
+    )?;
 
     Ok(Stats {
         total_events,
@@ -315,10 +591,85 @@ pub fn get_stats(conn: &Connection) -> rusqlite::Result<Stats> {
     })
 }
 
+#[derive(Debug, Serialize)]
+pub struct AgentTypeMetrics {
+    pub agent_type: String,
+    pub total_events: i64,
+    pub total_tokens_in: i64,
+    pub total_tokens_out: i64,
+    pub total_cost_usd: f64,
+}
+
+/// Aggregated event/token/cost totals grouped by `agent_type`, for metrics export.
+pub fn get_metrics_by_agent_type(conn: &Connection) -> rusqlite::Result<Vec<AgentTypeMetrics>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT
+            agent_type,
+            COUNT(*) as total_events,
+            COALESCE(SUM(tokens_in), 0) as total_tokens_in,
+            COALESCE(SUM(tokens_out), 0) as total_tokens_out,
+            COALESCE(SUM(cost_usd), 0) as total_cost_usd
+         FROM events
+         GROUP BY agent_type
+         ORDER BY agent_type",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(AgentTypeMetrics {
+            agent_type: row.get(0)?,
+            total_events: row.get(1)?,
+            total_tokens_in: row.get(2)?,
+            total_tokens_out: row.get(3)?,
+            total_cost_usd: row.get(4)?,
+        })
+    })?;
+    rows.collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct DetailedMetrics {
+    pub agent_type: String,
+    pub model: Option<String>,
+    pub event_type: String,
+    pub total_tokens_in: i64,
+    pub total_tokens_out: i64,
+    pub total_cost_usd: f64,
+}
+
+/// Aggregated token/cost totals grouped by `agent_type`, `model`, and
+/// `event_type`, for metrics export at a finer grain than
+/// `get_metrics_by_agent_type`.
+pub fn get_detailed_metrics(conn: &Connection) -> rusqlite::Result<Vec<DetailedMetrics>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT
+            agent_type,
+            model,
+            event_type,
+            COALESCE(SUM(tokens_in), 0) as total_tokens_in,
+            COALESCE(SUM(tokens_out), 0) as total_tokens_out,
+            COALESCE(SUM(cost_usd), 0) as total_cost_usd
+         FROM events
+         GROUP BY agent_type, model, event_type
+         ORDER BY agent_type, model, event_type",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(DetailedMetrics {
+            agent_type: row.get(0)?,
+            model: row.get(1)?,
+            event_type: row.get(2)?,
+            total_tokens_in: row.get(3)?,
+            total_tokens_out: row.get(4)?,
+            total_cost_usd: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct AnalyticsFilters {
     pub agent_type: Option<String>,
     pub since: Option<String>,
+    pub project: Option<String>,
+    pub branch: Option<String>,
 }
 
 // --- Advanced stats endpoints ---
@@ -333,13 +684,21 @@ pub struct ToolAnalyticsRow {
     pub by_agent: HashMap<String, i64>,
 }
 
+/// `tenant_id` of `None` reports across every tenant — only the
+/// unauthenticated `/metrics` endpoint (`api::metrics_handler`) passes that;
+/// every tenant-scoped caller passes `Some`, same convention as `get_stats`.
 pub fn get_tool_analytics(
     conn: &Connection,
     filters: &AnalyticsFilters,
+    tenant_id: Option<&str>,
 ) -> rusqlite::Result<Vec<ToolAnalyticsRow>> {
     let mut conditions = vec!["tool_name IS NOT NULL".to_string()];
     let mut params: Vec<SqlValue> = Vec::new();
 
+    if let Some(tenant_id) = tenant_id {
+        conditions.push("tenant_id = ?".to_string());
+        params.push(SqlValue::Text(tenant_id.to_string()));
+    }
     if let Some(agent_type) = filters.agent_type.as_deref() {
         conditions.push("agent_type = ?".to_string());
         params.push(SqlValue::Text(agent_type.to_string()));
@@ -348,6 +707,14 @@ pub fn get_tool_analytics(
         conditions.push("created_at >= ?".to_string());
         params.push(SqlValue::Text(since.to_string()));
     }
+    if let Some(project) = filters.project.as_deref() {
+        conditions.push("project = ?".to_string());
+        params.push(SqlValue::Text(project.to_string()));
+    }
+    if let Some(branch) = filters.branch.as_deref() {
+        conditions.push("branch = ?".to_string());
+        params.push(SqlValue::Text(branch.to_string()));
+    }
 
     let where_clause = format!("WHERE {}", conditions.join(" AND "));
     let params_refs: Vec<&dyn ToSql> = params.iter().map(|v| v as &dyn ToSql).collect();
@@ -420,9 +787,137 @@ pub fn get_tool_analytics(
         .collect())
 }
 
+/// Raw per-`(tool_name, status)` call count, e.g. for a Prometheus counter.
+/// `ToolAnalyticsRow` only carries the derived `error_rate`, which loses the
+/// underlying totals an alerting rule would want to rate() over.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolStatusCount {
+    pub tool_name: String,
+    pub status: String,
+    pub count: i64,
+}
+
+pub fn get_tool_status_counts(
+    conn: &Connection,
+    filters: &AnalyticsFilters,
+) -> rusqlite::Result<Vec<ToolStatusCount>> {
+    let mut conditions = vec!["tool_name IS NOT NULL".to_string()];
+    let mut params: Vec<SqlValue> = Vec::new();
+
+    if let Some(agent_type) = filters.agent_type.as_deref() {
+        conditions.push("agent_type = ?".to_string());
+        params.push(SqlValue::Text(agent_type.to_string()));
+    }
+    if let Some(since) = filters.since.as_deref() {
+        conditions.push("created_at >= ?".to_string());
+        params.push(SqlValue::Text(since.to_string()));
+    }
+    if let Some(project) = filters.project.as_deref() {
+        conditions.push("project = ?".to_string());
+        params.push(SqlValue::Text(project.to_string()));
+    }
+    if let Some(branch) = filters.branch.as_deref() {
+        conditions.push("branch = ?".to_string());
+        params.push(SqlValue::Text(branch.to_string()));
+    }
+
+    let where_clause = format!("WHERE {}", conditions.join(" AND "));
+    let params_refs: Vec<&dyn ToSql> = params.iter().map(|v| v as &dyn ToSql).collect();
+
+    let sql = format!(
+        "SELECT tool_name, status, COUNT(*) as count
+         FROM events
+         {}
+         GROUP BY tool_name, status
+         ORDER BY tool_name, status",
+        where_clause
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_refs.as_slice(), |row| {
+        Ok(ToolStatusCount {
+            tool_name: row.get(0)?,
+            status: row.get(1)?,
+            count: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Resolution for `get_cost_over_time`'s buckets. `Week` uses `%Y-%W`
+/// (SQLite's zero-indexed, Monday-anchored week-of-year) rather than an ISO
+/// week, same tradeoff the hourly bucket already made by using `strftime`
+/// directly instead of pulling in a calendar library.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum TimeGranularity {
+    #[default]
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl TimeGranularity {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input {
+            "hour" => Some(Self::Hour),
+            "day" => Some(Self::Day),
+            "week" => Some(Self::Week),
+            "month" => Some(Self::Month),
+            _ => None,
+        }
+    }
+
+    fn strftime_format(self) -> &'static str {
+        match self {
+            Self::Hour => "%Y-%m-%dT%H:00:00Z",
+            Self::Day => "%Y-%m-%d",
+            Self::Week => "%Y-%W",
+            Self::Month => "%Y-%m",
+        }
+    }
+}
+
+/// Extra dimension `get_cost_over_time` can break each time bucket down by,
+/// in addition to time. `AgentType` is never NULL on an event; the other
+/// three fall back to `'unknown'` the same way `get_cost_by_project` and
+/// `get_cost_by_model` already do.
+#[derive(Debug, Clone, Copy)]
+pub enum CostGroupBy {
+    Project,
+    Branch,
+    Model,
+    AgentType,
+}
+
+impl CostGroupBy {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input {
+            "project" => Some(Self::Project),
+            "branch" => Some(Self::Branch),
+            "model" => Some(Self::Model),
+            "agent_type" => Some(Self::AgentType),
+            _ => None,
+        }
+    }
+
+    fn column_expr(self) -> &'static str {
+        match self {
+            Self::Project => "COALESCE(project, 'unknown')",
+            Self::Branch => "COALESCE(branch, 'unknown')",
+            Self::Model => "COALESCE(model, 'unknown')",
+            Self::AgentType => "agent_type",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct CostBucket {
     pub bucket: String,
+    /// The `group_by` dimension's value for this row, e.g. a project name
+    /// when `group_by` was `Some(CostGroupBy::Project)`. `None` when
+    /// `get_cost_over_time` was called without a `group_by`.
+    pub group: Option<String>,
     pub cost_usd: f64,
     pub tokens_in: i64,
     pub tokens_out: i64,
@@ -432,9 +927,12 @@ pub struct CostBucket {
 pub fn get_cost_over_time(
     conn: &Connection,
     filters: &AnalyticsFilters,
+    granularity: TimeGranularity,
+    group_by: Option<CostGroupBy>,
+    tenant_id: &str,
 ) -> rusqlite::Result<Vec<CostBucket>> {
-    let mut conditions: Vec<String> = Vec::new();
-    let mut params: Vec<SqlValue> = Vec::new();
+    let mut conditions: Vec<String> = vec!["tenant_id = ?".to_string()];
+    let mut params: Vec<SqlValue> = vec![SqlValue::Text(tenant_id.to_string())];
 
     if let Some(agent_type) = filters.agent_type.as_deref() {
         conditions.push("agent_type = ?".to_string());
@@ -444,6 +942,14 @@ pub fn get_cost_over_time(
         conditions.push("COALESCE(client_timestamp, created_at) >= ?".to_string());
         params.push(SqlValue::Text(since.to_string()));
     }
+    if let Some(project) = filters.project.as_deref() {
+        conditions.push("project = ?".to_string());
+        params.push(SqlValue::Text(project.to_string()));
+    }
+    if let Some(branch) = filters.branch.as_deref() {
+        conditions.push("branch = ?".to_string());
+        params.push(SqlValue::Text(branch.to_string()));
+    }
 
     let where_clause = if conditions.is_empty() {
         String::new()
@@ -452,28 +958,304 @@ pub fn get_cost_over_time(
     };
     let params_refs: Vec<&dyn ToSql> = params.iter().map(|v| v as &dyn ToSql).collect();
 
+    let group_select = group_by.map(|g| g.column_expr()).unwrap_or("NULL");
+    let group_by_clause = match group_by {
+        Some(_) => "bucket, grp",
+        None => "bucket",
+    };
+
     let sql = format!(
         "SELECT
-            strftime('%Y-%m-%dT%H:00:00Z', COALESCE(client_timestamp, created_at)) as bucket,
+            strftime('{}', COALESCE(client_timestamp, created_at)) as bucket,
+            {} as grp,
             COALESCE(SUM(cost_usd), 0) as cost_usd,
             COALESCE(SUM(tokens_in), 0) as tokens_in,
             COALESCE(SUM(tokens_out), 0) as tokens_out,
             COUNT(*) as event_count
          FROM events
          {}
-         GROUP BY bucket
+         GROUP BY {}
          ORDER BY bucket ASC",
-        where_clause
+        granularity.strftime_format(),
+        group_select,
+        where_clause,
+        group_by_clause,
     );
 
     let mut stmt = conn.prepare(&sql)?;
     let rows = stmt.query_map(params_refs.as_slice(), |row| {
         Ok(CostBucket {
             bucket: row.get(0)?,
-            cost_usd: row.get(1)?,
-            tokens_in: row.get(2)?,
-            tokens_out: row.get(3)?,
+            group: row.get(1)?,
+            cost_usd: row.get(2)?,
+            tokens_in: row.get(3)?,
+            tokens_out: row.get(4)?,
+            event_count: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Time bucket resolution for `get_analytics`. A separate whitelist from
+/// `TimeGranularity` — the analytics endpoint only ever charts hour/day
+/// resolution, so there's no `Week`/`Month` variant to parse or reject.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum AnalyticsInterval {
+    #[default]
+    Hour,
+    Day,
+}
+
+impl AnalyticsInterval {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input {
+            "hour" => Some(Self::Hour),
+            "day" => Some(Self::Day),
+            _ => None,
+        }
+    }
+
+    fn strftime_format(self) -> &'static str {
+        match self {
+            Self::Hour => "%Y-%m-%dT%H:00:00Z",
+            Self::Day => "%Y-%m-%d",
+        }
+    }
+}
+
+/// Dimension `get_analytics` can break each time bucket down by, in addition
+/// to time. A separate whitelist from `CostGroupBy` since this endpoint also
+/// exposes `event_type`, which cost breakdowns have no use for.
+#[derive(Debug, Clone, Copy)]
+pub enum AnalyticsGroupBy {
+    Model,
+    Project,
+    Branch,
+    AgentType,
+    EventType,
+}
+
+impl AnalyticsGroupBy {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input {
+            "model" => Some(Self::Model),
+            "project" => Some(Self::Project),
+            "branch" => Some(Self::Branch),
+            "agent_type" => Some(Self::AgentType),
+            "event_type" => Some(Self::EventType),
+            _ => None,
+        }
+    }
+
+    fn column_expr(self) -> &'static str {
+        match self {
+            Self::Model => "COALESCE(model, 'unknown')",
+            Self::Project => "COALESCE(project, 'unknown')",
+            Self::Branch => "COALESCE(branch, 'unknown')",
+            Self::AgentType => "agent_type",
+            Self::EventType => "event_type",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyticsBucket {
+    pub bucket: String,
+    /// The `group_by` dimension's value for this row, `None` when
+    /// `get_analytics` was called without a `group_by`.
+    pub group: Option<String>,
+    pub event_count: i64,
+    pub tokens_in: i64,
+    pub tokens_out: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub cost_usd: f64,
+    pub avg_duration_ms: Option<f64>,
+}
+
+/// Server-side aggregation over `events` for dashboard charts: per-bucket
+/// (and optionally per-`group_by`) token/cost sums and average duration, so
+/// the frontend can render cost-over-time and per-model breakdowns directly
+/// instead of pulling raw events and aggregating client-side. `group_by` is
+/// whitelisted through `AnalyticsGroupBy::column_expr` and `interval`
+/// through `AnalyticsInterval::strftime_format`, so neither ever reaches the
+/// query as free-form SQL.
+pub fn get_analytics(
+    conn: &Connection,
+    filters: &AnalyticsFilters,
+    interval: AnalyticsInterval,
+    group_by: Option<AnalyticsGroupBy>,
+    tenant_id: &str,
+) -> rusqlite::Result<Vec<AnalyticsBucket>> {
+    let mut conditions: Vec<String> = vec!["tenant_id = ?".to_string()];
+    let mut params: Vec<SqlValue> = vec![SqlValue::Text(tenant_id.to_string())];
+
+    if let Some(agent_type) = filters.agent_type.as_deref() {
+        conditions.push("agent_type = ?".to_string());
+        params.push(SqlValue::Text(agent_type.to_string()));
+    }
+    if let Some(since) = filters.since.as_deref() {
+        conditions.push("COALESCE(client_timestamp, created_at) >= ?".to_string());
+        params.push(SqlValue::Text(since.to_string()));
+    }
+    if let Some(project) = filters.project.as_deref() {
+        conditions.push("project = ?".to_string());
+        params.push(SqlValue::Text(project.to_string()));
+    }
+    if let Some(branch) = filters.branch.as_deref() {
+        conditions.push("branch = ?".to_string());
+        params.push(SqlValue::Text(branch.to_string()));
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+    let params_refs: Vec<&dyn ToSql> = params.iter().map(|v| v as &dyn ToSql).collect();
+
+    let group_select = group_by.map(|g| g.column_expr()).unwrap_or("NULL");
+    let group_by_clause = match group_by {
+        Some(_) => "bucket, grp",
+        None => "bucket",
+    };
+
+    let sql = format!(
+        "SELECT
+            strftime('{}', COALESCE(client_timestamp, created_at)) as bucket,
+            {} as grp,
+            COUNT(*) as event_count,
+            COALESCE(SUM(tokens_in), 0) as tokens_in,
+            COALESCE(SUM(tokens_out), 0) as tokens_out,
+            COALESCE(SUM(cache_read_tokens), 0) as cache_read_tokens,
+            COALESCE(SUM(cache_write_tokens), 0) as cache_write_tokens,
+            COALESCE(SUM(cost_usd), 0) as cost_usd,
+            ROUND(AVG(duration_ms)) as avg_duration_ms
+         FROM events
+         {}
+         GROUP BY {}
+         ORDER BY bucket ASC",
+        interval.strftime_format(),
+        group_select,
+        where_clause,
+        group_by_clause,
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_refs.as_slice(), |row| {
+        Ok(AnalyticsBucket {
+            bucket: row.get(0)?,
+            group: row.get(1)?,
+            event_count: row.get(2)?,
+            tokens_in: row.get(3)?,
+            tokens_out: row.get(4)?,
+            cache_read_tokens: row.get(5)?,
+            cache_write_tokens: row.get(6)?,
+            cost_usd: row.get(7)?,
+            avg_duration_ms: row.get(8)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Same file-touching-tool heuristic `db::rollup` uses to back
+/// `session_stats.files_edited` — kept as a literal here rather than a
+/// shared constant since the two queries aggregate over different
+/// dimensions (per-session watermark vs. per-time-bucket) and would gain
+/// nothing from sharing the list besides an import.
+const EDIT_TOOL_NAMES: &str = "'Edit', 'Write', 'MultiEdit', 'apply_patch', 'write_stdin'";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityBucket {
+    pub bucket: String,
+    pub tokens_in: i64,
+    pub tokens_out: i64,
+    pub cost_usd: f64,
+    pub event_count: i64,
+    pub files_edited: i64,
+    pub lines_added: i64,
+    pub lines_removed: i64,
+}
+
+/// Time-bucketed usage trends for charting: tokens/cost/event counts like
+/// `get_cost_over_time`, plus the edit-activity columns `db::rollup` tracks
+/// per session (`files_edited`/`lines_added`/`lines_removed`), computed
+/// straight from `events` so a bucket's numbers don't depend on how
+/// recently the rollup timer last ran.
+///
+/// `utc_offset_minutes` shifts `created_at` before bucketing so day/week
+/// boundaries land on the caller's local midnight instead of UTC's — e.g.
+/// `-300` for US Eastern standard time. Filters go through the same
+/// `conditions`/`params` builder `get_sessions` and `get_cost_over_time`
+/// use, so `agent_type`/`since`/`project`/`branch` all apply here too; call
+/// once per value of a dimension (e.g. once per `project`) to chart trends
+/// split by it.
+pub fn get_activity_stats(
+    conn: &Connection,
+    filters: &AnalyticsFilters,
+    bucket: TimeGranularity,
+    utc_offset_minutes: i64,
+    tenant_id: &str,
+) -> rusqlite::Result<Vec<ActivityBucket>> {
+    let mut conditions: Vec<String> = vec!["tenant_id = ?".to_string()];
+    let mut params: Vec<SqlValue> =
+        vec![SqlValue::Integer(utc_offset_minutes), SqlValue::Text(tenant_id.to_string())];
+
+    if let Some(agent_type) = filters.agent_type.as_deref() {
+        conditions.push("agent_type = ?".to_string());
+        params.push(SqlValue::Text(agent_type.to_string()));
+    }
+    if let Some(since) = filters.since.as_deref() {
+        conditions.push("COALESCE(client_timestamp, created_at) >= ?".to_string());
+        params.push(SqlValue::Text(since.to_string()));
+    }
+    if let Some(project) = filters.project.as_deref() {
+        conditions.push("project = ?".to_string());
+        params.push(SqlValue::Text(project.to_string()));
+    }
+    if let Some(branch) = filters.branch.as_deref() {
+        conditions.push("branch = ?".to_string());
+        params.push(SqlValue::Text(branch.to_string()));
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+    let params_refs: Vec<&dyn ToSql> = params.iter().map(|v| v as &dyn ToSql).collect();
+
+    let sql = format!(
+        "SELECT
+            strftime('{}', datetime(COALESCE(client_timestamp, created_at), (?) || ' minutes')) as bucket,
+            COALESCE(SUM(tokens_in), 0) as tokens_in,
+            COALESCE(SUM(tokens_out), 0) as tokens_out,
+            COALESCE(SUM(cost_usd), 0) as cost_usd,
+            COUNT(*) as event_count,
+            COUNT(DISTINCT CASE
+                WHEN tool_name IN ({EDIT_TOOL_NAMES}) AND json_extract(metadata, '$.file_path') IS NOT NULL
+                THEN json_extract(metadata, '$.file_path')
+            END) as files_edited,
+            COALESCE(SUM(CAST(json_extract(metadata, '$.lines_added') AS INTEGER)), 0) as lines_added,
+            COALESCE(SUM(CAST(json_extract(metadata, '$.lines_removed') AS INTEGER)), 0) as lines_removed
+         FROM events
+         {where_clause}
+         GROUP BY bucket
+         ORDER BY bucket ASC",
+        bucket.strftime_format(),
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_refs.as_slice(), |row| {
+        Ok(ActivityBucket {
+            bucket: row.get(0)?,
+            tokens_in: row.get(1)?,
+            tokens_out: row.get(2)?,
+            cost_usd: row.get(3)?,
             event_count: row.get(4)?,
+            files_edited: row.get(5)?,
+            lines_added: row.get(6)?,
+            lines_removed: row.get(7)?,
         })
     })?;
     rows.collect()
@@ -491,9 +1273,10 @@ pub fn get_cost_by_project(
     conn: &Connection,
     limit: i64,
     filters: &AnalyticsFilters,
+    tenant_id: &str,
 ) -> rusqlite::Result<Vec<ProjectCostRow>> {
-    let mut conditions = vec!["e.cost_usd > 0".to_string()];
-    let mut params: Vec<SqlValue> = Vec::new();
+    let mut conditions = vec!["e.cost_usd > 0".to_string(), "e.tenant_id = ?".to_string()];
+    let mut params: Vec<SqlValue> = vec![SqlValue::Text(tenant_id.to_string())];
 
     if let Some(agent_type) = filters.agent_type.as_deref() {
         conditions.push("e.agent_type = ?".to_string());
@@ -503,6 +1286,14 @@ pub fn get_cost_by_project(
         conditions.push("e.created_at >= ?".to_string());
         params.push(SqlValue::Text(since.to_string()));
     }
+    if let Some(project) = filters.project.as_deref() {
+        conditions.push("e.project = ?".to_string());
+        params.push(SqlValue::Text(project.to_string()));
+    }
+    if let Some(branch) = filters.branch.as_deref() {
+        conditions.push("e.branch = ?".to_string());
+        params.push(SqlValue::Text(branch.to_string()));
+    }
 
     let where_clause = format!("WHERE {}", conditions.join(" AND "));
     params.push(SqlValue::Integer(limit));
@@ -547,9 +1338,14 @@ pub struct ModelCostRow {
 pub fn get_cost_by_model(
     conn: &Connection,
     filters: &AnalyticsFilters,
+    tenant_id: &str,
 ) -> rusqlite::Result<Vec<ModelCostRow>> {
-    let mut conditions = vec!["model IS NOT NULL".to_string(), "cost_usd > 0".to_string()];
-    let mut params: Vec<SqlValue> = Vec::new();
+    let mut conditions = vec![
+        "model IS NOT NULL".to_string(),
+        "cost_usd > 0".to_string(),
+        "tenant_id = ?".to_string(),
+    ];
+    let mut params: Vec<SqlValue> = vec![SqlValue::Text(tenant_id.to_string())];
 
     if let Some(agent_type) = filters.agent_type.as_deref() {
         conditions.push("agent_type = ?".to_string());
@@ -559,6 +1355,14 @@ pub fn get_cost_by_model(
         conditions.push("created_at >= ?".to_string());
         params.push(SqlValue::Text(since.to_string()));
     }
+    if let Some(project) = filters.project.as_deref() {
+        conditions.push("project = ?".to_string());
+        params.push(SqlValue::Text(project.to_string()));
+    }
+    if let Some(branch) = filters.branch.as_deref() {
+        conditions.push("branch = ?".to_string());
+        params.push(SqlValue::Text(branch.to_string()));
+    }
 
     let where_clause = format!("WHERE {}", conditions.join(" AND "));
     let params_refs: Vec<&dyn ToSql> = params.iter().map(|v| v as &dyn ToSql).collect();
@@ -607,14 +1411,22 @@ pub struct AgentUsageData {
     pub extended: Option<UsageWindow>,
 }
 
+/// `tenant_id` scopes the whole usage-monitor sweep to one tenant, same
+/// convention as `get_stats`: `None` reports across all tenants, which is
+/// what every call site passed implicitly before this filter existed.
 pub fn get_usage_monitor(
     conn: &Connection,
     usage_config: &UsageMonitorConfig,
+    tenant_id: Option<&str>,
 ) -> rusqlite::Result<Vec<AgentUsageData>> {
-    let mut stmt =
-        conn.prepare_cached("SELECT DISTINCT agent_type FROM events WHERE agent_type IS NOT NULL")?;
+    let agent_types_where = tenant_id
+        .map(|_| "agent_type IS NOT NULL AND tenant_id = ?1")
+        .unwrap_or("agent_type IS NOT NULL");
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT DISTINCT agent_type FROM events WHERE {agent_types_where}"
+    ))?;
     let agent_types = stmt
-        .query_map([], |row| row.get::<_, String>(0))?
+        .query_map(params_from_iter(tenant_id), |row| row.get::<_, String>(0))?
         .collect::<Result<Vec<_>, _>>()?;
 
     let mut results: Vec<AgentUsageData> = Vec::new();
@@ -630,31 +1442,34 @@ pub fn get_usage_monitor(
             crate::config::UsageLimitType::Cost => "COALESCE(SUM(cost_usd), 0)",
             crate::config::UsageLimitType::Tokens => "COALESCE(SUM(tokens_in + tokens_out), 0)",
         };
+        let tenant_clause = tenant_id.map(|_| " AND tenant_id = ?3").unwrap_or("");
 
         let session_sql = format!(
-            "SELECT {} as used
+            "SELECT {sum_expr} as used
              FROM events
-             WHERE agent_type = ?1 AND created_at >= datetime('now', ?2 || ' hours')",
-            sum_expr
+             WHERE agent_type = ?1 AND created_at >= datetime('now', ?2 || ' hours'){tenant_clause}"
         );
-        let session_used: f64 = conn.query_row(
-            &session_sql,
-            params![agent_type, format!("-{}", cfg.session_window_hours)],
-            |row| row.get(0),
-        )?;
+        let session_window_neg = format!("-{}", cfg.session_window_hours);
+        let mut session_params: Vec<&dyn ToSql> = vec![&agent_type, &session_window_neg];
+        if let Some(tenant_id) = tenant_id {
+            session_params.push(&tenant_id);
+        }
+        let session_used: f64 =
+            conn.query_row(&session_sql, session_params.as_slice(), |row| row.get(0))?;
 
         let extended = if cfg.extended_limit > 0.0 {
             let ext_sql = format!(
-                "SELECT {} as used
+                "SELECT {sum_expr} as used
                  FROM events
-                 WHERE agent_type = ?1 AND created_at >= datetime('now', ?2 || ' hours')",
-                sum_expr
+                 WHERE agent_type = ?1 AND created_at >= datetime('now', ?2 || ' hours'){tenant_clause}"
             );
-            let ext_used: f64 = conn.query_row(
-                &ext_sql,
-                params![agent_type, format!("-{}", cfg.extended_window_hours)],
-                |row| row.get(0),
-            )?;
+            let extended_window_neg = format!("-{}", cfg.extended_window_hours);
+            let mut ext_params: Vec<&dyn ToSql> = vec![&agent_type, &extended_window_neg];
+            if let Some(tenant_id) = tenant_id {
+                ext_params.push(&tenant_id);
+            }
+            let ext_used: f64 =
+                conn.query_row(&ext_sql, ext_params.as_slice(), |row| row.get(0))?;
 
             Some(UsageWindow {
                 used: ext_used,
@@ -734,14 +1549,20 @@ pub struct SessionFilters {
     pub agent_type: Option<String>,
     pub since: Option<String>,
     pub limit: Option<i64>,
+    pub project: Option<String>,
+    pub branch: Option<String>,
 }
 
+/// `tenant_id` is a required param rather than a `SessionFilters` field,
+/// same convention as `get_stats`/`update_idle_sessions`: it comes from the
+/// caller's `Extension<TenantId>`, never from client-supplied query params.
 pub fn get_sessions(
     conn: &Connection,
     filters: &SessionFilters,
+    tenant_id: &str,
 ) -> rusqlite::Result<Vec<SessionRow>> {
-    let mut conditions: Vec<String> = Vec::new();
-    let mut params: Vec<SqlValue> = Vec::new();
+    let mut conditions: Vec<String> = vec!["s.tenant_id = ?".to_string()];
+    let mut params: Vec<SqlValue> = vec![SqlValue::Text(tenant_id.to_string())];
 
     if let Some(status) = filters.status.as_deref() {
         conditions.push("s.status = ?".to_string());
@@ -759,17 +1580,26 @@ pub fn get_sessions(
         conditions.push("s.last_event_at >= ?".to_string());
         params.push(SqlValue::Text(since.to_string()));
     }
+    if let Some(project) = filters.project.as_deref() {
+        conditions.push("s.project = ?".to_string());
+        params.push(SqlValue::Text(project.to_string()));
+    }
+    if let Some(branch) = filters.branch.as_deref() {
+        conditions.push("s.branch = ?".to_string());
+        params.push(SqlValue::Text(branch.to_string()));
+    }
 
     let mut sql = String::from(
         "SELECT s.*,
-            COALESCE((SELECT COUNT(*) FROM events e WHERE e.session_id = s.id), 0) as event_count,
-            COALESCE((SELECT SUM(e.tokens_in) FROM events e WHERE e.session_id = s.id), 0) as tokens_in,
-            COALESCE((SELECT SUM(e.tokens_out) FROM events e WHERE e.session_id = s.id), 0) as tokens_out,
-            COALESCE((SELECT SUM(e.cost_usd) FROM events e WHERE e.session_id = s.id), 0) as total_cost_usd,
-            COALESCE((SELECT COUNT(DISTINCT json_extract(e.metadata, '$.file_path')) FROM events e WHERE e.session_id = s.id AND e.tool_name IN ('Edit', 'Write', 'MultiEdit', 'apply_patch', 'write_stdin') AND json_extract(e.metadata, '$.file_path') IS NOT NULL), 0) as files_edited,
-            COALESCE((SELECT SUM(CAST(json_extract(e.metadata, '$.lines_added') AS INTEGER)) FROM events e WHERE e.session_id = s.id AND json_extract(e.metadata, '$.lines_added') IS NOT NULL), 0) as lines_added,
-            COALESCE((SELECT SUM(CAST(json_extract(e.metadata, '$.lines_removed') AS INTEGER)) FROM events e WHERE e.session_id = s.id AND json_extract(e.metadata, '$.lines_removed') IS NOT NULL), 0) as lines_removed
-         FROM sessions s",
+            COALESCE(ss.event_count, 0) as event_count,
+            COALESCE(ss.tokens_in, 0) as tokens_in,
+            COALESCE(ss.tokens_out, 0) as tokens_out,
+            COALESCE(ss.total_cost_usd, 0) as total_cost_usd,
+            COALESCE(ss.files_edited, 0) as files_edited,
+            COALESCE(ss.lines_added, 0) as lines_added,
+            COALESCE(ss.lines_removed, 0) as lines_removed
+         FROM sessions s
+         LEFT JOIN session_stats ss ON ss.session_id = s.id",
     );
 
     if !conditions.is_empty() {
@@ -793,30 +1623,39 @@ pub fn get_sessions(
     rows.collect()
 }
 
+/// `tenant_id` scopes the session lookup itself — a session id from another
+/// tenant is reported as not found rather than leaking its events, the same
+/// as a nonexistent id would.
 pub fn get_session_with_events(
     conn: &Connection,
     session_id: &str,
     event_limit: i64,
+    tenant_id: &str,
 ) -> rusqlite::Result<(Option<SessionRow>, Vec<EventRow>)> {
     let mut session_stmt = conn.prepare_cached(
         "SELECT s.*,
-            COALESCE((SELECT COUNT(*) FROM events e WHERE e.session_id = s.id), 0) as event_count,
-            COALESCE((SELECT SUM(e.tokens_in) FROM events e WHERE e.session_id = s.id), 0) as tokens_in,
-            COALESCE((SELECT SUM(e.tokens_out) FROM events e WHERE e.session_id = s.id), 0) as tokens_out,
-            COALESCE((SELECT SUM(e.cost_usd) FROM events e WHERE e.session_id = s.id), 0) as total_cost_usd,
-            COALESCE((SELECT COUNT(DISTINCT json_extract(e.metadata, '$.file_path')) FROM events e WHERE e.session_id = s.id AND e.tool_name IN ('Edit', 'Write', 'MultiEdit', 'apply_patch', 'write_stdin') AND json_extract(e.metadata, '$.file_path') IS NOT NULL), 0) as files_edited,
-            COALESCE((SELECT SUM(CAST(json_extract(e.metadata, '$.lines_added') AS INTEGER)) FROM events e WHERE e.session_id = s.id AND json_extract(e.metadata, '$.lines_added') IS NOT NULL), 0) as lines_added,
-            COALESCE((SELECT SUM(CAST(json_extract(e.metadata, '$.lines_removed') AS INTEGER)) FROM events e WHERE e.session_id = s.id AND json_extract(e.metadata, '$.lines_removed') IS NOT NULL), 0) as lines_removed
+            COALESCE(ss.event_count, 0) as event_count,
+            COALESCE(ss.tokens_in, 0) as tokens_in,
+            COALESCE(ss.tokens_out, 0) as tokens_out,
+            COALESCE(ss.total_cost_usd, 0) as total_cost_usd,
+            COALESCE(ss.files_edited, 0) as files_edited,
+            COALESCE(ss.lines_added, 0) as lines_added,
+            COALESCE(ss.lines_removed, 0) as lines_removed
          FROM sessions s
-         WHERE s.id = ?1",
+         LEFT JOIN session_stats ss ON ss.session_id = s.id
+         WHERE s.id = ?1 AND s.tenant_id = ?2",
     )?;
 
-    let session = match session_stmt.query_row(params![session_id], SessionRow::from_row) {
+    let session = match session_stmt.query_row(params![session_id, tenant_id], SessionRow::from_row) {
         Ok(row) => Some(row),
         Err(rusqlite::Error::QueryReturnedNoRows) => None,
         Err(e) => return Err(e),
     };
 
+    if session.is_none() {
+        return Ok((None, Vec::new()));
+    }
+
     let mut event_stmt = conn.prepare_cached(
         "SELECT * FROM events WHERE session_id = ?1 ORDER BY created_at DESC LIMIT ?2",
     )?;
@@ -943,17 +1782,21 @@ pub struct TranscriptEvent {
 pub fn get_session_transcript(
     conn: &Connection,
     session_id: &str,
+    tenant_id: &str,
 ) -> rusqlite::Result<Vec<TranscriptEvent>> {
     let mut stmt = conn.prepare_cached(
         "SELECT id, event_type, tool_name, status, tokens_in, tokens_out,
                 model, cost_usd, duration_ms, created_at, client_timestamp, metadata
          FROM events
-         WHERE session_id = ?1
+         WHERE session_id = ?1 AND tenant_id = ?2
          ORDER BY created_at ASC, id ASC",
     )?;
 
     let rows = stmt.query_map(
-        params_from_iter([SqlValue::Text(session_id.to_string())]),
+        params_from_iter([
+            SqlValue::Text(session_id.to_string()),
+            SqlValue::Text(tenant_id.to_string()),
+        ]),
         |row| {
             Ok(TranscriptEvent {
                 id: row.get("id")?,
@@ -974,3 +1817,245 @@ pub fn get_session_transcript(
 
     rows.collect()
 }
+
+/// Direction for `get_session_transcript_page`'s keyset pagination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TranscriptOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// `(created_at, id)` of the last row a client already has — the opaque
+/// cursor the API layer encodes/decodes — plus an optional `event_type`
+/// allowlist so the `role`/`event_type` query filters push down into the
+/// `WHERE` clause instead of discarding rows after `LIMIT` already capped
+/// the page.
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptPageFilters {
+    pub after: Option<(String, i64)>,
+    pub order: TranscriptOrder,
+    pub event_types: Option<Vec<String>>,
+}
+
+/// Keyset-paginated variant of `get_session_transcript`: `WHERE (created_at,
+/// id) > (?, ?)` instead of `OFFSET`, so paging through a long-running
+/// session's transcript stays O(limit) per page and doesn't shift under a
+/// scrolling client as new events arrive mid-scroll.
+pub fn get_session_transcript_page(
+    conn: &Connection,
+    session_id: &str,
+    filters: &TranscriptPageFilters,
+    limit: i64,
+    tenant_id: &str,
+) -> rusqlite::Result<Vec<TranscriptEvent>> {
+    let mut conditions = vec!["session_id = ?".to_string(), "tenant_id = ?".to_string()];
+    let mut params: Vec<SqlValue> =
+        vec![SqlValue::Text(session_id.to_string()), SqlValue::Text(tenant_id.to_string())];
+
+    if let Some((created_at, id)) = &filters.after {
+        let op = match filters.order {
+            TranscriptOrder::Asc => ">",
+            TranscriptOrder::Desc => "<",
+        };
+        conditions.push(format!("(created_at, id) {op} (?, ?)"));
+        params.push(SqlValue::Text(created_at.clone()));
+        params.push(SqlValue::Integer(*id));
+    }
+
+    if let Some(event_types) = &filters.event_types {
+        if event_types.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders = vec!["?"; event_types.len()].join(", ");
+        conditions.push(format!("event_type IN ({placeholders})"));
+        for event_type in event_types {
+            params.push(SqlValue::Text(event_type.clone()));
+        }
+    }
+
+    let order_clause = match filters.order {
+        TranscriptOrder::Asc => "created_at ASC, id ASC",
+        TranscriptOrder::Desc => "created_at DESC, id DESC",
+    };
+    let where_clause = conditions.join(" AND ");
+    params.push(SqlValue::Integer(limit));
+    let params_refs: Vec<&dyn ToSql> = params.iter().map(|v| v as &dyn ToSql).collect();
+
+    let sql = format!(
+        "SELECT id, event_type, tool_name, status, tokens_in, tokens_out,
+                model, cost_usd, duration_ms, created_at, client_timestamp, metadata
+         FROM events
+         WHERE {where_clause}
+         ORDER BY {order_clause}
+         LIMIT ?"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_refs.as_slice(), |row| {
+        Ok(TranscriptEvent {
+            id: row.get("id")?,
+            event_type: row.get("event_type")?,
+            tool_name: row.get("tool_name")?,
+            status: row.get("status")?,
+            tokens_in: row.get("tokens_in")?,
+            tokens_out: row.get("tokens_out")?,
+            model: row.get("model")?,
+            cost_usd: row.get("cost_usd")?,
+            duration_ms: row.get("duration_ms")?,
+            created_at: row.get("created_at")?,
+            client_timestamp: row.get("client_timestamp")?,
+            metadata: row.get("metadata")?,
+        })
+    })?;
+
+    rows.collect()
+}
+
+/// Full-text search over `metadata`/`tool_name` via `events_fts` (the FTS5
+/// external-content table `db::migrations` keeps in sync with `events`).
+/// `query` is handed straight to FTS5's `MATCH` operator, so phrase queries
+/// (`"apply_patch auth.rs"`) and column-scoped terms (`tool_name:Edit`) work
+/// exactly the way they would against any FTS5 table — see SQLite's FTS5
+/// query syntax, no parsing of our own needed.
+///
+/// `filters.status`/`exclude_status` describe a session's lifecycle, not an
+/// event, and are ignored here; `agent_type`/`project`/`branch`/`since`
+/// narrow directly on the matched event's own columns (both live on
+/// `events`, not just `sessions`), so results don't need a join back to
+/// `sessions`. Ranked by `bm25()` — ascending, since FTS5's bm25 scores more
+/// relevant rows more negative. `tenant_id` narrows to the caller's own
+/// tenant's events, same convention as `get_sessions`.
+pub fn search_events(
+    conn: &Connection,
+    query: &str,
+    filters: &SessionFilters,
+    tenant_id: &str,
+) -> rusqlite::Result<Vec<TranscriptEvent>> {
+    let mut conditions = vec!["events_fts MATCH ?".to_string(), "e.tenant_id = ?".to_string()];
+    let mut params: Vec<SqlValue> =
+        vec![SqlValue::Text(query.to_string()), SqlValue::Text(tenant_id.to_string())];
+
+    if let Some(agent_type) = filters.agent_type.as_deref() {
+        conditions.push("e.agent_type = ?".to_string());
+        params.push(SqlValue::Text(agent_type.to_string()));
+    }
+    if let Some(project) = filters.project.as_deref() {
+        conditions.push("e.project = ?".to_string());
+        params.push(SqlValue::Text(project.to_string()));
+    }
+    if let Some(branch) = filters.branch.as_deref() {
+        conditions.push("e.branch = ?".to_string());
+        params.push(SqlValue::Text(branch.to_string()));
+    }
+    if let Some(since) = filters.since.as_deref() {
+        conditions.push("e.created_at >= ?".to_string());
+        params.push(SqlValue::Text(since.to_string()));
+    }
+
+    let where_clause = conditions.join(" AND ");
+    params.push(SqlValue::Integer(filters.limit.unwrap_or(50)));
+    let params_refs: Vec<&dyn ToSql> = params.iter().map(|v| v as &dyn ToSql).collect();
+
+    let sql = format!(
+        "SELECT e.id, e.event_type, e.tool_name, e.status, e.tokens_in, e.tokens_out,
+                e.model, e.cost_usd, e.duration_ms, e.created_at, e.client_timestamp, e.metadata
+         FROM events_fts
+         JOIN events e ON e.id = events_fts.rowid
+         WHERE {where_clause}
+         ORDER BY bm25(events_fts)
+         LIMIT ?"
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_refs.as_slice(), |row| {
+        Ok(TranscriptEvent {
+            id: row.get(0)?,
+            event_type: row.get(1)?,
+            tool_name: row.get(2)?,
+            status: row.get(3)?,
+            tokens_in: row.get(4)?,
+            tokens_out: row.get(5)?,
+            model: row.get(6)?,
+            cost_usd: row.get(7)?,
+            duration_ms: row.get(8)?,
+            created_at: row.get(9)?,
+            client_timestamp: row.get(10)?,
+            metadata: row.get(11)?,
+        })
+    })?;
+    rows.collect()
+}
+
+// --- API keys ---
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiKeyRow {
+    pub id: i64,
+    pub label: Option<String>,
+    pub scope: String,
+    pub created_at: String,
+    pub revoked_at: Option<String>,
+    pub tenant_id: String,
+}
+
+impl ApiKeyRow {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get("id")?,
+            label: row.get("label")?,
+            scope: row.get("scope")?,
+            created_at: row.get("created_at")?,
+            revoked_at: row.get("revoked_at")?,
+            tenant_id: row.get("tenant_id")?,
+        })
+    }
+}
+
+/// Insert a new API key given its pre-hashed value. `scope` must be
+/// `"read"`, `"write"`, or `"admin"` (enforced by the `api_keys.scope` CHECK
+/// constraint). `tenant_id` is the tenant every request authenticated with
+/// this key is attributed to — see `auth::DEFAULT_TENANT`.
+pub fn create_api_key(
+    conn: &Connection,
+    key_hash: &str,
+    label: Option<&str>,
+    scope: &str,
+    tenant_id: &str,
+) -> rusqlite::Result<ApiKeyRow> {
+    conn.execute(
+        "INSERT INTO api_keys (key_hash, label, scope, tenant_id) VALUES (?1, ?2, ?3, ?4)",
+        params![key_hash, label, scope, tenant_id],
+    )?;
+    let id = conn.last_insert_rowid();
+    conn.query_row(
+        "SELECT id, label, scope, created_at, revoked_at, tenant_id FROM api_keys WHERE id = ?1",
+        params![id],
+        ApiKeyRow::from_row,
+    )
+}
+
+/// All keys that have not been revoked, for loading into the in-memory
+/// auth lookup at startup and after any admin mutation.
+pub fn list_active_api_keys(conn: &Connection) -> rusqlite::Result<Vec<(String, ApiKeyRow)>> {
+    let mut stmt = conn.prepare(
+        "SELECT key_hash, id, label, scope, created_at, revoked_at, tenant_id
+         FROM api_keys WHERE revoked_at IS NULL",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let key_hash: String = row.get("key_hash")?;
+        Ok((key_hash, ApiKeyRow::from_row(row)?))
+    })?;
+    rows.collect()
+}
+
+/// Revoke a key by id, scoped to `tenant_id` so one tenant's admin key can
+/// never revoke (or probe for the existence of) another tenant's keys.
+/// Returns `true` if a row was updated.
+pub fn revoke_api_key(conn: &Connection, id: i64, tenant_id: &str) -> rusqlite::Result<bool> {
+    let changed = conn.execute(
+        "UPDATE api_keys SET revoked_at = datetime('now')
+         WHERE id = ?1 AND tenant_id = ?2 AND revoked_at IS NULL",
+        params![id, tenant_id],
+    )?;
+    Ok(changed > 0)
+}
@@ -0,0 +1,123 @@
+use rusqlite::{Connection, Result, params};
+
+/// Incrementally bring `session_stats`/`session_edited_files` up to date with
+/// `events`, so `queries::get_sessions`/`get_session_with_events` can join
+/// one row per session instead of re-running their correlated subqueries
+/// over the whole `events` table on every call.
+///
+/// Only sessions with events past their stored `last_rolled_event_id` are
+/// touched, and only those newer events are rescanned — each dirty session
+/// costs O(its new events), not O(all its events). Safe to call after a
+/// batch of inserts or on a timer; a session with nothing new since the last
+/// call does no work.
+///
+/// Returns the number of sessions rolled up.
+pub fn rollup_session_stats(conn: &Connection) -> Result<usize> {
+    let mut dirty_stmt = conn.prepare(
+        "SELECT e.session_id, COALESCE(ss.last_rolled_event_id, 0)
+         FROM events e
+         LEFT JOIN session_stats ss ON ss.session_id = e.session_id
+         WHERE e.id > COALESCE(ss.last_rolled_event_id, 0)
+         GROUP BY e.session_id",
+    )?;
+    let dirty: Vec<(String, i64)> = dirty_stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    drop(dirty_stmt);
+
+    let mut rolled = 0usize;
+    for (session_id, watermark) in dirty {
+        let tx = conn.unchecked_transaction()?;
+
+        let (event_count, tokens_in, tokens_out, total_cost_usd, lines_added, lines_removed, max_id): (
+            i64,
+            i64,
+            i64,
+            f64,
+            i64,
+            i64,
+            i64,
+        ) = tx.query_row(
+            "SELECT
+                COUNT(*),
+                COALESCE(SUM(tokens_in), 0),
+                COALESCE(SUM(tokens_out), 0),
+                COALESCE(SUM(cost_usd), 0),
+                COALESCE(SUM(CAST(json_extract(metadata, '$.lines_added') AS INTEGER)), 0),
+                COALESCE(SUM(CAST(json_extract(metadata, '$.lines_removed') AS INTEGER)), 0),
+                MAX(id)
+             FROM events
+             WHERE session_id = ?1 AND id > ?2",
+            params![session_id, watermark],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                ))
+            },
+        )?;
+
+        {
+            let mut file_stmt = tx.prepare(
+                "SELECT DISTINCT json_extract(metadata, '$.file_path')
+                 FROM events
+                 WHERE session_id = ?1 AND id > ?2
+                   AND tool_name IN ('Edit', 'Write', 'MultiEdit', 'apply_patch', 'write_stdin')
+                   AND json_extract(metadata, '$.file_path') IS NOT NULL",
+            )?;
+            let files = file_stmt
+                .query_map(params![session_id, watermark], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            for file_path in files {
+                tx.execute(
+                    "INSERT OR IGNORE INTO session_edited_files (session_id, file_path) VALUES (?1, ?2)",
+                    params![session_id, file_path],
+                )?;
+            }
+        }
+
+        let files_edited: i64 = tx.query_row(
+            "SELECT COUNT(*) FROM session_edited_files WHERE session_id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        )?;
+
+        tx.execute(
+            "INSERT INTO session_stats (
+                session_id, event_count, tokens_in, tokens_out, total_cost_usd,
+                files_edited, lines_added, lines_removed, last_rolled_event_id
+             )
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(session_id) DO UPDATE SET
+                event_count = session_stats.event_count + excluded.event_count,
+                tokens_in = session_stats.tokens_in + excluded.tokens_in,
+                tokens_out = session_stats.tokens_out + excluded.tokens_out,
+                total_cost_usd = session_stats.total_cost_usd + excluded.total_cost_usd,
+                files_edited = excluded.files_edited,
+                lines_added = session_stats.lines_added + excluded.lines_added,
+                lines_removed = session_stats.lines_removed + excluded.lines_removed,
+                last_rolled_event_id = excluded.last_rolled_event_id",
+            params![
+                session_id,
+                event_count,
+                tokens_in,
+                tokens_out,
+                total_cost_usd,
+                files_edited,
+                lines_added,
+                lines_removed,
+                max_id,
+            ],
+        )?;
+
+        tx.commit()?;
+        rolled += 1;
+    }
+
+    Ok(rolled)
+}
@@ -0,0 +1,78 @@
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Typed accessor over the `settings` table (migration `settings_table`) for
+/// user configuration — retention window, default project filter, backend
+/// port, idle-timeout threshold — that needs to survive restarts without a
+/// separate config file. Values are JSON-encoded so any serde-compatible
+/// type round-trips through the single `value` column; `key` is whatever
+/// the caller picks (e.g. `"retention_days"`).
+pub fn get<T: DeserializeOwned>(conn: &Connection, key: &str) -> rusqlite::Result<Option<T>> {
+    let raw: Option<String> = conn
+        .query_row("SELECT value FROM settings WHERE key = ?1", params![key], |row| row.get(0))
+        .optional()?;
+    Ok(raw.and_then(|raw| serde_json::from_str(&raw).ok()))
+}
+
+pub fn set<T: Serialize>(conn: &Connection, key: &str, value: &T) -> rusqlite::Result<()> {
+    let raw = serde_json::to_string(value)
+        .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
+    conn.execute(
+        "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, datetime('now'))
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        params![key, raw],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations;
+
+    fn open() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        migrations::migrate(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn get_returns_none_for_a_missing_key() {
+        let conn = open();
+        let value: Option<u32> = get(&conn, "idle_timeout_minutes").unwrap();
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    fn set_then_get_round_trips_a_value() {
+        let conn = open();
+        set(&conn, "idle_timeout_minutes", &30u32).unwrap();
+        let value: Option<u32> = get(&conn, "idle_timeout_minutes").unwrap();
+        assert_eq!(value, Some(30));
+    }
+
+    #[test]
+    fn set_overwrites_an_existing_key() {
+        let conn = open();
+        set(&conn, "default_project", &"alpha").unwrap();
+        set(&conn, "default_project", &"beta").unwrap();
+        let value: Option<String> = get(&conn, "default_project").unwrap();
+        assert_eq!(value.as_deref(), Some("beta"));
+    }
+
+    #[test]
+    fn values_round_trip_through_json_for_structured_types() {
+        #[derive(Serialize, serde::Deserialize, PartialEq, Debug)]
+        struct RetentionPolicy {
+            days: u32,
+            compact_on_startup: bool,
+        }
+
+        let conn = open();
+        let policy = RetentionPolicy { days: 90, compact_on_startup: true };
+        set(&conn, "retention_policy", &policy).unwrap();
+        let value: Option<RetentionPolicy> = get(&conn, "retention_policy").unwrap();
+        assert_eq!(value, Some(policy));
+    }
+}
@@ -0,0 +1,249 @@
+use std::fmt;
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::db::queries::{
+    self, AgentUsageData, AnalyticsFilters, EventRow, FilterOptions, InsertEventParams,
+    SessionFilters, SessionRow, Stats, ToolAnalyticsRow, TranscriptEvent,
+};
+use crate::db::schema;
+
+/// Error returned by a [`Store`] implementation. Wraps the backend-specific
+/// error so callers can log/report without matching on `rusqlite` (or, for
+/// `PostgresStore`, `tokio_postgres`) directly.
+#[derive(Debug)]
+pub enum StoreError {
+    Sqlite(rusqlite::Error),
+    Postgres(String),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Sqlite(e) => write!(f, "sqlite store error: {e}"),
+            StoreError::Postgres(e) => write!(f, "postgres store error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(e: rusqlite::Error) -> Self {
+        StoreError::Sqlite(e)
+    }
+}
+
+/// Pluggable persistence backend. Covers the subset of `db::queries`
+/// operations every route handler needs — insert plus the read paths behind
+/// `/api/stats`, `/api/stats/tools`, `/api/stats/usage-monitor`, and
+/// `/api/sessions` — so `AppState` can eventually hold `Box<dyn Store>`
+/// instead of a concrete `Mutex<rusqlite::Connection>` and let a deployment
+/// point at either SQLite or a shared Postgres instance.
+///
+/// `AppState` still owns the `rusqlite::Connection`/`DbPools` directly for
+/// every HTTP route today — rewiring every ingest path (`/api/events`,
+/// MQTT, NATS, relay, OTel, the importer) through `dyn Store` is follow-up
+/// work. `AppState::remote_store`, populated only when `Config::database_url`
+/// is set, is the first consumer: `run_idle_check_once` and
+/// `run_stats_broadcast_once` dispatch through it instead of `DbPools` when
+/// it's present, which is the one place a single locked SQLite writer was
+/// ever going to bottleneck a multi-instance deployment pointed at shared
+/// Postgres. What this trait plus `SqliteStore` and `db::postgres::PostgresStore`
+/// otherwise prove is that both backends can serve the same operations; the
+/// rest of `db::queries` (cost/project/model breakdowns, full-text search,
+/// API key management) isn't part of the trait yet and stays SQLite-only
+/// until it is.
+///
+/// Scope note: every method that reads or writes `sessions`/`events` takes
+/// (or, for `get_stats`/`get_usage_monitor`, optionally takes) a
+/// `tenant_id` — see `auth::DEFAULT_TENANT`. A request's tenant comes from
+/// its authenticated API key, never from client-supplied data.
+#[async_trait]
+pub trait Store: Send + Sync {
+    async fn init_schema(&self) -> Result<(), StoreError>;
+    async fn upsert_agent(&self, id: &str, agent_type: &str) -> Result<(), StoreError>;
+    async fn upsert_session(
+        &self,
+        id: &str,
+        agent_id: &str,
+        agent_type: &str,
+        project: Option<&str>,
+        branch: Option<&str>,
+        tenant_id: &str,
+    ) -> Result<(), StoreError>;
+    async fn insert_event(
+        &self,
+        params: &InsertEventParams<'_>,
+    ) -> Result<Option<EventRow>, StoreError>;
+    /// `tenant_id` of `None` reports across every tenant — used only by the
+    /// unauthenticated `/metrics` endpoint, which has no auth context to
+    /// scope by. Every other caller has a `TenantId` from auth and passes
+    /// `Some`. See the scope note on this trait.
+    async fn get_stats(&self, tenant_id: Option<&str>) -> Result<Stats, StoreError>;
+    /// Transitions `active` sessions past `timeout_minutes` of inactivity to
+    /// `idle`, and `idle` sessions past `2 * timeout_minutes` to `ended` —
+    /// see `queries::update_idle_sessions`. Returns the number of sessions
+    /// that moved from `active` to `idle` (not the `idle` -> `ended` count),
+    /// matching that function's return value. Scoped to a single tenant —
+    /// `runtime_tasks::run_idle_check_once` loops `list_tenants` and calls
+    /// this once per tenant.
+    async fn update_idle_sessions(
+        &self,
+        timeout_minutes: u64,
+        tenant_id: &str,
+    ) -> Result<usize, StoreError>;
+    /// `tenant_id` of `None` reports across every tenant — see
+    /// `queries::get_tool_analytics`.
+    async fn get_tool_analytics(
+        &self,
+        filters: &AnalyticsFilters,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<ToolAnalyticsRow>, StoreError>;
+    async fn get_usage_monitor(
+        &self,
+        usage_config: &crate::config::UsageMonitorConfig,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<AgentUsageData>, StoreError>;
+    /// Distinct tenants with at least one session — see
+    /// `queries::list_tenants`. Used by `runtime_tasks` to loop the idle
+    /// scan and stats rollup per tenant regardless of which backend is
+    /// configured.
+    async fn list_tenants(&self) -> Result<Vec<String>, StoreError>;
+    async fn get_sessions(
+        &self,
+        filters: &SessionFilters,
+        tenant_id: &str,
+    ) -> Result<Vec<SessionRow>, StoreError>;
+    async fn get_session_with_events(
+        &self,
+        session_id: &str,
+        event_limit: i64,
+        tenant_id: &str,
+    ) -> Result<(Option<SessionRow>, Vec<EventRow>), StoreError>;
+    async fn get_filter_options(&self) -> Result<FilterOptions, StoreError>;
+    async fn get_session_transcript(
+        &self,
+        session_id: &str,
+        tenant_id: &str,
+    ) -> Result<Vec<TranscriptEvent>, StoreError>;
+}
+
+/// `Store` implementation backed by the existing SQLite connection. Delegates
+/// straight through to `db::queries`; schema setup goes through
+/// `db::schema::apply_schema` and is unchanged.
+pub struct SqliteStore {
+    conn: Mutex<rusqlite::Connection>,
+}
+
+impl SqliteStore {
+    pub fn new(conn: rusqlite::Connection) -> Self {
+        Self { conn: Mutex::new(conn) }
+    }
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    async fn init_schema(&self) -> Result<(), StoreError> {
+        let conn = self.conn.lock().await;
+        schema::apply_schema(&conn)?;
+        Ok(())
+    }
+
+    async fn upsert_agent(&self, id: &str, agent_type: &str) -> Result<(), StoreError> {
+        let conn = self.conn.lock().await;
+        Ok(queries::upsert_agent(&conn, id, agent_type)?)
+    }
+
+    async fn upsert_session(
+        &self,
+        id: &str,
+        agent_id: &str,
+        agent_type: &str,
+        project: Option<&str>,
+        branch: Option<&str>,
+        tenant_id: &str,
+    ) -> Result<(), StoreError> {
+        let conn = self.conn.lock().await;
+        Ok(queries::upsert_session(&conn, id, agent_id, agent_type, project, branch, tenant_id)?)
+    }
+
+    async fn insert_event(
+        &self,
+        params: &InsertEventParams<'_>,
+    ) -> Result<Option<EventRow>, StoreError> {
+        let conn = self.conn.lock().await;
+        Ok(queries::insert_event(&conn, params)?)
+    }
+
+    async fn get_stats(&self, tenant_id: Option<&str>) -> Result<Stats, StoreError> {
+        let conn = self.conn.lock().await;
+        Ok(queries::get_stats(&conn, tenant_id)?)
+    }
+
+    async fn update_idle_sessions(
+        &self,
+        timeout_minutes: u64,
+        tenant_id: &str,
+    ) -> Result<usize, StoreError> {
+        let conn = self.conn.lock().await;
+        Ok(queries::update_idle_sessions(&conn, timeout_minutes, tenant_id)?)
+    }
+
+    async fn get_tool_analytics(
+        &self,
+        filters: &AnalyticsFilters,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<ToolAnalyticsRow>, StoreError> {
+        let conn = self.conn.lock().await;
+        Ok(queries::get_tool_analytics(&conn, filters, tenant_id)?)
+    }
+
+    async fn get_usage_monitor(
+        &self,
+        usage_config: &crate::config::UsageMonitorConfig,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<AgentUsageData>, StoreError> {
+        let conn = self.conn.lock().await;
+        Ok(queries::get_usage_monitor(&conn, usage_config, tenant_id)?)
+    }
+
+    async fn list_tenants(&self) -> Result<Vec<String>, StoreError> {
+        let conn = self.conn.lock().await;
+        Ok(queries::list_tenants(&conn)?)
+    }
+
+    async fn get_sessions(
+        &self,
+        filters: &SessionFilters,
+        tenant_id: &str,
+    ) -> Result<Vec<SessionRow>, StoreError> {
+        let conn = self.conn.lock().await;
+        Ok(queries::get_sessions(&conn, filters, tenant_id)?)
+    }
+
+    async fn get_session_with_events(
+        &self,
+        session_id: &str,
+        event_limit: i64,
+        tenant_id: &str,
+    ) -> Result<(Option<SessionRow>, Vec<EventRow>), StoreError> {
+        let conn = self.conn.lock().await;
+        Ok(queries::get_session_with_events(&conn, session_id, event_limit, tenant_id)?)
+    }
+
+    async fn get_filter_options(&self) -> Result<FilterOptions, StoreError> {
+        let conn = self.conn.lock().await;
+        Ok(queries::get_filter_options(&conn)?)
+    }
+
+    async fn get_session_transcript(
+        &self,
+        session_id: &str,
+        tenant_id: &str,
+    ) -> Result<Vec<TranscriptEvent>, StoreError> {
+        let conn = self.conn.lock().await;
+        Ok(queries::get_session_transcript(&conn, session_id, tenant_id)?)
+    }
+}
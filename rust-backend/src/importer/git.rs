@@ -0,0 +1,198 @@
+//! Grounds an `apply_patch` event's self-reported branch/diff stats in the
+//! project's actual git history via `gix`, instead of trusting whatever the
+//! agent claimed. Every step here is best-effort and synchronous (`gix`'s
+//! API is sync, so this fits directly into the importer's existing
+//! synchronous parse path with no extra threading): a project that isn't a
+//! git repo, a repo with no commits yet, or any `gix` call failing just
+//! means the corresponding field is left unset. Git correlation enriches an
+//! import, it never blocks or fails one.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+
+/// How many commits back from HEAD [`correlate`] will walk looking for one
+/// that touches the patched path at/after the patch's `client_timestamp`,
+/// before giving up on reconciliation.
+const RECONCILE_COMMIT_LOOKAHEAD: usize = 50;
+
+/// What [`correlate`] could confirm about one patched file against the
+/// project's real git history.
+#[derive(Debug, Clone, Default)]
+pub struct GitCorrelation {
+    /// HEAD's branch name at import time (e.g. `main`); `None` for a
+    /// detached HEAD.
+    pub branch: Option<String>,
+    /// HEAD's tip commit SHA at import time.
+    pub commit_sha: Option<String>,
+    /// Whether the patched file exists in HEAD's tree.
+    pub commit_verified: bool,
+    /// Set when a commit at/after `client_timestamp` touching the patched
+    /// path was found within [`RECONCILE_COMMIT_LOOKAHEAD`] commits of HEAD.
+    pub reconciliation: Option<DiffReconciliation>,
+}
+
+/// The agent's claimed `lines_added`/`lines_removed` for one file next to
+/// what the matched commit's tree actually shows. `actual_*` comes from a
+/// line-multiset comparison of the blob before/after that commit, not a
+/// full LCS diff — enough to flag a claim that's wildly off, not to
+/// reproduce `git diff --numstat` exactly.
+#[derive(Debug, Clone, Copy)]
+pub struct DiffReconciliation {
+    pub claimed_added: i64,
+    pub claimed_removed: i64,
+    pub actual_added: i64,
+    pub actual_removed: i64,
+}
+
+/// Opens `project_root` as a git repository, searching upward the way `git`
+/// itself does. Returns `None` if it isn't inside a git repo (or `gix`
+/// can't open it for any other reason) — callers should skip correlation
+/// entirely in that case rather than calling [`correlate`] with nothing to
+/// look up.
+pub fn open(project_root: &Path) -> Option<gix::Repository> {
+    gix::discover(project_root).ok()
+}
+
+/// Correlates one patched `file_path` (and the agent's claimed line counts
+/// for it) against `repo`'s current HEAD and recent history.
+pub fn correlate(
+    repo: &gix::Repository,
+    file_path: &str,
+    claimed_added: i64,
+    claimed_removed: i64,
+    client_timestamp: Option<DateTime<Utc>>,
+) -> GitCorrelation {
+    let mut result = GitCorrelation::default();
+
+    if let Ok(Some(name)) = repo.head_name() {
+        result.branch = name.shorten().to_str().ok().map(ToString::to_string);
+    }
+
+    let Ok(head_commit) = repo.head_commit() else {
+        return result;
+    };
+    result.commit_sha = Some(head_commit.id().to_hex().to_string());
+
+    let Ok(head_tree) = head_commit.tree() else {
+        return result;
+    };
+    result.commit_verified = tree_entry_oid(&head_tree, file_path).is_some();
+
+    result.reconciliation = find_reconciliation(
+        repo,
+        head_commit.id,
+        file_path,
+        claimed_added,
+        claimed_removed,
+        client_timestamp,
+    );
+
+    result
+}
+
+/// Walks back from `start`, skipping anything older than `client_timestamp`,
+/// looking for the first commit whose tree differs from its first parent's
+/// at `file_path`. When found, diffs the before/after blob contents with
+/// [`line_multiset_diff`] and returns the reconciliation.
+fn find_reconciliation(
+    repo: &gix::Repository,
+    start: gix::ObjectId,
+    file_path: &str,
+    claimed_added: i64,
+    claimed_removed: i64,
+    client_timestamp: Option<DateTime<Utc>>,
+) -> Option<DiffReconciliation> {
+    let cutoff = client_timestamp.map(|ts| ts.timestamp());
+    let walk = repo.rev_walk([start]).all().ok()?;
+
+    for info in walk.take(RECONCILE_COMMIT_LOOKAHEAD) {
+        let info = info.ok()?;
+        if let Some(cutoff) = cutoff
+            && info.commit_time.unwrap_or(0) < cutoff
+        {
+            continue;
+        }
+
+        let Ok(commit) = repo.find_commit(info.id) else {
+            continue;
+        };
+        let Ok(tree) = commit.tree() else { continue };
+        let Some(current_oid) = tree_entry_oid(&tree, file_path) else {
+            continue;
+        };
+
+        let parent_oid = commit
+            .parent_ids()
+            .next()
+            .and_then(|id| repo.find_commit(id.detach()).ok())
+            .and_then(|parent| parent.tree().ok())
+            .and_then(|parent_tree| tree_entry_oid(&parent_tree, file_path));
+
+        if parent_oid.as_ref() == Some(&current_oid) {
+            // Unchanged at this path in this commit — keep walking further
+            // back for the commit that actually introduced the edit.
+            continue;
+        }
+
+        let current_lines = blob_lines(repo, &current_oid);
+        let previous_lines = parent_oid.map(|oid| blob_lines(repo, &oid)).unwrap_or_default();
+        let (actual_added, actual_removed) = line_multiset_diff(&previous_lines, &current_lines);
+
+        return Some(DiffReconciliation {
+            claimed_added,
+            claimed_removed,
+            actual_added,
+            actual_removed,
+        });
+    }
+
+    None
+}
+
+fn tree_entry_oid(tree: &gix::Tree<'_>, file_path: &str) -> Option<gix::ObjectId> {
+    tree.lookup_entry_by_path(file_path)
+        .ok()
+        .flatten()
+        .map(|entry| entry.object_id())
+}
+
+fn blob_lines(repo: &gix::Repository, oid: &gix::ObjectId) -> Vec<String> {
+    let Ok(object) = repo.find_object(*oid) else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&object.data)
+        .lines()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Approximates added/removed line counts as a multiset difference between
+/// two line lists: a line present more times in `after` than `before`
+/// counts as added that many extra times, and vice versa for removed. This
+/// undercounts pure reordering (the same lines shuffled count as neither
+/// added nor removed) but is cheap and dependency-free, which is the point
+/// — it only needs to catch a claim that's in the right ballpark, not match
+/// `git diff --numstat` line for line.
+fn line_multiset_diff(before: &[String], after: &[String]) -> (i64, i64) {
+    use std::collections::HashMap;
+
+    let mut counts: HashMap<&str, i64> = HashMap::new();
+    for line in before {
+        *counts.entry(line.as_str()).or_insert(0) -= 1;
+    }
+    for line in after {
+        *counts.entry(line.as_str()).or_insert(0) += 1;
+    }
+
+    let mut added = 0i64;
+    let mut removed = 0i64;
+    for count in counts.values() {
+        if *count > 0 {
+            added += count;
+        } else {
+            removed -= count;
+        }
+    }
+    (added, removed)
+}
@@ -0,0 +1,2308 @@
+pub mod git;
+pub mod s3;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::db::queries::{self, InsertEventParams};
+use crate::importer::s3::{S3Object, S3Source};
+use crate::util::truncate::truncate_metadata;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportSource {
+    ClaudeCode,
+    Codex,
+    All,
+    /// Any other registered [`LogImporter::id`], named directly so
+    /// `--source <id>` can target a format added to [`all_importers`]
+    /// without this enum growing a new variant for every one.
+    Other(String),
+}
+
+impl ImportSource {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::ClaudeCode => "claude-code",
+            Self::Codex => "codex",
+            Self::All => "all",
+            Self::Other(id) => id,
+        }
+    }
+
+    /// Whether this selector covers the given importer's id.
+    fn matches(&self, importer_id: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::ClaudeCode => importer_id == "claude-code",
+            Self::Codex => importer_id == "codex",
+            Self::Other(id) => id == importer_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    pub source: ImportSource,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub dry_run: bool,
+    pub force: bool,
+    pub claude_dir: Option<PathBuf>,
+    pub codex_dir: Option<PathBuf>,
+    /// An S3-compatible bucket/prefix to import Claude Code transcripts
+    /// from, in addition to (not instead of) `claude_dir`/`codex_dir`. Built
+    /// with [`S3Source::parse`] from an `s3://bucket/prefix` URL; only
+    /// Claude Code's JSONL format is supported over this path today (see
+    /// `process_s3_object`).
+    pub s3: Option<S3Source>,
+    pub max_payload_kb: usize,
+    /// Whether `run_watch` should be entered after (or instead of) a one-shot
+    /// `run_import` pass. `run_import` itself ignores this — it's read by
+    /// callers like `bin/import.rs` deciding whether to keep running.
+    pub watch: bool,
+    /// Window `run_watch` waits after the first filesystem event on a burst
+    /// before processing it, so duplicate create/modify events for the same
+    /// path (some platforms emit two for a single directory creation) land
+    /// in the same debounced batch instead of triggering two import passes.
+    pub watch_debounce_ms: u64,
+    /// Remote collector endpoint for an [`HttpSink`]. When set, every
+    /// imported event is also forwarded there, in addition to the local
+    /// SQLite write `process_file` always does — there's no option yet to
+    /// skip the local write and export only.
+    pub export_url: Option<String>,
+    /// Event count an [`HttpSink`] batches before POSTing. Ignored when
+    /// `export_url` is unset.
+    pub export_flush_threshold: usize,
+    /// When true, `process_file`'s full-reparse fingerprint uses
+    /// [`sampled_hash_file`] instead of [`hash_file`] — a few KB read
+    /// instead of the whole artifact. `file_hash` is only ever compared
+    /// against itself (see the comment above the call site), so trading
+    /// exactness for speed on multi-gigabyte session logs is safe.
+    pub sampled_hash: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportFileResult {
+    pub path: String,
+    pub source: String,
+    pub events_found: usize,
+    pub events_imported: usize,
+    pub skipped_duplicate: usize,
+    pub skipped_unchanged: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportResult {
+    pub files: Vec<ImportFileResult>,
+    pub total_files: usize,
+    pub total_events_found: usize,
+    pub total_events_imported: usize,
+    pub total_duplicates: usize,
+    pub skipped_files: usize,
+}
+
+/// How far into a file the importer has already parsed, so a repeated
+/// `run_import` over a still-growing file can resume instead of reparsing
+/// from byte zero. `line_offset` and `cost_state` are Claude Code-specific
+/// parser state (the absolute line index baked into each `event_id`, and the
+/// running cumulative `costUSD` needed to keep computing per-line cost
+/// deltas) — Codex's parser always starts fresh (see `parse_codex_file`) and
+/// leaves them at their defaults.
+#[derive(Debug, Clone, Copy, Default)]
+struct ResumeState {
+    byte_offset: i64,
+    line_offset: i64,
+    cost_state: f64,
+}
+
+/// Number of leading bytes hashed to cheaply confirm a grown file's earlier
+/// content hasn't changed underneath the stored byte offset, without
+/// rehashing the whole (potentially large, still-growing) file.
+const HEADER_HASH_BYTES: usize = 4096;
+
+#[derive(Debug)]
+struct ImportedEvent {
+    event_id: Option<String>,
+    session_id: String,
+    agent_type: String,
+    event_type: String,
+    tool_name: Option<String>,
+    status: String,
+    tokens_in: i64,
+    tokens_out: i64,
+    branch: Option<String>,
+    project: Option<String>,
+    duration_ms: Option<i64>,
+    client_timestamp: Option<String>,
+    metadata: Value,
+    model: Option<String>,
+    cost_usd: Option<f64>,
+    cache_read_tokens: i64,
+    cache_write_tokens: i64,
+    source: String,
+}
+
+/// One pluggable agent log format `run_import`/`run_watch` know how to
+/// discover and parse. Add support for a new format (Gemini CLI, Aider,
+/// OpenCode, ...) by implementing this in its own module and pushing an
+/// instance into [`all_importers`] — `process_file`, `run_import`, and
+/// `run_watch` are all generic over the trait and need no changes.
+///
+/// `parse` carries the same [`ResumeState`] in/out as the old `parse_fn`
+/// function pointers did, so an importer that supports incremental resume
+/// (like Claude Code's) keeps doing so; one that always reparses the whole
+/// file (like Codex's) just ignores the input state and returns a fresh one.
+trait LogImporter: Send + Sync {
+    /// Short, stable identifier matched against `--source` and
+    /// `ImportSource::Other`/`ImportSource::matches` (e.g. `"claude-code"`).
+    fn id(&self) -> &'static str;
+    fn discover(&self, options: &ImportOptions) -> Vec<PathBuf>;
+    fn parse(
+        &self,
+        path: &Path,
+        options: &ImportOptions,
+        resume: ResumeState,
+    ) -> (Vec<ImportedEvent>, ResumeState);
+}
+
+struct ClaudeCodeImporter;
+
+impl LogImporter for ClaudeCodeImporter {
+    fn id(&self) -> &'static str {
+        "claude-code"
+    }
+
+    fn discover(&self, options: &ImportOptions) -> Vec<PathBuf> {
+        discover_claude_code_logs(options.claude_dir.as_deref())
+    }
+
+    fn parse(
+        &self,
+        path: &Path,
+        options: &ImportOptions,
+        resume: ResumeState,
+    ) -> (Vec<ImportedEvent>, ResumeState) {
+        parse_claude_code_file(path, options, resume)
+    }
+}
+
+struct CodexImporter;
+
+impl LogImporter for CodexImporter {
+    fn id(&self) -> &'static str {
+        "codex"
+    }
+
+    fn discover(&self, options: &ImportOptions) -> Vec<PathBuf> {
+        discover_codex_logs(options.codex_dir.as_deref())
+    }
+
+    fn parse(
+        &self,
+        path: &Path,
+        options: &ImportOptions,
+        resume: ResumeState,
+    ) -> (Vec<ImportedEvent>, ResumeState) {
+        parse_codex_file(path, options, resume)
+    }
+}
+
+/// Every log format `run_import`/`run_watch` currently know how to handle.
+/// This is the one place a new [`LogImporter`] needs to be registered.
+fn all_importers() -> Vec<Box<dyn LogImporter>> {
+    vec![Box::new(ClaudeCodeImporter), Box::new(CodexImporter)]
+}
+
+/// The `id()`s `--source` currently accepts, for CLI validation/help text.
+pub fn available_source_ids() -> Vec<&'static str> {
+    all_importers().iter().map(|i| i.id()).collect()
+}
+
+/// Normalized-event payload forwarded to an [`EventSink`] — intentionally
+/// smaller than `ImportedEvent`: just the fields worth sending to a remote
+/// collector, not the local-only metadata/duration/truncation bookkeeping.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedEvent {
+    pub session_id: String,
+    pub agent_type: String,
+    pub event_type: String,
+    pub model: Option<String>,
+    pub client_timestamp: Option<String>,
+    pub tokens_in: i64,
+    pub tokens_out: i64,
+    pub cost_usd: Option<f64>,
+}
+
+impl From<&ImportedEvent> for ExportedEvent {
+    fn from(event: &ImportedEvent) -> Self {
+        Self {
+            session_id: event.session_id.clone(),
+            agent_type: event.agent_type.clone(),
+            event_type: event.event_type.clone(),
+            model: event.model.clone(),
+            client_timestamp: event.client_timestamp.clone(),
+            tokens_in: event.tokens_in,
+            tokens_out: event.tokens_out,
+            cost_usd: event.cost_usd,
+        }
+    }
+}
+
+/// A destination for normalized import events, independent of the local
+/// SQLite write `process_file` always does. `write`/`flush` are
+/// best-effort, mirroring `otel::pipeline::Sink`: a sink that can't keep up
+/// logs and drops rather than failing the import.
+pub trait EventSink: Send + Sync {
+    fn write(&self, events: &[ExportedEvent]);
+    /// Send anything buffered so far. `run_import` calls this once after
+    /// every file has been processed, so a partial batch below the flush
+    /// threshold doesn't get stranded until the next run.
+    fn flush(&self);
+}
+
+/// Total number of send attempts (the first one included) `HttpSink` makes
+/// on a batch before giving up and logging it as dropped.
+const MAX_SEND_ATTEMPTS: u32 = 3;
+
+/// Batches [`ExportedEvent`]s and POSTs them to a remote collector as
+/// newline-delimited JSON once `flush_threshold` accumulate (or when
+/// `flush` is called explicitly). A failed send is retried with
+/// exponential backoff, up to `MAX_SEND_ATTEMPTS` attempts in all, before
+/// the batch is dropped and logged — this crate doesn't persist a dead-letter queue for
+/// export failures.
+pub struct HttpSink {
+    endpoint: String,
+    client: reqwest::blocking::Client,
+    flush_threshold: usize,
+    dry_run: bool,
+    buffer: Mutex<Vec<ExportedEvent>>,
+}
+
+impl HttpSink {
+    pub fn new(endpoint: impl Into<String>, flush_threshold: usize, dry_run: bool) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            client: reqwest::blocking::Client::new(),
+            flush_threshold: flush_threshold.max(1),
+            dry_run,
+            buffer: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn send_batch(&self, batch: &[ExportedEvent]) {
+        if batch.is_empty() {
+            return;
+        }
+
+        let body = batch
+            .iter()
+            .map(|event| serde_json::to_string(event).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // dry_run still builds `body` above so the sink's serialization
+        // path is exercised, it just never reaches the network.
+        if self.dry_run {
+            return;
+        }
+
+        for attempt in 1..=MAX_SEND_ATTEMPTS {
+            match self
+                .client
+                .post(&self.endpoint)
+                .header("Content-Type", "application/x-ndjson")
+                .body(body.clone())
+                .send()
+            {
+                Ok(resp) if resp.status().is_success() => return,
+                Ok(resp) => warn!(
+                    "HttpSink: {} responded with {} (attempt {attempt}/{MAX_SEND_ATTEMPTS})",
+                    self.endpoint,
+                    resp.status()
+                ),
+                Err(err) => warn!(
+                    "HttpSink: send to {} failed: {err} (attempt {attempt}/{MAX_SEND_ATTEMPTS})",
+                    self.endpoint
+                ),
+            }
+            if attempt < MAX_SEND_ATTEMPTS {
+                std::thread::sleep(Duration::from_millis(200 * 2u64.pow(attempt - 1)));
+            }
+        }
+        warn!(
+            "HttpSink: giving up on a batch of {} event(s) for {} after {MAX_SEND_ATTEMPTS} attempts",
+            batch.len(),
+            self.endpoint
+        );
+    }
+}
+
+impl EventSink for HttpSink {
+    fn write(&self, events: &[ExportedEvent]) {
+        let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        buffer.extend_from_slice(events);
+        if buffer.len() >= self.flush_threshold {
+            let batch = std::mem::take(&mut *buffer);
+            drop(buffer);
+            self.send_batch(&batch);
+        }
+    }
+
+    fn flush(&self) {
+        let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        if buffer.is_empty() {
+            return;
+        }
+        let batch = std::mem::take(&mut *buffer);
+        drop(buffer);
+        self.send_batch(&batch);
+    }
+}
+
+pub fn run_import(conn: &Connection, options: &ImportOptions) -> ImportResult {
+    let mut files: Vec<ImportFileResult> = Vec::new();
+    let sink: Option<Box<dyn EventSink>> = options.export_url.as_ref().map(|url| {
+        Box::new(HttpSink::new(
+            url.clone(),
+            options.export_flush_threshold,
+            options.dry_run,
+        )) as Box<dyn EventSink>
+    });
+
+    for importer in all_importers() {
+        if !options.source.matches(importer.id()) {
+            continue;
+        }
+        for path in importer.discover(options) {
+            files.push(process_file(
+                conn,
+                &path,
+                importer.id(),
+                options,
+                importer.as_ref(),
+                sink.as_deref(),
+            ));
+        }
+    }
+
+    if let Some(source) = &options.s3 {
+        files.extend(process_s3_source(conn, source, options, sink.as_deref()));
+    }
+
+    if let Some(sink) = &sink {
+        sink.flush();
+    }
+
+    fold_results(files)
+}
+
+/// Lists every `.jsonl` object under `source.prefix` and imports each one
+/// through [`process_s3_object`], on the caller's connection. Errors
+/// listing the bucket are logged and treated as "found nothing this pass"
+/// rather than failing the whole import — the same posture `process_file`
+/// takes toward an unreadable local file.
+fn process_s3_source(
+    conn: &Connection,
+    source: &S3Source,
+    options: &ImportOptions,
+    sink: Option<&dyn EventSink>,
+) -> Vec<ImportFileResult> {
+    let client = reqwest::blocking::Client::new();
+    let objects = match s3::list_objects(&client, source) {
+        Ok(objects) => objects,
+        Err(err) => {
+            warn!("failed to list s3://{}/{}: {err}", source.bucket, source.prefix);
+            return Vec::new();
+        }
+    };
+
+    objects
+        .iter()
+        .map(|object| process_s3_object(conn, &client, source, object, options, sink))
+        .collect()
+}
+
+/// The S3 analogue of `process_file`: same `import_state` skip/resume
+/// invariants (unchanged size + matching header hash skips entirely;
+/// otherwise resume from the stored byte offset), except the "file" is a
+/// remote object fetched via ranged `GetObject` requests instead of a local
+/// `fs::File`. `import_state.file_path` is keyed on `object.url(source)`
+/// (an opaque `s3://bucket/key` string) rather than a real filesystem path
+/// — `get_import_state`/`set_import_state` only ever round-trip it through
+/// `Path::display`, so that works unchanged.
+///
+/// Only Claude Code's JSONL format is supported here today: unlike local
+/// discovery (which walks separate Claude Code/Codex directory layouts),
+/// there isn't yet a concrete convention for distinguishing the two formats
+/// by object key, so `parse_claude_code_file` is applied unconditionally.
+fn process_s3_object(
+    conn: &Connection,
+    client: &reqwest::blocking::Client,
+    source: &S3Source,
+    object: &S3Object,
+    options: &ImportOptions,
+    sink: Option<&dyn EventSink>,
+) -> ImportFileResult {
+    let url = object.url(source);
+    let file_path_key = PathBuf::from(&url);
+    let file_size = object.size as i64;
+    let stored = get_import_state(conn, &file_path_key);
+
+    let header = match s3::get_object_range(client, source, &object.key, 0) {
+        Ok(bytes) => Some(sha256_hex(&bytes[..bytes.len().min(HEADER_HASH_BYTES)])),
+        Err(err) => {
+            warn!("failed to fetch header of {url}: {err}");
+            None
+        }
+    };
+
+    if !options.force
+        && let Some(state) = &stored
+        && file_size == state.file_size
+        && state.byte_offset == file_size
+        && header.is_some()
+        && state.header_hash == header
+    {
+        return ImportFileResult {
+            path: url,
+            source: "s3".to_string(),
+            events_found: 0,
+            events_imported: 0,
+            skipped_duplicate: 0,
+            skipped_unchanged: true,
+        };
+    }
+
+    let byte_offset = if !options.force
+        && let Some(state) = &stored
+        && file_size >= state.file_size
+        && header.is_some()
+        && state.header_hash == header
+    {
+        state.byte_offset as u64
+    } else {
+        0
+    };
+    let carried_resume = ResumeState {
+        byte_offset: 0,
+        line_offset: stored.as_ref().map(|s| s.line_offset).unwrap_or(0),
+        cost_state: stored.as_ref().map(|s| s.cost_state).unwrap_or(0.0),
+    };
+
+    let tail = match s3::get_object_range(client, source, &object.key, byte_offset) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            warn!("failed to fetch {url} from offset {byte_offset}: {err}");
+            return ImportFileResult {
+                path: url,
+                source: "s3".to_string(),
+                events_found: 0,
+                events_imported: 0,
+                skipped_duplicate: 0,
+                skipped_unchanged: false,
+            };
+        }
+    };
+
+    // `parse_claude_code_file` only knows how to read a local path, so the
+    // downloaded tail is staged to a scratch file under `--s3` is imported
+    // from offset zero relative to that tail, then the original
+    // `byte_offset` is added back before it's persisted below.
+    let scratch_path = env::temp_dir().join(format!("agentmonitor-s3-{}.jsonl", short_sha256_hex(&url)));
+    if fs::write(&scratch_path, &tail).is_err() {
+        warn!("failed to stage {url} to a scratch file for parsing");
+        return ImportFileResult {
+            path: url,
+            source: "s3".to_string(),
+            events_found: 0,
+            events_imported: 0,
+            skipped_duplicate: 0,
+            skipped_unchanged: false,
+        };
+    }
+    let (events, mut new_resume) = parse_claude_code_file(&scratch_path, options, carried_resume);
+    let _ = fs::remove_file(&scratch_path);
+    new_resume.byte_offset += byte_offset as i64;
+
+    let (events_imported, duplicates) = import_events(conn, &events, options.max_payload_kb, options.dry_run);
+
+    if let Some(sink) = sink {
+        let exported: Vec<ExportedEvent> = events.iter().map(ExportedEvent::from).collect();
+        sink.write(&exported);
+    }
+
+    // `file_hash` is only ever compared against itself by the (unused, for
+    // S3) local full-reparse path — the skip-check above is entirely
+    // header_hash/byte_offset-based — so the header hash doubles as
+    // `file_hash` here rather than paying for a whole-object download just
+    // to populate a column nothing reads back.
+    if !options.dry_run
+        && let Some(header) = &header
+    {
+        set_import_state(
+            conn,
+            &file_path_key,
+            header,
+            file_size,
+            "s3",
+            events_imported as i64,
+            new_resume,
+            header,
+        );
+    }
+
+    ImportFileResult {
+        path: url,
+        source: "s3".to_string(),
+        events_found: events.len(),
+        events_imported,
+        skipped_duplicate: duplicates,
+        skipped_unchanged: false,
+    }
+}
+
+/// Opens its own connections and fans `work` out across a small pool of
+/// worker threads instead of the strictly sequential, single-`&Connection`
+/// loop `run_import` runs. Each worker owns one WAL-mode connection for its
+/// whole slice of files (see `open_worker_connection`) rather than sharing
+/// the caller's, which is exactly why this can't be used against
+/// `:memory:` — SQLite's in-memory databases are private per-connection, so
+/// a second one would see an empty database (the same reason
+/// `db::pool::initialize` special-cases it to a single shared connection).
+/// That makes this `bin/import.rs`'s job specifically: the one caller with
+/// a real on-disk `db_path` and, on a cold run over a large
+/// `~/.claude/projects` tree, hundreds of files to get through.
+///
+/// Worker count is capped at both the CPU count and the number of
+/// discovered files, so a small import doesn't pay for idle connections.
+/// Contending writes (`insert_event`, `set_import_state`) already sit
+/// behind each connection's own `busy_timeout`; `with_busy_retry` backstops
+/// that with a few short app-level retries for the rare case a write is
+/// still contending once `busy_timeout` gives up. Results are sorted by
+/// path before folding into the returned `ImportResult`, so the totals and
+/// file ordering match a sequential `run_import` run regardless of which
+/// worker happened to finish which file first.
+pub fn run_import_parallel(db_path: &Path, passphrase: Option<&str>, options: &ImportOptions) -> ImportResult {
+    let sink: Option<Arc<dyn EventSink>> = options.export_url.as_ref().map(|url| {
+        Arc::new(HttpSink::new(url.clone(), options.export_flush_threshold, options.dry_run)) as Arc<dyn EventSink>
+    });
+
+    let mut work: Vec<(Arc<dyn LogImporter>, PathBuf)> = Vec::new();
+    for importer in all_importers() {
+        if !options.source.matches(importer.id()) {
+            continue;
+        }
+        let importer: Arc<dyn LogImporter> = Arc::from(importer);
+        for path in importer.discover(options) {
+            work.push((Arc::clone(&importer), path));
+        }
+    }
+
+    if work.is_empty() {
+        if let Some(sink) = &sink {
+            sink.flush();
+        }
+        return fold_results(Vec::new());
+    }
+
+    let worker_count = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(work.len());
+
+    let mut chunks: Vec<Vec<(Arc<dyn LogImporter>, PathBuf)>> = (0..worker_count).map(|_| Vec::new()).collect();
+    for (i, item) in work.into_iter().enumerate() {
+        chunks[i % worker_count].push(item);
+    }
+
+    let mut files: Vec<ImportFileResult> = std::thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| {
+                let sink = sink.clone();
+                scope.spawn(move || {
+                    let conn = match open_worker_connection(db_path, passphrase) {
+                        Ok(conn) => conn,
+                        Err(err) => {
+                            warn!("import worker failed to open its own DB connection: {err}");
+                            return Vec::new();
+                        }
+                    };
+                    chunk
+                        .into_iter()
+                        .map(|(importer, path)| {
+                            process_file(&conn, &path, importer.id(), options, importer.as_ref(), sink.as_deref())
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    });
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    // S3 listing/fetching is network-bound, not CPU-bound like the local
+    // worker pool above, so it runs after the pool on one more connection of
+    // its own (`open_worker_connection` again) rather than as a worker slice
+    // — there's only ever one `options.s3` source to split up.
+    if let Some(source) = &options.s3 {
+        match open_worker_connection(db_path, passphrase) {
+            Ok(conn) => {
+                let mut s3_files = process_s3_source(&conn, source, options, sink.as_deref());
+                files.append(&mut s3_files);
+            }
+            Err(err) => {
+                warn!("failed to open a DB connection for the --s3 import pass: {err}");
+            }
+        }
+    }
+
+    if let Some(sink) = &sink {
+        sink.flush();
+    }
+
+    fold_results(files)
+}
+
+fn fold_results(files: Vec<ImportFileResult>) -> ImportResult {
+    let mut total_events_found = 0usize;
+    let mut total_events_imported = 0usize;
+    let mut total_duplicates = 0usize;
+    let mut skipped_files = 0usize;
+
+    for file in &files {
+        total_events_found += file.events_found;
+        total_events_imported += file.events_imported;
+        total_duplicates += file.skipped_duplicate;
+        if file.skipped_unchanged {
+            skipped_files += 1;
+        }
+    }
+
+    ImportResult {
+        total_files: files.len(),
+        files,
+        total_events_found,
+        total_events_imported,
+        total_duplicates,
+        skipped_files,
+    }
+}
+
+/// Opens a standalone (non-pooled) WAL-mode connection to `db_path` for a
+/// `run_import_parallel` worker thread, with the same `PRAGMA key`/
+/// `busy_timeout` setup `db::pool::initialize`'s writer manager applies to
+/// every connection it hands out. Assumes `db_path` is already migrated —
+/// callers open a pooled connection first (which runs migrations) before
+/// handing worker threads their own raw ones.
+fn open_worker_connection(db_path: &Path, passphrase: Option<&str>) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(db_path)?;
+    if let Some(key) = passphrase {
+        conn.pragma_update(None, "key", key)?;
+    }
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.busy_timeout(Duration::from_millis(5000))?;
+    Ok(conn)
+}
+
+/// Retries `f` while SQLite reports `SQLITE_BUSY` — two `run_import_parallel`
+/// workers' writer connections contending past each one's own
+/// `busy_timeout` wait — with a short backoff between attempts, up to a
+/// handful of tries before giving up and propagating the error like any
+/// other write failure.
+fn with_busy_retry<T>(mut f: impl FnMut() -> rusqlite::Result<T>) -> rusqlite::Result<T> {
+    const MAX_ATTEMPTS: u32 = 5;
+    let mut attempt = 0;
+    loop {
+        match f() {
+            Err(rusqlite::Error::SqliteFailure(e, _))
+                if e.code == rusqlite::ErrorCode::DatabaseBusy && attempt + 1 < MAX_ATTEMPTS =>
+            {
+                attempt += 1;
+                std::thread::sleep(Duration::from_millis(50 * attempt as u64));
+            }
+            other => return other,
+        }
+    }
+}
+
+fn process_file(
+    conn: &Connection,
+    file_path: &Path,
+    source: &str,
+    options: &ImportOptions,
+    importer: &dyn LogImporter,
+    sink: Option<&dyn EventSink>,
+) -> ImportFileResult {
+    let file_size = fs::metadata(file_path).map(|m| m.len() as i64).unwrap_or(0);
+    let stored = get_import_state(conn, file_path);
+
+    // Already fully consumed and the file hasn't changed since: skip
+    // entirely. This only needs a `HEADER_HASH_BYTES`-sized read, not a
+    // whole-file hash — the point of `byte_offset`/`header_hash` is exactly
+    // to avoid paying an O(file size) cost on every run over an untouched
+    // multi-MB log.
+    if !options.force
+        && let Some(state) = &stored
+        && file_size == state.file_size
+        && state.byte_offset == file_size
+        && let Ok(current_header) = header_hash(file_path, HEADER_HASH_BYTES)
+        && state.header_hash.as_deref() == Some(current_header.as_str())
+    {
+        return ImportFileResult {
+            path: file_path.display().to_string(),
+            source: source.to_string(),
+            events_found: 0,
+            events_imported: 0,
+            skipped_duplicate: 0,
+            skipped_unchanged: true,
+        };
+    }
+
+    // Only resume from the stored offset if the file is still at least that
+    // long and its leading bytes still hash the same — otherwise an edited, rotated,
+    // or truncated-and-rewritten file could be misread as a clean append.
+    // Anything else (first import, `--force`, or a broken invariant) gets a
+    // full reparse from the start.
+    let resume = if !options.force
+        && let Some(state) = &stored
+        && file_size >= state.byte_offset
+        && let Ok(current_header) = header_hash(file_path, HEADER_HASH_BYTES)
+        && state.header_hash.as_deref() == Some(current_header.as_str())
+    {
+        ResumeState {
+            byte_offset: state.byte_offset,
+            line_offset: state.line_offset,
+            cost_state: state.cost_state,
+        }
+    } else {
+        ResumeState::default()
+    };
+
+    let (events, new_resume) = importer.parse(file_path, options, resume);
+
+    if let Some(sink) = sink {
+        let exported: Vec<ExportedEvent> = events.iter().map(ExportedEvent::from).collect();
+        sink.write(&exported);
+    }
+
+    let (events_imported, duplicates) =
+        import_events(conn, &events, options.max_payload_kb, options.dry_run);
+
+    let is_date_scoped = options.from.is_some() || options.to.is_some();
+    if !options.dry_run && !is_date_scoped && (!events.is_empty() || stored.is_some()) {
+        let header = header_hash(file_path, HEADER_HASH_BYTES).unwrap_or_default();
+        // `file_hash` is only ever compared against itself by the skip-check
+        // above, which now keys off `byte_offset`/`header_hash` instead.
+        // Recomputing it here would mean hashing the whole file on every
+        // incremental resume — exactly the O(file size) cost this mechanism
+        // exists to avoid — so only pay for a full hash when we actually did
+        // a full parse (i.e. started from byte zero); otherwise carry the
+        // previous value forward unchanged.
+        let full_reparse = resume.byte_offset == 0;
+        let hash = if full_reparse {
+            if options.sampled_hash {
+                sampled_hash_file(file_path, SAMPLED_HASH_THRESHOLD_BYTES).unwrap_or_default()
+            } else {
+                hash_file(file_path).unwrap_or_default()
+            }
+        } else {
+            stored
+                .as_ref()
+                .map(|state| state.file_hash.clone())
+                .unwrap_or_default()
+        };
+        set_import_state(
+            conn,
+            file_path,
+            &hash,
+            file_size,
+            source,
+            events_imported as i64,
+            new_resume,
+            &header,
+        );
+    }
+
+    ImportFileResult {
+        path: file_path.display().to_string(),
+        source: source.to_string(),
+        events_found: events.len(),
+        events_imported,
+        skipped_duplicate: duplicates,
+        skipped_unchanged: false,
+    }
+}
+
+/// `metadata` fields that identify *what* a tool call acted on, as opposed
+/// to fields like `commit_sha`/`commit_verified` that describe the state of
+/// the world *when it was imported* and can legitimately read differently
+/// on a later re-import of the same event.
+const IDENTITY_METADATA_FIELDS: &[&str] = &[
+    "file_path",
+    "old_path",
+    "new_path",
+    "command",
+    "pattern",
+    "query",
+];
+
+/// A stable fingerprint over the fields that define an event's identity —
+/// everything a human would call "the same event" — independent of where in
+/// the file it happened to land. Modeled on cargo's content-hash
+/// fingerprinting: hashing the fields themselves instead of a hand-built
+/// `"{session_id}:{event_index}"`-style string means reordering or dropping
+/// an unrelated line upstream (which shifts every `event_index` after it)
+/// doesn't make an otherwise-unchanged event look new.
+///
+/// Pulls `IDENTITY_METADATA_FIELDS` out of `metadata` rather than hashing it
+/// whole, so two apply_patch events in the same turn (same timestamp, same
+/// zeroed token counts) still fingerprint distinctly by file path, while
+/// derived/volatile metadata — git correlation's `commit_sha`, content
+/// previews, truncation flags — can't make an otherwise-identical event
+/// look new on a later re-import.
+fn content_fingerprint(event: &ImportedEvent) -> String {
+    let identity_metadata: String = IDENTITY_METADATA_FIELDS
+        .iter()
+        .filter_map(|key| event.metadata.get(*key).and_then(|v| v.as_str()))
+        .collect::<Vec<_>>()
+        .join("\u{1}");
+
+    short_sha256_hex(&format!(
+        "{}|{}|{}|{}|{}|{}|{}|{}|{}",
+        event.session_id,
+        event.agent_type,
+        event.event_type,
+        event.tool_name.as_deref().unwrap_or(""),
+        event.client_timestamp.as_deref().unwrap_or(""),
+        event.tokens_in,
+        event.tokens_out,
+        event.cost_usd.map(|c| c.to_bits()).unwrap_or(0),
+        identity_metadata,
+    ))
+}
+
+fn import_events(
+    conn: &Connection,
+    events: &[ImportedEvent],
+    max_payload_kb: usize,
+    dry_run: bool,
+) -> (usize, usize) {
+    if dry_run {
+        return (events.len(), 0);
+    }
+
+    let mut imported = 0usize;
+    let mut duplicates = 0usize;
+
+    for event in events {
+        let fingerprint = content_fingerprint(event);
+        let already_seen =
+            with_busy_retry(|| queries::fingerprint_exists(conn, &event.session_id, &fingerprint))
+                .unwrap_or(false);
+        if already_seen {
+            duplicates += 1;
+            continue;
+        }
+
+        let truncated = truncate_metadata(&event.metadata, max_payload_kb);
+        let params = InsertEventParams {
+            event_id: event.event_id.as_deref(),
+            session_id: &event.session_id,
+            agent_type: &event.agent_type,
+            event_type: &event.event_type,
+            tool_name: event.tool_name.as_deref(),
+            status: &event.status,
+            tokens_in: event.tokens_in,
+            tokens_out: event.tokens_out,
+            branch: event.branch.as_deref(),
+            project: event.project.as_deref(),
+            duration_ms: event.duration_ms,
+            client_timestamp: event.client_timestamp.as_deref(),
+            metadata: &truncated.value,
+            payload_truncated: truncated.truncated,
+            model: event.model.as_deref(),
+            cost_usd: event.cost_usd,
+            cache_read_tokens: event.cache_read_tokens,
+            cache_write_tokens: event.cache_write_tokens,
+            source: &event.source,
+            tenant_id: crate::auth::DEFAULT_TENANT,
+        };
+
+        match with_busy_retry(|| queries::insert_event(conn, &params)) {
+            Ok(Some(_)) => {
+                let _ =
+                    with_busy_retry(|| queries::record_fingerprint(conn, &event.session_id, &fingerprint));
+                imported += 1;
+            }
+            Ok(None) => duplicates += 1,
+            Err(_) => {}
+        }
+    }
+
+    (imported, duplicates)
+}
+
+/// Previously stored progress for a file, used to decide whether
+/// `process_file` can skip it entirely or resume an incremental parse.
+struct StoredImportState {
+    file_hash: String,
+    file_size: i64,
+    byte_offset: i64,
+    line_offset: i64,
+    cost_state: f64,
+    header_hash: Option<String>,
+}
+
+fn get_import_state(conn: &Connection, file_path: &Path) -> Option<StoredImportState> {
+    conn.query_row(
+        "SELECT file_hash, file_size, byte_offset, line_offset, cost_state, header_hash
+         FROM import_state WHERE file_path = ?1",
+        params![file_path.display().to_string()],
+        |row| {
+            Ok(StoredImportState {
+                file_hash: row.get(0)?,
+                file_size: row.get(1)?,
+                byte_offset: row.get(2)?,
+                line_offset: row.get(3)?,
+                cost_state: row.get(4)?,
+                header_hash: row.get(5)?,
+            })
+        },
+    )
+    .ok()
+}
+
+fn set_import_state(
+    conn: &Connection,
+    file_path: &Path,
+    file_hash: &str,
+    file_size: i64,
+    source: &str,
+    events_imported: i64,
+    resume: ResumeState,
+    header_hash: &str,
+) {
+    let _ = with_busy_retry(|| {
+        conn.execute(
+            "INSERT INTO import_state
+                (file_path, file_hash, file_size, source, events_imported, imported_at,
+                 byte_offset, line_offset, cost_state, header_hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'), ?6, ?7, ?8, ?9)
+             ON CONFLICT(file_path) DO UPDATE SET
+               file_hash = excluded.file_hash,
+               file_size = excluded.file_size,
+               events_imported = excluded.events_imported,
+               imported_at = datetime('now'),
+               byte_offset = excluded.byte_offset,
+               line_offset = excluded.line_offset,
+               cost_state = excluded.cost_state,
+               header_hash = excluded.header_hash",
+            params![
+                file_path.display().to_string(),
+                file_hash,
+                file_size,
+                source,
+                events_imported,
+                resume.byte_offset,
+                resume.line_offset,
+                resume.cost_state,
+                header_hash,
+            ],
+        )
+    });
+}
+
+/// Run one import pass, then keep watching the Claude/Codex log roots for
+/// filesystem changes and re-import as files grow, printing a running tally.
+/// Bursts of writes to the same file are debounced into a single re-import
+/// pass rather than one per OS event.
+///
+/// Each re-import pass goes through the same `process_file`/`ResumeState`
+/// path as a plain `run_import` call, so a grown Claude Code file resumes
+/// from its stored byte offset instead of reparsing from the start; Codex
+/// files are still reparsed in full each tick (see `parse_codex_file`). The
+/// `event_id` dedup at insert time makes either case idempotent against a
+/// `--force` rerun, and a per-file byte-length check before reparsing keeps
+/// an idle watch loop from doing any work at all.
+///
+/// `options.watch_debounce_ms` sets the coalescing window: some platforms
+/// emit two create events for one path (seen when a directory is created),
+/// and without debouncing each would trigger its own import pass, parsing
+/// the same appended lines twice in a single burst.
+pub fn run_watch(conn: &Connection, options: &ImportOptions) {
+    use notify::{Event, RecursiveMode, Watcher, recommended_watcher};
+    use std::collections::{HashMap, HashSet};
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    let claude_root = options
+        .claude_dir
+        .clone()
+        .or_else(|| home_dir().map(|h| h.join(".claude")))
+        .unwrap_or_else(|| PathBuf::from("."));
+    let codex_root = options
+        .codex_dir
+        .clone()
+        .or_else(|| env::var("CODEX_HOME").ok().map(PathBuf::from))
+        .or_else(|| home_dir().map(|h| h.join(".codex")))
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let mut known_sizes: HashMap<PathBuf, u64> = HashMap::new();
+    for importer in all_importers() {
+        for path in importer.discover(options) {
+            if let Some(len) = file_len(&path) {
+                known_sizes.insert(path, len);
+            }
+        }
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = match recommended_watcher(move |res| {
+        let _ = tx.send(res);
+    }) {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            eprintln!("failed to start filesystem watcher: {err}");
+            return;
+        }
+    };
+
+    for root in [&claude_root, &codex_root] {
+        if root.exists() {
+            let _ = watcher.watch(root, RecursiveMode::Recursive);
+        }
+    }
+
+    println!(
+        "Watching {} and {} for changes... (Ctrl+C to stop)",
+        claude_root.display(),
+        codex_root.display()
+    );
+
+    let mut total_events_imported = 0usize;
+    let mut total_duplicates = 0usize;
+    let debounce = Duration::from_millis(options.watch_debounce_ms);
+
+    loop {
+        let Ok(first) = rx.recv() else {
+            break;
+        };
+
+        // Drain any further events within the debounce window so a burst of
+        // writes to one file triggers a single re-import pass, not several.
+        let mut events: Vec<notify::Result<Event>> = vec![first];
+        let deadline = Instant::now() + debounce;
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            match rx.recv_timeout(remaining) {
+                Ok(event) => events.push(event),
+                Err(_) => break,
+            }
+        }
+
+        let changed_paths: HashSet<PathBuf> = events
+            .into_iter()
+            .filter_map(Result::ok)
+            .flat_map(|event| event.paths)
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("jsonl"))
+            .collect();
+        if changed_paths.is_empty() {
+            continue;
+        }
+
+        let mut any_grew = false;
+        for path in &changed_paths {
+            let len = file_len(path).unwrap_or(0);
+            if known_sizes.insert(path.clone(), len) != Some(len) {
+                any_grew = true;
+            }
+        }
+        if !any_grew {
+            continue;
+        }
+
+        let result = run_import(conn, options);
+        total_events_imported += result.total_events_imported;
+        total_duplicates += result.total_duplicates;
+
+        if result.total_events_imported > 0 || result.total_duplicates > 0 {
+            println!(
+                "[watch] +{} event(s) imported, +{} duplicate(s) (running totals: {} imported, {} duplicates)",
+                result.total_events_imported,
+                result.total_duplicates,
+                total_events_imported,
+                total_duplicates
+            );
+        }
+    }
+}
+
+/// Current on-disk byte length of a file, used by `run_watch` to tell
+/// whether a file actually grew since it was last checked.
+fn file_len(path: &Path) -> Option<u64> {
+    fs::metadata(path).ok().map(|m| m.len())
+}
+
+pub fn discover_claude_code_logs(base_dir: Option<&Path>) -> Vec<PathBuf> {
+    let claude_root = base_dir
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|h| h.join(".claude")))
+        .unwrap_or_else(|| PathBuf::from("."));
+    let projects_dir = claude_root.join("projects");
+    let mut files = Vec::new();
+    if !projects_dir.exists() {
+        return files;
+    }
+    let Ok(project_entries) = fs::read_dir(projects_dir) else {
+        return files;
+    };
+    for project in project_entries.flatten() {
+        if !project.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(project.path()) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_type().map(|ft| ft.is_file()).unwrap_or(false)
+                && path.extension().and_then(|s| s.to_str()) == Some("jsonl")
+            {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+pub fn discover_codex_logs(base_dir: Option<&Path>) -> Vec<PathBuf> {
+    let codex_home = base_dir
+        .map(PathBuf::from)
+        .or_else(|| env::var("CODEX_HOME").ok().map(PathBuf::from))
+        .or_else(|| home_dir().map(|h| h.join(".codex")))
+        .unwrap_or_else(|| PathBuf::from("."));
+    let sessions_dir = codex_home.join("sessions");
+    let mut files = Vec::new();
+    walk_jsonl_files(&sessions_dir, &mut files);
+    files.sort();
+    files
+}
+
+fn walk_jsonl_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
+            walk_jsonl_files(&path, out);
+            continue;
+        }
+        if entry.file_type().map(|ft| ft.is_file()).unwrap_or(false)
+            && path.extension().and_then(|s| s.to_str()) == Some("jsonl")
+        {
+            out.push(path);
+        }
+    }
+}
+
+/// Parses only the bytes appended since `resume.byte_offset` (or the whole
+/// file, when resuming from zero), continuing the absolute line index and
+/// running cost total `resume` carries so `event_id`s and cost deltas for
+/// the new lines come out identical to a full reparse. A trailing line with
+/// no newline yet (the writer mid-append) is left unconsumed — the returned
+/// `byte_offset` only advances past complete lines, so the next call picks
+/// it back up once it's been flushed.
+fn parse_claude_code_file(
+    file_path: &Path,
+    options: &ImportOptions,
+    resume: ResumeState,
+) -> (Vec<ImportedEvent>, ResumeState) {
+    let mut events: Vec<ImportedEvent> = Vec::new();
+    let Ok(tail) = read_from_offset(file_path, resume.byte_offset as u64) else {
+        return (events, resume);
+    };
+
+    let file_basename = file_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let mut prev_cost_usd = resume.cost_state;
+    let mut consumed_bytes = 0usize;
+    let mut lines_consumed = 0i64;
+
+    let mut pos = 0usize;
+    while let Some(newline_rel) = tail[pos..].find('\n') {
+        let line_end = pos + newline_rel;
+        let raw_line = tail[pos..line_end].strip_suffix('\r').unwrap_or(&tail[pos..line_end]);
+        let i = resume.line_offset + lines_consumed;
+        lines_consumed += 1;
+        pos = line_end + 1;
+        consumed_bytes = pos;
+
+        let Ok(line) = serde_json::from_str::<Value>(raw_line) else {
+            continue;
+        };
+        let Some(line_type) = get_string(&line, "type") else {
+            continue;
+        };
+
+        let session_id = get_string(&line, "sessionId").unwrap_or_else(|| file_basename.clone());
+
+        let timestamp = get_string(&line, "timestamp");
+        if let Some(ts) = timestamp.as_deref().and_then(parse_timestamp_utc) {
+            if let Some(from) = options.from.as_ref()
+                && ts < *from
+            {
+                continue;
+            }
+            if let Some(to) = options.to.as_ref()
+                && ts > *to
+            {
+                continue;
+            }
+        }
+
+        let event_type = match line_type.as_str() {
+            "tool_use" => "tool_use",
+            "tool_result" => "tool_use",
+            "assistant" => "llm_response",
+            "error" => "error",
+            "session_start" => "session_start",
+            "session_end" => "session_end",
+            _ => "response",
+        }
+        .to_string();
+
+        let tool_name = get_string(&line, "name").or_else(|| get_string(&line, "tool_name"));
+        let message = line.get("message");
+        let model = get_string(&line, "model").or_else(|| {
+            message
+                .and_then(|m| m.get("model"))
+                .and_then(|v| v.as_str())
+                .map(ToString::to_string)
+        });
+
+        let usage = line
+            .get("usage")
+            .or_else(|| message.and_then(|m| m.get("usage")));
+        let tokens_in = usage
+            .and_then(|u| u.get("input_tokens"))
+            .and_then(as_i64)
+            .unwrap_or(0);
+        let tokens_out = usage
+            .and_then(|u| u.get("output_tokens"))
+            .and_then(as_i64)
+            .unwrap_or(0);
+        let cache_read_tokens = usage
+            .and_then(|u| u.get("cache_read_input_tokens"))
+            .and_then(as_i64)
+            .unwrap_or(0);
+        let cache_write_tokens = usage
+            .and_then(|u| u.get("cache_creation_input_tokens"))
+            .and_then(as_i64)
+            .unwrap_or(0);
+
+        let mut cost_delta: Option<f64> = None;
+        if let Some(current_cost) = line.get("costUSD").and_then(as_f64)
+            && current_cost > 0.0
+        {
+            let mut delta = current_cost - prev_cost_usd;
+            if delta < 0.0 {
+                delta = 0.0;
+            }
+            prev_cost_usd = current_cost;
+            if delta > 0.0 {
+                cost_delta = Some(delta);
+            }
+        }
+
+        let project = get_string(&line, "cwd").as_deref().and_then(path_basename);
+        let branch = get_string(&line, "gitBranch");
+
+        let status = if line_type == "error"
+            || line.get("is_error").and_then(|v| v.as_bool()) == Some(true)
+            || get_string(&line, "status").as_deref() == Some("error")
+        {
+            "error".to_string()
+        } else {
+            "success".to_string()
+        };
+
+        let mut metadata = Map::new();
+        if let Some(err) = line.get("error") {
+            if let Some(err_str) = err.as_str() {
+                metadata.insert("error".into(), Value::String(err_str.to_string()));
+            } else if let Some(msg) = err.get("message").and_then(|v| v.as_str()) {
+                metadata.insert("error".into(), Value::String(msg.to_string()));
+            }
+        }
+
+        if let Some(content_value) = line.get("content") {
+            if let Some(content_str) = content_value.as_str() {
+                metadata.insert(
+                    "content_preview".into(),
+                    Value::String(slice_chars(content_str, 500)),
+                );
+            } else if let Some(arr) = content_value.as_array() {
+                let mut parts = Vec::new();
+                for block in arr {
+                    if let Some(text) = block.get("text").and_then(|v| v.as_str()) {
+                        parts.push(text.to_string());
+                    }
+                }
+                if !parts.is_empty() {
+                    metadata.insert(
+                        "content_preview".into(),
+                        Value::String(slice_chars(&parts.join("\n"), 500)),
+                    );
+                }
+            }
+        }
+
+        if line_type == "tool_use"
+            && let Some(input) = line.get("input").and_then(|v| v.as_object())
+        {
+            for key in ["command", "file_path", "pattern", "query"] {
+                if let Some(val) = input.get(key).and_then(|v| v.as_str()) {
+                    metadata.insert(key.to_string(), Value::String(val.to_string()));
+                }
+            }
+            if let Some(tool) = tool_name.as_deref() {
+                if tool == "Edit" || tool == "MultiEdit" {
+                    if let Some(old_str) = input.get("old_string").and_then(|v| v.as_str()) {
+                        metadata.insert(
+                            "lines_removed".into(),
+                            Value::Number((old_str.lines().count() as i64).into()),
+                        );
+                    }
+                    if let Some(new_str) = input.get("new_string").and_then(|v| v.as_str()) {
+                        metadata.insert(
+                            "lines_added".into(),
+                            Value::Number((new_str.lines().count() as i64).into()),
+                        );
+                    }
+                } else if tool == "Write"
+                    && let Some(text) = input.get("content").and_then(|v| v.as_str())
+                {
+                    metadata.insert(
+                        "lines_added".into(),
+                        Value::Number((text.lines().count() as i64).into()),
+                    );
+                }
+            }
+        }
+
+        if line_type == "tool_result"
+            && let Some(output) = line.get("output")
+        {
+            let rendered = if let Some(text) = output.as_str() {
+                text.to_string()
+            } else {
+                output.to_string()
+            };
+            metadata.insert(
+                "content_preview".into(),
+                Value::String(slice_chars(&rendered, 500)),
+            );
+        }
+
+        let event_id = format!(
+            "import-cc-{}",
+            short_sha256_hex(&format!("claude-code:{session_id}:{i}"))
+        );
+        let is_tool_use = event_type == "tool_use";
+
+        events.push(ImportedEvent {
+            event_id: Some(event_id),
+            session_id,
+            agent_type: "claude_code".to_string(),
+            event_type,
+            tool_name: if is_tool_use { tool_name } else { None },
+            status,
+            tokens_in,
+            tokens_out,
+            branch,
+            project,
+            duration_ms: line
+                .get("duration_ms")
+                .and_then(as_i64)
+                .or_else(|| line.get("durationMs").and_then(as_i64)),
+            client_timestamp: timestamp,
+            metadata: Value::Object(metadata),
+            model,
+            cost_usd: cost_delta,
+            cache_read_tokens,
+            cache_write_tokens,
+            source: "import".to_string(),
+        });
+    }
+
+    let new_resume = ResumeState {
+        byte_offset: resume.byte_offset + consumed_bytes as i64,
+        line_offset: resume.line_offset + lines_consumed,
+        cost_state: prev_cost_usd,
+    };
+    (events, new_resume)
+}
+
+/// Codex's JSONL carries running totals and a single synthesized
+/// `session_end` summarizing the whole session, so (unlike Claude Code) it
+/// can't be parsed incrementally line-by-line — it always reparses the
+/// whole file. `resume`/`ResumeState` are accepted only to satisfy
+/// `LogImporter::parse`'s signature; the returned state just records the
+/// file's current length so `process_file`'s unchanged-file skip still
+/// works.
+fn parse_codex_file(
+    file_path: &Path,
+    options: &ImportOptions,
+    _resume: ResumeState,
+) -> (Vec<ImportedEvent>, ResumeState) {
+    let mut events: Vec<ImportedEvent> = Vec::new();
+    let Ok(content) = fs::read_to_string(file_path) else {
+        return (events, ResumeState::default());
+    };
+    let processed_resume = ResumeState {
+        byte_offset: content.len() as i64,
+        ..ResumeState::default()
+    };
+
+    let profile = read_codex_profile(options.codex_dir.as_deref());
+    let default_model = profile.model.clone();
+    let mut lines: Vec<Value> = Vec::new();
+    for raw in content.lines() {
+        if let Ok(parsed) = serde_json::from_str::<Value>(raw) {
+            lines.push(parsed);
+        }
+    }
+
+    let mut session_id: Option<String> = None;
+    let mut cwd: Option<String> = None;
+    let mut session_ts: Option<String> = None;
+
+    for line in &lines {
+        if get_string(line, "type").as_deref() != Some("session_meta") {
+            continue;
+        }
+        let payload = line.get("payload").unwrap_or(&Value::Null);
+        session_id = get_string(payload, "id");
+        cwd = get_string(payload, "cwd");
+        session_ts = get_string(payload, "timestamp").or_else(|| get_string(line, "timestamp"));
+        break;
+    }
+
+    if session_id.is_none() {
+        session_id = Some(
+            file_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown")
+                .to_string(),
+        );
+    }
+    let session_id = session_id.unwrap_or_else(|| "unknown".to_string());
+
+    if let Some(ts) = session_ts.as_deref().and_then(parse_timestamp_utc) {
+        if let Some(from) = options.from.as_ref()
+            && ts < *from
+        {
+            return (events, processed_resume);
+        }
+        if let Some(to) = options.to.as_ref()
+            && ts > *to
+        {
+            return (events, processed_resume);
+        }
+    }
+
+    let project = cwd.as_deref().and_then(path_basename);
+    // Opened once per file (not per patch event) since every apply_patch in
+    // this session shares the same `cwd` — `git::open` walks up the
+    // filesystem looking for a `.git`, which isn't free to repeat.
+    let repo = cwd.as_deref().map(Path::new).and_then(git::open);
+    let mut prev_tokens_in = 0_i64;
+    let mut prev_tokens_out = 0_i64;
+    let mut prev_cache_read = 0_i64;
+    let mut event_index = 0usize;
+
+    for line in &lines {
+        let line_type = get_string(line, "type").unwrap_or_default();
+        let timestamp = get_string(line, "timestamp");
+        let payload = line.get("payload").unwrap_or(&Value::Null);
+
+        if line_type == "session_meta" {
+            let metadata = json!({
+                "cli_version": get_string(payload, "originator"),
+                "cwd": cwd,
+            });
+            let event_id = format!(
+                "import-cdx-{}",
+                short_sha256_hex(&format!("codex:{session_id}:meta"))
+            );
+            events.push(ImportedEvent {
+                event_id: Some(event_id),
+                session_id: session_id.clone(),
+                agent_type: "codex".to_string(),
+                event_type: "session_start".to_string(),
+                tool_name: None,
+                status: "success".to_string(),
+                tokens_in: 0,
+                tokens_out: 0,
+                branch: None,
+                project: project.clone(),
+                duration_ms: None,
+                client_timestamp: timestamp,
+                metadata,
+                model: default_model.clone(),
+                cost_usd: None,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                source: "import".to_string(),
+            });
+            continue;
+        }
+
+        if line_type == "event_msg" && get_string(payload, "type").as_deref() == Some("token_count")
+        {
+            let usage = payload
+                .get("info")
+                .and_then(|v| v.get("total_token_usage"))
+                .unwrap_or(&Value::Null);
+            let total_in = usage.get("input_tokens").and_then(as_i64).unwrap_or(0);
+            let total_out = usage.get("output_tokens").and_then(as_i64).unwrap_or(0);
+            let total_cache = usage
+                .get("cached_input_tokens")
+                .and_then(as_i64)
+                .unwrap_or(0);
+
+            let delta_in = total_in - prev_tokens_in;
+            let delta_out = total_out - prev_tokens_out;
+            let delta_cache_read = total_cache - prev_cache_read;
+
+            prev_tokens_in = total_in;
+            prev_tokens_out = total_out;
+            prev_cache_read = total_cache;
+
+            if delta_in <= 0 && delta_out <= 0 {
+                continue;
+            }
+
+            let event_id = format!(
+                "import-cdx-{}",
+                short_sha256_hex(&format!("codex:{session_id}:token:{event_index}"))
+            );
+            let metadata = json!({
+                "_synthetic": true,
+                "_source": "codex_session_jsonl",
+            });
+
+            events.push(ImportedEvent {
+                event_id: Some(event_id),
+                session_id: session_id.clone(),
+                agent_type: "codex".to_string(),
+                event_type: "llm_response".to_string(),
+                tool_name: None,
+                status: "success".to_string(),
+                tokens_in: delta_in,
+                tokens_out: delta_out,
+                branch: None,
+                project: project.clone(),
+                duration_ms: None,
+                client_timestamp: timestamp,
+                metadata,
+                model: default_model.clone(),
+                cost_usd: None,
+                cache_read_tokens: delta_cache_read,
+                cache_write_tokens: 0,
+                source: "import".to_string(),
+            });
+            event_index += 1;
+            continue;
+        }
+
+        if line_type == "response_item" {
+            let patch_content = extract_patch_content(payload);
+            if let Some(patch) = patch_content {
+                for meta in parse_patch_meta(&patch) {
+                    let event_id = format!(
+                        "import-cdx-{}",
+                        short_sha256_hex(&format!("codex:{session_id}:patch:{event_index}"))
+                    );
+                    let correlation = repo.as_ref().map(|repo| {
+                        git::correlate(
+                            repo,
+                            &meta.file_path,
+                            meta.lines_added,
+                            meta.lines_removed,
+                            timestamp.as_deref().and_then(parse_timestamp_utc),
+                        )
+                    });
+                    let metadata = json!({
+                        "file_path": meta.file_path,
+                        "old_path": meta.old_path,
+                        "new_path": meta.new_path,
+                        "lines_added": meta.lines_added,
+                        "lines_removed": meta.lines_removed,
+                        "hunks": meta.hunks.iter().map(|(added, removed)| json!({
+                            "added": added,
+                            "removed": removed,
+                        })).collect::<Vec<_>>(),
+                        "is_binary": meta.is_binary,
+                        "commit_sha": correlation.as_ref().and_then(|c| c.commit_sha.clone()),
+                        "commit_verified": correlation.as_ref().map(|c| c.commit_verified).unwrap_or(false),
+                        "diff_reconciliation": correlation.as_ref().and_then(|c| c.reconciliation).map(|r| json!({
+                            "claimed_added": r.claimed_added,
+                            "claimed_removed": r.claimed_removed,
+                            "actual_added": r.actual_added,
+                            "actual_removed": r.actual_removed,
+                        })),
+                    });
+                    events.push(ImportedEvent {
+                        event_id: Some(event_id),
+                        session_id: session_id.clone(),
+                        agent_type: "codex".to_string(),
+                        event_type: "tool_use".to_string(),
+                        tool_name: Some("apply_patch".to_string()),
+                        status: "success".to_string(),
+                        tokens_in: 0,
+                        tokens_out: 0,
+                        branch: correlation.as_ref().and_then(|c| c.branch.clone()),
+                        project: project.clone(),
+                        duration_ms: None,
+                        client_timestamp: timestamp.clone(),
+                        metadata,
+                        model: None,
+                        cost_usd: None,
+                        cache_read_tokens: 0,
+                        cache_write_tokens: 0,
+                        source: "import".to_string(),
+                    });
+                    event_index += 1;
+                }
+            }
+        }
+    }
+
+    if !events.is_empty() {
+        let last_ts = lines.last().and_then(|v| get_string(v, "timestamp"));
+        let event_id = format!(
+            "import-cdx-{}",
+            short_sha256_hex(&format!("codex:{session_id}:end"))
+        );
+        let metadata = json!({
+            "total_tokens_in": prev_tokens_in,
+            "total_tokens_out": prev_tokens_out,
+            "total_cache_read": prev_cache_read,
+            "model_provider": profile.model_provider,
+            "reasoning_effort": profile.reasoning_effort,
+            "approval_policy": profile.approval_policy,
+            "profile": profile.profile_name,
+        });
+        events.push(ImportedEvent {
+            event_id: Some(event_id),
+            session_id,
+            agent_type: "codex".to_string(),
+            event_type: "session_end".to_string(),
+            tool_name: None,
+            status: "success".to_string(),
+            tokens_in: 0,
+            tokens_out: 0,
+            branch: None,
+            project,
+            duration_ms: None,
+            client_timestamp: last_ts,
+            metadata,
+            model: default_model,
+            cost_usd: None,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            source: "import".to_string(),
+        });
+    }
+
+    (events, processed_resume)
+}
+
+fn extract_patch_content(payload: &Value) -> Option<String> {
+    if get_string(payload, "name").as_deref() == Some("apply_patch")
+        && let Some(input) = get_string(payload, "input")
+    {
+        return Some(input);
+    }
+
+    if get_string(payload, "name").as_deref() == Some("exec_command")
+        && let Some(arguments) = get_string(payload, "arguments")
+    {
+        if let Ok(parsed) = serde_json::from_str::<Value>(&arguments)
+            && let Some(cmd) = get_string(&parsed, "cmd")
+            && cmd.starts_with("apply_patch")
+        {
+            return Some(cmd);
+        }
+        if arguments.starts_with("apply_patch") || arguments.contains("*** Begin Patch") {
+            return Some(arguments);
+        }
+    }
+
+    None
+}
+
+/// Per-file diff stats for one `*** Update/Add/Delete File:` block within an
+/// `apply_patch` payload. A single payload can contain several such blocks
+/// (one per touched file), so `parse_patch_meta` returns one `PatchMeta` per
+/// block rather than collapsing the whole patch into one entry.
+struct PatchMeta {
+    /// The file this block is reported under — `new_path` for a rename,
+    /// otherwise the path from the block's own header.
+    file_path: String,
+    /// Set alongside `new_path` when this block is a rename (an
+    /// `*** Update File:` header immediately followed by `*** Move to:`).
+    old_path: Option<String>,
+    new_path: Option<String>,
+    lines_added: i64,
+    lines_removed: i64,
+    /// `(added, removed)` per `@@`-delimited hunk, in patch order. Mirrors a
+    /// unified-diff hunk breakdown rather than just the block-wide totals
+    /// above.
+    hunks: Vec<(i64, i64)>,
+    /// True when the block has a body (something other than headers/`Move
+    /// to`) but none of it is `+`/`-` diff lines — Codex emits these for
+    /// binary file changes, which would otherwise look like a zero-line,
+    /// content-free edit.
+    is_binary: bool,
+}
+
+/// In-progress accumulation for the file block currently being read by
+/// `parse_patch_meta`, flushed into a [`PatchMeta`] once the next header (or
+/// end of patch) is reached.
+struct PatchBlockBuilder {
+    file_path: String,
+    old_path: Option<String>,
+    new_path: Option<String>,
+    hunks: Vec<(i64, i64)>,
+    current_hunk: (i64, i64),
+    has_current_hunk: bool,
+    saw_body_line: bool,
+}
+
+impl PatchBlockBuilder {
+    fn new(file_path: String) -> Self {
+        Self {
+            file_path,
+            old_path: None,
+            new_path: None,
+            hunks: Vec::new(),
+            current_hunk: (0, 0),
+            has_current_hunk: false,
+            saw_body_line: false,
+        }
+    }
+
+    fn start_hunk(&mut self) {
+        self.flush_hunk();
+        self.has_current_hunk = true;
+    }
+
+    fn flush_hunk(&mut self) {
+        if self.has_current_hunk {
+            self.hunks.push(self.current_hunk);
+        }
+        self.current_hunk = (0, 0);
+        self.has_current_hunk = false;
+    }
+
+    fn finish(mut self) -> PatchMeta {
+        self.flush_hunk();
+        let lines_added: i64 = self.hunks.iter().map(|(added, _)| added).sum();
+        let lines_removed: i64 = self.hunks.iter().map(|(_, removed)| removed).sum();
+        let is_binary = self.hunks.is_empty() && self.saw_body_line;
+        PatchMeta {
+            file_path: self.new_path.clone().unwrap_or(self.file_path),
+            old_path: self.old_path,
+            new_path: self.new_path,
+            lines_added,
+            lines_removed,
+            hunks: self.hunks,
+            is_binary,
+        }
+    }
+}
+
+/// Splits an `apply_patch` payload into one [`PatchMeta`] per `*** Update
+/// File:`/`*** Add File:`/`*** Delete File:` block, attributing hunks and
+/// added/removed counts to the right file instead of pooling them across the
+/// whole patch.
+fn parse_patch_meta(patch: &str) -> Vec<PatchMeta> {
+    let mut results = Vec::new();
+    let mut current: Option<PatchBlockBuilder> = None;
+
+    for line in patch.lines() {
+        if let Some(path) = line
+            .strip_prefix("*** Update File: ")
+            .or_else(|| line.strip_prefix("*** Add File: "))
+            .or_else(|| line.strip_prefix("*** Delete File: "))
+        {
+            if let Some(block) = current.take() {
+                results.push(block.finish());
+            }
+            current = Some(PatchBlockBuilder::new(path.trim().to_string()));
+            continue;
+        }
+
+        if let Some(path) = line.strip_prefix("*** Move to: ") {
+            if let Some(block) = current.as_mut() {
+                block.old_path = Some(block.file_path.clone());
+                block.new_path = Some(path.trim().to_string());
+            }
+            continue;
+        }
+
+        let Some(block) = current.as_mut() else {
+            continue;
+        };
+
+        if line.starts_with("@@") {
+            block.start_hunk();
+            continue;
+        }
+
+        if line.starts_with("***") {
+            continue;
+        }
+
+        if line.starts_with('+') && !line.starts_with("+++") {
+            block.current_hunk.0 += 1;
+            block.has_current_hunk = true;
+        } else if line.starts_with('-') && !line.starts_with("---") {
+            block.current_hunk.1 += 1;
+            block.has_current_hunk = true;
+        } else if !line.trim().is_empty() {
+            block.saw_body_line = true;
+        }
+    }
+
+    if let Some(block) = current.take() {
+        results.push(block.finish());
+    }
+
+    results
+}
+
+/// The slice of `~/.codex/config.toml` import cares about. Deserialized
+/// straight from TOML rather than hand-scanned line by line, so a profiled
+/// config, a `[model_providers.x]` table, or a multi-line value doesn't
+/// silently defeat a `contains("model")` scan the way the old reader did —
+/// everything not listed here (sandbox settings, MCP server definitions,
+/// ...) is simply ignored by `#[serde(default)]` letting unknown keys pass.
+#[derive(Debug, Default, Deserialize)]
+struct CodexConfigFile {
+    model: Option<String>,
+    model_provider: Option<String>,
+    model_reasoning_effort: Option<String>,
+    approval_policy: Option<String>,
+    profile: Option<String>,
+}
+
+/// The fields of `CodexConfigFile` import actually surfaces, resolved with
+/// the same `base_dir`/`CODEX_HOME`/`~/.codex` precedence the old
+/// `read_codex_model` used. All fields are `None` when there's no config
+/// file, or it fails to parse.
+#[derive(Debug, Default, Clone)]
+struct CodexProfile {
+    model: Option<String>,
+    model_provider: Option<String>,
+    reasoning_effort: Option<String>,
+    approval_policy: Option<String>,
+    profile_name: Option<String>,
+}
+
+fn read_codex_profile(base_dir: Option<&Path>) -> CodexProfile {
+    let Some(base) = base_dir
+        .map(PathBuf::from)
+        .or_else(|| env::var("CODEX_HOME").ok().map(PathBuf::from))
+        .or_else(|| home_dir().map(|h| h.join(".codex")))
+    else {
+        return CodexProfile::default();
+    };
+
+    let config_path = base.join("config.toml");
+    let Ok(content) = fs::read_to_string(config_path) else {
+        return CodexProfile::default();
+    };
+    let parsed: CodexConfigFile = toml::from_str(&content).unwrap_or_default();
+
+    CodexProfile {
+        model: parsed.model,
+        model_provider: parsed.model_provider,
+        reasoning_effort: parsed.model_reasoning_effort,
+        approval_policy: parsed.approval_policy,
+        profile_name: parsed.profile,
+    }
+}
+
+fn parse_timestamp_utc(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(raw)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+}
+
+/// Reads a file starting at `offset` bytes in, returning everything from
+/// there to EOF as a string. `offset` must land on a UTF-8 char boundary —
+/// true in practice here since the only offsets ever passed in are ones
+/// `parse_claude_code_file` previously returned, each just past a `\n`.
+fn read_from_offset(path: &Path, offset: u64) -> Result<String, std::io::Error> {
+    use std::io::{Read, Seek, SeekFrom};
+    let mut file = fs::File::open(path)?;
+    if offset > 0 {
+        file.seek(SeekFrom::Start(offset))?;
+    }
+    let mut buf = String::new();
+    file.read_to_string(&mut buf)?;
+    Ok(buf)
+}
+
+fn hash_file(path: &Path) -> Result<String, std::io::Error> {
+    let bytes = fs::read(path)?;
+    Ok(sha256_hex(&bytes))
+}
+
+/// Below this size, [`sampled_hash_file`] just hashes the whole file —
+/// sampling three 4 KiB windows out of something that small wouldn't save
+/// any real work and would only make the fingerprint weaker.
+const SAMPLED_HASH_THRESHOLD_BYTES: u64 = 16 * 1024;
+
+/// Window size `sampled_hash_file` reads at each sample point.
+const SAMPLED_HASH_WINDOW_BYTES: usize = 4096;
+
+/// Cheap fingerprint for files too large to be worth hashing in full: below
+/// `threshold` bytes this just delegates to [`hash_file`]; above it, it
+/// reads fixed 4 KiB windows at the start, middle, and end of the file and
+/// hashes those plus the file's length (as 8 little-endian bytes) instead of
+/// every byte in between. Mixing the length in is load-bearing — without it,
+/// two files that happen to share their sampled windows but differ in size
+/// elsewhere would hash identically.
+fn sampled_hash_file(path: &Path, threshold: u64) -> Result<String, std::io::Error> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let len = fs::metadata(path)?.len();
+    if len < threshold {
+        return hash_file(path);
+    }
+
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+
+    let mut read_window = |hasher: &mut Sha256, offset: u64| -> std::io::Result<()> {
+        file.seek(SeekFrom::Start(offset))?;
+        let mut buf = vec![0u8; SAMPLED_HASH_WINDOW_BYTES];
+        let mut total_read = 0usize;
+        loop {
+            match file.read(&mut buf[total_read..])? {
+                0 => break,
+                read => total_read += read,
+            }
+        }
+        buf.truncate(total_read);
+        hasher.update(&buf);
+        Ok(())
+    };
+
+    read_window(&mut hasher, 0)?;
+    read_window(&mut hasher, len / 2)?;
+    read_window(&mut hasher, len.saturating_sub(SAMPLED_HASH_WINDOW_BYTES as u64))?;
+    hasher.update(len.to_le_bytes());
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Hash of the file's leading `n` bytes (or the whole file if it's shorter),
+/// used to confirm a grown file's earlier content is still what was already
+/// parsed without reading/hashing the whole (possibly large) file.
+fn header_hash(path: &Path, n: usize) -> Result<String, std::io::Error> {
+    use std::io::Read;
+    let mut file = fs::File::open(path)?;
+    let mut buf = vec![0u8; n];
+    let mut total_read = 0usize;
+    loop {
+        match file.read(&mut buf[total_read..])? {
+            0 => break,
+            read => total_read += read,
+        }
+    }
+    buf.truncate(total_read);
+    Ok(sha256_hex(&buf))
+}
+
+fn short_sha256_hex(input: &str) -> String {
+    let digest = sha256_hex(input.as_bytes());
+    digest.chars().take(32).collect()
+}
+
+fn sha256_hex(input: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(input);
+    format!("{:x}", hasher.finalize())
+}
+
+fn get_string(v: &Value, key: &str) -> Option<String> {
+    v.get(key).and_then(|x| x.as_str()).map(ToString::to_string)
+}
+
+fn as_i64(v: &Value) -> Option<i64> {
+    v.as_i64().or_else(|| v.as_f64().map(|f| f as i64))
+}
+
+fn as_f64(v: &Value) -> Option<f64> {
+    v.as_f64().or_else(|| v.as_i64().map(|i| i as f64))
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from)
+}
+
+fn path_basename(path: &str) -> Option<String> {
+    Path::new(path)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .map(ToString::to_string)
+}
+
+fn slice_chars(value: &str, max_chars: usize) -> String {
+    value.chars().take(max_chars).collect()
+}
+
+/// Golden-fixture coverage for the two format-specific parsers. The
+/// fixtures under `tests/fixtures/import/` are sanitized real-session
+/// shapes (see `bin/sanitize_import_fixture.rs`) rather than hand-rolled
+/// minimal cases, so drift in event_type mapping, delta arithmetic, or
+/// Edit/Write line-count extraction surfaces as a failing assertion here
+/// instead of silently producing wrong rows in production.
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{
+        ImportOptions, ImportSource, ResumeState, SAMPLED_HASH_THRESHOLD_BYTES, hash_file, parse_claude_code_file,
+        parse_codex_file, parse_patch_meta, sampled_hash_file, short_sha256_hex,
+    };
+
+    fn test_options() -> ImportOptions {
+        ImportOptions {
+            source: ImportSource::All,
+            from: None,
+            to: None,
+            dry_run: false,
+            force: false,
+            claude_dir: None,
+            codex_dir: None,
+            s3: None,
+            max_payload_kb: 64,
+            watch: false,
+            watch_debounce_ms: 500,
+            export_url: None,
+            export_flush_threshold: 100,
+            sampled_hash: false,
+        }
+    }
+
+    #[test]
+    fn parses_sanitized_claude_code_fixture() {
+        let fixture = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/import/claude_code_sample.jsonl"
+        ));
+        let (events, resume) = parse_claude_code_file(fixture, &test_options(), ResumeState::default());
+
+        assert_eq!(events.len(), 7);
+        assert_eq!(resume.line_offset, 7);
+
+        let bash = &events[0];
+        assert_eq!(bash.event_type, "tool_use");
+        assert_eq!(bash.tool_name.as_deref(), Some("Bash"));
+        assert_eq!(bash.project.as_deref(), Some("widget-app"));
+        assert_eq!(bash.status, "success");
+
+        let edit = &events[1];
+        assert_eq!(edit.metadata["lines_removed"], 3);
+        assert_eq!(edit.metadata["lines_added"], 2);
+
+        let tool_result = &events[2];
+        assert_eq!(tool_result.event_type, "tool_use");
+        assert_eq!(tool_result.metadata["content_preview"], "redacted-tool-output-1");
+
+        let first_response = &events[3];
+        assert_eq!(first_response.event_type, "llm_response");
+        assert_eq!(first_response.cost_usd, Some(0.05));
+
+        let write = &events[4];
+        assert_eq!(write.metadata["lines_added"], 4);
+
+        let second_response = &events[5];
+        assert!((second_response.cost_usd.unwrap() - 0.03).abs() < 1e-9);
+
+        let error = &events[6];
+        assert_eq!(error.event_type, "error");
+        assert_eq!(error.status, "error");
+        assert_eq!(error.metadata["error"], "redacted-error-message-1");
+    }
+
+    #[test]
+    fn parses_sanitized_codex_fixture() {
+        let fixture = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/import/codex_sample.jsonl"
+        ));
+        let (events, _resume) = parse_codex_file(fixture, &test_options(), ResumeState::default());
+
+        assert_eq!(events.len(), 5);
+
+        let session_start = &events[0];
+        assert_eq!(session_start.event_type, "session_start");
+        assert_eq!(session_start.project.as_deref(), Some("widget-app"));
+
+        let first_tokens = &events[1];
+        assert_eq!(first_tokens.tokens_in, 800);
+        assert_eq!(first_tokens.tokens_out, 120);
+        assert_eq!(first_tokens.cache_read_tokens, 0);
+
+        let second_tokens = &events[2];
+        assert_eq!(second_tokens.tokens_in, 1200);
+        assert_eq!(second_tokens.tokens_out, 180);
+        assert_eq!(second_tokens.cache_read_tokens, 500);
+
+        let patch = &events[3];
+        assert_eq!(patch.tool_name.as_deref(), Some("apply_patch"));
+        assert_eq!(patch.metadata["file_path"], "redacted-path-3.rs");
+        assert_eq!(patch.metadata["lines_added"], 2);
+        assert_eq!(patch.metadata["lines_removed"], 1);
+
+        let session_end = &events[4];
+        assert_eq!(session_end.event_type, "session_end");
+        assert_eq!(session_end.metadata["total_tokens_in"], 2000);
+        assert_eq!(session_end.metadata["total_tokens_out"], 300);
+        assert_eq!(session_end.metadata["total_cache_read"], 500);
+    }
+
+    #[test]
+    fn parse_patch_meta_attributes_hunks_per_file_and_detects_renames_and_binaries() {
+        let patch = "*** Begin Patch\n\
+             *** Update File: src/a.rs\n\
+             @@\n\
+             +added a line 1\n\
+             +added a line 2\n\
+             -removed a line\n\
+             *** Update File: src/old_name.rs\n\
+             *** Move to: src/new_name.rs\n\
+             @@\n\
+             +renamed file content\n\
+             *** Add File: assets/logo.png\n\
+             binary-diff-not-a-plus-minus-line\n\
+             *** End Patch";
+
+        let metas = parse_patch_meta(patch);
+        assert_eq!(metas.len(), 3);
+
+        let a = &metas[0];
+        assert_eq!(a.file_path, "src/a.rs");
+        assert!(a.old_path.is_none() && a.new_path.is_none());
+        assert_eq!(a.lines_added, 2);
+        assert_eq!(a.lines_removed, 1);
+        assert_eq!(a.hunks, vec![(2, 1)]);
+        assert!(!a.is_binary);
+
+        let renamed = &metas[1];
+        assert_eq!(renamed.file_path, "src/new_name.rs");
+        assert_eq!(renamed.old_path.as_deref(), Some("src/old_name.rs"));
+        assert_eq!(renamed.new_path.as_deref(), Some("src/new_name.rs"));
+        assert_eq!(renamed.lines_added, 1);
+        assert_eq!(renamed.lines_removed, 0);
+
+        let binary = &metas[2];
+        assert_eq!(binary.file_path, "assets/logo.png");
+        assert!(binary.is_binary);
+        assert_eq!(binary.lines_added, 0);
+        assert_eq!(binary.lines_removed, 0);
+    }
+
+    /// Writes `data` under a unique name in the OS temp dir for a
+    /// `sampled_hash_file` test, cleaned up on drop.
+    struct ScratchFile(std::path::PathBuf);
+
+    impl ScratchFile {
+        fn new(name: &str, data: &[u8]) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "agentmonitor-test-{name}-{}",
+                short_sha256_hex(&format!("{name}{}", data.len()))
+            ));
+            std::fs::write(&path, data).expect("write scratch fixture");
+            Self(path)
+        }
+    }
+
+    impl Drop for ScratchFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn sampled_hash_falls_back_to_full_hash_below_threshold() {
+        let file = ScratchFile::new("small", b"short file content");
+        assert_eq!(
+            sampled_hash_file(&file.0, SAMPLED_HASH_THRESHOLD_BYTES).unwrap(),
+            hash_file(&file.0).unwrap()
+        );
+    }
+
+    #[test]
+    fn sampled_hash_differs_for_large_files_that_differ_only_in_the_middle() {
+        let threshold = 16 * 1024;
+        let size = threshold * 2;
+
+        let mut data_a = vec![b'a'; size];
+        data_a[size / 2] = b'x';
+        let mut data_b = vec![b'a'; size];
+        data_b[size / 2] = b'y';
+
+        let a = ScratchFile::new("middle-a", &data_a);
+        let b = ScratchFile::new("middle-b", &data_b);
+
+        assert_ne!(
+            sampled_hash_file(&a.0, threshold as u64).unwrap(),
+            sampled_hash_file(&b.0, threshold as u64).unwrap()
+        );
+    }
+
+    #[test]
+    fn sampled_hash_differs_for_same_sampled_bytes_but_different_length() {
+        let threshold = 16 * 1024;
+
+        // Same leading/middle/trailing windows (all 'a's), only the length
+        // in between differs — without mixing the length into the digest
+        // these would otherwise collide.
+        let short = ScratchFile::new("short", &vec![b'a'; threshold * 2]);
+        let long = ScratchFile::new("long", &vec![b'a'; threshold * 3]);
+
+        assert_ne!(
+            sampled_hash_file(&short.0, threshold as u64).unwrap(),
+            sampled_hash_file(&long.0, threshold as u64).unwrap()
+        );
+    }
+}
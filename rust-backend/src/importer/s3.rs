@@ -0,0 +1,251 @@
+//! Minimal SigV4 client for listing and ranged-reading objects in an
+//! S3-compatible bucket (AWS S3, MinIO, Cloudflare R2, ...) — just enough to
+//! support `run_import`'s `s3://bucket/prefix` source. Signs requests with
+//! hand-rolled HMAC-SHA256 over the already-present `sha2` dependency
+//! rather than adding an `hmac` crate or the full AWS SDK, since this only
+//! ever needs two request shapes (`ListObjectsV2`, ranged `GetObject`) and
+//! path-style addressing.
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+/// Credentials and target bucket/prefix for an S3-compatible import source.
+/// Credentials and endpoint come from the environment, never from a CLI
+/// flag or the `s3://` URL itself — mirroring `AGENTMONITOR_DB_PASSPHRASE`,
+/// so they never land in argv or shell history.
+#[derive(Debug, Clone)]
+pub struct S3Source {
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub prefix: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+impl S3Source {
+    /// Parses `s3://bucket/prefix` and reads `AGENTMONITOR_S3_ENDPOINT`
+    /// (default `https://s3.amazonaws.com`), `AGENTMONITOR_S3_REGION`
+    /// (default `us-east-1`), `AGENTMONITOR_S3_ACCESS_KEY_ID`, and
+    /// `AGENTMONITOR_S3_SECRET_ACCESS_KEY`. Returns `None` if `url` isn't an
+    /// `s3://` URL, the bucket is empty, or either credential env var is
+    /// unset.
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("s3://")?;
+        let (bucket, prefix) = match rest.split_once('/') {
+            Some((bucket, prefix)) => (bucket.to_string(), prefix.to_string()),
+            None => (rest.to_string(), String::new()),
+        };
+        if bucket.is_empty() {
+            return None;
+        }
+        Some(Self {
+            endpoint: std::env::var("AGENTMONITOR_S3_ENDPOINT")
+                .unwrap_or_else(|_| "https://s3.amazonaws.com".to_string()),
+            region: std::env::var("AGENTMONITOR_S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+            bucket,
+            prefix,
+            access_key_id: std::env::var("AGENTMONITOR_S3_ACCESS_KEY_ID").ok()?,
+            secret_access_key: std::env::var("AGENTMONITOR_S3_SECRET_ACCESS_KEY").ok()?,
+        })
+    }
+
+    fn host(&self) -> String {
+        self.endpoint
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches('/')
+            .to_string()
+    }
+
+    fn base_url(&self) -> String {
+        format!("{}/{}", self.endpoint.trim_end_matches('/'), self.bucket)
+    }
+}
+
+/// One `.jsonl` object discovered under an [`S3Source`]'s prefix.
+#[derive(Debug, Clone)]
+pub struct S3Object {
+    pub key: String,
+    pub size: u64,
+}
+
+impl S3Object {
+    /// Stable identity stored in `import_state.file_path` for this object —
+    /// an opaque URL, not a real filesystem path, but `get_import_state`/
+    /// `set_import_state` only ever round-trip it through `Path::display`,
+    /// so it works unchanged.
+    pub fn url(&self, source: &S3Source) -> String {
+        format!("s3://{}/{}", source.bucket, self.key)
+    }
+}
+
+/// HMAC-SHA256 over `sha2::Sha256`. SigV4 key derivation chains this four
+/// times (date -> region -> service -> `aws4_request`), then once more over
+/// the canonical request's string-to-sign for the final signature.
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+    let mut key_block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        key_block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        key_block[..key.len()].copy_from_slice(key);
+    }
+    let mut ipad = [0x36u8; BLOCK_SIZE];
+    let mut opad = [0x5cu8; BLOCK_SIZE];
+    for i in 0..BLOCK_SIZE {
+        ipad[i] ^= key_block[i];
+        opad[i] ^= key_block[i];
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(ipad);
+    inner.update(data);
+    let inner_digest = inner.finalize();
+
+    let mut outer = Sha256::new();
+    outer.update(opad);
+    outer.update(inner_digest);
+    outer.finalize().into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Everything `sign_get` computed that the caller needs to attach as
+/// headers alongside `authorization` on the actual request.
+struct SignedGet {
+    authorization: String,
+    amz_date: String,
+    content_sha256: String,
+}
+
+/// Signs a bodyless GET (the only kind this module issues — `ListObjectsV2`
+/// and ranged `GetObject`) per AWS SigV4, using header-based auth rather
+/// than presigned query params.
+fn sign_get(source: &S3Source, canonical_uri: &str, canonical_query: &str, now: DateTime<Utc>) -> SignedGet {
+    let host = source.host();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date_stamp = now.format("%Y%m%d").to_string();
+    let content_sha256 = hex(&Sha256::digest(b""));
+
+    let canonical_headers = format!("host:{host}\nx-amz-content-sha256:{content_sha256}\nx-amz-date:{amz_date}\n");
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+
+    let canonical_request =
+        format!("GET\n{canonical_uri}\n{canonical_query}\n{canonical_headers}\n{signed_headers}\n{content_sha256}");
+
+    let credential_scope = format!("{date_stamp}/{}/s3/aws4_request", source.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{credential_scope}\n{}",
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let k_date = hmac_sha256(format!("AWS4{}", source.secret_access_key).as_bytes(), date_stamp.as_bytes());
+    let k_region = hmac_sha256(&k_date, source.region.as_bytes());
+    let k_service = hmac_sha256(&k_region, b"s3");
+    let k_signing = hmac_sha256(&k_service, b"aws4_request");
+    let signature = hex(&hmac_sha256(&k_signing, string_to_sign.as_bytes()));
+
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{credential_scope}, SignedHeaders={signed_headers}, Signature={signature}",
+        source.access_key_id
+    );
+
+    SignedGet { authorization, amz_date, content_sha256 }
+}
+
+/// Lists every `.jsonl` object under `source.prefix`. Only handles a single
+/// (unpaginated) `ListObjectsV2` response — a bucket with more than 1000
+/// matching objects needs `continuation-token` support this doesn't have
+/// yet.
+pub fn list_objects(client: &reqwest::blocking::Client, source: &S3Source) -> Result<Vec<S3Object>, String> {
+    let canonical_query = format!("list-type=2&prefix={}", urlencode(&source.prefix));
+    let url = format!("{}?{canonical_query}", source.base_url());
+    let signed = sign_get(source, &format!("/{}", source.bucket), &canonical_query, Utc::now());
+
+    let response = client
+        .get(&url)
+        .header("host", source.host())
+        .header("x-amz-date", &signed.amz_date)
+        .header("x-amz-content-sha256", &signed.content_sha256)
+        .header("authorization", &signed.authorization)
+        .send()
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        return Err(format!("list_objects failed: HTTP {}", response.status()));
+    }
+    let body = response.text().map_err(|e| e.to_string())?;
+    Ok(parse_list_objects_xml(&body))
+}
+
+/// Fetches `key` starting at byte `start` (via a `Range: bytes=start-`
+/// request), for resuming a `.jsonl` object without re-downloading bytes
+/// already imported.
+pub fn get_object_range(
+    client: &reqwest::blocking::Client,
+    source: &S3Source,
+    key: &str,
+    start: u64,
+) -> Result<Vec<u8>, String> {
+    let canonical_uri = format!("/{}/{key}", source.bucket);
+    let url = format!("{}/{key}", source.base_url());
+    let signed = sign_get(source, &canonical_uri, "", Utc::now());
+
+    let response = client
+        .get(&url)
+        .header("host", source.host())
+        .header("x-amz-date", &signed.amz_date)
+        .header("x-amz-content-sha256", &signed.content_sha256)
+        .header("authorization", &signed.authorization)
+        .header("range", format!("bytes={start}-"))
+        .send()
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        return Err(format!("get_object failed for {key}: HTTP {}", response.status()));
+    }
+    response.bytes().map(|b| b.to_vec()).map_err(|e| e.to_string())
+}
+
+fn parse_list_objects_xml(body: &str) -> Vec<S3Object> {
+    let mut objects = Vec::new();
+    for block in body.split("<Contents>").skip(1) {
+        let block = block.split("</Contents>").next().unwrap_or("");
+        let key = extract_tag(block, "Key");
+        let size = extract_tag(block, "Size").and_then(|s| s.parse::<u64>().ok());
+        if let (Some(key), Some(size)) = (key, size)
+            && key.ends_with(".jsonl")
+        {
+            objects.push(S3Object { key, size });
+        }
+    }
+    objects
+}
+
+fn extract_tag(block: &str, tag: &str) -> Option<String> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let start = block.find(&open)? + open.len();
+    let end = start + block[start..].find(&close)?;
+    Some(block[start..end].to_string())
+}
+
+/// Percent-encodes a prefix for the `prefix=` query param, per SigV4's
+/// "encode everything except unreserved characters" rule. Object keys in
+/// `list_objects`'/`get_object_range`'s results are assumed already
+/// URL-safe (no raw `/`-path-segment encoding is applied beyond this),
+/// which holds for the `projects/<name>/<session>.jsonl`-style keys this
+/// module targets.
+fn urlencode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => out.push(byte as char),
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
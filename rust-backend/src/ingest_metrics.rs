@@ -0,0 +1,88 @@
+//! In-process ingest counters surfaced by `GET /metrics` alongside the
+//! DB-derived gauges in `api::metrics`. These track the *server's own*
+//! operational throughput — requests received, deduplicated, rejected, and
+//! truncated — which isn't recoverable from `events` table queries alone
+//! since a rejected or deduplicated request never gets a row.
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Why an ingested item was rejected, for the `reason` label on
+/// `agentmonitor_events_rejected_total`.
+#[derive(Debug, Clone, Copy)]
+pub enum RejectReason {
+    /// Failed `contracts::validation::normalize_ingest_event_ref`.
+    Validation,
+    /// A batch exceeded `Config::max_batch_size`.
+    BatchTooLarge,
+    /// `queries::insert_event` (or the transaction around it) failed.
+    InternalError,
+}
+
+impl RejectReason {
+    fn as_str(self) -> &'static str {
+        match self {
+            RejectReason::Validation => "validation",
+            RejectReason::BatchTooLarge => "batch_too_large",
+            RejectReason::InternalError => "internal_error",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct IngestCounters {
+    events_received: AtomicU64,
+    duplicates: AtomicU64,
+    rejected_validation: AtomicU64,
+    rejected_batch_too_large: AtomicU64,
+    rejected_internal_error: AtomicU64,
+    payloads_truncated: AtomicU64,
+}
+
+/// Point-in-time read of every counter, for `api::metrics::metrics_handler`.
+pub struct IngestCountersSnapshot {
+    pub events_received: u64,
+    pub duplicates: u64,
+    pub rejected: Vec<(&'static str, u64)>,
+    pub payloads_truncated: u64,
+}
+
+impl IngestCounters {
+    pub fn record_received(&self) {
+        self.events_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_duplicate(&self) {
+        self.duplicates.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_rejected(&self, reason: RejectReason) {
+        let counter = match reason {
+            RejectReason::Validation => &self.rejected_validation,
+            RejectReason::BatchTooLarge => &self.rejected_batch_too_large,
+            RejectReason::InternalError => &self.rejected_internal_error,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_truncated(&self) {
+        self.payloads_truncated.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> IngestCountersSnapshot {
+        IngestCountersSnapshot {
+            events_received: self.events_received.load(Ordering::Relaxed),
+            duplicates: self.duplicates.load(Ordering::Relaxed),
+            rejected: vec![
+                (RejectReason::Validation.as_str(), self.rejected_validation.load(Ordering::Relaxed)),
+                (
+                    RejectReason::BatchTooLarge.as_str(),
+                    self.rejected_batch_too_large.load(Ordering::Relaxed),
+                ),
+                (
+                    RejectReason::InternalError.as_str(),
+                    self.rejected_internal_error.load(Ordering::Relaxed),
+                ),
+            ],
+            payloads_truncated: self.payloads_truncated.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -1,43 +1,188 @@
 pub mod api;
+pub mod auth;
+pub mod auto_import;
+pub mod cluster;
 pub mod config;
 pub mod contracts;
 pub mod db;
 pub mod importer;
+pub mod ingest_metrics;
+pub mod mqtt;
+pub mod nats;
+pub mod notifier;
 pub mod otel;
 pub mod pricing;
+pub mod relay;
+pub mod runtime_contract;
+pub mod runtime_host;
+pub mod runtime_settings;
+pub mod runtime_tasks;
 pub mod sse;
 pub mod state;
+#[cfg(feature = "systemd")]
+pub mod systemd;
+pub mod task_supervisor;
+pub mod tls;
 pub mod util;
 
 use std::sync::Arc;
+use std::time::Duration;
 
+use axum::BoxError;
+use axum::Json;
 use axum::Router;
-use axum::routing::{get, post};
+use axum::error_handling::HandleErrorLayer;
+use axum::extract::{DefaultBodyLimit, Request};
+use axum::http::StatusCode;
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use serde_json::json;
+use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
 
 use crate::state::AppState;
 
 /// Build the application router with all routes wired.
+///
+/// Routes are split into four auth tiers: `/api/health`, `/metrics`
+/// (also served at `/api/metrics`), and `/summary` (also `/api/summary`)
+/// stay open so health checks and scrapers never need a key; ingest
+/// routes require a write-scoped key; admin routes require an admin-scoped
+/// key; everything else (dashboard reads) accepts a key of any scope. See `auth` — when no
+/// keys are configured, every tier is open (auth is opt-in). Ingest routes
+/// additionally carry a whole-request body size cap so a single oversized
+/// POST can't balloon memory before `max_payload_kb` truncation ever runs;
+/// every other route falls back to `Config::max_body_kb`. Every route
+/// except `/api/stream`/`/api/ws` also carries `Config::request_timeout_ms`
+/// — those two are long-lived SSE/WebSocket connections, not bounded
+/// requests, so a blanket timeout would disconnect every client
+/// periodically instead of bounding one slow request.
 pub fn build_router(state: Arc<AppState>) -> Router {
-    Router::new()
+    let open = Router::new()
         .route("/api/health", get(api::health_handler))
+        .route("/metrics", get(api::metrics_handler))
+        .route("/api/metrics", get(api::metrics_handler))
+        .route("/summary", get(api::summary_handler))
+        .route("/api/summary", get(api::summary_handler));
+
+    let ingest = Router::new()
         .route("/api/events", post(api::ingest_single))
         .route("/api/events/batch", post(api::ingest_batch))
+        .route("/api/otel/v1/logs", post(api::otel_logs_handler))
+        .route("/api/otel/v1/metrics", post(api::otel_metrics_handler))
+        .route("/api/otel/v1/traces", post(api::otel_traces_handler))
+        .route("/api/cluster/broadcast", post(api::cluster_broadcast_handler))
+        .layer(DefaultBodyLimit::max(state.config.max_ingest_body_kb * 1024));
+
+    // A distinct `require_admin` layer, not `require_write`: an ordinary
+    // ingest key must not be able to mint or revoke API keys — see
+    // `auth::KeyScope::Admin`.
+    let admin = Router::new()
+        .route("/api/admin/keys", post(api::create_key_handler))
+        .route("/api/admin/keys/{id}", delete(api::revoke_key_handler))
+        .route_layer(middleware::from_fn_with_state(state.clone(), crate::auth::require_admin));
+
+    let write = ingest
+        .route_layer(middleware::from_fn_with_state(state.clone(), crate::auth::require_write));
+
+    let read = Router::new()
         .route("/api/stats", get(api::stats_handler))
         .route("/api/stats/tools", get(api::stats_tools_handler))
         .route("/api/stats/cost", get(api::stats_cost_handler))
+        .route("/api/stats/activity", get(api::stats_activity_handler))
         .route("/api/stats/usage-monitor", get(api::usage_monitor_handler))
-        .route("/api/otel/v1/logs", post(api::otel_logs_handler))
-        .route("/api/otel/v1/metrics", post(api::otel_metrics_handler))
-        .route("/api/otel/v1/traces", post(api::otel_traces_handler))
+        .route("/api/analytics", get(api::analytics_handler))
         .route("/api/sessions", get(api::sessions_list_handler))
         .route(
             "/api/sessions/{id}/transcript",
             get(api::session_transcript_handler),
         )
         .route("/api/sessions/{id}", get(api::session_detail_handler))
+        .route("/api/search/events", get(api::search_events_handler))
+        .route("/api/events/poll", get(api::poll_events))
         .route("/api/filter-options", get(api::filter_options_handler))
+        .route("/api/upstreams", get(api::upstreams_handler))
+        .route_layer(middleware::from_fn_with_state(state.clone(), crate::auth::require_read));
+
+    let streaming = Router::new()
         .route("/api/stream", get(api::stream_handler))
+        .route("/api/ws", get(api::ws_handler))
+        .route_layer(middleware::from_fn_with_state(state.clone(), crate::auth::require_read));
+
+    let timeout_ms = state.config.request_timeout_ms;
+    let bounded = open.merge(write).merge(read).merge(admin).layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(handle_request_error))
+            .timeout(Duration::from_millis(timeout_ms)),
+    );
+
+    bounded
+        .merge(streaming)
+        .layer(DefaultBodyLimit::max(state.config.max_body_kb * 1024))
+        .layer(middleware::from_fn(normalize_oversized_body_response))
         .layer(CorsLayer::permissive())
         .with_state(state)
 }
+
+/// Build the router for the separate management listener (see
+/// `runtime_host::start_with_config`'s `Config::management_port` handling).
+/// Kept off the main router/port entirely rather than just another
+/// `/api/admin`-style route: an operator pointing this at a knob that
+/// changes live background-job behavior (`AppState::runtime_settings`)
+/// wants that reachable without exposing it on whatever port faces the
+/// dashboard. Every route requires a write-scoped key, same as the ingest
+/// routes on the main router.
+pub fn build_management_router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route(
+            "/runtime/settings",
+            get(api::get_settings_handler).put(api::update_settings_handler),
+        )
+        .route("/runtime/stats-broadcast", post(api::trigger_stats_broadcast_handler))
+        .route("/runtime/idle-check", post(api::trigger_idle_check_handler))
+        .route_layer(middleware::from_fn_with_state(state.clone(), crate::auth::require_write))
+        .with_state(state)
+}
+
+/// `DefaultBodyLimit` rejects an oversized body with a 413 whose body is
+/// plain text ("length limit exceeded"), since that rejection happens
+/// inside the `Bytes`/`Json` extractor rather than a handler we control.
+/// Normalize it to the same `{ "error": ... }` shape every other rejection
+/// in this crate returns, so clients don't need a special case for one
+/// failure mode.
+async fn normalize_oversized_body_response(req: Request, next: Next) -> Response {
+    let response = next.run(req).await;
+    // A handler's own JSON 413 (e.g. `ingest_batch`'s batch-size rejection)
+    // already has the right shape and extra fields; leave it alone.
+    let is_json = response
+        .headers()
+        .get(axum::http::header::CONTENT_TYPE)
+        .is_some_and(|v| v.as_bytes().starts_with(b"application/json"));
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json {
+        return (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(json!({"error": "request body too large"})),
+        )
+            .into_response();
+    }
+    response
+}
+
+/// Converts a `tower::timeout::Timeout` error (the only error this router's
+/// stack can produce — every handler itself returns `Infallible`) into a
+/// structured 408, matching the `{ "error": ... }` shape used everywhere
+/// else rather than leaving the connection to be dropped.
+async fn handle_request_error(err: BoxError) -> impl IntoResponse {
+    if err.is::<tower::timeout::error::Elapsed>() {
+        (
+            StatusCode::REQUEST_TIMEOUT,
+            Json(json!({"error": "request timed out"})),
+        )
+    } else {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "internal server error"})),
+        )
+    }
+}
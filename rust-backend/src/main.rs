@@ -21,8 +21,12 @@ async fn main() {
     let bind_addr = config.bind_addr();
     let auto_import_interval_minutes = config.auto_import_interval_minutes;
 
-    let conn = db::initialize(&config.db_path).expect("Failed to initialize database");
-    let state: Arc<AppState> = AppState::new(conn, config);
+    let pool = db::pool::initialize(&config.db_path, config.db_pool_size, config.db_passphrase.as_deref())
+        .expect("Failed to initialize database");
+    let state: Arc<AppState> = AppState::new(pool, config);
+    agentmonitor_rs::auth::bootstrap(&state)
+        .await
+        .expect("Failed to load API keys");
 
     let app = agentmonitor_rs::build_router(Arc::clone(&state));
 
@@ -41,6 +45,25 @@ async fn main() {
         info!("Auto-import: every {}m", auto_import_interval_minutes);
     }
 
+    if let Some(nats_url) = state.config.nats_url.clone() {
+        match agentmonitor_rs::nats::connect(&nats_url).await {
+            Ok(client) => {
+                info!("NATS: connected to {nats_url}");
+                state.set_nats_client(client.clone()).await;
+                if let Some(subject) = state.config.nats_subscribe_subject.clone() {
+                    tokio::spawn(agentmonitor_rs::nats::run_subscriber(
+                        Arc::clone(&state),
+                        client,
+                        subject,
+                    ));
+                }
+            }
+            Err(err) => {
+                tracing::error!("NATS: connection failed, continuing without federation: {err}");
+            }
+        }
+    }
+
     let listener = tokio::net::TcpListener::bind(&bind_addr)
         .await
         .expect("Failed to bind");
@@ -0,0 +1,141 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rumqttc::{AsyncClient, Event as MqttEvent, EventLoop, MqttOptions, Packet, QoS};
+use tracing::{error, info, warn};
+
+use crate::state::AppState;
+
+#[derive(Debug)]
+pub enum MqttError {
+    Subscribe(rumqttc::ClientError),
+}
+
+impl fmt::Display for MqttError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Subscribe(err) => write!(f, "MQTT subscribe failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for MqttError {}
+
+/// Build an MQTT client + event loop for the configured broker. Unlike
+/// `nats::connect`, rumqttc doesn't dial until the event loop is first
+/// polled, so this can't fail on its own — a broker that's unreachable only
+/// shows up once `run_subscriber` starts polling.
+pub fn connect(
+    host: &str,
+    port: u16,
+    client_id: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+) -> (AsyncClient, EventLoop) {
+    let mut options = MqttOptions::new(client_id, host, port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if let (Some(username), Some(password)) = (username, password) {
+        options.set_credentials(username, password);
+    }
+    AsyncClient::new(options, 64)
+}
+
+/// Mirrors an SSE hub broadcast onto `topic`, in the exact
+/// `{"type": ..., "payload": ...}` envelope `api::stream` sends to browsers
+/// (see `api::stream::to_sse_event`), so an MQTT-only observer without a
+/// persistent browser connection sees the same shape.
+pub async fn publish_envelope(client: &AsyncClient, topic: &str, kind: &str, payload: &serde_json::Value) {
+    let frame = serde_json::json!({ "type": kind, "payload": payload });
+    let bytes = match serde_json::to_vec(&frame) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("failed to serialize {kind} frame for MQTT publish: {e}");
+            return;
+        }
+    };
+    if let Err(e) = client.publish(topic, QoS::AtLeastOnce, false, bytes).await {
+        warn!("MQTT publish to {topic} failed: {e}");
+    }
+}
+
+/// Subscribes to `events_topic` (e.g. `agentmonitor/events/+`, one level per
+/// publishing agent) and feeds every message through the same
+/// normalize/dedup/persist path `/api/events` uses, so a remote agent
+/// publishing straight to the broker can't double-insert an `event_id` any
+/// more than a federated NATS instance can. Drives `eventloop` for the
+/// lifetime of the subscription; a poll error is logged and retried rather
+/// than ending the subscriber, since rumqttc reconnects the underlying
+/// socket on its own.
+pub async fn run_subscriber(
+    state: Arc<AppState>,
+    client: AsyncClient,
+    mut eventloop: EventLoop,
+    events_topic: String,
+) -> Result<(), MqttError> {
+    client
+        .subscribe(&events_topic, QoS::AtLeastOnce)
+        .await
+        .map_err(MqttError::Subscribe)?;
+    info!("MQTT: subscribed to {events_topic}");
+
+    loop {
+        match eventloop.poll().await {
+            Ok(MqttEvent::Incoming(Packet::Publish(publish))) => {
+                let Ok(body) = serde_json::from_slice::<serde_json::Value>(&publish.payload) else {
+                    warn!("MQTT: dropping malformed message on {}", publish.topic);
+                    continue;
+                };
+                ingest_from_mqtt(&state, body).await;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                warn!("MQTT connection error, retrying: {e}");
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+/// Normalize, dedup, and persist one inbound MQTT message the same way
+/// `ingest_single` does for an HTTP POST — including the `event_id` dedup —
+/// stamping `source: "mqtt"` the same way `relay::ingest_relayed_event`
+/// stamps its own origin tag.
+async fn ingest_from_mqtt(state: &Arc<AppState>, body: serde_json::Value) {
+    use crate::contracts::event::NormalizeResult;
+    use crate::contracts::validation::normalize_from_value;
+    use crate::db::queries;
+    use crate::util::truncate::truncate_metadata;
+
+    let NormalizeResult::Ok { mut event } = normalize_from_value(body, &state.config.ingest_validation) else {
+        warn!("MQTT: dropping invalid event payload");
+        return;
+    };
+    event.source = Some("mqtt".to_string());
+
+    let max_kb = state.config.max_payload_kb;
+    let truncated = truncate_metadata(&event.metadata, max_kb);
+    let params = crate::api::insert_params(
+        &event,
+        &truncated.value,
+        truncated.truncated,
+        crate::auth::DEFAULT_TENANT,
+    );
+
+    let db = match state.write_conn() {
+        Ok(db) => db,
+        Err(e) => {
+            warn!("MQTT: failed to check out a database connection: {e}");
+            return;
+        }
+    };
+    match queries::insert_event(&db, &params) {
+        Ok(Some(_row)) => {
+            state.notify_new_events.notify_waiters();
+        }
+        Ok(None) => {
+            // Deduplicated — already persisted by an earlier delivery.
+        }
+        Err(e) => warn!("MQTT: insert_event error: {e}"),
+    }
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+use std::sync::Arc;
+
+use futures_util::StreamExt;
+use tracing::{error, info, warn};
+
+use crate::contracts::event::NormalizedEvent;
+use crate::state::AppState;
+
+#[derive(Debug)]
+pub enum NatsError {
+    Connect(async_nats::ConnectError),
+}
+
+impl fmt::Display for NatsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connect(err) => write!(f, "NATS connection failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for NatsError {}
+
+/// Connect to a NATS server. Only the initial dial is our concern here —
+/// once connected, `async_nats::Client` reconnects on its own.
+pub async fn connect(url: &str) -> Result<async_nats::Client, NatsError> {
+    async_nats::connect(url).await.map_err(NatsError::Connect)
+}
+
+/// Subject an event republishes to: `<prefix>.<agent_type>.<event_type>`.
+pub fn publish_subject(prefix: &str, agent_type: &str, event_type: &str) -> String {
+    format!("{prefix}.{agent_type}.{event_type}")
+}
+
+/// Fire-and-forget republish of an event this instance just accepted.
+/// Errors are logged, not propagated — a NATS hiccup should never fail the
+/// HTTP response for an event that's already committed to the database.
+pub async fn publish_event(client: &async_nats::Client, prefix: &str, event: &NormalizedEvent) {
+    let subject = publish_subject(prefix, &event.agent_type, &event.event_type);
+    let payload = match serde_json::to_vec(event) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("failed to serialize event for NATS publish: {e}");
+            return;
+        }
+    };
+    if let Err(e) = client.publish(subject, payload.into()).await {
+        warn!("NATS publish failed: {e}");
+    }
+}
+
+/// Subscribe to the inbound ingest subject and feed every message through
+/// the same normalize/dedup/persist path `/api/events` uses, so a federated
+/// instance (or a non-HTTP producer) can't double-insert an `event_id`.
+pub async fn run_subscriber(state: Arc<AppState>, client: async_nats::Client, subject: String) {
+    let mut sub = match client.subscribe(subject.clone()).await {
+        Ok(sub) => sub,
+        Err(e) => {
+            error!("NATS subscribe to {subject} failed: {e}");
+            return;
+        }
+    };
+    info!("NATS: subscribed to {subject}");
+
+    while let Some(message) = sub.next().await {
+        let Ok(body) = serde_json::from_slice::<serde_json::Value>(&message.payload) else {
+            warn!("NATS: dropping malformed message on {subject}");
+            continue;
+        };
+        ingest_from_nats(&state, body).await;
+    }
+}
+
+/// Normalize, dedup, and persist one inbound NATS message the same way
+/// `ingest_single` does for an HTTP POST — including the `event_id` dedup,
+/// so a message replayed by JetStream doesn't double-count.
+async fn ingest_from_nats(state: &Arc<AppState>, body: serde_json::Value) {
+    use crate::contracts::event::NormalizeResult;
+    use crate::contracts::validation::normalize_from_value;
+    use crate::db::queries;
+    use crate::util::truncate::truncate_metadata;
+
+    let NormalizeResult::Ok { event } = normalize_from_value(body, &state.config.ingest_validation) else {
+        warn!("NATS: dropping invalid event payload");
+        return;
+    };
+
+    let max_kb = state.config.max_payload_kb;
+    let truncated = truncate_metadata(&event.metadata, max_kb);
+    let params = crate::api::insert_params(
+        &event,
+        &truncated.value,
+        truncated.truncated,
+        crate::auth::DEFAULT_TENANT,
+    );
+
+    let db = match state.write_conn() {
+        Ok(db) => db,
+        Err(e) => {
+            warn!("NATS: failed to check out a database connection: {e}");
+            return;
+        }
+    };
+    match queries::insert_event(&db, &params) {
+        Ok(Some(_row)) => {
+            state.notify_new_events.notify_waiters();
+        }
+        Ok(None) => {
+            // Deduplicated — already persisted by an earlier delivery.
+        }
+        Err(e) => warn!("NATS: insert_event error: {e}"),
+    }
+}
@@ -0,0 +1,402 @@
+//! Outbound alerting for session-idle and usage-threshold events. Hooks into
+//! the same places `run_idle_check_once`/`run_stats_broadcast_once` already
+//! broadcast to over SSE/MQTT (see `runtime_tasks`) and fans each alert out
+//! to every sink `Config` configures — a webhook POST and/or SMTP email —
+//! with per-sink retry and a dedupe window so a session sitting idle (or an
+//! agent staying over its usage-monitor budget) across many ticks fires one
+//! alert, not one per tick.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use lettre::message::Message;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::config::NotifierConfig;
+
+/// How many attempts (the first one included) a sink gets at one alert
+/// before it's dropped, and the
+/// fixed delay between attempts — fixed rather than exponential since a
+/// sink that's actually down won't be helped by backing off further, same
+/// reasoning `relay::RECONNECT_DELAY` uses for upstream reconnects.
+const RETRY_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    SessionIdle,
+    CostBudget,
+    TokenRateSpike,
+}
+
+impl AlertKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::SessionIdle => "session_idle",
+            Self::CostBudget => "cost_budget",
+            Self::TokenRateSpike => "token_rate_spike",
+        }
+    }
+}
+
+/// One outbound alert. `subject_id` is whatever the alert is about (the
+/// string `"sessions"` for `SessionIdle`, since `update_idle_sessions` only
+/// reports a count rather than which sessions idled; an `agent_type` for
+/// `CostBudget`/`TokenRateSpike`) — it's both the dedupe key alongside
+/// `kind` and part of the rendered message.
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub subject_id: String,
+    pub message: String,
+    pub detail: Value,
+}
+
+impl Alert {
+    fn to_payload(&self) -> Value {
+        json!({
+            "type": self.kind.as_str(),
+            "subject": self.subject_id,
+            "message": self.message,
+            "detail": self.detail,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum NotifierError {
+    Webhook(reqwest::Error),
+    WebhookStatus(reqwest::StatusCode),
+    Smtp(lettre::transport::smtp::Error),
+    SmtpMessage(lettre::error::Error),
+}
+
+impl fmt::Display for NotifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Webhook(err) => write!(f, "webhook request failed: {err}"),
+            Self::WebhookStatus(status) => write!(f, "webhook returned {status}"),
+            Self::Smtp(err) => write!(f, "SMTP send failed: {err}"),
+            Self::SmtpMessage(err) => write!(f, "failed to build alert email: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for NotifierError {}
+
+/// One outbound channel an `Alert` can be delivered to.
+#[async_trait]
+trait NotifySink: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn send(&self, alert: &Alert) -> Result<(), NotifierError>;
+}
+
+/// POSTs `alert.to_payload()` as JSON, the same `{"type", "subject",
+/// "message", "detail"}` shape regardless of which `AlertKind` fired, so a
+/// receiving endpoint doesn't need a special case per kind.
+struct WebhookSink {
+    url: String,
+    client: reqwest::Client,
+}
+
+#[async_trait]
+impl NotifySink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<(), NotifierError> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&alert.to_payload())
+            .send()
+            .await
+            .map_err(NotifierError::Webhook)?;
+        if !response.status().is_success() {
+            return Err(NotifierError::WebhookStatus(response.status()));
+        }
+        Ok(())
+    }
+}
+
+/// Emails every configured recipient individually (one `Message` per
+/// recipient rather than one email with multiple `To` headers), so one
+/// rejected address doesn't keep the alert from reaching the others.
+struct SmtpSink {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Address,
+    to: Vec<Address>,
+}
+
+#[async_trait]
+impl NotifySink for SmtpSink {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<(), NotifierError> {
+        for recipient in &self.to {
+            let email = Message::builder()
+                .from(self.from.clone().into())
+                .to(recipient.clone().into())
+                .subject(format!("agentmonitor alert: {}", alert.kind.as_str()))
+                .body(alert.message.clone())
+                .map_err(NotifierError::SmtpMessage)?;
+            self.transport.send(email).await.map_err(NotifierError::Smtp)?;
+        }
+        Ok(())
+    }
+}
+
+/// One configured sink plus a runtime on/off switch — see
+/// `Notifier::set_channel_enabled`. Disabling a channel leaves it built
+/// (a webhook client/SMTP transport isn't cheap to reconstruct) and just
+/// skips it in `notify`, so re-enabling it takes effect immediately.
+struct NamedSink {
+    sink: Box<dyn NotifySink>,
+    enabled: AtomicBool,
+}
+
+/// Fans an `Alert` out to every sink `Config` configured, deduping repeats
+/// of the same `(AlertKind, subject_id)` within `dedupe_window`. Built once
+/// in `AppState::new`; an instance with no sinks configured is a cheap no-op
+/// (`notify` returns before taking the dedupe lock), same dormant-until-
+/// configured shape as `relay_status`/`mqtt`. Individual sinks can be
+/// toggled afterwards through `set_channel_enabled` — see
+/// `api::runtime_admin` for the management endpoint that calls it.
+pub struct Notifier {
+    sinks: Vec<NamedSink>,
+    dedupe_window: Duration,
+    last_fired: Mutex<HashMap<(AlertKind, String), Instant>>,
+}
+
+impl Notifier {
+    pub fn from_config(config: &NotifierConfig) -> Self {
+        let mut sinks: Vec<NamedSink> = Vec::new();
+
+        if let Some(url) = &config.webhook_url {
+            sinks.push(NamedSink {
+                sink: Box::new(WebhookSink {
+                    url: url.clone(),
+                    client: reqwest::Client::new(),
+                }),
+                enabled: AtomicBool::new(true),
+            });
+        }
+
+        if let Some(sink) = build_smtp_sink(config) {
+            sinks.push(NamedSink {
+                sink: Box::new(sink),
+                enabled: AtomicBool::new(true),
+            });
+        }
+
+        Self {
+            sinks,
+            dedupe_window: Duration::from_secs(config.dedupe_window_secs),
+            last_fired: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// True when no sink is configured — lets a call site skip building an
+    /// `Alert` entirely rather than constructing one just to have `notify`
+    /// discard it.
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Name and current on/off state of every configured sink, for
+    /// `GET /runtime/settings`.
+    pub fn channel_states(&self) -> Vec<(&'static str, bool)> {
+        self.sinks
+            .iter()
+            .map(|named| (named.sink.name(), named.enabled.load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    /// Toggle the sink named `name` on or off. Returns `false` if no sink
+    /// with that name is configured, so the caller (`api::runtime_admin`)
+    /// can report an unknown channel instead of silently ignoring it.
+    pub fn set_channel_enabled(&self, name: &str, enabled: bool) -> bool {
+        match self.sinks.iter().find(|named| named.sink.name() == name) {
+            Some(named) => {
+                named.enabled.store(enabled, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Deliver `alert` to every enabled sink, unless the same
+    /// `(kind, subject_id)` already fired within `dedupe_window`.
+    pub async fn notify(&self, alert: Alert) {
+        if self.is_empty() {
+            return;
+        }
+        if self.is_deduped(&alert) {
+            return;
+        }
+        for named in &self.sinks {
+            if named.enabled.load(Ordering::Relaxed) {
+                self.send_with_retry(named.sink.as_ref(), &alert).await;
+            }
+        }
+    }
+
+    fn is_deduped(&self, alert: &Alert) -> bool {
+        let key = (alert.kind, alert.subject_id.clone());
+        let now = Instant::now();
+        let mut last_fired = self.last_fired.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(fired_at) = last_fired.get(&key) {
+            if now.duration_since(*fired_at) < self.dedupe_window {
+                return true;
+            }
+        }
+        last_fired.insert(key, now);
+        false
+    }
+
+    async fn send_with_retry(&self, sink: &dyn NotifySink, alert: &Alert) {
+        for attempt in 1..=RETRY_ATTEMPTS {
+            match sink.send(alert).await {
+                Ok(()) => return,
+                Err(err) if attempt < RETRY_ATTEMPTS => {
+                    warn!(
+                        "notifier: {} delivery failed (attempt {attempt}/{RETRY_ATTEMPTS}), retrying: {err}",
+                        sink.name()
+                    );
+                    tokio::time::sleep(RETRY_DELAY).await;
+                }
+                Err(err) => {
+                    warn!(
+                        "notifier: {} delivery failed after {RETRY_ATTEMPTS} attempts, giving up: {err}",
+                        sink.name()
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Builds the SMTP sink from `config`, or `None` if SMTP isn't configured
+/// (no `smtp_host`) or `smtp_from`/`smtp_to` don't parse as addresses —
+/// malformed config is logged and skipped rather than failing startup,
+/// same as a malformed `AGENTMONITOR_API_KEYS` entry.
+fn build_smtp_sink(config: &NotifierConfig) -> Option<SmtpSink> {
+    let host = config.smtp_host.as_deref()?;
+
+    let from = match config.smtp_from.as_deref()?.parse::<Address>() {
+        Ok(addr) => addr,
+        Err(err) => {
+            warn!("notifier: invalid AGENTMONITOR_NOTIFY_SMTP_FROM, SMTP sink disabled: {err}");
+            return None;
+        }
+    };
+
+    let to: Vec<Address> = config
+        .smtp_to
+        .as_deref()?
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .filter_map(|addr| match addr.parse::<Address>() {
+            Ok(addr) => Some(addr),
+            Err(err) => {
+                warn!("notifier: dropping invalid AGENTMONITOR_NOTIFY_SMTP_TO entry {addr:?}: {err}");
+                None
+            }
+        })
+        .collect();
+    if to.is_empty() {
+        warn!("notifier: no valid AGENTMONITOR_NOTIFY_SMTP_TO recipients, SMTP sink disabled");
+        return None;
+    }
+
+    let mut builder = AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+        .map_err(|err| warn!("notifier: failed to build SMTP transport for {host}: {err}"))
+        .ok()?
+        .port(config.smtp_port);
+    if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+        builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+
+    Some(SmtpSink {
+        transport: builder.build(),
+        from,
+        to,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+
+    struct CountingSink {
+        sends: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl NotifySink for CountingSink {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        async fn send(&self, _alert: &Alert) -> Result<(), NotifierError> {
+            self.sends.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn test_notifier(sends: Arc<AtomicUsize>) -> Notifier {
+        Notifier {
+            sinks: vec![NamedSink {
+                sink: Box::new(CountingSink { sends }),
+                enabled: AtomicBool::new(true),
+            }],
+            dedupe_window: Duration::from_secs(60),
+            last_fired: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn test_alert(subject: &str) -> Alert {
+        Alert {
+            kind: AlertKind::SessionIdle,
+            subject_id: subject.to_string(),
+            message: "test".to_string(),
+            detail: Value::Null,
+        }
+    }
+
+    #[tokio::test]
+    async fn repeat_alert_within_window_is_deduped() {
+        let sends = Arc::new(AtomicUsize::new(0));
+        let notifier = test_notifier(sends.clone());
+
+        notifier.notify(test_alert("sessions")).await;
+        notifier.notify(test_alert("sessions")).await;
+
+        assert_eq!(sends.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn distinct_subjects_are_not_deduped() {
+        let sends = Arc::new(AtomicUsize::new(0));
+        let notifier = test_notifier(sends.clone());
+
+        notifier.notify(test_alert("claude_code")).await;
+        notifier.notify(test_alert("codex")).await;
+
+        assert_eq!(sends.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,127 @@
+//! Pluggable persistence for OTLP cumulative-counter state.
+//!
+//! `compute_delta` needs to remember, per series key, the last value it saw
+//! and the `startTimeUnixNano` it was reported under. Keeping that only in
+//! an in-process `HashMap` means every restart forgets where each counter
+//! left off, which can mis-attribute the next batch's delta. `CumulativeStore`
+//! lets a deployment swap in a durable backend instead.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use crate::otel::parser::CumulativeEntry;
+
+/// Backend for per-series cumulative-counter state. `get`/`set` are sync
+/// (not `async`) because implementations only ever do an in-memory or local
+/// SQLite lookup — never worth forcing every call site onto an async runtime
+/// for.
+pub trait CumulativeStore: Send + Sync {
+    fn get(&self, key: &str) -> Option<CumulativeEntry>;
+    fn set(&self, key: &str, entry: CumulativeEntry);
+}
+
+/// Default backend: state lives only for the lifetime of the process.
+#[derive(Default)]
+pub struct InMemoryCumulativeStore {
+    entries: Mutex<HashMap<String, CumulativeEntry>>,
+}
+
+impl InMemoryCumulativeStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CumulativeStore for InMemoryCumulativeStore {
+    fn get(&self, key: &str) -> Option<CumulativeEntry> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .copied()
+    }
+
+    fn set(&self, key: &str, entry: CumulativeEntry) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.to_string(), entry);
+    }
+}
+
+/// Error returned by a [`CumulativeStore`] implementation that talks to a
+/// real backend.
+#[cfg(feature = "sqlite-cumulative-store")]
+#[derive(Debug)]
+pub enum CumulativeStoreError {
+    Sqlite(rusqlite::Error),
+}
+
+#[cfg(feature = "sqlite-cumulative-store")]
+impl fmt::Display for CumulativeStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CumulativeStoreError::Sqlite(e) => write!(f, "cumulative store error: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite-cumulative-store")]
+impl std::error::Error for CumulativeStoreError {}
+
+/// Durable backend so long-running collectors keep correct per-series state
+/// across restarts. Gated behind a feature flag since most deployments are
+/// fine with the in-memory default and don't need the extra table.
+#[cfg(feature = "sqlite-cumulative-store")]
+pub struct SqliteCumulativeStore {
+    conn: Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "sqlite-cumulative-store")]
+impl SqliteCumulativeStore {
+    pub fn new(conn: rusqlite::Connection) -> Result<Self, CumulativeStoreError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS otel_cumulative_state (
+                key TEXT PRIMARY KEY,
+                last_value REAL NOT NULL,
+                start_time_nanos TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(CumulativeStoreError::Sqlite)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+#[cfg(feature = "sqlite-cumulative-store")]
+impl CumulativeStore for SqliteCumulativeStore {
+    fn get(&self, key: &str) -> Option<CumulativeEntry> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.query_row(
+            "SELECT last_value, start_time_nanos FROM otel_cumulative_state WHERE key = ?1",
+            [key],
+            |row| {
+                let last_value: f64 = row.get(0)?;
+                let start_time_nanos: String = row.get(1)?;
+                Ok(CumulativeEntry {
+                    last_value,
+                    start_time_nanos: start_time_nanos.parse().unwrap_or(0),
+                })
+            },
+        )
+        .ok()
+    }
+
+    fn set(&self, key: &str, entry: CumulativeEntry) {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = conn.execute(
+            "INSERT INTO otel_cumulative_state (key, last_value, start_time_nanos)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET last_value = excluded.last_value, start_time_nanos = excluded.start_time_nanos",
+            rusqlite::params![key, entry.last_value, entry.start_time_nanos.to_string()],
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod cumulative_store;
+pub mod parser;
+pub mod pipeline;
+pub mod protobuf;
@@ -1,7 +1,9 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
 use serde_json::{Map, Value, json};
 
+use crate::otel::cumulative_store::CumulativeStore;
+
 #[derive(Debug, Clone)]
 pub struct ParsedOtelLogEvent {
     pub session_id: String,
@@ -22,6 +24,26 @@ pub struct ParsedOtelLogEvent {
     pub metadata: Value,
 }
 
+#[derive(Debug, Clone)]
+pub struct ParsedOtelSpanEvent {
+    pub session_id: String,
+    pub agent_type: String,
+    pub event_type: String,
+    pub tool_name: Option<String>,
+    pub status: String,
+    pub tokens_in: i64,
+    pub tokens_out: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub model: Option<String>,
+    pub cost_usd: Option<f64>,
+    pub duration_ms: Option<i64>,
+    pub project: Option<String>,
+    pub branch: Option<String>,
+    pub client_timestamp: Option<String>,
+    pub metadata: Value,
+}
+
 #[derive(Debug, Clone)]
 pub struct ParsedMetricDelta {
     pub session_id: String,
@@ -34,6 +56,35 @@ pub struct ParsedMetricDelta {
     pub cost_usd_delta: f64,
 }
 
+/// One bucket of a histogram data point: `count_delta` observations fell at
+/// or below `upper_bound` (`None` for the final, unbounded overflow bucket).
+#[derive(Debug, Clone)]
+pub struct HistogramBucketDelta {
+    pub upper_bound: Option<f64>,
+    pub count_delta: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedHistogramDelta {
+    pub session_id: String,
+    pub agent_type: String,
+    pub model: Option<String>,
+    pub metric_name: String,
+    pub count_delta: i64,
+    pub sum_delta: f64,
+    pub buckets: Vec<HistogramBucketDelta>,
+}
+
+/// Result of parsing an OTLP metrics payload: `sum`/`gauge` instruments
+/// collapse into per-session token/cost deltas, while `histogram`
+/// instruments (e.g. `gen_ai.client.operation.duration`) are kept as their
+/// own distribution so callers can chart percentiles instead of only totals.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedOtelMetrics {
+    pub deltas: Vec<ParsedMetricDelta>,
+    pub histograms: Vec<ParsedHistogramDelta>,
+}
+
 const CLAUDE_EVENT_MAP: &[(&str, &str)] = &[
     ("claude_code.tool_result", "tool_use"),
     ("claude_code.tool_use", "tool_use"),
@@ -231,9 +282,12 @@ fn parse_body_object(body: Option<&Value>) -> Option<Map<String, Value>> {
     None
 }
 
+fn parse_nanos(nanos: Option<&str>) -> Option<u128> {
+    nanos?.parse::<u128>().ok()
+}
+
 fn nano_to_iso(nanos: Option<&str>) -> Option<String> {
-    let nanos = nanos?;
-    let as_u128 = nanos.parse::<u128>().ok()?;
+    let as_u128 = parse_nanos(nanos)?;
     let ms = (as_u128 / 1_000_000) as i64;
     if ms <= 0 {
         return None;
@@ -284,6 +338,24 @@ fn map_event_name(agent_type: &str, event_name: &str) -> Option<String> {
     }
 }
 
+/// Maps the `gen_ai.operation.name` semantic-convention attribute to an
+/// event type, for spans whose own `name` doesn't match anything in
+/// `map_event_name`'s agent-specific vocabulary (e.g. a span named after the
+/// model call itself, `chat claude-sonnet-4-20250514`, rather than one of
+/// the `claude_code.*`/`codex.*` event names). See
+/// <https://opentelemetry.io/docs/specs/semconv/gen-ai/gen-ai-spans/> for the
+/// operation name vocabulary.
+fn map_gen_ai_operation_name(operation_name: &str) -> Option<String> {
+    match operation_name {
+        "chat" | "text_completion" | "generate_content" | "embeddings" => {
+            Some("llm_response".to_string())
+        }
+        "execute_tool" => Some("tool_use".to_string()),
+        "create_agent" | "invoke_agent" => Some("session_start".to_string()),
+        _ => None,
+    }
+}
+
 fn resolve_event_type(
     agent_type: &str,
     event_name: Option<&str>,
@@ -504,6 +576,177 @@ pub fn parse_otel_logs(payload: &Value) -> Vec<ParsedOtelLogEvent> {
     out
 }
 
+/// Fold a span `events` entry (its own `timeUnixNano` plus attributes) into
+/// a plain JSON object so it can live inside the parent event's metadata.
+fn parse_span_event(event: &Value) -> Value {
+    let mut obj = Map::new();
+    if let Some(name) = event.get("name").and_then(|v| v.as_str()) {
+        obj.insert("name".to_string(), Value::String(name.to_string()));
+    }
+    if let Some(ts) = nano_to_iso(event.get("timeUnixNano").and_then(|v| v.as_str())) {
+        obj.insert("timestamp".to_string(), Value::String(ts));
+    }
+    let attrs = event.get("attributes").and_then(|v| v.as_array());
+    if let Some(attrs) = attrs {
+        let mut attr_map = Map::new();
+        for kv in attrs {
+            if let Some(k) = kv.get("key").and_then(|v| v.as_str())
+                && let Some(v) = kv.get("value")
+            {
+                attr_map.insert(k.to_string(), extract_any_value(v));
+            }
+        }
+        obj.insert("attributes".to_string(), Value::Object(attr_map));
+    }
+    Value::Object(obj)
+}
+
+fn parse_span(span: &Value, resource_attrs: Option<&Vec<Value>>) -> Option<ParsedOtelSpanEvent> {
+    let span_attrs = span.get("attributes").and_then(|v| v.as_array());
+
+    let session_id = get_attr_string(span_attrs, "gen_ai.session.id")
+        .or_else(|| get_attr_string(span_attrs, "conversation.id"))
+        .or_else(|| get_attr_string(resource_attrs, "session.id"))
+        .or_else(|| get_attr_string(resource_attrs, "gen_ai.session.id"))
+        .or_else(|| get_attr_string(resource_attrs, "conversation.id"))?;
+
+    let agent_type = resolve_service_name(resource_attrs);
+
+    let span_name = span.get("name").and_then(|v| v.as_str());
+    let event_type = span_name
+        .and_then(|name| map_event_name(&agent_type, name))
+        .or_else(|| {
+            get_attr_string(span_attrs, "gen_ai.operation.name")
+                .as_deref()
+                .and_then(map_gen_ai_operation_name)
+        })
+        .unwrap_or_else(|| "tool_use".to_string());
+
+    let status_code = span
+        .get("status")
+        .and_then(|s| s.get("code"))
+        .and_then(|v| v.as_i64());
+    let status = if status_code == Some(2) { "error" } else { "success" };
+
+    let start_nanos = parse_nanos(span.get("startTimeUnixNano").and_then(|v| v.as_str()));
+    let end_nanos = parse_nanos(span.get("endTimeUnixNano").and_then(|v| v.as_str()));
+    let duration_ms = match (start_nanos, end_nanos) {
+        (Some(start), Some(end)) if end >= start => Some(((end - start) / 1_000_000) as i64),
+        _ => None,
+    };
+
+    let trace_id = span.get("traceId").and_then(|v| v.as_str()).map(str::to_string);
+    let span_id = span.get("spanId").and_then(|v| v.as_str()).map(str::to_string);
+    let parent_span_id = span
+        .get("parentSpanId")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string);
+
+    let tool_name = get_attr_string(span_attrs, "gen_ai.tool.name")
+        .or_else(|| get_attr_string(span_attrs, "tool_name"))
+        .or_else(|| get_attr_string(span_attrs, "tool.name"));
+
+    let model = get_attr_string(span_attrs, "gen_ai.request.model")
+        .or_else(|| get_attr_string(span_attrs, "model"));
+
+    let tokens_in = get_attr_number(span_attrs, "gen_ai.usage.input_tokens").unwrap_or(0.0) as i64;
+    let tokens_out =
+        get_attr_number(span_attrs, "gen_ai.usage.output_tokens").unwrap_or(0.0) as i64;
+    let cache_read_tokens =
+        get_attr_number(span_attrs, "gen_ai.usage.cache_read_input_tokens").unwrap_or(0.0) as i64;
+    let cache_write_tokens =
+        get_attr_number(span_attrs, "gen_ai.usage.cache_creation_input_tokens").unwrap_or(0.0)
+            as i64;
+    let cost_usd = get_attr_number(span_attrs, "gen_ai.usage.cost");
+
+    let project = get_attr_string(span_attrs, "project")
+        .or_else(|| get_attr_string(resource_attrs, "project"));
+    let branch = get_attr_string(span_attrs, "branch")
+        .or_else(|| get_attr_string(resource_attrs, "branch"));
+
+    let client_timestamp = nano_to_iso(span.get("startTimeUnixNano").and_then(|v| v.as_str()));
+
+    let span_events: Vec<Value> = span
+        .get("events")
+        .and_then(|v| v.as_array())
+        .map(|events| events.iter().map(parse_span_event).collect())
+        .unwrap_or_default();
+
+    let mut metadata = Map::new();
+    if let Some(t) = trace_id {
+        metadata.insert("trace_id".to_string(), Value::String(t));
+    }
+    if let Some(s) = span_id {
+        metadata.insert("span_id".to_string(), Value::String(s));
+    }
+    if let Some(p) = parent_span_id {
+        metadata.insert("parent_span_id".to_string(), Value::String(p));
+    }
+    if !span_events.is_empty() {
+        metadata.insert("span_events".to_string(), Value::Array(span_events));
+    }
+
+    Some(ParsedOtelSpanEvent {
+        session_id,
+        agent_type,
+        event_type,
+        tool_name,
+        status: status.to_string(),
+        tokens_in,
+        tokens_out,
+        cache_read_tokens,
+        cache_write_tokens,
+        model,
+        cost_usd,
+        duration_ms,
+        project,
+        branch,
+        client_timestamp,
+        metadata: Value::Object(metadata),
+    })
+}
+
+/// Walks `resourceSpans → scopeSpans → spans`, mapping each span through the
+/// same event-type taxonomy as `parse_otel_logs` so spans and log events
+/// share one vocabulary.
+pub fn parse_otel_traces(payload: &Value) -> Vec<ParsedOtelSpanEvent> {
+    let mut out = Vec::new();
+    let resource_spans = payload
+        .get("resourceSpans")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    for rs in resource_spans {
+        let resource_attrs = rs
+            .get("resource")
+            .and_then(|r| r.get("attributes"))
+            .and_then(|v| v.as_array());
+
+        let scope_spans = rs
+            .get("scopeSpans")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        for ss in scope_spans {
+            let spans = ss
+                .get("spans")
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default();
+            for span in spans {
+                if let Some(event) = parse_span(&span, resource_attrs) {
+                    out.push(event);
+                }
+            }
+        }
+    }
+
+    out
+}
+
 fn get_data_point_value(dp: &Value) -> f64 {
     if let Some(v) = dp.get("asDouble").and_then(|v| v.as_f64()) {
         return v;
@@ -521,26 +764,74 @@ fn get_data_point_value(dp: &Value) -> f64 {
     0.0
 }
 
+/// Read a numeric field that may be encoded as a JSON number or (since OTLP
+/// fixed64/uint64 fields are often stringified to avoid JS precision loss)
+/// as a numeric string.
+fn get_number_field(value: &Value, key: &str) -> Option<f64> {
+    let raw = value.get(key)?;
+    if let Some(n) = raw.as_f64() {
+        return Some(n);
+    }
+    raw.as_str().and_then(|s| s.parse::<f64>().ok())
+}
+
+/// Read an array field, coercing each element the same way as `get_number_field`.
+fn get_number_array(value: &Value, key: &str) -> Vec<f64> {
+    value
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|e| e.as_f64().or_else(|| e.as_str().and_then(|s| s.parse::<f64>().ok())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Tracks a cumulative OTLP counter's last observed value and the
+/// `startTimeUnixNano` it was reported under, so a process restart (which
+/// resets the counter to zero under a new start time) can be told apart from
+/// a normal monotonic increase.
+#[derive(Debug, Clone, Copy)]
+pub struct CumulativeEntry {
+    pub last_value: f64,
+    pub start_time_nanos: u128,
+}
+
 fn compute_delta(
-    cumulative_state: &mut HashMap<String, f64>,
+    cumulative_state: &dyn CumulativeStore,
     key: &str,
     current_value: f64,
+    start_time_nanos: u128,
 ) -> f64 {
-    let last = cumulative_state.insert(key.to_string(), current_value);
-    match last {
+    let entry = cumulative_state.get(key);
+    cumulative_state.set(
+        key,
+        CumulativeEntry {
+            last_value: current_value,
+            start_time_nanos,
+        },
+    );
+
+    match entry {
         None => current_value,
         Some(prev) => {
-            let delta = current_value - prev;
-            if delta > 0.0 { delta } else { 0.0 }
+            let reset = prev.start_time_nanos != start_time_nanos || current_value < prev.last_value;
+            if reset {
+                current_value
+            } else {
+                current_value - prev.last_value
+            }
         }
     }
 }
 
 pub fn parse_otel_metrics(
     payload: &Value,
-    cumulative_state: &mut HashMap<String, f64>,
-) -> Vec<ParsedMetricDelta> {
+    cumulative_state: &dyn CumulativeStore,
+) -> ParsedOtelMetrics {
     let mut out = Vec::new();
+    let mut histograms = Vec::new();
 
     let resource_metrics = payload
         .get("resourceMetrics")
@@ -599,6 +890,9 @@ pub fn parse_otel_metrics(
 
                 for dp in data_points {
                     let raw = get_data_point_value(&dp);
+                    let start_time_nanos =
+                        parse_nanos(dp.get("startTimeUnixNano").and_then(|v| v.as_str()))
+                            .unwrap_or(0);
                     let dp_attrs = dp.get("attributes").and_then(|v| v.as_array());
 
                     let model = get_attr_string(dp_attrs, "model")
@@ -617,7 +911,7 @@ pub fn parse_otel_metrics(
                     );
 
                     let delta = if is_cumulative {
-                        compute_delta(cumulative_state, &key, raw)
+                        compute_delta(cumulative_state, &key, raw, start_time_nanos)
                     } else {
                         raw
                     };
@@ -664,9 +958,109 @@ pub fn parse_otel_metrics(
                         });
                     }
                 }
+
+                let histogram_points = metric
+                    .get("histogram")
+                    .and_then(|h| h.get("dataPoints"))
+                    .and_then(|v| v.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+
+                if histogram_points.is_empty() {
+                    continue;
+                }
+
+                let histogram_is_cumulative = metric
+                    .get("histogram")
+                    .and_then(|h| h.get("aggregationTemporality"))
+                    .and_then(|v| v.as_i64())
+                    == Some(2);
+
+                for dp in histogram_points {
+                    let dp_attrs = dp.get("attributes").and_then(|v| v.as_array());
+                    let model = get_attr_string(dp_attrs, "model")
+                        .or_else(|| get_attr_string(dp_attrs, "gen_ai.request.model"))
+                        .or_else(|| get_attr_string(resource_attrs, "model"));
+                    let start_time_nanos =
+                        parse_nanos(dp.get("startTimeUnixNano").and_then(|v| v.as_str()))
+                            .unwrap_or(0);
+
+                    let base_key = format!(
+                        "{}|{}|{}|{}",
+                        session_id,
+                        agent_type,
+                        metric_name,
+                        model.clone().unwrap_or_default()
+                    );
+
+                    let count_raw = get_number_field(&dp, "count").unwrap_or(0.0);
+                    let sum_raw = get_number_field(&dp, "sum").unwrap_or(0.0);
+                    let bucket_counts = get_number_array(&dp, "bucketCounts");
+                    let explicit_bounds = get_number_array(&dp, "explicitBounds");
+
+                    let count_delta = if histogram_is_cumulative {
+                        compute_delta(
+                            cumulative_state,
+                            &format!("{base_key}|count"),
+                            count_raw,
+                            start_time_nanos,
+                        )
+                    } else {
+                        count_raw
+                    };
+
+                    let sum_delta = if histogram_is_cumulative {
+                        compute_delta(
+                            cumulative_state,
+                            &format!("{base_key}|sum"),
+                            sum_raw,
+                            start_time_nanos,
+                        )
+                    } else {
+                        sum_raw
+                    };
+
+                    let buckets: Vec<HistogramBucketDelta> = bucket_counts
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &raw)| {
+                            let bucket_delta = if histogram_is_cumulative {
+                                compute_delta(
+                                    cumulative_state,
+                                    &format!("{base_key}|bucket{i}"),
+                                    raw,
+                                    start_time_nanos,
+                                )
+                            } else {
+                                raw
+                            };
+                            HistogramBucketDelta {
+                                upper_bound: explicit_bounds.get(i).copied(),
+                                count_delta: bucket_delta as i64,
+                            }
+                        })
+                        .collect();
+
+                    if count_delta <= 0.0 && sum_delta <= 0.0 {
+                        continue;
+                    }
+
+                    histograms.push(ParsedHistogramDelta {
+                        session_id: session_id.clone(),
+                        agent_type: agent_type.clone(),
+                        model,
+                        metric_name: metric_name.clone(),
+                        count_delta: count_delta as i64,
+                        sum_delta,
+                        buckets,
+                    });
+                }
             }
         }
     }
 
-    out
+    ParsedOtelMetrics {
+        deltas: out,
+        histograms,
+    }
 }
@@ -0,0 +1,326 @@
+//! A small filter/sink pipeline for parsed OTLP log events.
+//!
+//! `parse_otel_logs` hands back a plain `Vec<ParsedOtelLogEvent>` that today
+//! the otel handlers insert straight into the database. This module gives
+//! callers a composable alternative: run parsed events through an ordered
+//! chain of [`Filter`]s (which may drop or mutate events) and then fan them
+//! out to one or more [`Sink`]s. Wiring `api::otel::otel_logs_handler` to run
+//! through a configured `Pipeline` instead of inserting directly is
+//! follow-up work — this module stands on its own so it can be exercised and
+//! tested independently first.
+
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde_json::{Value, json};
+use tracing::warn;
+
+use super::parser::ParsedOtelLogEvent;
+
+/// A predicate/mutation step in a [`Pipeline`]. Returning `false` drops the
+/// event; mutations made before returning `false` are discarded along with
+/// the event, but mutations made by earlier filters in the chain persist.
+pub trait Filter: Send + Sync {
+    fn apply(&self, event: &mut ParsedOtelLogEvent) -> bool;
+}
+
+/// A terminal step in a [`Pipeline]` that receives whatever events survived
+/// the filter chain. Sinks are best-effort: emit errors are logged, not
+/// propagated, so one failing sink can't block the others or the caller.
+pub trait Sink: Send + Sync {
+    fn emit(&self, events: &[ParsedOtelLogEvent]);
+}
+
+/// Runs parsed events through an ordered filter chain, then into every
+/// configured sink. Built with the `with_filter`/`with_sink` builder methods.
+#[derive(Default)]
+pub struct Pipeline {
+    filters: Vec<Box<dyn Filter>>,
+    sinks: Vec<Box<dyn Sink>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_filter(mut self, filter: impl Filter + 'static) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    pub fn with_sink(mut self, sink: impl Sink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Filter `events` in place, then emit the survivors to every sink.
+    pub fn process(&self, mut events: Vec<ParsedOtelLogEvent>) {
+        events.retain_mut(|event| self.filters.iter().all(|filter| filter.apply(event)));
+        for sink in &self.sinks {
+            sink.emit(&events);
+        }
+    }
+}
+
+// ---- Built-in filters ------------------------------------------------
+
+/// Keeps only events whose `agent_type` is in the allow-list.
+pub struct AgentTypeFilter {
+    allow: HashSet<String>,
+}
+
+impl AgentTypeFilter {
+    pub fn new(allow: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            allow: allow.into_iter().collect(),
+        }
+    }
+}
+
+impl Filter for AgentTypeFilter {
+    fn apply(&self, event: &mut ParsedOtelLogEvent) -> bool {
+        self.allow.is_empty() || self.allow.contains(&event.agent_type)
+    }
+}
+
+/// Keeps only events whose `event_type` is in the allow-list.
+pub struct EventTypeFilter {
+    allow: HashSet<String>,
+}
+
+impl EventTypeFilter {
+    pub fn new(allow: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            allow: allow.into_iter().collect(),
+        }
+    }
+}
+
+impl Filter for EventTypeFilter {
+    fn apply(&self, event: &mut ParsedOtelLogEvent) -> bool {
+        self.allow.is_empty() || self.allow.contains(&event.event_type)
+    }
+}
+
+/// Keeps only events whose `project` is in the allow-list. An event with no
+/// `project` at all is dropped once the filter is configured with any
+/// projects to allow.
+pub struct ProjectFilter {
+    allow: HashSet<String>,
+}
+
+impl ProjectFilter {
+    pub fn new(allow: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            allow: allow.into_iter().collect(),
+        }
+    }
+}
+
+impl Filter for ProjectFilter {
+    fn apply(&self, event: &mut ParsedOtelLogEvent) -> bool {
+        if self.allow.is_empty() {
+            return true;
+        }
+        event
+            .project
+            .as_ref()
+            .is_some_and(|project| self.allow.contains(project))
+    }
+}
+
+/// Deterministically keeps roughly `rate` (0.0..=1.0) of the events it sees,
+/// without pulling in a dependency on a random number generator: it tracks
+/// how many events it has accepted against how many it "should" have
+/// accepted by now and lets the next one through whenever it's behind.
+pub struct SamplingFilter {
+    rate: f64,
+    seen: AtomicU64,
+    accepted: AtomicU64,
+}
+
+impl SamplingFilter {
+    pub fn new(rate: f64) -> Self {
+        Self {
+            rate: rate.clamp(0.0, 1.0),
+            seen: AtomicU64::new(0),
+            accepted: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Filter for SamplingFilter {
+    fn apply(&self, _event: &mut ParsedOtelLogEvent) -> bool {
+        let seen = self.seen.fetch_add(1, Ordering::Relaxed) + 1;
+        let target = (seen as f64 * self.rate) as u64;
+        if target > self.accepted.load(Ordering::Relaxed) {
+            self.accepted.fetch_add(1, Ordering::Relaxed);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+// ---- Built-in sinks ---------------------------------------------------
+
+/// Keeps every emitted event in memory, for tests or short-lived debugging
+/// views. Not size-bounded — callers that run this long-lived should drain
+/// it periodically.
+#[derive(Default)]
+pub struct BufferSink {
+    events: Mutex<Vec<ParsedOtelLogEvent>>,
+}
+
+impl BufferSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return a copy of everything emitted so far.
+    pub fn snapshot(&self) -> Vec<ParsedOtelLogEvent> {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Return everything emitted so far and empty the buffer.
+    pub fn drain(&self) -> Vec<ParsedOtelLogEvent> {
+        std::mem::take(&mut self.events.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Sink for BufferSink {
+    fn emit(&self, events: &[ParsedOtelLogEvent]) {
+        self.events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend_from_slice(events);
+    }
+}
+
+/// Appends each event to a file as newline-delimited JSON, one object per
+/// line. Opens (and creates) the file on every `emit` call in append mode —
+/// fine for the low event rates this collector sees; a long-lived file
+/// handle would need its own lifecycle management this sink doesn't do.
+pub struct JsonlFileSink {
+    path: PathBuf,
+}
+
+impl JsonlFileSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn event_to_json(event: &ParsedOtelLogEvent) -> Value {
+        json!({
+            "session_id": event.session_id,
+            "agent_type": event.agent_type,
+            "event_type": event.event_type,
+            "tool_name": event.tool_name,
+            "status": event.status,
+            "tokens_in": event.tokens_in,
+            "tokens_out": event.tokens_out,
+            "model": event.model,
+            "cost_usd": event.cost_usd,
+            "duration_ms": event.duration_ms,
+            "project": event.project,
+            "branch": event.branch,
+            "client_timestamp": event.client_timestamp,
+            "metadata": event.metadata,
+        })
+    }
+}
+
+impl Sink for JsonlFileSink {
+    fn emit(&self, events: &[ParsedOtelLogEvent]) {
+        if events.is_empty() {
+            return;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&self.path);
+        let mut file = match file {
+            Ok(f) => f,
+            Err(e) => {
+                warn!("JsonlFileSink: failed to open {:?}: {e}", self.path);
+                return;
+            }
+        };
+
+        for event in events {
+            let line = serde_json::to_string(&Self::event_to_json(event)).unwrap_or_default();
+            if let Err(e) = writeln!(file, "{line}") {
+                warn!("JsonlFileSink: write to {:?} failed: {e}", self.path);
+                return;
+            }
+        }
+    }
+}
+
+/// Re-exports events to a downstream OTLP/JSON collector by reconstructing a
+/// minimal `ExportLogsServiceRequest` JSON body (one log record per event)
+/// and POSTing it to `{endpoint}`. Fire-and-forget: the request runs on a
+/// spawned task so a slow or unreachable downstream collector never blocks
+/// the caller.
+pub struct OtlpForwardSink {
+    endpoint: String,
+    client: reqwest::Client,
+}
+
+impl OtlpForwardSink {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn event_to_otlp_json(event: &ParsedOtelLogEvent) -> Value {
+        let attr = |key: &str, value: &str| {
+            json!({ "key": key, "value": { "stringValue": value } })
+        };
+
+        json!({
+            "resourceLogs": [{
+                "resource": {
+                    "attributes": [
+                        attr("service.name", &event.agent_type),
+                        attr("gen_ai.session.id", &event.session_id),
+                    ]
+                },
+                "scopeLogs": [{
+                    "logRecords": [{
+                        "timeUnixNano": event.client_timestamp.clone().unwrap_or_default(),
+                        "attributes": [attr("event.name", &event.event_type)],
+                        "body": {
+                            "stringValue": serde_json::to_string(&event.metadata).unwrap_or_default(),
+                        },
+                    }]
+                }]
+            }]
+        })
+    }
+}
+
+impl Sink for OtlpForwardSink {
+    fn emit(&self, events: &[ParsedOtelLogEvent]) {
+        if events.is_empty() {
+            return;
+        }
+
+        let bodies: Vec<Value> = events.iter().map(Self::event_to_otlp_json).collect();
+        let endpoint = self.endpoint.clone();
+        let client = self.client.clone();
+
+        tokio::spawn(async move {
+            for body in bodies {
+                if let Err(e) = client.post(&endpoint).json(&body).send().await {
+                    warn!("OtlpForwardSink: forwarding to {endpoint} failed: {e}");
+                }
+            }
+        });
+    }
+}
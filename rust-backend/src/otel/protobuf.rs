@@ -0,0 +1,551 @@
+//! Minimal OTLP/protobuf decoder.
+//!
+//! Collectors configured for `http/protobuf` (the default for most OTel SDKs)
+//! POST `ExportLogsServiceRequest` / `ExportMetricsServiceRequest` /
+//! `ExportTraceServiceRequest` messages as raw protobuf bytes rather than the
+//! OTLP/JSON shape `otel::parser` understands. Rather than pull in a full
+//! `prost`-generated client for three small request messages, this walks the
+//! protobuf wire format directly and rebuilds the same OTLP/JSON
+//! `serde_json::Value` shape (`stringValue`, `intValue`, `kvlistValue`, ...)
+//! so the existing `parse_otel_logs`/`parse_otel_metrics`/`parse_otel_traces`
+//! functions can be reused unchanged. Byte fields (`trace_id`, `span_id`,
+//! `parent_span_id`) are rendered as lowercase hex, matching what OTel
+//! exporters put in the JSON encoding of the same request.
+//!
+//! Malformed or truncated input simply yields a partial/empty object instead
+//! of an error — decoding stops at the first field it can't parse, and
+//! whatever fields were already read are kept. This mirrors how collectors
+//! tolerate unknown/extra fields in protobuf, at the cost of not surfacing a
+//! 400 for genuinely corrupt payloads.
+
+use serde_json::{Map, Value, json};
+
+enum WireValue<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+impl<'a> WireValue<'a> {
+    fn as_bytes(&self) -> Option<&'a [u8]> {
+        match self {
+            WireValue::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            WireValue::Varint(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    fn as_fixed64_u64(&self) -> Option<u64> {
+        match self {
+            WireValue::Fixed64(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    fn as_fixed64_f64(&self) -> Option<f64> {
+        self.as_fixed64_u64().map(f64::from_bits)
+    }
+}
+
+fn decode_varint(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut result: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        result |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(result);
+        }
+        shift += 7;
+        if shift >= 64 {
+            return None;
+        }
+    }
+}
+
+/// Walk one message's top-level fields as `(field_number, value)` pairs.
+/// Stops (returning what it has so far) at the first field it can't decode.
+fn iter_fields(bytes: &[u8]) -> Vec<(u64, WireValue<'_>)> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let Some(tag) = decode_varint(bytes, &mut pos) else {
+            break;
+        };
+        let field_number = tag >> 3;
+        let wire_type = tag & 0x7;
+        match wire_type {
+            0 => match decode_varint(bytes, &mut pos) {
+                Some(v) => out.push((field_number, WireValue::Varint(v))),
+                None => break,
+            },
+            1 => {
+                if pos + 8 > bytes.len() {
+                    break;
+                }
+                let v = u64::from_le_bytes(bytes[pos..pos + 8].try_into().unwrap());
+                pos += 8;
+                out.push((field_number, WireValue::Fixed64(v)));
+            }
+            2 => {
+                let Some(len) = decode_varint(bytes, &mut pos) else {
+                    break;
+                };
+                let len = len as usize;
+                let Some(end) = pos.checked_add(len) else {
+                    break;
+                };
+                if end > bytes.len() {
+                    break;
+                }
+                out.push((field_number, WireValue::Bytes(&bytes[pos..end])));
+                pos = end;
+            }
+            5 => {
+                if pos + 4 > bytes.len() {
+                    break;
+                }
+                let v = u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap());
+                pos += 4;
+                out.push((field_number, WireValue::Fixed32(v)));
+            }
+            _ => break,
+        }
+    }
+    out
+}
+
+fn repeated<'a, T>(
+    bytes: &'a [u8],
+    field_no: u64,
+    decode: impl Fn(&'a [u8]) -> T,
+) -> Vec<T> {
+    iter_fields(bytes)
+        .into_iter()
+        .filter_map(|(f, v)| (f == field_no).then(|| v.as_bytes().map(&decode)).flatten())
+        .collect()
+}
+
+fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn utf8(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+// ---- KeyValue / AnyValue ----------------------------------------------
+
+fn decode_any_value(bytes: &[u8]) -> Value {
+    for (field, value) in iter_fields(bytes) {
+        match field {
+            1 => {
+                if let Some(b) = value.as_bytes() {
+                    return json!({ "stringValue": utf8(b) });
+                }
+            }
+            2 => {
+                if let Some(v) = value.as_u64() {
+                    return json!({ "boolValue": v != 0 });
+                }
+            }
+            3 => {
+                if let Some(v) = value.as_u64() {
+                    return json!({ "intValue": v as i64 });
+                }
+            }
+            4 => {
+                if let Some(v) = value.as_fixed64_f64() {
+                    return json!({ "doubleValue": v });
+                }
+            }
+            5 => {
+                if let Some(b) = value.as_bytes() {
+                    return json!({ "arrayValue": { "values": repeated(b, 1, decode_any_value) } });
+                }
+            }
+            6 => {
+                if let Some(b) = value.as_bytes() {
+                    return json!({ "kvlistValue": { "values": repeated(b, 1, decode_key_value) } });
+                }
+            }
+            _ => {}
+        }
+    }
+    Value::Null
+}
+
+fn decode_key_value(bytes: &[u8]) -> Value {
+    let mut key = String::new();
+    let mut value = Value::Null;
+    for (field, v) in iter_fields(bytes) {
+        match field {
+            1 => {
+                if let Some(b) = v.as_bytes() {
+                    key = utf8(b);
+                }
+            }
+            2 => {
+                if let Some(b) = v.as_bytes() {
+                    value = decode_any_value(b);
+                }
+            }
+            _ => {}
+        }
+    }
+    json!({ "key": key, "value": value })
+}
+
+fn decode_resource(bytes: &[u8]) -> Value {
+    json!({ "attributes": repeated(bytes, 1, decode_key_value) })
+}
+
+// ---- Logs ---------------------------------------------------------------
+
+fn decode_log_record(bytes: &[u8]) -> Value {
+    let mut obj = Map::new();
+    let mut attributes = Vec::new();
+    for (field, v) in iter_fields(bytes) {
+        match field {
+            1 => {
+                if let Some(n) = v.as_fixed64_u64() {
+                    obj.insert("timeUnixNano".into(), Value::String(n.to_string()));
+                }
+            }
+            3 => {
+                if let Some(b) = v.as_bytes() {
+                    obj.insert("severityText".into(), Value::String(utf8(b)));
+                }
+            }
+            5 => {
+                if let Some(b) = v.as_bytes() {
+                    obj.insert("body".into(), decode_any_value(b));
+                }
+            }
+            6 => {
+                if let Some(b) = v.as_bytes() {
+                    attributes.push(decode_key_value(b));
+                }
+            }
+            _ => {}
+        }
+    }
+    obj.insert("attributes".into(), Value::Array(attributes));
+    Value::Object(obj)
+}
+
+fn decode_scope_logs(bytes: &[u8]) -> Value {
+    json!({ "logRecords": repeated(bytes, 2, decode_log_record) })
+}
+
+fn decode_resource_logs(bytes: &[u8]) -> Value {
+    let resource = repeated(bytes, 1, decode_resource).into_iter().next().unwrap_or(json!({}));
+    json!({
+        "resource": resource,
+        "scopeLogs": repeated(bytes, 2, decode_scope_logs),
+    })
+}
+
+/// Decode an `ExportLogsServiceRequest` into the OTLP/JSON shape consumed by
+/// `otel::parser::parse_otel_logs`.
+pub fn decode_export_logs_request(bytes: &[u8]) -> Value {
+    json!({ "resourceLogs": repeated(bytes, 1, decode_resource_logs) })
+}
+
+// ---- Metrics --------------------------------------------------------------
+
+fn decode_number_data_point(bytes: &[u8]) -> Value {
+    let mut obj = Map::new();
+    let mut attributes = Vec::new();
+    for (field, v) in iter_fields(bytes) {
+        match field {
+            7 => {
+                if let Some(b) = v.as_bytes() {
+                    attributes.push(decode_key_value(b));
+                }
+            }
+            4 => {
+                if let Some(f) = v.as_fixed64_f64() {
+                    obj.insert("asDouble".into(), json!(f));
+                }
+            }
+            6 => {
+                if let Some(n) = v.as_fixed64_u64() {
+                    obj.insert("asInt".into(), json!(n as i64));
+                }
+            }
+            _ => {}
+        }
+    }
+    obj.insert("attributes".into(), Value::Array(attributes));
+    Value::Object(obj)
+}
+
+fn decode_gauge(bytes: &[u8]) -> Value {
+    json!({ "dataPoints": repeated(bytes, 1, decode_number_data_point) })
+}
+
+fn decode_sum(bytes: &[u8]) -> Value {
+    let mut obj = Map::new();
+    let data_points = repeated(bytes, 1, decode_number_data_point);
+    for (field, v) in iter_fields(bytes) {
+        if field == 2 {
+            if let Some(n) = v.as_u64() {
+                obj.insert("aggregationTemporality".into(), json!(n as i64));
+            }
+        }
+    }
+    obj.insert("dataPoints".into(), Value::Array(data_points));
+    Value::Object(obj)
+}
+
+/// `repeated fixed64`/`repeated double` fields use proto3's packed encoding
+/// by default: one length-delimited field carrying the values back to back
+/// rather than one tag per entry, unlike every other repeated field this
+/// module decodes (`KeyValue`, `NumberDataPoint`, ...), which are message
+/// types and so can't be packed.
+fn decode_packed_fixed64(bytes: &[u8]) -> Vec<u64> {
+    bytes
+        .chunks_exact(8)
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+fn decode_histogram_data_point(bytes: &[u8]) -> Value {
+    let mut obj = Map::new();
+    let mut attributes = Vec::new();
+    let mut bucket_counts = Vec::new();
+    let mut explicit_bounds = Vec::new();
+    for (field, v) in iter_fields(bytes) {
+        match field {
+            2 => {
+                if let Some(n) = v.as_fixed64_u64() {
+                    obj.insert("startTimeUnixNano".into(), Value::String(n.to_string()));
+                }
+            }
+            3 => {
+                if let Some(n) = v.as_fixed64_u64() {
+                    obj.insert("timeUnixNano".into(), Value::String(n.to_string()));
+                }
+            }
+            4 => {
+                if let Some(n) = v.as_fixed64_u64() {
+                    obj.insert("count".into(), Value::String(n.to_string()));
+                }
+            }
+            5 => {
+                if let Some(f) = v.as_fixed64_f64() {
+                    obj.insert("sum".into(), json!(f));
+                }
+            }
+            6 => {
+                if let Some(b) = v.as_bytes() {
+                    bucket_counts = decode_packed_fixed64(b);
+                }
+            }
+            7 => {
+                if let Some(b) = v.as_bytes() {
+                    explicit_bounds = decode_packed_fixed64(b).into_iter().map(f64::from_bits).collect();
+                }
+            }
+            9 => {
+                if let Some(b) = v.as_bytes() {
+                    attributes.push(decode_key_value(b));
+                }
+            }
+            _ => {}
+        }
+    }
+    obj.insert("attributes".into(), Value::Array(attributes));
+    obj.insert(
+        "bucketCounts".into(),
+        Value::Array(bucket_counts.into_iter().map(|n| Value::String(n.to_string())).collect()),
+    );
+    obj.insert("explicitBounds".into(), Value::Array(explicit_bounds.into_iter().map(|f| json!(f)).collect()));
+    Value::Object(obj)
+}
+
+fn decode_histogram(bytes: &[u8]) -> Value {
+    let mut obj = Map::new();
+    let data_points = repeated(bytes, 1, decode_histogram_data_point);
+    for (field, v) in iter_fields(bytes) {
+        if field == 2 {
+            if let Some(n) = v.as_u64() {
+                obj.insert("aggregationTemporality".into(), json!(n as i64));
+            }
+        }
+    }
+    obj.insert("dataPoints".into(), Value::Array(data_points));
+    Value::Object(obj)
+}
+
+fn decode_metric(bytes: &[u8]) -> Value {
+    let mut obj = Map::new();
+    for (field, v) in iter_fields(bytes) {
+        match field {
+            1 => {
+                if let Some(b) = v.as_bytes() {
+                    obj.insert("name".into(), Value::String(utf8(b)));
+                }
+            }
+            5 => {
+                if let Some(b) = v.as_bytes() {
+                    obj.insert("gauge".into(), decode_gauge(b));
+                }
+            }
+            7 => {
+                if let Some(b) = v.as_bytes() {
+                    obj.insert("sum".into(), decode_sum(b));
+                }
+            }
+            9 => {
+                if let Some(b) = v.as_bytes() {
+                    obj.insert("histogram".into(), decode_histogram(b));
+                }
+            }
+            _ => {}
+        }
+    }
+    Value::Object(obj)
+}
+
+fn decode_scope_metrics(bytes: &[u8]) -> Value {
+    json!({ "metrics": repeated(bytes, 2, decode_metric) })
+}
+
+fn decode_resource_metrics(bytes: &[u8]) -> Value {
+    let resource = repeated(bytes, 1, decode_resource).into_iter().next().unwrap_or(json!({}));
+    json!({
+        "resource": resource,
+        "scopeMetrics": repeated(bytes, 2, decode_scope_metrics),
+    })
+}
+
+/// Decode an `ExportMetricsServiceRequest` into the OTLP/JSON shape consumed
+/// by `otel::parser::parse_otel_metrics`.
+pub fn decode_export_metrics_request(bytes: &[u8]) -> Value {
+    json!({ "resourceMetrics": repeated(bytes, 1, decode_resource_metrics) })
+}
+
+// ---- Traces -----------------------------------------------------------
+
+fn decode_span_event(bytes: &[u8]) -> Value {
+    let mut obj = Map::new();
+    let mut attributes = Vec::new();
+    for (field, v) in iter_fields(bytes) {
+        match field {
+            1 => {
+                if let Some(n) = v.as_fixed64_u64() {
+                    obj.insert("timeUnixNano".into(), Value::String(n.to_string()));
+                }
+            }
+            2 => {
+                if let Some(b) = v.as_bytes() {
+                    obj.insert("name".into(), Value::String(utf8(b)));
+                }
+            }
+            3 => {
+                if let Some(b) = v.as_bytes() {
+                    attributes.push(decode_key_value(b));
+                }
+            }
+            _ => {}
+        }
+    }
+    obj.insert("attributes".into(), Value::Array(attributes));
+    Value::Object(obj)
+}
+
+fn decode_status_code(bytes: &[u8]) -> Option<i64> {
+    iter_fields(bytes)
+        .into_iter()
+        .find_map(|(f, v)| (f == 3).then(|| v.as_u64()).flatten())
+        .map(|n| n as i64)
+}
+
+fn decode_span(bytes: &[u8]) -> Value {
+    let mut obj = Map::new();
+    let mut attributes = Vec::new();
+    let mut events = Vec::new();
+    for (field, v) in iter_fields(bytes) {
+        match field {
+            1 => {
+                if let Some(b) = v.as_bytes() {
+                    obj.insert("traceId".into(), Value::String(bytes_to_hex(b)));
+                }
+            }
+            2 => {
+                if let Some(b) = v.as_bytes() {
+                    obj.insert("spanId".into(), Value::String(bytes_to_hex(b)));
+                }
+            }
+            4 => {
+                if let Some(b) = v.as_bytes() {
+                    obj.insert("parentSpanId".into(), Value::String(bytes_to_hex(b)));
+                }
+            }
+            5 => {
+                if let Some(b) = v.as_bytes() {
+                    obj.insert("name".into(), Value::String(utf8(b)));
+                }
+            }
+            7 => {
+                if let Some(n) = v.as_fixed64_u64() {
+                    obj.insert("startTimeUnixNano".into(), Value::String(n.to_string()));
+                }
+            }
+            8 => {
+                if let Some(n) = v.as_fixed64_u64() {
+                    obj.insert("endTimeUnixNano".into(), Value::String(n.to_string()));
+                }
+            }
+            9 => {
+                if let Some(b) = v.as_bytes() {
+                    attributes.push(decode_key_value(b));
+                }
+            }
+            11 => {
+                if let Some(b) = v.as_bytes() {
+                    events.push(decode_span_event(b));
+                }
+            }
+            15 => {
+                if let Some(b) = v.as_bytes()
+                    && let Some(code) = decode_status_code(b)
+                {
+                    obj.insert("status".into(), json!({ "code": code }));
+                }
+            }
+            _ => {}
+        }
+    }
+    obj.insert("attributes".into(), Value::Array(attributes));
+    obj.insert("events".into(), Value::Array(events));
+    Value::Object(obj)
+}
+
+fn decode_scope_spans(bytes: &[u8]) -> Value {
+    json!({ "spans": repeated(bytes, 2, decode_span) })
+}
+
+fn decode_resource_spans(bytes: &[u8]) -> Value {
+    let resource = repeated(bytes, 1, decode_resource).into_iter().next().unwrap_or(json!({}));
+    json!({
+        "resource": resource,
+        "scopeSpans": repeated(bytes, 2, decode_scope_spans),
+    })
+}
+
+/// Decode an `ExportTraceServiceRequest` into the OTLP/JSON shape consumed by
+/// `otel::parser::parse_otel_traces`.
+pub fn decode_export_trace_request(bytes: &[u8]) -> Value {
+    json!({ "resourceSpans": repeated(bytes, 1, decode_resource_spans) })
+}
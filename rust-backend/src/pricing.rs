@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::env;
 use std::sync::OnceLock;
 
 use serde::Deserialize;
@@ -10,6 +11,18 @@ const CLAUDE_PRICING_JSON: &str = include_str!("../../src/pricing/data/claude.js
 const CODEX_PRICING_JSON: &str = include_str!("../../src/pricing/data/codex.json");
 const GEMINI_PRICING_JSON: &str = include_str!("../../src/pricing/data/gemini.json");
 
+/// Version stamp used when no override file is loaded (an override file
+/// without its own `version` is stamped `external:<path>` instead). Bump this alongside the bundled JSON files
+/// whenever their rates change, so rows computed under the old rates are
+/// still distinguishable from ones computed under the new ones.
+const BUILTIN_PRICING_VERSION: &str = "builtin-2026-01";
+
+/// Path to a JSON file (same shape as the bundled data files, see
+/// `PricingDataFile`) whose models/aliases are layered on top of the
+/// built-in defaults, overriding any canonical name they share. Optional —
+/// unset means only the bundled defaults apply.
+const PRICING_FILE_ENV: &str = "AGENTMONITOR_PRICING_FILE";
+
 #[derive(Debug, Clone, Copy)]
 struct ModelPricing {
     input_cost_per_token: f64,
@@ -18,14 +31,19 @@ struct ModelPricing {
     cache_write_cost_per_token: f64,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct PricingRegistry {
     models: HashMap<String, ModelPricing>,
     aliases: HashMap<String, String>,
+    /// Identifies the rate table snapshot that produced a cost, so a later
+    /// `--recompute-costs` pass can tell which rows were priced under it.
+    version: String,
 }
 
 #[derive(Debug, Deserialize)]
 struct PricingDataFile {
+    #[serde(default)]
+    version: Option<String>,
     models: HashMap<String, PricingDataModel>,
 }
 
@@ -55,7 +73,11 @@ static PRICING_REGISTRY: OnceLock<PricingRegistry> = OnceLock::new();
 
 impl PricingRegistry {
     fn load() -> Self {
-        let mut registry = Self::default();
+        let mut registry = Self {
+            models: HashMap::new(),
+            aliases: HashMap::new(),
+            version: BUILTIN_PRICING_VERSION.to_string(),
+        };
         for raw in [CLAUDE_PRICING_JSON, CODEX_PRICING_JSON, GEMINI_PRICING_JSON] {
             let parsed = serde_json::from_str::<PricingDataFile>(raw);
             match parsed {
@@ -63,6 +85,23 @@ impl PricingRegistry {
                 Err(err) => warn!("failed to parse pricing data file: {err}"),
             }
         }
+
+        if let Ok(path) = env::var(PRICING_FILE_ENV) {
+            match std::fs::read_to_string(&path) {
+                Ok(raw) => match serde_json::from_str::<PricingDataFile>(&raw) {
+                    Ok(file) => {
+                        registry.version = file
+                            .version
+                            .clone()
+                            .unwrap_or_else(|| format!("external:{path}"));
+                        registry.load_provider(file);
+                    }
+                    Err(err) => warn!("failed to parse {PRICING_FILE_ENV} override ({path}): {err}"),
+                },
+                Err(err) => warn!("failed to read {PRICING_FILE_ENV} override ({path}): {err}"),
+            }
+        }
+
         registry
     }
 
@@ -116,9 +155,18 @@ pub fn calculate_cost(model_name: &str, tokens: TokenCounts) -> Option<f64> {
     registry.calculate(model_name, tokens)
 }
 
+/// Identifies the currently loaded rate table, so callers that derive a
+/// `cost_usd` from `calculate_cost` can stamp the row with the version that
+/// produced it. Changes whenever `AGENTMONITOR_PRICING_FILE` points at a
+/// file with a different `version`, or falls back to `BUILTIN_PRICING_VERSION`
+/// when no override is configured.
+pub fn pricing_version() -> &'static str {
+    &PRICING_REGISTRY.get_or_init(PricingRegistry::load).version
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{TokenCounts, calculate_cost};
+    use super::{BUILTIN_PRICING_VERSION, TokenCounts, calculate_cost, pricing_version};
 
     #[test]
     fn calculates_cost_for_known_model() {
@@ -163,4 +211,9 @@ mod tests {
         );
         assert!(cost.is_none());
     }
+
+    #[test]
+    fn pricing_version_defaults_to_builtin_without_an_override_file() {
+        assert_eq!(pricing_version(), BUILTIN_PRICING_VERSION);
+    }
 }
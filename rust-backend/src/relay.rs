@@ -0,0 +1,238 @@
+//! Optional relay/upstream mode: this process opens each configured
+//! upstream's `/api/stream` SSE endpoint, re-ingests every event it sees
+//! through the same normalize/dedup/persist path `/api/events` uses, and
+//! forwards it to this instance's own SSE subscribers. That gives one
+//! dashboard a unified `/api/stats`/`/api/sessions`/`/api/stream` over many
+//! machines without any of them needing direct DB access.
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::state::AppState;
+
+/// How long a relay task waits before re-dialing an upstream whose stream
+/// just ended (cleanly or otherwise). Fixed rather than exponential — an
+/// upstream that's actually down won't be helped by backing off further,
+/// and one that's flapping back up shouldn't be kept waiting longer than
+/// this.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub enum RelayError {
+    Connect(reqwest::Error),
+    Status(reqwest::StatusCode),
+    Stream(reqwest::Error),
+}
+
+impl fmt::Display for RelayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connect(err) => write!(f, "connection failed: {err}"),
+            Self::Status(status) => write!(f, "unexpected response status {status}"),
+            Self::Stream(err) => write!(f, "stream read failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for RelayError {}
+
+/// Connection health for one configured upstream, updated by its relay task
+/// and read back by `api::relay::upstreams_handler` — the same
+/// "background task owns state a handler reads back" shape `AppState.nats`
+/// uses, just with plain atomics/a mutex instead of a client handle.
+pub struct UpstreamStatus {
+    pub url: String,
+    connected: AtomicBool,
+    last_error: Mutex<Option<String>>,
+}
+
+impl UpstreamStatus {
+    pub(crate) fn new(url: String) -> Arc<Self> {
+        Arc::new(Self {
+            url,
+            connected: AtomicBool::new(false),
+            last_error: Mutex::new(None),
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn set_connected(&self, connected: bool) {
+        self.connected.store(connected, Ordering::Relaxed);
+    }
+
+    fn set_error(&self, message: Option<String>) {
+        *self.last_error.lock().unwrap_or_else(|e| e.into_inner()) = message;
+    }
+}
+
+/// Build one `UpstreamStatus` per `Config::upstreams` entry, unconnected
+/// until `spawn_upstream_tasks` starts a task for it. Split out from
+/// spawning so `AppState::new` can populate `relay_status` before an
+/// `Arc<AppState>` exists for a task to hold a handle to.
+pub fn build_statuses(config: &Config) -> Vec<Arc<UpstreamStatus>> {
+    config.upstreams.iter().cloned().map(UpstreamStatus::new).collect()
+}
+
+/// Spawn one background task per entry in `state.relay_status`, each
+/// streaming from its upstream for the life of the process.
+pub fn spawn_upstream_tasks(state: &Arc<AppState>) {
+    for status in &state.relay_status {
+        tokio::spawn(run_relay_client(Arc::clone(state), Arc::clone(status)));
+    }
+}
+
+/// Dial `status.url`, stream events until the connection drops, then wait
+/// `RECONNECT_DELAY` and try again — for the lifetime of the process, same
+/// as `nats::run_subscriber` doesn't stop retrying either.
+async fn run_relay_client(state: Arc<AppState>, status: Arc<UpstreamStatus>) {
+    loop {
+        if let Err(err) = connect_and_stream(&state, &status).await {
+            warn!("relay: {}: {err}", status.url);
+        }
+        status.set_connected(false);
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn connect_and_stream(state: &Arc<AppState>, status: &Arc<UpstreamStatus>) -> Result<(), RelayError> {
+    let url = format!("{}/api/stream", status.url.trim_end_matches('/'));
+    let response = reqwest::Client::new()
+        .get(&url)
+        .header("Accept", "text/event-stream")
+        .send()
+        .await
+        .map_err(RelayError::Connect)?;
+
+    if !response.status().is_success() {
+        return Err(RelayError::Status(response.status()));
+    }
+
+    info!("relay: connected to upstream {}", status.url);
+    status.set_connected(true);
+    status.set_error(None);
+
+    let mut bytes = response.bytes_stream();
+    let mut buffer = String::new();
+    while let Some(chunk) = bytes.next().await {
+        let chunk = chunk.map_err(RelayError::Stream)?;
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+        while let Some(boundary) = buffer.find("\n\n") {
+            let frame = buffer[..boundary].to_string();
+            buffer.drain(..boundary + 2);
+            ingest_frame(state, status, &frame).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse one `data: {...}` SSE frame from an upstream's `/api/stream` and,
+/// if it's an `"event"` frame (as opposed to `"connected"`/`"stats"`/
+/// `"session_update"`/`"gap"`), re-ingest its payload.
+async fn ingest_frame(state: &Arc<AppState>, status: &Arc<UpstreamStatus>, frame: &str) {
+    let Some(data_line) = frame
+        .lines()
+        .find_map(|line| line.strip_prefix("data:"))
+    else {
+        return;
+    };
+
+    let Ok(envelope) = serde_json::from_str::<Value>(data_line.trim_start()) else {
+        warn!("relay: {}: dropping malformed SSE frame", status.url);
+        return;
+    };
+
+    if envelope.get("type").and_then(Value::as_str) != Some("event") {
+        return;
+    }
+    let Some(payload) = envelope.get("payload").cloned() else {
+        return;
+    };
+
+    ingest_relayed_event(state, &status.url, payload).await;
+}
+
+/// Normalize, dedup, and persist one event relayed from an upstream the
+/// same way `ingest_single` does for an HTTP POST, then forward it to this
+/// instance's own SSE subscribers through the local hub. `event.source` is
+/// overwritten with `origin_tag(upstream_url)` regardless of what the
+/// upstream stamped, so `/api/filter-options` and `/api/stats` can break
+/// usage down by which upstream an event actually came from.
+async fn ingest_relayed_event(state: &Arc<AppState>, upstream_url: &str, payload: Value) {
+    use crate::contracts::event::NormalizeResult;
+    use crate::contracts::validation::normalize_from_value;
+    use crate::db::queries;
+    use crate::util::truncate::truncate_metadata;
+
+    let NormalizeResult::Ok { mut event } = normalize_from_value(payload, &state.config.ingest_validation) else {
+        warn!("relay: {upstream_url}: dropping invalid event payload");
+        return;
+    };
+    event.source = Some(origin_tag(upstream_url));
+
+    let max_kb = state.config.max_payload_kb;
+    let truncated = truncate_metadata(&event.metadata, max_kb);
+    let params = crate::api::insert_params(
+        &event,
+        &truncated.value,
+        truncated.truncated,
+        crate::auth::DEFAULT_TENANT,
+    );
+
+    let db = match state.write_conn() {
+        Ok(db) => db,
+        Err(e) => {
+            warn!("relay: {upstream_url}: failed to check out a database connection: {e}");
+            return;
+        }
+    };
+    match queries::insert_event(&db, &params) {
+        Ok(Some(row)) => {
+            let row_value = serde_json::to_value(&row).unwrap_or_else(|_| Value::from(serde_json::json!({})));
+            state.sse_hub.broadcast("event", &row_value);
+            state.notify_new_events.notify_waiters();
+        }
+        Ok(None) => {
+            // Deduplicated — already persisted by an earlier delivery.
+        }
+        Err(e) => warn!("relay: {upstream_url}: insert_event error: {e}"),
+    }
+}
+
+/// `relay:<host:port>` — stable, human-readable, and distinct from the
+/// handful of bare values (`api`, `import`, `ws`) `events.source` otherwise
+/// holds.
+fn origin_tag(upstream_url: &str) -> String {
+    let host = upstream_url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+    format!("relay:{host}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origin_tag_strips_scheme_and_trailing_slash() {
+        assert_eq!(origin_tag("http://host-a:3142/"), "relay:host-a:3142");
+        assert_eq!(origin_tag("https://host-b:3142"), "relay:host-b:3142");
+    }
+}
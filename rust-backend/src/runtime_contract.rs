@@ -1,5 +1,6 @@
 use std::fmt;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 use crate::config::Config;
 use crate::runtime_host::{RuntimeHost, RuntimeHostError};
@@ -8,13 +9,15 @@ use crate::runtime_host::{RuntimeHost, RuntimeHostError};
 pub struct RuntimeEndpoint {
     local_addr: SocketAddr,
     base_url: String,
+    ipc_socket_path: Option<PathBuf>,
 }
 
 impl RuntimeEndpoint {
-    fn from_local_addr(local_addr: SocketAddr) -> Self {
+    fn from_host(host: &RuntimeHost) -> Self {
         Self {
-            local_addr,
-            base_url: format!("http://{local_addr}"),
+            local_addr: host.local_addr(),
+            base_url: format!("http://{}", host.local_addr()),
+            ipc_socket_path: host.ipc_socket_path().map(PathBuf::from),
         }
     }
 
@@ -25,6 +28,12 @@ impl RuntimeEndpoint {
     pub fn base_url(&self) -> &str {
         &self.base_url
     }
+
+    /// The Unix domain socket `runtime_host` bound alongside the TCP
+    /// listener, if `Config::ipc_socket_path` was set and binding succeeded.
+    pub fn ipc_socket_path(&self) -> Option<&std::path::Path> {
+        self.ipc_socket_path.as_deref()
+    }
 }
 
 #[derive(Debug)]
@@ -64,6 +73,16 @@ impl RuntimeContract {
         self.endpoint.base_url()
     }
 
+    pub fn ipc_socket_path(&self) -> Option<&std::path::Path> {
+        self.endpoint.ipc_socket_path()
+    }
+
+    /// The shared application state backing this runtime, if it hasn't been
+    /// shut down yet. See `RuntimeHost::app_state`.
+    pub fn app_state(&self) -> Option<&std::sync::Arc<crate::state::AppState>> {
+        self.host.as_ref().map(RuntimeHost::app_state)
+    }
+
     pub async fn shutdown(mut self) -> Result<(), RuntimeContractError> {
         if let Some(host) = self.host.take() {
             host.stop()
@@ -78,7 +97,7 @@ pub async fn start_with_config(config: Config) -> Result<RuntimeContract, Runtim
     let host = crate::runtime_host::start_with_config(config)
         .await
         .map_err(map_start_error)?;
-    let endpoint = RuntimeEndpoint::from_local_addr(host.local_addr());
+    let endpoint = RuntimeEndpoint::from_host(&host);
     Ok(RuntimeContract {
         host: Some(host),
         endpoint,
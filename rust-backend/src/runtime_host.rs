@@ -5,20 +5,22 @@ use std::time::Duration;
 
 use tokio::sync::watch;
 use tokio::task::JoinHandle;
-use tracing::info;
+use tracing::{error, info};
 
 use crate::auto_import::run_auto_import_once;
 use crate::config::Config;
 use crate::db;
-use crate::runtime_tasks::{run_idle_check_once, run_stats_broadcast_once};
+use crate::runtime_tasks::{run_idle_check_once, run_session_rollup_once, run_stats_broadcast_once};
 use crate::state::AppState;
+use crate::task_supervisor;
 
 #[derive(Debug)]
 pub enum RuntimeHostError {
-    Db(rusqlite::Error),
+    Db(crate::db::pool::PoolError),
     Bind(std::io::Error),
     Server(std::io::Error),
     Join(tokio::task::JoinError),
+    Tls(crate::tls::TlsConfigError),
 }
 
 impl fmt::Display for RuntimeHostError {
@@ -28,6 +30,7 @@ impl fmt::Display for RuntimeHostError {
             Self::Bind(err) => write!(f, "listener bind failed: {err}"),
             Self::Server(err) => write!(f, "server exited with error: {err}"),
             Self::Join(err) => write!(f, "task join failed: {err}"),
+            Self::Tls(err) => write!(f, "TLS setup failed: {err}"),
         }
     }
 }
@@ -36,9 +39,15 @@ impl std::error::Error for RuntimeHostError {}
 
 pub struct RuntimeHost {
     local_addr: SocketAddr,
+    ipc_socket_path: Option<std::path::PathBuf>,
+    state: Arc<AppState>,
     shutdown_tx: Option<watch::Sender<bool>>,
+    management_addr: Option<SocketAddr>,
     server_handle: JoinHandle<std::io::Result<()>>,
+    ipc_server_handle: Option<JoinHandle<std::io::Result<()>>>,
+    management_server_handle: Option<JoinHandle<std::io::Result<()>>>,
     task_handles: Vec<JoinHandle<()>>,
+    systemd_notify: bool,
 }
 
 impl RuntimeHost {
@@ -46,34 +55,122 @@ impl RuntimeHost {
         self.local_addr
     }
 
+    /// The Unix domain socket the IPC listener is bound to, if
+    /// `Config::ipc_socket_path` was set and binding it succeeded. See
+    /// `bind_ipc_socket`.
+    pub fn ipc_socket_path(&self) -> Option<&std::path::Path> {
+        self.ipc_socket_path.as_deref()
+    }
+
+    /// The address the management listener is bound to, if
+    /// `Config::management_port` was set and binding it succeeded. See
+    /// `build_management_router`.
+    pub fn management_addr(&self) -> Option<SocketAddr> {
+        self.management_addr
+    }
+
+    /// The shared application state backing this runtime. Lets in-process
+    /// callers (the desktop shell's IPC commands) call a handler's logic
+    /// directly instead of round-tripping through the HTTP listener — see
+    /// `api::health::build`.
+    pub fn app_state(&self) -> &Arc<AppState> {
+        &self.state
+    }
+
     pub async fn stop(mut self) -> Result<(), RuntimeHostError> {
+        #[cfg(feature = "systemd")]
+        if self.systemd_notify {
+            // This repo's only restart path is stop() followed by a fresh
+            // start_with_config(), so RELOADING=1 here pairs with the
+            // READY=1 the next start_with_config() sends once it's back up.
+            crate::systemd::notify_reloading();
+        }
+
         if let Some(shutdown_tx) = self.shutdown_tx.take() {
             let _ = shutdown_tx.send(true);
         }
 
-        for handle in self.task_handles {
-            if let Err(err) = handle.await {
-                if !err.is_cancelled() {
-                    return Err(RuntimeHostError::Join(err));
+        // Everything below has already been told to shut down — give it up
+        // to `shutdown_grace_ms` in total to drain in-flight requests and
+        // background task runs on its own before forcibly aborting whatever
+        // is still outstanding, rather than waiting on it indefinitely.
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(self.state.config.shutdown_grace_ms);
+
+        for mut handle in std::mem::take(&mut self.task_handles) {
+            match Self::await_with_deadline(&mut handle, deadline).await {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) if !err.is_cancelled() => return Err(RuntimeHostError::Join(err)),
+                Ok(Err(_)) => {}
+                Err(_) => {
+                    error!("background task did not finish within the shutdown grace period, aborting");
+                    handle.abort();
                 }
             }
         }
 
-        match self.server_handle.await {
-            Ok(Ok(())) => Ok(()),
-            Ok(Err(err)) => Err(RuntimeHostError::Server(err)),
-            Err(err) => Err(RuntimeHostError::Join(err)),
+        if let Some(mut ipc_handle) = self.ipc_server_handle.take() {
+            match Self::await_with_deadline(&mut ipc_handle, deadline).await {
+                Ok(Ok(Ok(()))) => {}
+                Ok(Ok(Err(err))) => return Err(RuntimeHostError::Server(err)),
+                Ok(Err(err)) => return Err(RuntimeHostError::Join(err)),
+                Err(_) => {
+                    error!("IPC listener did not finish within the shutdown grace period, aborting");
+                    ipc_handle.abort();
+                }
+            }
+        }
+        if let Some(path) = self.ipc_socket_path.take() {
+            let _ = std::fs::remove_file(path);
+        }
+
+        if let Some(mut management_handle) = self.management_server_handle.take() {
+            match Self::await_with_deadline(&mut management_handle, deadline).await {
+                Ok(Ok(Ok(()))) => {}
+                Ok(Ok(Err(err))) => return Err(RuntimeHostError::Server(err)),
+                Ok(Err(err)) => return Err(RuntimeHostError::Join(err)),
+                Err(_) => {
+                    error!("management listener did not finish within the shutdown grace period, aborting");
+                    management_handle.abort();
+                }
+            }
+        }
+
+        match Self::await_with_deadline(&mut self.server_handle, deadline).await {
+            Ok(Ok(Ok(()))) => Ok(()),
+            Ok(Ok(Err(err))) => Err(RuntimeHostError::Server(err)),
+            Ok(Err(err)) => Err(RuntimeHostError::Join(err)),
+            Err(_) => {
+                error!("HTTP listener did not finish within the shutdown grace period, aborting");
+                self.server_handle.abort();
+                Ok(())
+            }
         }
     }
+
+    /// Awaits `handle` with whatever time remains until `deadline`, returning
+    /// `Err(Elapsed)` if it's still running once that runs out. Never sleeps
+    /// longer than `deadline` even if called repeatedly, so a grace period
+    /// shared across several handles bounds their *total* drain time rather
+    /// than giving each one the full period.
+    async fn await_with_deadline<T>(
+        handle: &mut JoinHandle<T>,
+        deadline: tokio::time::Instant,
+    ) -> Result<Result<T, tokio::task::JoinError>, tokio::time::error::Elapsed> {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        tokio::time::timeout(remaining, handle).await
+    }
 }
 
 pub async fn start_with_config(config: Config) -> Result<RuntimeHost, RuntimeHostError> {
     let bind_addr = config.bind_addr();
     let auto_import_interval_minutes = config.auto_import_interval_minutes;
-    let stats_interval_ms = config.stats_interval_ms;
+    let management_port = config.management_port;
+    let systemd_notify = config.systemd_notify;
+    let ipc_socket_path = config.ipc_socket_path.clone();
 
-    let conn = db::initialize(&config.db_path).map_err(RuntimeHostError::Db)?;
-    let state: Arc<AppState> = AppState::new(conn, config);
+    let pool = db::pool::initialize(&config.db_path, config.db_pool_size, config.db_passphrase.as_deref())
+        .map_err(RuntimeHostError::Db)?;
+    let state: Arc<AppState> = AppState::new(pool, config);
     let app = crate::build_router(Arc::clone(&state));
 
     let listener = tokio::net::TcpListener::bind(&bind_addr)
@@ -81,15 +178,49 @@ pub async fn start_with_config(config: Config) -> Result<RuntimeHost, RuntimeHos
         .map_err(RuntimeHostError::Bind)?;
     let local_addr = listener.local_addr().map_err(RuntimeHostError::Bind)?;
 
+    // TLS is opt-in: both tls_cert_path and tls_key_path must be set, or the
+    // listener stays plaintext exactly as before this existed. Built eagerly
+    // (rather than lazily on first connection) so a misconfigured cert/key
+    // fails startup loudly instead of every accepted connection silently.
+    let tls_config = match (&state.config.tls_cert_path, &state.config.tls_key_path) {
+        (Some(cert_path), Some(key_path)) => {
+            info!("TLS: terminating connections with cert {}", cert_path.display());
+            Some(crate::tls::load_server_config(cert_path, key_path).map_err(RuntimeHostError::Tls)?)
+        }
+        _ => None,
+    };
+
+    // The Unix domain socket is additional, not a replacement for the TCP
+    // listener above — the webview still loads the app and talks to the API
+    // over `http://local_addr`. This just gives the Tauri shell's own IPC
+    // commands (see `agentmonitor_tauri_lib::ipc`) a transport that doesn't
+    // require a listening TCP port.
+    #[cfg(unix)]
+    let (ipc_listener, bound_ipc_socket_path) = match ipc_socket_path {
+        Some(path) => match bind_ipc_socket(&path).await {
+            Ok(listener) => (Some(listener), Some(path)),
+            Err(err) => {
+                error!("IPC socket: failed to bind {}, continuing TCP-only: {err}", path.display());
+                (None, None)
+            }
+        },
+        None => (None, None),
+    };
+    #[cfg(not(unix))]
+    let (ipc_listener, bound_ipc_socket_path): (Option<tokio::net::TcpListener>, Option<std::path::PathBuf>) = {
+        if ipc_socket_path.is_some() {
+            error!("IPC socket: Unix domain socket IPC is not supported on this platform, continuing TCP-only");
+        }
+        (None, None)
+    };
+
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    spawn_signal_shutdown_task(shutdown_tx.clone());
 
     let mut task_handles = Vec::new();
-    task_handles.push(spawn_stats_task(
-        Arc::clone(&state),
-        stats_interval_ms,
-        shutdown_rx.clone(),
-    ));
+    task_handles.push(spawn_stats_task(Arc::clone(&state), shutdown_rx.clone()));
     task_handles.push(spawn_idle_task(Arc::clone(&state), shutdown_rx.clone()));
+    task_handles.push(spawn_session_rollup_task(Arc::clone(&state), shutdown_rx.clone()));
     if auto_import_interval_minutes > 0 {
         info!("Auto-import: every {}m", auto_import_interval_minutes);
         task_handles.push(spawn_auto_import_task(
@@ -99,42 +230,184 @@ pub async fn start_with_config(config: Config) -> Result<RuntimeHost, RuntimeHos
         ));
     }
 
-    let server_handle = tokio::spawn(async move {
-        axum::serve(listener, app)
-            .with_graceful_shutdown(wait_for_shutdown_signal(shutdown_rx))
-            .await
+    if let Some(nats_url) = state.config.nats_url.clone() {
+        match crate::nats::connect(&nats_url).await {
+            Ok(client) => {
+                info!("NATS: connected to {nats_url}");
+                state.set_nats_client(client.clone()).await;
+                if let Some(subject) = state.config.nats_subscribe_subject.clone() {
+                    // Not added to task_handles: it runs for the lifetime of
+                    // the NATS subscription rather than the shutdown_rx loop
+                    // the other tasks poll, so RuntimeHost::stop() shouldn't
+                    // wait on it.
+                    tokio::spawn(crate::nats::run_subscriber(
+                        Arc::clone(&state),
+                        client,
+                        subject,
+                    ));
+                }
+            }
+            Err(err) => {
+                error!("NATS: connection failed, continuing without federation: {err}");
+            }
+        }
+    }
+
+    if let Some(mqtt_host) = state.config.mqtt_host.clone() {
+        let (client, eventloop) = crate::mqtt::connect(
+            &mqtt_host,
+            state.config.mqtt_port,
+            &state.config.mqtt_client_id,
+            state.config.mqtt_username.as_deref(),
+            state.config.mqtt_password.as_deref(),
+        );
+        info!("MQTT: connecting to {mqtt_host}:{}", state.config.mqtt_port);
+        state.set_mqtt_client(client.clone()).await;
+        let events_topic = state.config.mqtt_events_topic.clone();
+        let subscriber_state = Arc::clone(&state);
+        // Not added to task_handles: it runs for the lifetime of the MQTT
+        // subscription rather than the shutdown_rx loop the other tasks
+        // poll, same reasoning as the NATS subscriber above.
+        tokio::spawn(async move {
+            if let Err(e) = crate::mqtt::run_subscriber(subscriber_state, client, eventloop, events_topic).await {
+                error!("MQTT: subscriber stopped: {e}");
+            }
+        });
+    }
+
+    if !state.relay_status.is_empty() {
+        info!("Relay: subscribing to {} upstream(s)", state.relay_status.len());
+        crate::relay::spawn_upstream_tasks(&state);
+    }
+
+    // The management API is opt-in and served on its own listener, same
+    // dormant-until-configured shape as the IPC socket above — unlike the
+    // IPC socket, though, this one is TCP-reachable off the host, so it
+    // stays off a separate port rather than just another path on the main
+    // router, and carries its own require_write auth just like the ingest
+    // routes do.
+    let (management_server_handle, management_addr) = match management_port {
+        Some(port) => {
+            let management_bind_addr = format!("{}:{port}", state.config.host);
+            match tokio::net::TcpListener::bind(&management_bind_addr).await {
+                Ok(listener) => {
+                    let addr = listener.local_addr().map_err(RuntimeHostError::Bind)?;
+                    info!("Management API: listening on {addr}");
+                    let management_app = crate::build_management_router(Arc::clone(&state));
+                    let management_shutdown_rx = shutdown_rx.clone();
+                    let handle = tokio::spawn(async move {
+                        axum::serve(listener, management_app)
+                            .with_graceful_shutdown(wait_for_shutdown_signal(management_shutdown_rx))
+                            .await
+                    });
+                    (Some(handle), Some(addr))
+                }
+                Err(err) => {
+                    error!("Management API: failed to bind {management_bind_addr}, continuing without it: {err}");
+                    (None, None)
+                }
+            }
+        }
+        None => (None, None),
+    };
+
+    // Ready once the listener is bound and the router is about to start
+    // serving — `/api/health` always returns 200 from here on, so this is
+    // equivalent to "health passes" for a process that has no external
+    // dependency to wait on.
+    state.set_ready(true);
+
+    #[cfg(feature = "systemd")]
+    if systemd_notify {
+        crate::systemd::notify_ready();
+        if let Some(interval) = crate::systemd::watchdog_interval() {
+            task_handles.push(spawn_watchdog_task(interval, shutdown_rx.clone()));
+        }
+    }
+
+    let ipc_server_handle = ipc_listener.map(|ipc_listener| {
+        let ipc_app = app.clone();
+        let ipc_shutdown_rx = shutdown_rx.clone();
+        tokio::spawn(async move {
+            axum::serve(ipc_listener, ipc_app)
+                .with_graceful_shutdown(wait_for_shutdown_signal(ipc_shutdown_rx))
+                .await
+        })
     });
 
+    let server_handle = match tls_config {
+        None => tokio::spawn(async move {
+            axum::serve(listener, app)
+                .with_graceful_shutdown(wait_for_shutdown_signal(shutdown_rx))
+                .await
+        }),
+        Some(tls_config) => tokio::spawn(serve_tls(listener, app, tls_config, shutdown_rx)),
+    };
+
     Ok(RuntimeHost {
         local_addr,
+        ipc_socket_path: bound_ipc_socket_path,
+        management_addr,
+        state,
         shutdown_tx: Some(shutdown_tx),
         server_handle,
+        ipc_server_handle,
+        management_server_handle,
         task_handles,
+        systemd_notify,
     })
 }
 
-fn spawn_stats_task(
-    state: Arc<AppState>,
-    interval_ms: u64,
-    mut shutdown_rx: watch::Receiver<bool>,
-) -> JoinHandle<()> {
-    tokio::spawn(async move {
-        loop {
-            if sleep_or_shutdown(Duration::from_millis(interval_ms), &mut shutdown_rx).await {
-                break;
-            }
-            let _ = run_stats_broadcast_once(Arc::clone(&state)).await;
-        }
-    })
+/// Bind `path` as a Unix domain socket listener. Only called on Unix targets
+/// (see the `cfg(not(unix))` branch in `start_with_config`, which logs and
+/// falls back to TCP-only instead). Removes a stale socket file left behind
+/// by an unclean shutdown before binding, mirroring the "bind fails loudly,
+/// stale state doesn't linger silently" shape `db::pool` uses for its own
+/// file-based resource.
+#[cfg(unix)]
+async fn bind_ipc_socket(path: &std::path::Path) -> std::io::Result<tokio::net::UnixListener> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    match std::fs::remove_file(path) {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    tokio::net::UnixListener::bind(path)
+}
+
+fn spawn_stats_task(state: Arc<AppState>, shutdown_rx: watch::Receiver<bool>) -> JoinHandle<()> {
+    task_supervisor::spawn_dynamic(
+        "stats_broadcast",
+        state,
+        |state| Duration::from_millis(state.runtime_settings.stats_interval_ms()),
+        shutdown_rx,
+        |state| async move {
+            let _ = run_stats_broadcast_once(state).await;
+        },
+    )
 }
 
-fn spawn_idle_task(state: Arc<AppState>, mut shutdown_rx: watch::Receiver<bool>) -> JoinHandle<()> {
+fn spawn_idle_task(state: Arc<AppState>, shutdown_rx: watch::Receiver<bool>) -> JoinHandle<()> {
+    task_supervisor::spawn(
+        "idle_sweep",
+        state,
+        Duration::from_secs(60),
+        shutdown_rx,
+        |state| async move {
+            let _ = run_idle_check_once(state).await;
+        },
+    )
+}
+
+fn spawn_session_rollup_task(state: Arc<AppState>, mut shutdown_rx: watch::Receiver<bool>) -> JoinHandle<()> {
     tokio::spawn(async move {
         loop {
-            if sleep_or_shutdown(Duration::from_secs(60), &mut shutdown_rx).await {
+            if sleep_or_shutdown(Duration::from_secs(30), &mut shutdown_rx).await {
                 break;
             }
-            let _ = run_idle_check_once(Arc::clone(&state)).await;
+            let _ = run_session_rollup_once(Arc::clone(&state)).await;
         }
     })
 }
@@ -142,25 +415,36 @@ fn spawn_idle_task(state: Arc<AppState>, mut shutdown_rx: watch::Receiver<bool>)
 fn spawn_auto_import_task(
     state: Arc<AppState>,
     interval_minutes: u64,
+    shutdown_rx: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    task_supervisor::spawn_with_initial_delay(
+        "auto_import",
+        state,
+        Duration::from_secs(5),
+        Duration::from_secs(interval_minutes * 60),
+        shutdown_rx,
+        |state| async move {
+            let _ = run_auto_import_once(state).await;
+        },
+    )
+}
+
+#[cfg(feature = "systemd")]
+fn spawn_watchdog_task(
+    interval: Duration,
     mut shutdown_rx: watch::Receiver<bool>,
 ) -> JoinHandle<()> {
     tokio::spawn(async move {
-        let interval = Duration::from_secs(interval_minutes * 60);
-        if sleep_or_shutdown(Duration::from_secs(5), &mut shutdown_rx).await {
-            return;
-        }
-        let _ = run_auto_import_once(Arc::clone(&state)).await;
-
         loop {
             if sleep_or_shutdown(interval, &mut shutdown_rx).await {
                 break;
             }
-            let _ = run_auto_import_once(Arc::clone(&state)).await;
+            crate::systemd::send_watchdog_ping();
         }
     })
 }
 
-async fn sleep_or_shutdown(duration: Duration, shutdown_rx: &mut watch::Receiver<bool>) -> bool {
+pub(crate) async fn sleep_or_shutdown(duration: Duration, shutdown_rx: &mut watch::Receiver<bool>) -> bool {
     tokio::select! {
         _ = tokio::time::sleep(duration) => false,
         changed = shutdown_rx.changed() => match changed {
@@ -170,6 +454,40 @@ async fn sleep_or_shutdown(duration: Duration, shutdown_rx: &mut watch::Receiver
     }
 }
 
+/// Flips `shutdown_tx` on Ctrl-C or, on Unix, SIGTERM — the same watch
+/// channel `RuntimeHost::stop` sends on, so a deployed binary shuts down
+/// cleanly (draining in-flight requests and background tasks within
+/// `Config::shutdown_grace_ms`) on either signal without needing its own
+/// `stop()` call. Not tracked in `task_handles`: like the NATS subscriber
+/// above, it doesn't poll `shutdown_rx` (it exits only after it has sent
+/// the signal itself), so `stop()` doesn't wait on it.
+fn spawn_signal_shutdown_task(shutdown_tx: watch::Sender<bool>) {
+    tokio::spawn(async move {
+        #[cfg(unix)]
+        {
+            let mut sigterm = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(sigterm) => sigterm,
+                Err(err) => {
+                    error!("failed to install SIGTERM handler, only Ctrl-C will trigger shutdown: {err}");
+                    let _ = tokio::signal::ctrl_c().await;
+                    let _ = shutdown_tx.send(true);
+                    return;
+                }
+            };
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => info!("received Ctrl-C, shutting down"),
+                _ = sigterm.recv() => info!("received SIGTERM, shutting down"),
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = tokio::signal::ctrl_c().await;
+            info!("received Ctrl-C, shutting down");
+        }
+        let _ = shutdown_tx.send(true);
+    });
+}
+
 async fn wait_for_shutdown_signal(mut shutdown_rx: watch::Receiver<bool>) {
     while shutdown_rx.changed().await.is_ok() {
         if *shutdown_rx.borrow() {
@@ -177,3 +495,76 @@ async fn wait_for_shutdown_signal(mut shutdown_rx: watch::Receiver<bool>) {
         }
     }
 }
+
+/// TLS counterpart to `axum::serve` — axum's own `serve` only accepts a
+/// plain `TcpListener`, so a TLS listener needs its own accept loop: accept
+/// the raw TCP connection, run the rustls handshake, then hand the
+/// resulting stream to hyper-util same as axum does internally. Each
+/// connection gets its own task so one slow handshake can't hold up new
+/// connections or the others already being served.
+async fn serve_tls(
+    listener: tokio::net::TcpListener,
+    app: axum::Router,
+    tls_config: std::sync::Arc<rustls::ServerConfig>,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> std::io::Result<()> {
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use hyper_util::server::conn::auto;
+    use hyper_util::service::TowerToHyperService;
+    use tokio_rustls::TlsAcceptor;
+
+    let acceptor = TlsAcceptor::from(tls_config);
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _peer_addr) = match accepted {
+                    Ok(v) => v,
+                    Err(err) => {
+                        error!("TLS: failed to accept connection: {err}");
+                        continue;
+                    }
+                };
+
+                let acceptor = acceptor.clone();
+                let app = app.clone();
+                let mut conn_shutdown_rx = shutdown_rx.clone();
+
+                tokio::spawn(async move {
+                    let tls_stream = match acceptor.accept(stream).await {
+                        Ok(stream) => stream,
+                        Err(err) => {
+                            error!("TLS: handshake failed: {err}");
+                            return;
+                        }
+                    };
+
+                    let io = TokioIo::new(tls_stream);
+                    let service = TowerToHyperService::new(app);
+                    let conn = auto::Builder::new(TokioExecutor::new())
+                        .serve_connection_with_upgrades(io, service);
+                    tokio::pin!(conn);
+
+                    tokio::select! {
+                        result = conn.as_mut() => {
+                            if let Err(err) = result {
+                                error!("TLS: connection error: {err}");
+                            }
+                        }
+                        _ = conn_shutdown_rx.changed() => {
+                            conn.as_mut().graceful_shutdown();
+                            let _ = conn.await;
+                        }
+                    }
+                });
+            }
+            changed = shutdown_rx.changed() => {
+                if changed.is_err() || *shutdown_rx.borrow() {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
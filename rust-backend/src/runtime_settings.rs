@@ -0,0 +1,46 @@
+//! Live-tunable knobs for background jobs: the idle-session timeout
+//! `run_idle_check_once` sweeps against, and the interval `spawn_stats_task`
+//! sleeps between stats broadcasts. Both start from `Config` but, unlike the
+//! rest of `Config`, can be changed after startup through the management API
+//! (`api::runtime_admin`) without a restart — same atomics-behind-a-shared-
+//! struct shape as `ingest_metrics::IngestCounters`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::config::Config;
+
+pub struct RuntimeSettings {
+    idle_timeout_minutes: AtomicU64,
+    stats_interval_ms: AtomicU64,
+}
+
+impl RuntimeSettings {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            idle_timeout_minutes: AtomicU64::new(config.session_timeout_minutes),
+            stats_interval_ms: AtomicU64::new(config.stats_interval_ms),
+        }
+    }
+
+    /// Current active -> idle cutoff, in minutes — `run_idle_check_once`
+    /// also idles-to-ends sessions at twice this value. Read fresh every
+    /// sweep, so a change here takes effect on the next tick.
+    pub fn idle_timeout_minutes(&self) -> u64 {
+        self.idle_timeout_minutes.load(Ordering::Relaxed)
+    }
+
+    pub fn set_idle_timeout_minutes(&self, minutes: u64) {
+        self.idle_timeout_minutes.store(minutes, Ordering::Relaxed);
+    }
+
+    /// Current delay between stats broadcasts — `spawn_stats_task` reads
+    /// this before every sleep rather than capturing it once at startup, so
+    /// a change takes effect on the very next wait.
+    pub fn stats_interval_ms(&self) -> u64 {
+        self.stats_interval_ms.load(Ordering::Relaxed)
+    }
+
+    pub fn set_stats_interval_ms(&self, ms: u64) {
+        self.stats_interval_ms.store(ms, Ordering::Relaxed);
+    }
+}
@@ -1,33 +1,122 @@
 use std::sync::Arc;
 
-use serde_json::json;
+use serde_json::{json, Value};
 
-use crate::db::queries;
+use crate::auth::DEFAULT_TENANT;
+use crate::db::{queries, rollup};
+use crate::notifier::{Alert, AlertKind};
 use crate::state::AppState;
 
-/// Run one stats broadcast cycle.
-/// Returns true when a stats event was broadcast to at least one connected SSE client.
-pub async fn run_stats_broadcast_once(state: Arc<AppState>) -> bool {
-    if state.sse_hub.client_count() == 0 {
-        return false;
-    }
-
-    let db = state.db.lock().await;
-    let stats = queries::get_stats(&db).unwrap_or_else(|_| queries::Stats {
+fn zero_stats() -> queries::Stats {
+    queries::Stats {
         total_events: 0,
         active_sessions: 0,
         total_sessions: 0,
         total_tokens_in: 0,
         total_tokens_out: 0,
         total_cost_usd: 0.0,
-    });
-    let usage_monitor =
-        queries::get_usage_monitor(&db, &state.config.usage_monitor).unwrap_or_default();
-    drop(db);
-
-    state.sse_hub.broadcast(
-        "stats",
-        &json!({
+    }
+}
+
+/// Tenants to run a per-tenant cycle over — every tenant with at least one
+/// session, or just `DEFAULT_TENANT` when there aren't any yet, so a fresh
+/// deployment still gets its usual zeroed broadcast/idle-check pass instead
+/// of silently skipping it because `list_tenants` came back empty.
+async fn list_tenants(state: &Arc<AppState>) -> Vec<String> {
+    let tenants = if let Some(store) = &state.remote_store {
+        store.list_tenants().await.unwrap_or_default()
+    } else {
+        state
+            .read_conn()
+            .ok()
+            .and_then(|db| queries::list_tenants(&db).ok())
+            .unwrap_or_default()
+    };
+    if tenants.is_empty() {
+        vec![DEFAULT_TENANT.to_string()]
+    } else {
+        tenants
+    }
+}
+
+/// Fetch `get_stats`/`get_usage_monitor` for one tenant, through
+/// `state.remote_store` when a Postgres backend is configured, otherwise
+/// through the local `DbPools` reader — see `AppState::remote_store`.
+async fn fetch_tenant_stats(
+    state: &Arc<AppState>,
+    tenant_id: &str,
+) -> (queries::Stats, Vec<queries::AgentUsageData>) {
+    if let Some(store) = &state.remote_store {
+        let stats = store.get_stats(Some(tenant_id)).await.unwrap_or_else(|_| zero_stats());
+        let usage_monitor = store
+            .get_usage_monitor(&state.config.usage_monitor, Some(tenant_id))
+            .await
+            .unwrap_or_default();
+        (stats, usage_monitor)
+    } else {
+        let conn = state.read_conn().ok();
+        let stats = conn
+            .as_ref()
+            .and_then(|db| queries::get_stats(db, Some(tenant_id)).ok())
+            .unwrap_or_else(zero_stats);
+        let usage_monitor = conn
+            .as_ref()
+            .and_then(|db| queries::get_usage_monitor(db, &state.config.usage_monitor, Some(tenant_id)).ok())
+            .unwrap_or_default();
+        (stats, usage_monitor)
+    }
+}
+
+/// Run one stats broadcast cycle, once per tenant returned by
+/// `list_tenants` — each tenant's `stats` envelope carries its own
+/// `tenant_id` (see `BroadcastEvent::new`) so `SseHub`/WebSocket
+/// subscribers only ever see their own tenant's numbers.
+/// Returns true when at least one stats event was broadcast to a connected
+/// SSE client (across every tenant, not a specific one).
+pub async fn run_stats_broadcast_once(state: Arc<AppState>) -> bool {
+    let sse_active = state.sse_hub.client_count() > 0;
+    let mqtt_client = state.mqtt_client().await;
+    // The usage-monitor aggregation below is also the notifier's threshold
+    // check, and a cluster peer can have local subscribers even when this
+    // node doesn't, so a configured webhook/SMTP sink or peer keeps this
+    // cycle running regardless of this node's own SSE/MQTT clients — only
+    // skip the whole cycle when nothing at all is listening for it.
+    if !sse_active && mqtt_client.is_none() && state.notifier.is_empty() && state.cluster.is_empty() {
+        return false;
+    }
+
+    for tenant_id in list_tenants(&state).await {
+        let (stats, usage_monitor) = fetch_tenant_stats(&state, &tenant_id).await;
+
+        if !state.notifier.is_empty() {
+            for agent in &usage_monitor {
+                if agent.session.limit > 0.0 && agent.session.used >= agent.session.limit {
+                    let kind = match agent.limit_type.as_str() {
+                        "cost" => AlertKind::CostBudget,
+                        _ => AlertKind::TokenRateSpike,
+                    };
+                    state
+                        .notifier
+                        .notify(Alert {
+                            kind,
+                            subject_id: format!("{tenant_id}:{}", agent.agent_type),
+                            message: format!(
+                                "{} crossed its {}-hour usage budget: {:.2}/{:.2} ({})",
+                                agent.agent_type,
+                                agent.session.window_hours,
+                                agent.session.used,
+                                agent.session.limit,
+                                agent.limit_type,
+                            ),
+                            detail: serde_json::to_value(agent).unwrap_or(Value::Null),
+                        })
+                        .await;
+                }
+            }
+        }
+
+        let payload = json!({
+            "tenant_id": tenant_id,
             "total_events": stats.total_events,
             "active_sessions": stats.active_sessions,
             "total_sessions": stats.total_sessions,
@@ -35,25 +124,89 @@ pub async fn run_stats_broadcast_once(state: Arc<AppState>) -> bool {
             "total_tokens_out": stats.total_tokens_out,
             "total_cost_usd": stats.total_cost_usd,
             "usage_monitor": usage_monitor,
-        }),
-    );
-    true
+        });
+
+        if sse_active {
+            state.sse_hub.broadcast("stats", &payload);
+        }
+        if !state.cluster.is_empty() {
+            state.cluster.forward_local("stats", &payload).await;
+        }
+        if let Some(client) = &mqtt_client {
+            crate::mqtt::publish_envelope(client, &state.config.mqtt_stats_topic, "stats", &payload).await;
+        }
+    }
+
+    sse_active
 }
 
-/// Run one idle-session check cycle.
-/// Returns the number of sessions transitioned from active -> idle.
+/// Run one idle-session check cycle, once per tenant returned by
+/// `list_tenants` — same backend dispatch as `run_stats_broadcast_once`.
+/// The timeout is read from `state.runtime_settings` rather than `Config`
+/// directly, so it can be retuned live through the management API — see
+/// `api::runtime_admin`.
+/// Returns the total number of sessions transitioned from active -> idle,
+/// across every tenant.
 pub async fn run_idle_check_once(state: Arc<AppState>) -> usize {
-    let timeout_minutes = state.config.session_timeout_minutes;
-    let db = state.db.lock().await;
-    let idled = queries::update_idle_sessions(&db, timeout_minutes).unwrap_or(0);
-    drop(db);
-
-    if idled > 0 && state.sse_hub.client_count() > 0 {
-        state.sse_hub.broadcast(
-            "session_update",
-            &json!({ "type": "idle_check", "idled": idled }),
-        );
+    let timeout_minutes = state.runtime_settings.idle_timeout_minutes();
+    let mut total_idled = 0usize;
+
+    for tenant_id in list_tenants(&state).await {
+        let idled = if let Some(store) = &state.remote_store {
+            store
+                .update_idle_sessions(timeout_minutes, &tenant_id)
+                .await
+                .unwrap_or(0)
+        } else {
+            state
+                .write_conn()
+                .ok()
+                .and_then(|db| queries::update_idle_sessions(&db, timeout_minutes, &tenant_id).ok())
+                .unwrap_or(0)
+        };
+
+        if idled == 0 {
+            continue;
+        }
+        total_idled += idled;
+
+        let payload = json!({ "type": "idle_check", "idled": idled, "tenant_id": tenant_id });
+        if state.sse_hub.client_count() > 0 {
+            state.sse_hub.broadcast("session_update", &payload);
+        }
+        if !state.cluster.is_empty() {
+            state.cluster.forward_local("session_update", &payload).await;
+        }
+        if let Some(client) = state.mqtt_client().await {
+            crate::mqtt::publish_envelope(&client, &state.config.mqtt_sessions_topic, "session_update", &payload).await;
+        }
+        // `update_idle_sessions` only reports a count, not which sessions
+        // idled, so this alert dedupes on the tenant-scoped subject
+        // `"<tenant_id>:sessions"` rather than per session — see
+        // `Alert::subject_id`.
+        state
+            .notifier
+            .notify(Alert {
+                kind: AlertKind::SessionIdle,
+                subject_id: format!("{tenant_id}:sessions"),
+                message: format!("{idled} session(s) went idle"),
+                detail: payload,
+            })
+            .await;
     }
 
-    idled
+    total_idled
+}
+
+/// Run one `session_stats` rollup cycle. A catch-all for ingest paths that
+/// don't roll up after their own writes (importer, NATS, otel, websocket) —
+/// `ingest_batch` already rolls up its own sessions inline, so those are
+/// typically no-ops here.
+/// Returns the number of sessions rolled up.
+pub async fn run_session_rollup_once(state: Arc<AppState>) -> usize {
+    state
+        .write_conn()
+        .ok()
+        .and_then(|db| rollup::rollup_session_stats(&db).ok())
+        .unwrap_or(0)
 }
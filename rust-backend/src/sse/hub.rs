@@ -1,48 +1,227 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
 use serde_json::Value;
 use tokio::sync::broadcast;
 
+use crate::auth::DEFAULT_TENANT;
+
+/// A broadcast message fanned out to SSE clients, carrying the fields a
+/// client-side filter can match against without re-parsing the payload.
+#[derive(Clone)]
+pub struct BroadcastEvent {
+    /// Monotonically increasing, assigned by `SseHub::broadcast`. This is the
+    /// id sent as the SSE frame's `id:` field and the one a reconnecting
+    /// client echoes back via `Last-Event-ID` — the same sequence regardless
+    /// of `kind`, so replay works across a mix of event/stats/session_update
+    /// frames.
+    pub id: u64,
+    /// The outer envelope kind, e.g. "event", "stats", "session_update".
+    pub kind: String,
+    /// The inner `NormalizedEvent.event_type` (e.g. "tool_use"), when the
+    /// payload carries one — absent for "stats"/"session_update" frames.
+    pub event_type: Option<String>,
+    pub session_id: Option<String>,
+    pub agent_type: Option<String>,
+    /// Which tenant this broadcast belongs to, extracted the same way as
+    /// `session_id`/`agent_type` and defaulting to `DEFAULT_TENANT` for
+    /// frames with no tenant in their payload (e.g. pre-multi-tenant
+    /// callers that never learned to stamp one). Unlike the other three
+    /// fields, this is not an optional narrowing a client can opt into —
+    /// `matches` enforces it unconditionally, since it's the isolation
+    /// boundary between tenants' live streams.
+    pub tenant_id: String,
+    pub payload: Arc<Value>,
+}
+
+impl BroadcastEvent {
+    fn new(id: u64, kind: &str, payload: &Value) -> Self {
+        let session_id = payload
+            .get("session_id")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let agent_type = payload
+            .get("agent_type")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let event_type = payload
+            .get("event_type")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let tenant_id = payload
+            .get("tenant_id")
+            .and_then(Value::as_str)
+            .unwrap_or(DEFAULT_TENANT)
+            .to_string();
+        Self {
+            id,
+            kind: kind.to_string(),
+            event_type,
+            session_id,
+            agent_type,
+            tenant_id,
+            payload: Arc::new(payload.clone()),
+        }
+    }
+
+    /// Whether this broadcast matches a client's subscription filter. The
+    /// tenant check always applies, regardless of whether the client set
+    /// any of the other (optional, narrowing) fields — see the note on
+    /// `tenant_id`.
+    pub fn matches(&self, filter: &SseFilter) -> bool {
+        if self.tenant_id != filter.tenant_id {
+            return false;
+        }
+        if let Some(want) = &filter.session_id
+            && self.session_id.as_deref() != Some(want.as_str())
+        {
+            return false;
+        }
+        if let Some(want) = &filter.agent_type
+            && self.agent_type.as_deref() != Some(want.as_str())
+        {
+            return false;
+        }
+        if let Some(want) = &filter.event_type
+            && self.event_type.as_deref() != Some(want.as_str())
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// A per-client subscription predicate. `tenant_id` comes from the
+/// authenticated request (`auth::TenantId`, see `SseHub::subscribe`) and is
+/// always enforced; `session_id`/`agent_type`/`event_type` are optional
+/// narrowing built from `/api/stream` query params or a WebSocket
+/// `{"subscribe": {...}}` message.
+#[derive(Clone, Debug)]
+pub struct SseFilter {
+    pub tenant_id: String,
+    pub session_id: Option<String>,
+    pub agent_type: Option<String>,
+    pub event_type: Option<String>,
+}
+
+impl SseFilter {
+    /// A filter for `tenant_id` with no further narrowing — the starting
+    /// point for a freshly subscribed client before it applies its own
+    /// `session_id`/`agent_type`/`event_type` query params or WS message.
+    pub fn for_tenant(tenant_id: impl Into<String>) -> Self {
+        Self { tenant_id: tenant_id.into(), session_id: None, agent_type: None, event_type: None }
+    }
+
+    /// Whether any of the optional narrowing fields are set — `tenant_id` is
+    /// excluded since it's always enforced, not an opt-in narrowing.
+    pub fn is_empty(&self) -> bool {
+        self.session_id.is_none() && self.agent_type.is_none() && self.event_type.is_none()
+    }
+}
+
 /// SSE hub using tokio::broadcast for fan-out delivery.
 /// Tracks client count for health reporting and max-client enforcement.
 pub struct SseHub {
-    tx: broadcast::Sender<String>,
+    tx: broadcast::Sender<BroadcastEvent>,
     client_count: Arc<AtomicUsize>,
     max_clients: usize,
+    next_id: AtomicU64,
+    history: Mutex<VecDeque<BroadcastEvent>>,
+    replay_capacity: usize,
 }
 
 impl SseHub {
-    pub fn new(max_clients: usize) -> Self {
+    /// `replay_capacity` is `Config::sse_replay_buffer` — how many past
+    /// broadcasts stay around for `Last-Event-ID` replay before the oldest
+    /// gets evicted. Sized for a brief network blip, not a long outage; a
+    /// client gone longer than this misses the gap and gets a resync marker
+    /// (see `SseHub::oldest_id`) instead of a partial replay.
+    pub fn new(max_clients: usize, replay_capacity: usize) -> Self {
         // Channel capacity — if a consumer lags behind this many messages, it gets dropped.
         let (tx, _) = broadcast::channel(256);
         Self {
             tx,
             client_count: Arc::new(AtomicUsize::new(0)),
             max_clients,
+            next_id: AtomicU64::new(1),
+            history: Mutex::new(VecDeque::with_capacity(replay_capacity.max(1))),
+            replay_capacity: replay_capacity.max(1),
         }
     }
 
-    /// Try to subscribe a new client. Returns None if max clients reached.
-    pub fn subscribe(&self) -> Option<SseClient> {
-        let current = self.client_count.load(Ordering::Relaxed);
-        if current >= self.max_clients {
-            return None;
+    /// Try to subscribe a new client to a given tenant's stream. Returns
+    /// None if max clients reached. `tenant_id` becomes the base
+    /// `SseFilter` (see `SseClient::filter`/`SseFilter::for_tenant`) that
+    /// every frame this client receives is checked against, before any
+    /// further `session_id`/`agent_type`/`event_type` narrowing it applies
+    /// on top.
+    ///
+    /// The admission check and the increment happen in one
+    /// `compare_exchange` loop rather than a separate `load` + `fetch_add`,
+    /// so two connections racing in at once can't both read "one under the
+    /// limit" and together push the count past `max_clients`.
+    pub fn subscribe(&self, tenant_id: impl Into<String>) -> Option<SseClient> {
+        let mut current = self.client_count.load(Ordering::Relaxed);
+        loop {
+            if current >= self.max_clients {
+                return None;
+            }
+            match self.client_count.compare_exchange_weak(
+                current,
+                current + 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(observed) => current = observed,
+            }
         }
-        self.client_count.fetch_add(1, Ordering::Relaxed);
         let rx = self.tx.subscribe();
         Some(SseClient {
             rx,
             count: Arc::clone(&self.client_count),
+            filter: SseFilter::for_tenant(tenant_id),
         })
     }
 
-    /// Broadcast a typed message to all connected clients.
-    pub fn broadcast(&self, event_type: &str, payload: &Value) {
-        let msg = serde_json::json!({ "type": event_type, "payload": payload });
-        let formatted = format!("data: {}\n\n", msg);
+    /// Broadcast a typed message to all connected clients. Each subscriber
+    /// applies its own `SseFilter` to decide whether to forward it. Assigns
+    /// the next history id and keeps the event around for `replay_since`.
+    pub fn broadcast(&self, kind: &str, payload: &Value) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let event = BroadcastEvent::new(id, kind, payload);
+
+        {
+            let mut history = self.history.lock().unwrap();
+            if history.len() >= self.replay_capacity {
+                history.pop_front();
+            }
+            history.push_back(event.clone());
+        }
+
         // Ignore send errors — means no active receivers.
-        let _ = self.tx.send(formatted);
+        let _ = self.tx.send(event);
+    }
+
+    /// Buffered broadcasts with `id` greater than `since_id`, oldest first.
+    /// Used to replay what a client missed across a `Last-Event-ID` reconnect;
+    /// an id older than everything still buffered just replays the full history.
+    pub fn replay_since(&self, since_id: u64) -> Vec<BroadcastEvent> {
+        let history = self.history.lock().unwrap();
+        history
+            .iter()
+            .filter(|event| event.id > since_id)
+            .cloned()
+            .collect()
+    }
+
+    /// The id of the oldest broadcast still in the replay buffer, or `None`
+    /// if nothing has been broadcast yet. A reconnecting client whose
+    /// `Last-Event-ID` predates this has missed frames the buffer already
+    /// evicted — callers use this to detect that gap before replaying.
+    pub fn oldest_id(&self) -> Option<u64> {
+        self.history.lock().unwrap().front().map(|event| event.id)
     }
 
     /// Current number of connected SSE clients.
@@ -53,13 +232,18 @@ impl SseHub {
 
 /// A client subscription. Call `into_parts()` to get the receiver and drop guard separately.
 pub struct SseClient {
-    rx: broadcast::Receiver<String>,
+    rx: broadcast::Receiver<BroadcastEvent>,
     count: Arc<AtomicUsize>,
+    /// The tenant-scoped base filter from `SseHub::subscribe` — callers
+    /// clone this and layer their own `session_id`/`agent_type`/`event_type`
+    /// narrowing on top rather than building a filter from scratch, so the
+    /// tenant can't accidentally be dropped along the way.
+    pub filter: SseFilter,
 }
 
 impl SseClient {
     /// Split into the broadcast receiver and a drop guard that decrements the count.
-    pub fn into_parts(self) -> (broadcast::Receiver<String>, SseDropGuard) {
+    pub fn into_parts(self) -> (broadcast::Receiver<BroadcastEvent>, SseDropGuard) {
         let guard = SseDropGuard { count: Arc::clone(&self.count) };
         // Use ManuallyDrop to avoid running Drop on self (which would double-decrement).
         let this = std::mem::ManuallyDrop::new(self);
@@ -85,3 +269,55 @@ impl Drop for SseDropGuard {
         self.count.fetch_sub(1, Ordering::Relaxed);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(tenant_id: &str) -> BroadcastEvent {
+        BroadcastEvent::new(
+            1,
+            "event",
+            &serde_json::json!({
+                "tenant_id": tenant_id,
+                "session_id": "sess-1",
+                "agent_type": "claude_code",
+                "event_type": "tool_use",
+            }),
+        )
+    }
+
+    #[test]
+    fn matches_rejects_cross_tenant_event_even_with_no_other_filter() {
+        let filter = SseFilter::for_tenant("tenant-a");
+        assert!(!event("tenant-b").matches(&filter));
+    }
+
+    #[test]
+    fn matches_rejects_cross_tenant_event_when_every_other_field_matches() {
+        let filter = SseFilter {
+            tenant_id: "tenant-a".to_string(),
+            session_id: Some("sess-1".to_string()),
+            agent_type: Some("claude_code".to_string()),
+            event_type: Some("tool_use".to_string()),
+        };
+        assert!(!event("tenant-b").matches(&filter));
+    }
+
+    #[test]
+    fn matches_accepts_same_tenant_event() {
+        let filter = SseFilter::for_tenant("tenant-a");
+        assert!(event("tenant-a").matches(&filter));
+    }
+
+    #[test]
+    fn matches_respects_narrowing_within_the_same_tenant() {
+        let filter = SseFilter {
+            tenant_id: "tenant-a".to_string(),
+            session_id: Some("sess-other".to_string()),
+            agent_type: None,
+            event_type: None,
+        };
+        assert!(!event("tenant-a").matches(&filter));
+    }
+}
@@ -1,31 +1,199 @@
-use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
 
-use rusqlite::Connection;
-use tokio::sync::Mutex;
+use tokio::sync::{Notify, RwLock};
 
+use crate::auth::AuthStore;
+use crate::cluster::Broadcasting;
 use crate::config::Config;
+use crate::db::pool::{DbPools, PoolError, PooledConn};
+use crate::db::store::Store;
+use crate::ingest_metrics::IngestCounters;
+use crate::notifier::Notifier;
+use crate::otel::cumulative_store::{CumulativeStore, InMemoryCumulativeStore};
+use crate::relay::{self, UpstreamStatus};
+use crate::runtime_settings::RuntimeSettings;
 use crate::sse::hub::SseHub;
+use crate::task_supervisor::TaskHealthMap;
 
 /// Shared application state accessible from all route handlers.
 pub struct AppState {
-    pub db: Mutex<Connection>,
-    pub otel_cumulative_state: Mutex<HashMap<String, f64>>,
+    pub db: DbPools,
+    pub otel_cumulative_state: Box<dyn CumulativeStore>,
     pub config: Config,
     pub start_time: Instant,
     pub sse_hub: SseHub,
+    pub auth: AuthStore,
+    /// Set after startup by `runtime_host` once the (optional) NATS
+    /// connection succeeds — starts empty like `auth`, which is likewise
+    /// populated post-construction via `reload`.
+    nats: RwLock<Option<async_nats::Client>>,
+    /// Set after startup by `runtime_host` once the (optional) MQTT
+    /// connection is established — same dormant-until-populated shape as
+    /// `nats`.
+    mqtt: RwLock<Option<rumqttc::AsyncClient>>,
+    /// Flips to `true` once `runtime_host` has bound the listener and the
+    /// server is actually serving — surfaced via `/api/health` and, when the
+    /// `systemd` feature is enabled, gates the `READY=1` notification.
+    ready: AtomicBool,
+    /// One entry per `Config::upstreams`, built at construction time so it
+    /// exists before `runtime_host` spawns the task that actually connects
+    /// each one — see `relay::spawn_upstream_tasks`. Empty when relay mode
+    /// isn't configured.
+    pub relay_status: Vec<Arc<UpstreamStatus>>,
+    /// In-process ingest throughput counters, surfaced as
+    /// `agentmonitor_events_*`/`agentmonitor_payloads_truncated_total` by
+    /// `api::metrics::metrics_handler`. See `ingest_metrics`.
+    pub ingest_counters: IngestCounters,
+    /// Per-job health for every `task_supervisor::spawn`-managed background
+    /// loop in `runtime_host`, keyed by job name. See `task_supervisor`.
+    pub task_health: TaskHealthMap,
+    /// Woken by every call site that successfully persists a new event
+    /// (ingest, relay, NATS, auto-import), so `api::events::poll_events` can
+    /// park a long-poll request instead of busy-looping until something
+    /// shows up. A notification with no parked waiter is just dropped, same
+    /// as `sse_hub`'s broadcast channel.
+    pub notify_new_events: Notify,
+    /// The `db::Store` backend for `Config::database_url`, when set —
+    /// `None` means this process has no Postgres configured and every
+    /// handler keeps reading/writing through `db` like before this existed.
+    /// See the scope note on `db::store::Store` for which call sites
+    /// dispatch through this versus `db` directly.
+    pub remote_store: Option<Arc<dyn Store>>,
+    /// Outbound alerting sinks built from `Config::notifier` — see
+    /// `runtime_tasks::run_idle_check_once`/`run_stats_broadcast_once` for
+    /// the two places an alert can fire from.
+    pub notifier: Notifier,
+    /// Idle timeout and stats broadcast interval, live-tunable through the
+    /// management API (`api::runtime_admin`) instead of fixed at startup
+    /// like the rest of `Config`. See `runtime_settings`.
+    pub runtime_settings: RuntimeSettings,
+    /// Cluster peer forwarding for `stats`/`session_update` broadcasts —
+    /// see `cluster`. Empty `Config::peer_urls` makes this a no-op.
+    pub cluster: Broadcasting,
 }
 
 impl AppState {
-    pub fn new(db: Connection, config: Config) -> Arc<Self> {
-        let sse_hub = SseHub::new(config.max_sse_clients);
+    pub fn new(db: DbPools, config: Config) -> Arc<Self> {
+        let sse_hub = SseHub::new(config.max_sse_clients, config.sse_replay_buffer);
+        let relay_status = relay::build_statuses(&config);
+        let notifier = Notifier::from_config(&config.notifier);
+        let runtime_settings = RuntimeSettings::new(&config);
+        let cluster = Broadcasting::new(&config);
+        // Building a `SqliteStore` here would open a second, independent
+        // connection to `db_path` (or, for `:memory:`, a private database
+        // the `db` pools above can't see) — so `remote_store` only ever
+        // gets populated for Postgres, where `PostgresStore` owning its own
+        // pool is exactly the point.
+        let remote_store: Option<Arc<dyn Store>> = config.database_url.as_deref().and_then(|url| {
+            match crate::db::postgres::PostgresStore::connect(url) {
+                Ok(store) => Some(Arc::new(store) as Arc<dyn Store>),
+                Err(err) => {
+                    tracing::error!("Postgres store: connection setup failed, falling back to {:?}: {err}", config.db_path);
+                    None
+                }
+            }
+        });
         Arc::new(Self {
-            db: Mutex::new(db),
-            otel_cumulative_state: Mutex::new(HashMap::new()),
+            db,
+            otel_cumulative_state: Box::new(InMemoryCumulativeStore::new()),
             config,
             start_time: Instant::now(),
             sse_hub,
+            auth: AuthStore::empty(),
+            nats: RwLock::new(None),
+            mqtt: RwLock::new(None),
+            ready: AtomicBool::new(false),
+            relay_status,
+            ingest_counters: IngestCounters::default(),
+            task_health: TaskHealthMap::default(),
+            notify_new_events: Notify::new(),
+            remote_store,
+            notifier,
+            runtime_settings,
+            cluster,
         })
     }
+
+    /// Mark the server as ready (or not) for traffic. Called by
+    /// `runtime_host` once the listener is bound and serving.
+    pub fn set_ready(&self, ready: bool) {
+        self.ready.store(ready, Ordering::Relaxed);
+    }
+
+    /// Whether the server is ready for traffic yet.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Relaxed)
+    }
+
+    /// Record the connected NATS client so handlers can republish accepted
+    /// events. A no-op until `runtime_host` calls this during startup.
+    pub async fn set_nats_client(&self, client: async_nats::Client) {
+        *self.nats.write().await = Some(client);
+    }
+
+    /// The current NATS client handle, if the integration is connected.
+    /// Cloning an `async_nats::Client` is cheap — it's a handle onto the
+    /// shared connection, not a new socket.
+    pub async fn nats_client(&self) -> Option<async_nats::Client> {
+        self.nats.read().await.clone()
+    }
+
+    /// Record the connected MQTT client so `runtime_tasks` can mirror
+    /// broadcasts onto it. A no-op until `runtime_host` calls this during
+    /// startup. See `nats_client`.
+    pub async fn set_mqtt_client(&self, client: rumqttc::AsyncClient) {
+        *self.mqtt.write().await = Some(client);
+    }
+
+    /// The current MQTT client handle, if the integration is connected.
+    /// Cloning an `AsyncClient` is cheap — it's a handle onto the shared
+    /// connection, not a new socket.
+    pub async fn mqtt_client(&self) -> Option<rumqttc::AsyncClient> {
+        self.mqtt.read().await.clone()
+    }
+
+    /// Check out a read-only pooled connection. Analytics queries go through
+    /// here so a long scan never contends with `write_conn` for SQLite's
+    /// single writer lock — see `db::pool::DbPools`.
+    pub fn read_conn(&self) -> Result<PooledConn, r2d2::Error> {
+        self.db.reader.get()
+    }
+
+    /// Check out the (single) writer connection. See `read_conn`.
+    pub fn write_conn(&self) -> Result<PooledConn, r2d2::Error> {
+        self.db.writer.get()
+    }
+
+    /// Like `read_conn`, but checks out the connection on a blocking-pool
+    /// thread instead of the caller's tokio worker thread. A bounded reader
+    /// pool (`Config::max_read_pool_size`) still makes a caller wait when
+    /// every connection is checked out, but that wait now parks a blocking
+    /// thread rather than one of the async runtime's worker threads, so
+    /// other requests keep making progress instead of queueing behind it.
+    /// HTTP handlers should prefer this over `read_conn`; `read_conn` still
+    /// exists for the handful of call sites that already run off the async
+    /// runtime (timers, CLI tools).
+    pub async fn read_conn_blocking(&self) -> Result<PooledConn, PoolError> {
+        let pool = self.db.reader.clone();
+        tokio::task::spawn_blocking(move || pool.get())
+            .await
+            .map_err(PoolError::Join)?
+            .map_err(PoolError::Checkout)
+    }
+
+    /// Like `write_conn`, but checks out the single writer connection on a
+    /// blocking-pool thread instead of the caller's tokio worker thread —
+    /// the same reasoning as `read_conn_blocking`, applied to the writer
+    /// pool's one connection, so a request that arrives while another write
+    /// is in flight waits on a blocking thread instead of stalling the async
+    /// runtime. See `db::pool::DbPools`.
+    pub async fn write_conn_blocking(&self) -> Result<PooledConn, PoolError> {
+        let pool = self.db.writer.clone();
+        tokio::task::spawn_blocking(move || pool.get())
+            .await
+            .map_err(PoolError::Join)?
+            .map_err(PoolError::Checkout)
+    }
 }
@@ -0,0 +1,77 @@
+//! Optional systemd readiness/watchdog integration, compiled in only under
+//! the `systemd` feature and active only when `AGENTMONITOR_SYSTEMD_NOTIFY`
+//! is set (see `Config::systemd_notify`). Lets a unit run `Type=notify` so
+//! the manager knows when the server is actually accepting connections,
+//! rather than guessing from process start.
+//!
+//! All notifications are best-effort: `sd_notify::notify` itself already
+//! no-ops when `NOTIFY_SOCKET` isn't set (i.e. not running under systemd),
+//! so callers don't need to check for that separately.
+
+use std::time::Duration;
+
+use tracing::warn;
+
+/// Tell the service manager the server is bound and serving.
+pub fn notify_ready() {
+    if let Err(err) = sd_notify::notify(false, &[sd_notify::NotifyState::Ready]) {
+        warn!("systemd: READY notification failed: {err}");
+    }
+}
+
+/// Tell the service manager a config-driven restart is in progress. This
+/// repo's only restart path is `RuntimeHost::stop()` followed by a fresh
+/// `start_with_config` call, so `stop()` sends this and the next
+/// `start_with_config` sends `notify_ready` again once it's back up.
+pub fn notify_reloading() {
+    if let Err(err) = sd_notify::notify(false, &[sd_notify::NotifyState::Reloading]) {
+        warn!("systemd: RELOADING notification failed: {err}");
+    }
+}
+
+/// Send a single `WATCHDOG=1` ping.
+pub fn send_watchdog_ping() {
+    if let Err(err) = sd_notify::notify(false, &[sd_notify::NotifyState::Watchdog]) {
+        warn!("systemd: WATCHDOG notification failed: {err}");
+    }
+}
+
+/// How often to ping the watchdog, derived from `WATCHDOG_USEC` (set by
+/// systemd when the unit has `WatchdogSec=` configured). Pings at half the
+/// configured interval, the safety margin systemd's own docs recommend, so a
+/// single slow tick doesn't trip the watchdog. Returns `None` if the
+/// watchdog isn't enabled for this unit.
+pub fn watchdog_interval() -> Option<Duration> {
+    let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    if usec == 0 {
+        return None;
+    }
+    Some(Duration::from_micros(usec) / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watchdog_interval_is_none_without_env_var() {
+        // SAFETY: single-threaded test, no other test in this module reads
+        // or writes WATCHDOG_USEC.
+        unsafe {
+            std::env::remove_var("WATCHDOG_USEC");
+        }
+        assert!(watchdog_interval().is_none());
+    }
+
+    #[test]
+    fn watchdog_interval_halves_the_configured_microseconds() {
+        // SAFETY: see above.
+        unsafe {
+            std::env::set_var("WATCHDOG_USEC", "2000000");
+        }
+        assert_eq!(watchdog_interval(), Some(Duration::from_secs(1)));
+        unsafe {
+            std::env::remove_var("WATCHDOG_USEC");
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Supervises `runtime_host`'s periodic background jobs (stats broadcast,
+//! idle-session sweep, auto-import). Those used to be bare `tokio::spawn`
+//! loops that swallowed every error with `let _ = ...` and, if the closure
+//! ever panicked, died permanently with nothing left running it again.
+//! [`spawn`] instead runs each tick's body inside its own inner `JoinHandle`
+//! and, on `JoinError::is_panic()`, restarts it with exponential backoff —
+//! and every run, successful or not, is recorded on
+//! [`crate::state::AppState::task_health`] so a future health handler can
+//! see which jobs are actually alive.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::error;
+
+use crate::runtime_host::sleep_or_shutdown;
+use crate::state::AppState;
+
+/// Backoff applied after a panicking run before it's retried, doubling from
+/// here up to [`MAX_BACKOFF`].
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Cap on the panic-restart backoff — a job stuck panicking every tick
+/// forever still gets retried at least once a minute rather than backing
+/// off indefinitely.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Point-in-time health for one supervised job, keyed by name in
+/// [`crate::state::AppState::task_health`].
+#[derive(Debug, Clone, Default)]
+pub struct TaskHealth {
+    pub last_run: Option<DateTime<Utc>>,
+    pub last_success: Option<DateTime<Utc>>,
+    pub consecutive_failures: u32,
+    pub total_runs: u64,
+    /// Duration of the most recently completed run, successful or not —
+    /// surfaced as `agentmonitor_task_last_run_duration_ms` so a stalled or
+    /// slow-creeping job shows up before it starts missing its interval.
+    pub last_duration_ms: u64,
+    /// Running sum of every run's duration, paired with `total_runs` to
+    /// derive an average in `api::metrics` without keeping a histogram.
+    pub total_duration_ms: u64,
+}
+
+pub type TaskHealthMap = Mutex<HashMap<&'static str, TaskHealth>>;
+
+/// Records one run of `name` — called from [`run_supervised_tick`] whether
+/// the run completed normally or panicked.
+pub fn record_run(health: &TaskHealthMap, name: &'static str, succeeded: bool, duration: Duration) {
+    let mut health = health.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let entry = health.entry(name).or_default();
+    entry.total_runs += 1;
+    entry.last_run = Some(Utc::now());
+    entry.last_duration_ms = duration.as_millis() as u64;
+    entry.total_duration_ms += entry.last_duration_ms;
+    if succeeded {
+        entry.last_success = Some(Utc::now());
+        entry.consecutive_failures = 0;
+    } else {
+        entry.consecutive_failures += 1;
+    }
+}
+
+/// Like [`spawn`], but waits `initial_delay` (instead of `interval`) before
+/// the first run — `runtime_host`'s auto-import job wants a short fixed
+/// delay after startup rather than waiting out its whole multi-minute
+/// interval before ever running once.
+pub fn spawn_with_initial_delay<F, Fut>(
+    name: &'static str,
+    state: Arc<AppState>,
+    initial_delay: Duration,
+    interval: Duration,
+    mut shutdown_rx: watch::Receiver<bool>,
+    body: F,
+) -> JoinHandle<()>
+where
+    F: Fn(Arc<AppState>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let body = Arc::new(body);
+    tokio::spawn(async move {
+        if sleep_or_shutdown(initial_delay, &mut shutdown_rx).await {
+            return;
+        }
+        run_supervised_tick(name, &state, &body, &mut shutdown_rx).await;
+
+        loop {
+            if sleep_or_shutdown(interval, &mut shutdown_rx).await {
+                break;
+            }
+            if run_supervised_tick(name, &state, &body, &mut shutdown_rx).await {
+                return;
+            }
+        }
+    })
+}
+
+/// Spawns `body` as a supervised periodic job named `name`: waits `interval`,
+/// then runs `body(state)` to completion inside its own inner `JoinHandle`
+/// every tick. A panicking run is restarted immediately (after the current
+/// backoff) rather than waiting for the next `interval` tick — a job that
+/// just crashed generally needs retrying in seconds, not whenever its next
+/// scheduled tick happens to land — with the backoff doubling on each
+/// consecutive panic up to [`MAX_BACKOFF`] and resetting back to
+/// [`INITIAL_BACKOFF`] once a run completes without panicking.
+pub fn spawn<F, Fut>(
+    name: &'static str,
+    state: Arc<AppState>,
+    interval: Duration,
+    mut shutdown_rx: watch::Receiver<bool>,
+    body: F,
+) -> JoinHandle<()>
+where
+    F: Fn(Arc<AppState>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let body = Arc::new(body);
+    tokio::spawn(async move {
+        loop {
+            if sleep_or_shutdown(interval, &mut shutdown_rx).await {
+                break;
+            }
+            if run_supervised_tick(name, &state, &body, &mut shutdown_rx).await {
+                return;
+            }
+        }
+    })
+}
+
+/// Like [`spawn`], but re-reads the wait duration from `interval_fn(&state)`
+/// before every tick instead of capturing a fixed `Duration` once — lets a
+/// job's cadence be retuned live through shared state (e.g.
+/// `AppState::runtime_settings`) rather than only at startup.
+pub fn spawn_dynamic<F, Fut, I>(
+    name: &'static str,
+    state: Arc<AppState>,
+    interval_fn: I,
+    mut shutdown_rx: watch::Receiver<bool>,
+    body: F,
+) -> JoinHandle<()>
+where
+    F: Fn(Arc<AppState>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+    I: Fn(&AppState) -> Duration + Send + Sync + 'static,
+{
+    let body = Arc::new(body);
+    tokio::spawn(async move {
+        loop {
+            let interval = interval_fn(&state);
+            if sleep_or_shutdown(interval, &mut shutdown_rx).await {
+                break;
+            }
+            if run_supervised_tick(name, &state, &body, &mut shutdown_rx).await {
+                return;
+            }
+        }
+    })
+}
+
+/// One supervised invocation of `body(state)`, including the panic-restart
+/// backoff loop. Returns `true` if the shutdown signal fired while waiting
+/// out a backoff (the caller should stop rather than scheduling another
+/// tick), `false` otherwise.
+async fn run_supervised_tick<F, Fut>(
+    name: &'static str,
+    state: &Arc<AppState>,
+    body: &Arc<F>,
+    shutdown_rx: &mut watch::Receiver<bool>,
+) -> bool
+where
+    F: Fn(Arc<AppState>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let mut backoff = INITIAL_BACKOFF;
+    loop {
+        let run_state = Arc::clone(state);
+        let run_body = Arc::clone(body);
+        let started_at = tokio::time::Instant::now();
+        let result = tokio::spawn(async move { run_body(run_state).await }).await;
+        record_run(&state.task_health, name, result.is_ok(), started_at.elapsed());
+
+        match result {
+            Ok(()) => return false,
+            Err(join_err) if join_err.is_panic() => {
+                error!("background task '{name}' panicked, restarting in {backoff:?}");
+                if sleep_or_shutdown(backoff, shutdown_rx).await {
+                    return true;
+                }
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+            // Cancelled, not panicked — only happens during shutdown.
+            Err(_) => return true,
+        }
+    }
+}
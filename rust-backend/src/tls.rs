@@ -0,0 +1,63 @@
+//! Loads a PEM certificate chain + private key into a `rustls::ServerConfig`
+//! for `runtime_host`'s optional TLS listener. Kept separate from
+//! `runtime_host` so the cert/key parsing (and its own error cases) don't
+//! clutter the accept-loop code that actually uses the result.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+use std::sync::Arc;
+
+use rustls_pemfile::{certs, private_key};
+
+#[derive(Debug)]
+pub enum TlsConfigError {
+    Io(io::Error),
+    NoCertificates,
+    NoPrivateKey,
+    Rustls(rustls::Error),
+}
+
+impl fmt::Display for TlsConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read TLS cert/key file: {err}"),
+            Self::NoCertificates => write!(f, "TLS cert file contained no certificates"),
+            Self::NoPrivateKey => write!(f, "TLS key file contained no private key"),
+            Self::Rustls(err) => write!(f, "failed to build TLS server config: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for TlsConfigError {}
+
+/// Load `cert_path`'s PEM certificate chain and `key_path`'s PEM private key
+/// into a `rustls::ServerConfig` for `runtime_host::start_with_config`'s TLS
+/// listener. Both files are read in full on startup rather than watched —
+/// picking up a renewed cert means restarting the process, same as every
+/// other `Config` field.
+pub fn load_server_config(
+    cert_path: &Path,
+    key_path: &Path,
+) -> Result<Arc<rustls::ServerConfig>, TlsConfigError> {
+    let cert_file = File::open(cert_path).map_err(TlsConfigError::Io)?;
+    let cert_chain = certs(&mut BufReader::new(cert_file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(TlsConfigError::Io)?;
+    if cert_chain.is_empty() {
+        return Err(TlsConfigError::NoCertificates);
+    }
+
+    let key_file = File::open(key_path).map_err(TlsConfigError::Io)?;
+    let private_key = private_key(&mut BufReader::new(key_file))
+        .map_err(TlsConfigError::Io)?
+        .ok_or(TlsConfigError::NoPrivateKey)?;
+
+    let config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(cert_chain, private_key)
+        .map_err(TlsConfigError::Rustls)?;
+
+    Ok(Arc::new(config))
+}
@@ -0,0 +1,2 @@
+pub mod chunking;
+pub mod truncate;
@@ -0,0 +1,131 @@
+//! Content-defined chunking (CDC) for splitting a byte stream into
+//! variable-length, dedup-friendly pieces. `db::queries` uses this to
+//! persist oversized event metadata as a set of content-addressed chunks
+//! instead of discarding it the way `truncate_metadata` does — see
+//! `payload_chunks`.
+//!
+//! Boundaries are found with a rolling hash over the last
+//! [`WINDOW_SIZE`] bytes: a position is a boundary once `hash &
+//! BOUNDARY_MASK == BOUNDARY_MASK`, which — for a well-mixed hash — lands on
+//! average every `BOUNDARY_MASK + 1` bytes. [`MIN_CHUNK_SIZE`] and
+//! [`MAX_CHUNK_SIZE`] clamp the variance so a pathological input (long runs
+//! of the same byte, one huge field) can't produce a degenerate chunk
+//! count either way.
+
+use blake3::Hasher;
+
+/// Width of the rolling hash's sliding window.
+const WINDOW_SIZE: usize = 64;
+/// 13 one-bits gives an average chunk size of 2^13 = 8 KiB.
+const BOUNDARY_MASK: u64 = (1 << 13) - 1;
+const MIN_CHUNK_SIZE: usize = 2 * 1024;
+const MAX_CHUNK_SIZE: usize = 64 * 1024;
+/// Odd multiplier for the rolling polynomial hash — any odd 64-bit constant
+/// works, this one is just a widely-used hash multiplier.
+const MULTIPLIER: u64 = 0x517c_c1b7_2722_0a95;
+
+/// One content-defined chunk: its bytes and their blake3 hex digest, which
+/// doubles as the dedup key in `payload_chunks`.
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub content_hash: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Splits `data` into content-defined chunks covering it exactly once, in
+/// order — concatenating every chunk's `bytes` reconstructs `data`.
+/// Identical input bytes anywhere (even across separate calls) always
+/// produce chunks with the same `content_hash`, which is what lets
+/// `payload_chunks` store each one only once.
+pub fn chunk(data: &[u8]) -> Vec<Chunk> {
+    if data.is_empty() {
+        return Vec::new();
+    }
+
+    let multiplier_pow_window = MULTIPLIER.wrapping_pow(WINDOW_SIZE as u32);
+    let mut chunks = Vec::new();
+    let mut start = 0usize;
+    let mut hash: u64 = 0;
+
+    for (i, &byte) in data.iter().enumerate() {
+        hash = hash.wrapping_mul(MULTIPLIER).wrapping_add(byte as u64);
+        if i >= WINDOW_SIZE {
+            let outgoing = data[i - WINDOW_SIZE] as u64;
+            hash = hash.wrapping_sub(outgoing.wrapping_mul(multiplier_pow_window));
+        }
+
+        let chunk_len = i + 1 - start;
+        let at_boundary = chunk_len >= MIN_CHUNK_SIZE && (hash & BOUNDARY_MASK) == BOUNDARY_MASK;
+        if at_boundary || chunk_len >= MAX_CHUNK_SIZE {
+            chunks.push(make_chunk(&data[start..=i]));
+            start = i + 1;
+        }
+    }
+
+    if start < data.len() {
+        chunks.push(make_chunk(&data[start..]));
+    }
+
+    chunks
+}
+
+/// Reassembles chunks produced by [`chunk`] (or any bytes in the same
+/// order) back into the original stream.
+pub fn reassemble(chunks: &[Chunk]) -> Vec<u8> {
+    chunks.iter().flat_map(|c| c.bytes.iter().copied()).collect()
+}
+
+fn make_chunk(bytes: &[u8]) -> Chunk {
+    Chunk {
+        content_hash: Hasher::new().update(bytes).finalize().to_hex().to_string(),
+        bytes: bytes.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_input_produces_no_chunks() {
+        assert!(chunk(&[]).is_empty());
+    }
+
+    #[test]
+    fn chunks_reassemble_to_the_original_input() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let chunks = chunk(&data);
+        assert!(chunks.len() > 1);
+        assert_eq!(reassemble(&chunks), data);
+    }
+
+    #[test]
+    fn every_chunk_respects_the_size_bounds_except_a_trailing_remainder() {
+        let data: Vec<u8> = (0..500_000u32).map(|i| (i % 197) as u8).collect();
+        let chunks = chunk(&data);
+        for (i, c) in chunks.iter().enumerate() {
+            assert!(c.bytes.len() <= MAX_CHUNK_SIZE);
+            if i + 1 < chunks.len() {
+                assert!(c.bytes.len() >= MIN_CHUNK_SIZE);
+            }
+        }
+    }
+
+    #[test]
+    fn identical_runs_produce_identical_chunk_hashes() {
+        let repeated = "the quick brown fox jumps over the lazy dog ".repeat(1000);
+        let a = chunk(repeated.as_bytes());
+        let b = chunk(repeated.as_bytes());
+        let hashes_a: Vec<&str> = a.iter().map(|c| c.content_hash.as_str()).collect();
+        let hashes_b: Vec<&str> = b.iter().map(|c| c.content_hash.as_str()).collect();
+        assert_eq!(hashes_a, hashes_b);
+    }
+
+    #[test]
+    fn a_small_input_is_a_single_chunk() {
+        let data = b"short metadata payload";
+        let chunks = chunk(data);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].bytes, data);
+    }
+}
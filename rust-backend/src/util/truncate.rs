@@ -1,5 +1,13 @@
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use serde_json::{Map, Value};
 
+use crate::util::chunking::{self, Chunk};
+
+/// zstd compression level for oversized metadata — favors speed over ratio
+/// since this runs inline on the ingest path.
+const COMPRESSION_LEVEL: i32 = 3;
+
 /// Priority keys to preserve when building a truncated metadata summary.
 /// Mirrors METADATA_PRIORITY_KEYS in TypeScript queries.ts.
 const PRIORITY_KEYS: &[&str] = &[
@@ -19,6 +27,71 @@ pub struct TruncateResult {
     pub truncated: bool,
 }
 
+/// Marker stored in `events.metadata` for a row whose real payload lives in
+/// `payload_chunks` instead — `queries::reassemble_chunked_metadata` reverses
+/// it given the event's id. Unlike `inflate_metadata`'s compressed wrapper,
+/// this can't be reversed from the string alone since the chunks live in
+/// another table.
+pub const CHUNKED_PAYLOAD_MARKER: &str = r#"{"_chunked_payload":true}"#;
+
+/// Either metadata small enough to store inline (same as `truncate_metadata`
+/// would produce), or metadata that overflowed `max_payload_kb` and was
+/// split into content-defined chunks instead of truncated. Produced by
+/// [`store_or_truncate_metadata`] when chunked storage is enabled.
+pub enum MetadataStorage {
+    Inline(TruncateResult),
+    Chunked(Vec<Chunk>),
+}
+
+impl MetadataStorage {
+    /// The string to put in the `events.metadata` column — the inline value,
+    /// or [`CHUNKED_PAYLOAD_MARKER`] for a chunked payload.
+    pub fn column_value(&self) -> &str {
+        match self {
+            MetadataStorage::Inline(result) => &result.value,
+            MetadataStorage::Chunked(_) => CHUNKED_PAYLOAD_MARKER,
+        }
+    }
+
+    /// Whether `events.payload_truncated` should be set — true both for an
+    /// actually-truncated inline value and for a chunked payload, since
+    /// either way the `metadata` column alone isn't the real payload.
+    pub fn payload_truncated(&self) -> bool {
+        match self {
+            MetadataStorage::Inline(result) => result.truncated,
+            MetadataStorage::Chunked(_) => true,
+        }
+    }
+}
+
+/// Like [`truncate_metadata`], but when `store_large` is true and the
+/// serialized metadata overflows `max_payload_kb`, splits it into
+/// content-defined chunks (see `util::chunking`) instead of discarding
+/// anything — the caller is expected to persist the chunks (via
+/// `queries::persist_chunked_metadata`) once the event row they belong to
+/// exists. Metadata that already fits inline never gets chunked, same as a
+/// small payload is never truncated.
+pub fn store_or_truncate_metadata(metadata: &Value, max_payload_kb: usize, store_large: bool) -> MetadataStorage {
+    if !store_large {
+        return MetadataStorage::Inline(truncate_metadata(metadata, max_payload_kb));
+    }
+
+    let max_bytes = max_payload_kb * 1024;
+    let serialized = match metadata {
+        Value::String(s) => s.clone(),
+        other => serde_json::to_string(other).unwrap_or_else(|_| r#"{"_serialization_error":true}"#.into()),
+    };
+
+    if serialized.len() <= max_bytes {
+        return MetadataStorage::Inline(TruncateResult {
+            value: serialized,
+            truncated: false,
+        });
+    }
+
+    MetadataStorage::Chunked(chunking::chunk(serialized.as_bytes()))
+}
+
 /// UTF-8 safe byte truncation — mirrors TypeScript utf8SliceByBytes().
 /// Slices the string at the last valid char boundary that fits within max_bytes.
 fn utf8_slice_by_bytes(input: &str, max_bytes: usize) -> &str {
@@ -62,6 +135,49 @@ fn build_truncated_generic_summary(original_bytes: usize) -> String {
     .unwrap()
 }
 
+/// Try a reversible compressed form of `serialized` before falling back to
+/// the lossy priority-key summary. Returns `None` if zstd compression still
+/// doesn't fit within `max_bytes` (or fails), so the caller can fall through.
+fn try_compress(serialized: &str, max_bytes: usize) -> Option<String> {
+    let compressed = zstd::stream::encode_all(serialized.as_bytes(), COMPRESSION_LEVEL).ok()?;
+    let wrapper = serde_json::json!({
+        "_compressed": "zstd",
+        "_encoding": "base64",
+        "_original_bytes": serialized.len(),
+        "data": BASE64.encode(&compressed),
+    });
+    let wrapped = serde_json::to_string(&wrapper).ok()?;
+    (wrapped.len() <= max_bytes).then_some(wrapped)
+}
+
+/// Reverse the wrapper `try_compress` produces, returning the original
+/// metadata value. Anything that isn't a recognized compressed wrapper
+/// (including plain JSON or the lossy priority-key summary) is parsed and
+/// returned as-is — callers don't need to know which shape they're holding.
+pub fn inflate_metadata(value: &str) -> Value {
+    let parsed: Value = match serde_json::from_str(value) {
+        Ok(v) => v,
+        Err(_) => return Value::String(value.to_string()),
+    };
+
+    let is_zstd_base64 = parsed.get("_compressed").and_then(Value::as_str) == Some("zstd")
+        && parsed.get("_encoding").and_then(Value::as_str) == Some("base64");
+    if !is_zstd_base64 {
+        return parsed;
+    }
+
+    let Some(data) = parsed.get("data").and_then(Value::as_str) else {
+        return parsed;
+    };
+    let Ok(compressed) = BASE64.decode(data) else {
+        return parsed;
+    };
+    let Ok(decompressed) = zstd::stream::decode_all(&compressed[..]) else {
+        return parsed;
+    };
+    serde_json::from_slice(&decompressed).unwrap_or(parsed)
+}
+
 /// Truncate metadata to fit within max_payload_kb, mirroring TypeScript truncateMetadata().
 pub fn truncate_metadata(metadata: &Value, max_payload_kb: usize) -> TruncateResult {
     let max_bytes = max_payload_kb * 1024;
@@ -93,6 +209,15 @@ pub fn truncate_metadata(metadata: &Value, max_payload_kb: usize) -> TruncateRes
         };
     }
 
+    // Prefer a reversible compressed form over the lossy summary below —
+    // only fall back to dropping fields if even compressed it doesn't fit.
+    if let Some(compressed) = try_compress(&serialized, max_bytes) {
+        return TruncateResult {
+            value: compressed,
+            truncated: true,
+        };
+    }
+
     // Build summary
     let summary = if let Value::Object(obj) = metadata {
         build_truncated_object_summary(obj, byte_len)
@@ -174,4 +299,51 @@ mod tests {
         assert!(result.truncated);
         assert!(result.value.is_empty());
     }
+
+    #[test]
+    fn oversized_but_compressible_metadata_round_trips_losslessly() {
+        // Highly repetitive, so zstd easily shrinks it under a 1 KB budget
+        // even though the raw JSON is much larger.
+        let mut obj = serde_json::Map::new();
+        obj.insert("command".into(), json!("important-cmd"));
+        obj.insert("big_field".into(), json!("x".repeat(5000)));
+        let meta = Value::Object(obj);
+
+        let result = truncate_metadata(&meta, 1);
+        assert!(result.truncated);
+
+        let wrapper: Value = serde_json::from_str(&result.value).unwrap();
+        assert_eq!(wrapper["_compressed"], json!("zstd"));
+        assert_eq!(wrapper["_encoding"], json!("base64"));
+
+        let restored = inflate_metadata(&result.value);
+        assert_eq!(restored, meta);
+    }
+
+    #[test]
+    fn incompressible_oversized_metadata_falls_back_to_priority_summary() {
+        // Random-ish bytes that zstd can't meaningfully shrink, so even the
+        // compressed wrapper overflows the tiny budget and we fall back.
+        let mut rng_like = String::new();
+        for i in 0..4000u32 {
+            rng_like.push_str(&format!("{:x}", i.wrapping_mul(2654435761)));
+        }
+        let mut obj = serde_json::Map::new();
+        obj.insert("command".into(), json!("important-cmd"));
+        obj.insert("noise".into(), json!(rng_like));
+        let meta = Value::Object(obj);
+
+        let result = truncate_metadata(&meta, 1);
+        assert!(result.truncated);
+        let parsed: Value = serde_json::from_str(&result.value).unwrap();
+        assert_eq!(parsed["_truncated"], json!(true));
+        assert_eq!(parsed["command"], json!("important-cmd"));
+        assert!(parsed.get("_compressed").is_none());
+    }
+
+    #[test]
+    fn inflate_metadata_passes_through_plain_json() {
+        let plain = r#"{"command":"ls"}"#;
+        assert_eq!(inflate_metadata(plain), json!({"command": "ls"}));
+    }
 }
@@ -0,0 +1,121 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::body::Body;
+use http_body_util::BodyExt;
+use hyper::Request;
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+use agentmonitor_rs::config::Config;
+use agentmonitor_rs::db;
+use agentmonitor_rs::state::AppState;
+
+fn test_state() -> Arc<AppState> {
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let config = Config::from_env();
+    AppState::new(conn, config)
+}
+
+async fn request(
+    app: &axum::Router,
+    method: &str,
+    uri: &str,
+    key: Option<&str>,
+    body: Option<Value>,
+) -> (u16, Value) {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(key) = key {
+        builder = builder.header("x-api-key", key);
+    }
+    let body = match body {
+        Some(v) => {
+            builder = builder.header("content-type", "application/json");
+            Body::from(serde_json::to_vec(&v).unwrap())
+        }
+        None => Body::empty(),
+    };
+    let req = builder.body(body).unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    let status = response.status().as_u16();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let parsed: Value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, parsed)
+}
+
+#[tokio::test]
+async fn unauthenticated_requests_allowed_when_no_keys_configured() {
+    let state = test_state();
+    let app = agentmonitor_rs::build_router(state);
+
+    let (status, _) = request(
+        &app,
+        "POST",
+        "/api/events",
+        None,
+        Some(json!({"session_id": "s1", "agent_type": "claude_code", "event_type": "tool_use"})),
+    )
+    .await;
+    assert_eq!(status, 201);
+
+    let (status, _) = request(&app, "GET", "/api/stats", None, None).await;
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn write_route_rejects_missing_or_read_only_key_once_keys_exist() {
+    let state = test_state();
+
+    let (_, created) = {
+        let app = agentmonitor_rs::build_router(Arc::clone(&state));
+        request(
+            &app,
+            "POST",
+            "/api/admin/keys",
+            None,
+            Some(json!({"key": "write-key-1", "scope": "write"})),
+        )
+        .await
+    };
+    assert_eq!(created["scope"], "write");
+
+    {
+        let db = state.write_conn().expect("checkout db connection");
+        agentmonitor_rs::db::queries::create_api_key(
+            &db,
+            &agentmonitor_rs::auth::hash_key("read-key-1"),
+            None,
+            "read",
+        )
+        .expect("insert read key");
+        state.auth.reload(&db).await.expect("reload auth store");
+    }
+
+    let app = agentmonitor_rs::build_router(Arc::clone(&state));
+
+    let (status, _) = request(&app, "POST", "/api/events", None, Some(json!({}))).await;
+    assert_eq!(status, 401);
+
+    let (status, _) = request(
+        &app,
+        "POST",
+        "/api/events",
+        Some("read-key-1"),
+        Some(json!({"session_id": "s1", "agent_type": "claude_code", "event_type": "tool_use"})),
+    )
+    .await;
+    assert_eq!(status, 401);
+
+    let (status, _) = request(
+        &app,
+        "POST",
+        "/api/events",
+        Some("write-key-1"),
+        Some(json!({"session_id": "s1", "agent_type": "claude_code", "event_type": "tool_use"})),
+    )
+    .await;
+    assert_eq!(status, 201);
+
+    let (status, _) = request(&app, "GET", "/api/stats", Some("read-key-1"), None).await;
+    assert_eq!(status, 200);
+}
@@ -47,7 +47,7 @@ fn create_claude_fixture(root: &Path) {
 }
 
 fn build_state() -> Arc<AppState> {
-    let conn = db::initialize(Path::new(":memory:")).expect("in-memory DB");
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
     let config = Config::from_env();
     AppState::new(conn, config)
 }
@@ -70,13 +70,13 @@ async fn auto_import_broadcasts_session_update_when_new_events_imported() {
     .await;
     assert_eq!(result.total_events_imported, 2);
 
-    let msg = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+    let event = tokio::time::timeout(Duration::from_secs(1), rx.recv())
         .await
         .expect("expected broadcast within timeout")
         .expect("broadcast channel recv failed");
-    assert!(msg.contains("\"type\":\"session_update\""));
-    assert!(msg.contains("\"type\":\"auto_import\""));
-    assert!(msg.contains("\"imported\":2"));
+    assert_eq!(event.kind, "session_update");
+    assert_eq!(event.payload["type"], "auto_import");
+    assert_eq!(event.payload["imported"], 2);
 }
 
 #[tokio::test]
@@ -1,5 +1,6 @@
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::body::Body;
 use http_body_util::BodyExt;
@@ -11,12 +12,15 @@ use agentmonitor_rs::config::Config;
 use agentmonitor_rs::db;
 use agentmonitor_rs::state::AppState;
 
+fn test_state() -> Arc<AppState> {
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let config = Config::from_env();
+    AppState::new(conn, config)
+}
+
 /// Build a test app with an in-memory SQLite database.
 fn test_app() -> axum::Router {
-    let conn = db::initialize(Path::new(":memory:")).expect("in-memory DB");
-    let config = Config::from_env();
-    let state: Arc<AppState> = AppState::new(conn, config);
-    agentmonitor_rs::build_router(state)
+    agentmonitor_rs::build_router(test_state())
 }
 
 /// Helper: send a POST request with JSON body, return (status_code, parsed body).
@@ -35,6 +39,16 @@ async fn post_json(app: &axum::Router, uri: &str, body: Value) -> (u16, Value) {
     (status, parsed)
 }
 
+/// Helper: send a GET request, return (status_code, parsed body).
+async fn get_json(app: &axum::Router, uri: &str) -> (u16, Value) {
+    let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    let status = response.status().as_u16();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let parsed: Value = serde_json::from_slice(&bytes).unwrap();
+    (status, parsed)
+}
+
 fn valid_event() -> Value {
     json!({
         "session_id": "sess-1",
@@ -127,6 +141,14 @@ async fn batch_valid_events_returns_201() {
     assert_eq!(body["duplicates"], 0);
     assert!(body["rejected"].as_array().unwrap().is_empty());
     assert_eq!(body["ids"].as_array().unwrap().len(), 2);
+
+    assert_eq!(body["inserted_count"], 2);
+    assert_eq!(body["deduplicated_count"], 0);
+    assert_eq!(body["error_count"], 0);
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["status"], "inserted");
+    assert_eq!(results[1]["status"], "inserted");
 }
 
 #[tokio::test]
@@ -137,6 +159,86 @@ async fn batch_missing_events_key_returns_400() {
     assert_eq!(body["error"], "Expected { events: [...] }");
 }
 
+#[tokio::test]
+async fn batch_accepts_bare_array_body() {
+    let app = test_app();
+    let (status, body) = post_json(&app, "/api/events/batch", json!([
+        {"session_id": "s1", "agent_type": "claude_code", "event_type": "tool_use"},
+        {"session_id": "s2", "agent_type": "codex", "event_type": "llm_request"}
+    ])).await;
+
+    assert_eq!(status, 201);
+    assert_eq!(body["inserted_count"], 2);
+}
+
+#[tokio::test]
+async fn batch_atomic_rolls_back_entire_batch_on_any_failure() {
+    let app = test_app();
+    let (status, body) = post_json(&app, "/api/events/batch", json!({
+        "atomic": true,
+        "events": [
+            {"session_id": "s1", "agent_type": "claude_code", "event_type": "tool_use"},
+            {"bogus": true}
+        ]
+    })).await;
+
+    assert_eq!(status, 200);
+    assert_eq!(body["inserted_count"], 0);
+    assert_eq!(body["error_count"], 2);
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results[0]["status"], "error");
+    assert_eq!(results[1]["status"], "error");
+
+    // Nothing should have actually been persisted.
+    let (_, sessions) = get_json(&app, "/api/sessions").await;
+    assert!(
+        sessions["sessions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|s| s["id"] != "s1")
+    );
+}
+
+#[tokio::test]
+async fn batch_atomic_commits_when_every_item_succeeds() {
+    let app = test_app();
+    let (status, body) = post_json(&app, "/api/events/batch", json!({
+        "atomic": true,
+        "events": [
+            {"session_id": "s1", "agent_type": "claude_code", "event_type": "tool_use"},
+            {"session_id": "s2", "agent_type": "codex", "event_type": "llm_request"}
+        ]
+    })).await;
+
+    assert_eq!(status, 201);
+    assert_eq!(body["inserted_count"], 2);
+}
+
+#[tokio::test]
+async fn batch_atomic_query_param_rolls_back_entire_batch_on_any_failure() {
+    let app = test_app();
+    let (status, body) = post_json(&app, "/api/events/batch?atomic=true", json!({
+        "events": [
+            {"session_id": "s1", "agent_type": "claude_code", "event_type": "tool_use"},
+            {"bogus": true}
+        ]
+    })).await;
+
+    assert_eq!(status, 200);
+    assert_eq!(body["inserted_count"], 0);
+    assert_eq!(body["error_count"], 2);
+
+    let (_, sessions) = get_json(&app, "/api/sessions").await;
+    assert!(
+        sessions["sessions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|s| s["id"] != "s1")
+    );
+}
+
 #[tokio::test]
 async fn batch_partial_rejection() {
     let app = test_app();
@@ -156,6 +258,13 @@ async fn batch_partial_rejection() {
     assert_eq!(rejected.len(), 1);
     assert_eq!(rejected[0]["index"], 1);
     assert!(!rejected[0]["errors"].as_array().unwrap().is_empty());
+
+    assert_eq!(body["inserted_count"], 2);
+    assert_eq!(body["error_count"], 1);
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[1]["index"], 1);
+    assert_eq!(results[1]["status"], "error");
 }
 
 #[tokio::test]
@@ -183,6 +292,14 @@ async fn batch_dedup_counted_separately() {
     assert_eq!(body["received"], 1);
     assert_eq!(body["duplicates"], 1);
     assert_eq!(body["rejected"].as_array().unwrap().len(), 1);
+
+    assert_eq!(body["inserted_count"], 1);
+    assert_eq!(body["deduplicated_count"], 1);
+    assert_eq!(body["error_count"], 1);
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results[0]["status"], "inserted");
+    assert_eq!(results[1]["status"], "deduplicated");
+    assert_eq!(results[2]["status"], "error");
 }
 
 // --- Session lifecycle ---
@@ -263,6 +380,45 @@ async fn oversized_metadata_gets_truncated() {
     assert_eq!(body["received"], 1);
 }
 
+#[tokio::test]
+async fn oversized_metadata_is_chunked_when_store_large_payloads_is_enabled() {
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let mut config = Config::from_env();
+    config.max_payload_kb = 10;
+    config.store_large_payloads = true;
+    let state = AppState::new(conn, config);
+    let app = agentmonitor_rs::build_router(Arc::clone(&state));
+
+    let big = "y".repeat(20_000);
+    let (status, body) = post_json(&app, "/api/events", json!({
+        "session_id": "chunked-sess",
+        "agent_type": "claude_code",
+        "event_type": "tool_use",
+        "metadata": {"big_field": big, "command": "important-cmd"}
+    })).await;
+
+    assert_eq!(status, 201);
+    assert_eq!(body["received"], 1);
+
+    let db = state.write_conn().expect("db connection");
+    let row_id: i64 = db
+        .query_row(
+            "SELECT id FROM events WHERE session_id = 'chunked-sess'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("inserted row");
+    let metadata: String = db
+        .query_row("SELECT metadata FROM events WHERE id = ?1", [row_id], |row| row.get(0))
+        .unwrap();
+    assert_eq!(metadata, agentmonitor_rs::util::truncate::CHUNKED_PAYLOAD_MARKER);
+
+    let reassembled = db::queries::reassemble_chunked_metadata(&db, row_id)
+        .expect("reassemble query succeeds")
+        .expect("chunks were persisted");
+    assert_eq!(reassembled["command"], "important-cmd");
+}
+
 // --- Rejection error format ---
 
 #[tokio::test]
@@ -283,3 +439,114 @@ async fn batch_rejection_errors_use_field_colon_message_format() {
         assert!(s.contains(": "), "expected 'field: message' format, got: {s}");
     }
 }
+
+// --- Batch size cap ---
+
+#[tokio::test]
+async fn batch_over_max_size_returns_413() {
+    let app = test_app();
+    let events: Vec<Value> = (0..501)
+        .map(|i| json!({"session_id": format!("s{i}"), "agent_type": "codex", "event_type": "tool_use"}))
+        .collect();
+
+    let (status, body) = post_json(&app, "/api/events/batch", json!({ "events": events })).await;
+
+    assert_eq!(status, 413);
+    assert_eq!(body["max_batch_size"], 500);
+    assert_eq!(body["received"], 501);
+}
+
+// --- Aggregated SSE broadcast ---
+
+#[tokio::test]
+async fn batch_insert_broadcasts_single_aggregated_session_update() {
+    let state = test_state();
+    let client = state.sse_hub.subscribe().expect("expected SSE client slot");
+    let (mut rx, _guard) = client.into_parts();
+    let app = agentmonitor_rs::build_router(Arc::clone(&state));
+
+    let (status, _) = post_json(&app, "/api/events/batch", json!({
+        "events": [
+            {"session_id": "s1", "agent_type": "claude_code", "event_type": "tool_use"},
+            {"session_id": "s2", "agent_type": "codex", "event_type": "llm_request"}
+        ]
+    })).await;
+    assert_eq!(status, 201);
+
+    let event = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+        .await
+        .expect("expected broadcast within timeout")
+        .expect("broadcast channel recv failed");
+    assert_eq!(event.kind, "session_update");
+    assert_eq!(event.payload["type"], "batch_import");
+    assert_eq!(event.payload["imported"], 2);
+
+    let second = tokio::time::timeout(Duration::from_millis(100), rx.recv()).await;
+    assert!(second.is_err(), "expected exactly one aggregated broadcast, not one per row");
+}
+
+// --- Long-poll catch-up ---
+
+#[tokio::test]
+async fn poll_returns_immediately_when_events_already_exist() {
+    let app = test_app();
+
+    let (_, body) = post_json(&app, "/api/events", valid_event()).await;
+    let id = body["ids"][0].as_i64().unwrap();
+
+    let (status, body) = get_json(&app, &format!("/api/events/poll?since={}", id - 1)).await;
+
+    assert_eq!(status, 200);
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["id"], id);
+    assert_eq!(body["next_cursor"], id);
+}
+
+#[tokio::test]
+async fn poll_times_out_with_empty_events_and_current_max_id_as_cursor() {
+    let app = test_app();
+
+    let (_, body) = post_json(&app, "/api/events", valid_event()).await;
+    let id = body["ids"][0].as_i64().unwrap();
+
+    let (status, body) = get_json(&app, &format!("/api/events/poll?since={id}&timeout_ms=50")).await;
+
+    assert_eq!(status, 200);
+    assert_eq!(body["events"].as_array().unwrap().len(), 0);
+    assert_eq!(body["next_cursor"], id);
+}
+
+#[tokio::test]
+async fn poll_wakes_up_as_soon_as_a_new_event_is_inserted() {
+    let state = test_state();
+    let app = agentmonitor_rs::build_router(Arc::clone(&state));
+
+    let (_, body) = post_json(&app, "/api/events", valid_event()).await;
+    let id = body["ids"][0].as_i64().unwrap();
+
+    let poll_app = app.clone();
+    let poll = tokio::spawn(async move {
+        get_json(&poll_app, &format!("/api/events/poll?since={id}&timeout_ms=5000")).await
+    });
+
+    // Give the poll a moment to register as a waiter before the insert fires.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let (status, _) = post_json(&app, "/api/events", json!({
+        "session_id": "sess-1",
+        "agent_type": "claude_code",
+        "event_type": "tool_use",
+        "tool_name": "Write"
+    })).await;
+    assert_eq!(status, 201);
+
+    let (status, body) = tokio::time::timeout(Duration::from_secs(1), poll)
+        .await
+        .expect("poll task should finish well before its own timeout")
+        .expect("poll task panicked");
+
+    assert_eq!(status, 200);
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["id"], id + 1);
+}
@@ -6,10 +6,12 @@ use serde_json::{Value, json};
 use tempfile::TempDir;
 
 use agentmonitor_rs::db;
-use agentmonitor_rs::importer::{ImportOptions, ImportSource, run_import};
+use agentmonitor_rs::importer::{ImportOptions, ImportSource, run_import, run_import_parallel};
 
 fn setup_db() -> rusqlite::Connection {
-    db::initialize(Path::new(":memory:")).expect("in-memory DB")
+    let conn = rusqlite::Connection::open_in_memory().expect("in-memory DB");
+    db::schema::apply_schema(&conn).expect("apply schema");
+    conn
 }
 
 fn make_options(source: ImportSource) -> ImportOptions {
@@ -21,7 +23,13 @@ fn make_options(source: ImportSource) -> ImportOptions {
         force: false,
         claude_dir: None,
         codex_dir: None,
+        s3: None,
         max_payload_kb: 64,
+        watch: false,
+        watch_debounce_ms: 500,
+        export_url: None,
+        export_flush_threshold: 100,
+        sampled_hash: false,
     }
 }
 
@@ -238,6 +246,75 @@ fn imports_codex_session_meta_and_token_counts() {
     assert!(response_cost.unwrap_or(0.0) > 0.0);
 }
 
+#[test]
+fn resumes_from_stored_offset_on_appended_lines() {
+    let conn = setup_db();
+    let temp = TempDir::new().expect("temp dir");
+    let file_path = create_claude_fixture(temp.path());
+
+    let mut options = make_options(ImportSource::ClaudeCode);
+    options.claude_dir = Some(temp.path().to_path_buf());
+    let first = run_import(&conn, &options);
+    assert_eq!(first.total_events_imported, 2);
+
+    let mut data = fs::read_to_string(&file_path).expect("read fixture");
+    data.push('\n');
+    data.push_str(
+        &json!({
+            "type": "assistant",
+            "sessionId": "session-abc",
+            "model": "claude-sonnet-4-5-20250929",
+            "timestamp": "2026-02-01T10:02:00Z",
+            "costUSD": 0.02,
+            "usage": { "input_tokens": 500, "output_tokens": 100 }
+        })
+        .to_string(),
+    );
+    fs::write(&file_path, data).expect("append new line");
+
+    let second = run_import(&conn, &options);
+    assert_eq!(second.total_events_found, 1);
+    assert_eq!(second.total_events_imported, 1);
+    assert_eq!(second.total_duplicates, 0);
+
+    let imported_count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM events WHERE source = 'import' AND agent_type = 'claude_code'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(imported_count, 3);
+
+    let new_cost: Option<f64> = conn
+        .query_row(
+            "SELECT cost_usd FROM events WHERE client_timestamp = '2026-02-01T10:02:00Z'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert!((new_cost.unwrap_or(0.0) - 0.01).abs() < 1e-9);
+}
+
+#[test]
+fn dry_run_export_skips_the_network_but_still_imports() {
+    let conn = setup_db();
+    let temp = TempDir::new().expect("temp dir");
+    create_claude_fixture(temp.path());
+
+    let mut options = make_options(ImportSource::ClaudeCode);
+    options.claude_dir = Some(temp.path().to_path_buf());
+    options.dry_run = true;
+    // Unroutable port: if HttpSink ignored `dry_run` and actually tried to
+    // send, this would hang retrying instead of returning immediately.
+    options.export_url = Some("http://127.0.0.1:9/export".to_string());
+    options.export_flush_threshold = 1;
+
+    let result = run_import(&conn, &options);
+    assert_eq!(result.total_events_found, 2);
+    assert_eq!(result.total_events_imported, 2);
+}
+
 #[test]
 fn date_filters_limit_imported_events() {
     let conn = setup_db();
@@ -254,3 +331,38 @@ fn date_filters_limit_imported_events() {
     let result = run_import(&conn, &options);
     assert_eq!(result.total_events_imported, 1);
 }
+
+#[test]
+fn parallel_import_matches_sequential_totals_across_sources() {
+    // `run_import_parallel` needs a real on-disk DB (unlike `setup_db`'s
+    // `:memory:`): its worker threads each open their own connection
+    // against `db_path`, and `:memory:` databases are private per-connection.
+    let fixtures = TempDir::new().expect("fixtures dir");
+    create_claude_fixture(fixtures.path());
+    create_codex_fixture(fixtures.path());
+
+    let db_dir = TempDir::new().expect("db dir");
+    let db_path = db_dir.path().join("agentmonitor.db");
+    {
+        let conn = rusqlite::Connection::open(&db_path).expect("open file-backed DB");
+        db::schema::apply_schema(&conn).expect("apply schema");
+    }
+
+    let mut options = make_options(ImportSource::All);
+    options.claude_dir = Some(fixtures.path().to_path_buf());
+    options.codex_dir = Some(fixtures.path().to_path_buf());
+
+    let result = run_import_parallel(&db_path, None, &options);
+
+    assert_eq!(result.total_files, 2);
+    assert_eq!(result.total_events_imported, 4);
+    assert_eq!(result.total_duplicates, 0);
+    assert_eq!(result.skipped_files, 0);
+
+    // Deterministic regardless of which worker thread finished which file
+    // first.
+    let paths: Vec<&str> = result.files.iter().map(|f| f.path.as_str()).collect();
+    let mut sorted = paths.clone();
+    sorted.sort();
+    assert_eq!(paths, sorted);
+}
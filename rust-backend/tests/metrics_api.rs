@@ -0,0 +1,185 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::body::Body;
+use http_body_util::BodyExt;
+use hyper::Request;
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+use agentmonitor_rs::config::Config;
+use agentmonitor_rs::db;
+use agentmonitor_rs::state::AppState;
+
+fn test_app() -> axum::Router {
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let config = Config::from_env();
+    let state: Arc<AppState> = AppState::new(conn, config);
+    agentmonitor_rs::build_router(state)
+}
+
+async fn post_json(app: &axum::Router, uri: &str, body: Value) -> u16 {
+    let req = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    app.clone().oneshot(req).await.unwrap().status().as_u16()
+}
+
+async fn get_text(app: &axum::Router, uri: &str) -> (u16, String, Option<String>) {
+    let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    let status = response.status().as_u16();
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body = String::from_utf8(bytes.to_vec()).unwrap();
+    (status, body, content_type)
+}
+
+#[tokio::test]
+async fn api_metrics_serves_same_exposition_as_metrics() {
+    let app = test_app();
+
+    post_json(
+        &app,
+        "/api/events",
+        json!({
+            "session_id": "metrics-sess",
+            "agent_type": "claude_code",
+            "event_type": "llm_response",
+            "status": "success",
+            "tokens_in": 10,
+            "tokens_out": 5,
+            "cost_usd": 0.02
+        }),
+    )
+    .await;
+
+    let (status, body, content_type) = get_text(&app, "/api/metrics").await;
+    assert_eq!(status, 200);
+    assert_eq!(content_type.as_deref(), Some("text/plain; version=0.0.4"));
+
+    assert!(body.contains("# HELP agentmonitor_events_total"));
+    assert!(body.contains("# TYPE agentmonitor_events_total counter"));
+    assert!(body.contains("agentmonitor_events_total{agent_type=\"claude_code\"} 1"));
+    assert!(body.contains("agentmonitor_tokens_in_total{agent_type=\"claude_code\"} 10"));
+    assert!(body.contains("agentmonitor_tokens_out_total{agent_type=\"claude_code\"} 5"));
+    assert!(body.contains("# TYPE agentmonitor_sessions_active gauge"));
+    assert!(body.contains("# TYPE agentmonitor_sessions_total gauge"));
+
+    let (legacy_status, legacy_body, _) = get_text(&app, "/metrics").await;
+    assert_eq!(legacy_status, 200);
+    assert_eq!(legacy_body, body);
+
+    assert!(body.contains("agentmonitor_input_tokens_total{agent_type=\"claude_code\",model=\"unknown\",event_type=\"llm_response\"} 10"));
+    assert!(body.contains("agentmonitor_output_tokens_total{agent_type=\"claude_code\",model=\"unknown\",event_type=\"llm_response\"} 5"));
+    assert!(body.contains("agentmonitor_cost_usd_total{agent_type=\"claude_code\",model=\"unknown\",event_type=\"llm_response\"} 0.02"));
+}
+
+#[tokio::test]
+async fn metrics_reports_tool_invocations_by_status() {
+    let app = test_app();
+
+    post_json(
+        &app,
+        "/api/events",
+        json!({
+            "session_id": "tool-metrics-sess",
+            "agent_type": "claude_code",
+            "event_type": "tool_use",
+            "tool_name": "Bash",
+            "status": "success",
+        }),
+    )
+    .await;
+    post_json(
+        &app,
+        "/api/events",
+        json!({
+            "session_id": "tool-metrics-sess",
+            "agent_type": "claude_code",
+            "event_type": "tool_use",
+            "tool_name": "Bash",
+            "status": "error",
+        }),
+    )
+    .await;
+
+    let (status, body, _) = get_text(&app, "/api/metrics").await;
+    assert_eq!(status, 200);
+
+    assert!(body.contains("# TYPE agentmonitor_tool_invocations_total counter"));
+    assert!(body.contains("agentmonitor_tool_invocations_total{tool_name=\"Bash\",status=\"success\"} 1"));
+    assert!(body.contains("agentmonitor_tool_invocations_total{tool_name=\"Bash\",status=\"error\"} 1"));
+}
+
+#[tokio::test]
+async fn metrics_reports_ingest_throughput_counters() {
+    let app = test_app();
+
+    let accepted = json!({
+        "event_id": "ingest-metrics-evt-1",
+        "session_id": "ingest-metrics-sess",
+        "agent_type": "claude_code",
+        "event_type": "tool_use",
+        "tool_name": "Bash"
+    });
+    assert_eq!(post_json(&app, "/api/events", accepted.clone()).await, 201);
+    // Same event_id posted again hits the UNIQUE constraint in
+    // `queries::insert_event` and is reported back as a duplicate.
+    assert_eq!(post_json(&app, "/api/events", accepted).await, 200);
+    assert_eq!(
+        post_json(&app, "/api/events", json!({"agent_type": "claude_code"})).await,
+        400
+    );
+
+    let (status, body, _) = get_text(&app, "/api/metrics").await;
+    assert_eq!(status, 200);
+
+    assert!(body.contains("# TYPE agentmonitor_ingest_events_received_total counter"));
+    assert!(body.contains("agentmonitor_ingest_events_received_total 3"));
+    assert!(body.contains("agentmonitor_ingest_duplicates_total 1"));
+    assert!(body.contains("agentmonitor_ingest_rejected_total{reason=\"validation\"} 1"));
+    assert!(body.contains("agentmonitor_ingest_rejected_total{reason=\"batch_too_large\"} 0"));
+}
+
+#[tokio::test]
+async fn summary_reports_per_session_rollups() {
+    let app = test_app();
+
+    post_json(
+        &app,
+        "/api/events",
+        json!({
+            "session_id": "summary-sess",
+            "agent_type": "codex",
+            "event_type": "tool_use",
+            "status": "success",
+            "tokens_in": 20,
+            "tokens_out": 8,
+            "cost_usd": 0.05
+        }),
+    )
+    .await;
+
+    let (status, body, _) = get_text(&app, "/api/summary").await;
+    assert_eq!(status, 200);
+    let sessions: Value = serde_json::from_str(&body).expect("summary body is JSON");
+    let session = sessions
+        .as_array()
+        .and_then(|rows| rows.iter().find(|row| row["session_id"] == "summary-sess"))
+        .expect("summary-sess present in /api/summary");
+    assert_eq!(session["agent_type"], "codex");
+    assert_eq!(session["tokens_in"], 20);
+    assert_eq!(session["tokens_out"], 8);
+
+    let (legacy_status, legacy_body, _) = get_text(&app, "/summary").await;
+    assert_eq!(legacy_status, 200);
+    assert_eq!(legacy_body, body);
+}
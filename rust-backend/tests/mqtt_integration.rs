@@ -0,0 +1,74 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use agentmonitor_rs::config::Config;
+use agentmonitor_rs::db;
+use agentmonitor_rs::state::AppState;
+
+// There's no MQTT broker available in this test environment, so these cover
+// the deterministic pieces of the integration (config defaults and the
+// dedup path the subscriber reuses from `/api/events`) rather than a live
+// publish/subscribe round trip.
+
+#[test]
+fn mqtt_is_dormant_by_default() {
+    let config = Config::from_env();
+    assert!(config.mqtt_host.is_none());
+    assert_eq!(config.mqtt_port, 1883);
+    assert_eq!(config.mqtt_events_topic, "agentmonitor/events/+");
+    assert_eq!(config.mqtt_stats_topic, "agentmonitor/stats");
+    assert_eq!(config.mqtt_sessions_topic, "agentmonitor/sessions");
+}
+
+#[tokio::test]
+async fn mqtt_client_accessor_is_none_until_set() {
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let config = Config::from_env();
+    let state: Arc<AppState> = AppState::new(conn, config);
+
+    assert!(state.mqtt_client().await.is_none());
+}
+
+#[tokio::test]
+async fn mqtt_delivered_event_dedup_matches_http_ingest_semantics() {
+    // Same invariant as federated_event_dedup_matches_http_ingest_semantics
+    // in nats_integration.rs — a message published twice (e.g. a retried
+    // QoS 1 publish) must only persist once, which is what protects against
+    // double-counting a redelivered event.
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let config = Config::from_env();
+    let state: Arc<AppState> = AppState::new(conn, config);
+    let app = agentmonitor_rs::build_router(Arc::clone(&state));
+
+    use axum::body::Body;
+    use http_body_util::BodyExt;
+    use hyper::Request;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    let event = json!({
+        "event_id": "mqtt-redelivery-1",
+        "session_id": "mqtt-session-1",
+        "agent_type": "codex",
+        "event_type": "llm_request"
+    });
+
+    for _ in 0..2 {
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/events")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(&event).unwrap()))
+            .unwrap();
+        let _ = app.clone().oneshot(req).await.unwrap();
+    }
+
+    let req = Request::builder()
+        .uri("/api/stats")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let stats: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(stats["total_events"], 1);
+}
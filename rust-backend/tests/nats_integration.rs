@@ -0,0 +1,81 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use agentmonitor_rs::config::Config;
+use agentmonitor_rs::db;
+use agentmonitor_rs::nats::publish_subject;
+use agentmonitor_rs::state::AppState;
+
+// There's no NATS broker available in this test environment, so these cover
+// the deterministic pieces of the integration (subject naming, config
+// defaults, and the dedup path the subscriber reuses from `/api/events`)
+// rather than a live publish/subscribe round trip.
+
+#[test]
+fn publish_subject_is_prefix_agent_type_event_type() {
+    assert_eq!(
+        publish_subject("agentmonitor.events", "codex", "llm_request"),
+        "agentmonitor.events.codex.llm_request"
+    );
+}
+
+#[test]
+fn nats_is_dormant_by_default() {
+    let config = Config::from_env();
+    assert!(config.nats_url.is_none());
+    assert_eq!(config.nats_publish_subject_prefix, "agentmonitor.events");
+    assert!(config.nats_subscribe_subject.is_none());
+}
+
+#[tokio::test]
+async fn nats_client_accessor_is_none_until_set() {
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let config = Config::from_env();
+    let state: Arc<AppState> = AppState::new(conn, config);
+
+    assert!(state.nats_client().await.is_none());
+}
+
+#[tokio::test]
+async fn federated_event_dedup_matches_http_ingest_semantics() {
+    // Mirrors invariant_event_persistence_and_dedup in desktop_invariants.rs:
+    // a NATS-delivered event carries the same event_id dedup contract as a
+    // POST /api/events — inserting the same event_id twice must only persist
+    // it once, which is what protects against a JetStream redelivery.
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let config = Config::from_env();
+    let state: Arc<AppState> = AppState::new(conn, config);
+    let app = agentmonitor_rs::build_router(Arc::clone(&state));
+
+    use axum::body::Body;
+    use http_body_util::BodyExt;
+    use hyper::Request;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    let event = json!({
+        "event_id": "nats-redelivery-1",
+        "session_id": "nats-session-1",
+        "agent_type": "codex",
+        "event_type": "llm_request"
+    });
+
+    for _ in 0..2 {
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/events")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(&event).unwrap()))
+            .unwrap();
+        let _ = app.clone().oneshot(req).await.unwrap();
+    }
+
+    let req = Request::builder()
+        .uri("/api/stats")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let stats: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(stats["total_events"], 1);
+}
@@ -12,7 +12,7 @@ use agentmonitor_rs::db;
 use agentmonitor_rs::state::AppState;
 
 fn test_app() -> axum::Router {
-    let conn = db::initialize(Path::new(":memory:")).expect("in-memory DB");
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
     let config = Config::from_env();
     let state: Arc<AppState> = AppState::new(conn, config);
     agentmonitor_rs::build_router(state)
@@ -33,12 +33,12 @@ async fn post_json(app: &axum::Router, uri: &str, body: Value) -> (u16, Value) {
     (status, parsed)
 }
 
-async fn post_protobuf(app: &axum::Router, uri: &str) -> (u16, Value) {
+async fn post_protobuf(app: &axum::Router, uri: &str, body: Vec<u8>) -> (u16, Value) {
     let req = Request::builder()
         .method("POST")
         .uri(uri)
         .header("content-type", "application/x-protobuf")
-        .body(Body::from(vec![0x0a, 0x00]))
+        .body(Body::from(body))
         .unwrap();
     let response = app.clone().oneshot(req).await.unwrap();
     let status = response.status().as_u16();
@@ -47,6 +47,52 @@ async fn post_protobuf(app: &axum::Router, uri: &str) -> (u16, Value) {
     (status, parsed)
 }
 
+/// Protobuf varint-encode a tag for `(field_number, wire_type)`.
+fn tag(field_number: u64, wire_type: u8) -> Vec<u8> {
+    varint((field_number << 3) | wire_type as u64)
+}
+
+fn varint(mut v: u64) -> Vec<u8> {
+    let mut out = Vec::new();
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+        if v == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+    out
+}
+
+fn length_delimited(field_number: u64, payload: Vec<u8>) -> Vec<u8> {
+    let mut out = tag(field_number, 2);
+    out.extend(varint(payload.len() as u64));
+    out.extend(payload);
+    out
+}
+
+fn fixed64_field(field_number: u64, bits: u64) -> Vec<u8> {
+    let mut out = tag(field_number, 1);
+    out.extend(bits.to_le_bytes());
+    out
+}
+
+fn varint_field(field_number: u64, value: u64) -> Vec<u8> {
+    let mut out = tag(field_number, 0);
+    out.extend(varint(value));
+    out
+}
+
+/// Build a `KeyValue { key, value: { stringValue } }` message.
+fn proto_string_kv(key: &str, value: &str) -> Vec<u8> {
+    let string_value = length_delimited(1, value.as_bytes().to_vec());
+    let mut out = length_delimited(1, key.as_bytes().to_vec());
+    out.extend(length_delimited(2, string_value));
+    out
+}
+
 async fn get_json(app: &axum::Router, uri: &str) -> (u16, Value) {
     let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
     let response = app.clone().oneshot(req).await.unwrap();
@@ -65,11 +111,63 @@ fn parse_event_metadata(event: &Value) -> Value {
 }
 
 #[tokio::test]
-async fn otel_logs_rejects_protobuf() {
+async fn otel_logs_tolerates_truncated_protobuf() {
+    // A handful of bytes that look like a varint-tagged field but cut off
+    // mid-message. The decoder should bail out at the first field it can't
+    // parse and return whatever partial structure it already built, not
+    // reject the request with a 4xx.
     let app = test_app();
-    let (status, body) = post_protobuf(&app, "/api/otel/v1/logs").await;
-    assert_eq!(status, 415);
-    assert!(body["error"].is_string());
+    let (status, _) = post_protobuf(&app, "/api/otel/v1/logs", vec![0x0a, 0xff]).await;
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn otel_logs_accepts_empty_protobuf() {
+    let app = test_app();
+    let (status, body) = post_protobuf(&app, "/api/otel/v1/logs", Vec::new()).await;
+    assert_eq!(status, 200);
+    assert_eq!(body, json!({}));
+}
+
+#[tokio::test]
+async fn otel_logs_ingests_protobuf_encoded_event() {
+    let app = test_app();
+    let session_id = "otel-logs-protobuf-sess";
+
+    // ExportLogsServiceRequest { resource_logs: [ ResourceLogs {
+    //   resource: Resource { attributes: [service.name, gen_ai.session.id] },
+    //   scope_logs: [ ScopeLogs { log_records: [ LogRecord {
+    //     attributes: [event.name, gen_ai.tool.name]
+    //   } ] } ]
+    // } ] }
+    let resource_attrs = [
+        length_delimited(1, proto_string_kv("service.name", "claude_code")),
+        length_delimited(1, proto_string_kv("gen_ai.session.id", session_id)),
+    ]
+    .concat();
+    let resource_field = length_delimited(1, resource_attrs); // ResourceLogs.resource
+
+    let log_record_attrs = [
+        length_delimited(6, proto_string_kv("event.name", "claude_code.tool_result")),
+        length_delimited(6, proto_string_kv("gen_ai.tool.name", "Bash")),
+    ]
+    .concat();
+    let log_record_field = length_delimited(2, log_record_attrs); // ScopeLogs.log_records
+    let scope_logs_field = length_delimited(2, log_record_field); // ResourceLogs.scope_logs
+
+    let resource_logs_message = [resource_field, scope_logs_field].concat();
+    let body = length_delimited(1, resource_logs_message); // ExportLogsServiceRequest.resource_logs
+
+    let (status, _) = post_protobuf(&app, "/api/otel/v1/logs", body).await;
+    assert_eq!(status, 200);
+
+    let (session_status, body) =
+        get_json(&app, "/api/sessions/otel-logs-protobuf-sess?event_limit=10").await;
+    assert_eq!(session_status, 200);
+    let events = body["events"].as_array().unwrap();
+    assert!(events.iter().any(|e| {
+        e["event_type"] == "tool_use" && e["tool_name"] == "Bash" && e["source"] == "otel"
+    }));
 }
 
 #[tokio::test]
@@ -198,10 +296,116 @@ async fn otel_logs_user_prompt_keeps_existing_message() {
 }
 
 #[tokio::test]
-async fn otel_metrics_rejects_protobuf() {
+async fn otel_metrics_ingests_protobuf_encoded_sum() {
     let app = test_app();
-    let (status, _) = post_protobuf(&app, "/api/otel/v1/metrics").await;
-    assert_eq!(status, 415);
+    let session_id = "otel-metrics-protobuf-sess";
+
+    // ExportMetricsServiceRequest { resource_metrics: [ ResourceMetrics {
+    //   resource: Resource { attributes: [service.name, gen_ai.session.id] },
+    //   scope_metrics: [ ScopeMetrics { metrics: [ Metric {
+    //     name: "claude_code.token.usage",
+    //     sum: Sum { data_points: [ NumberDataPoint {
+    //       as_int: 1000, attributes: [type=input, model=...]
+    //     } ] }
+    //   } ] } ]
+    // } ] }
+    let resource_attrs = [
+        length_delimited(1, proto_string_kv("service.name", "claude_code")),
+        length_delimited(1, proto_string_kv("gen_ai.session.id", session_id)),
+    ]
+    .concat();
+    let resource_field = length_delimited(1, resource_attrs);
+
+    let dp_attrs = [
+        length_delimited(7, proto_string_kv("type", "input")),
+        length_delimited(7, proto_string_kv("model", "claude-sonnet-4-20250514")),
+    ]
+    .concat();
+    let dp_message = [dp_attrs, fixed64_field(6, 1000u64)].concat();
+    let sum_message = length_delimited(1, dp_message); // Sum.data_points
+    let metric_message = [
+        length_delimited(1, b"claude_code.token.usage".to_vec()), // Metric.name
+        length_delimited(7, sum_message),                         // Metric.sum
+    ]
+    .concat();
+    let metrics_field = length_delimited(2, metric_message); // ScopeMetrics.metrics
+    let resource_metrics_message = [resource_field, length_delimited(2, metrics_field)].concat();
+    let body = length_delimited(1, resource_metrics_message);
+
+    let (status, _) = post_protobuf(&app, "/api/otel/v1/metrics", body).await;
+    assert_eq!(status, 200);
+
+    let (session_status, session_body) =
+        get_json(&app, "/api/sessions/otel-metrics-protobuf-sess?event_limit=20").await;
+    assert_eq!(session_status, 200);
+    let events = session_body["events"].as_array().unwrap();
+    assert!(events.iter().any(|e| e["tokens_in"] == 1000 && e["source"] == "otel"));
+}
+
+/// Histogram metrics aren't persisted as events yet (see the scope note on
+/// `otel_metrics_handler`), so this exercises the decode path directly
+/// rather than through the HTTP API: build a protobuf-encoded
+/// `HistogramDataPoint` by hand and confirm `otel::protobuf` reconstructs
+/// the same shape `otel::parser::parse_otel_metrics` already knows how to
+/// read from the JSON path.
+#[test]
+fn otel_protobuf_decodes_histogram_metric() {
+    use agentmonitor_rs::otel::cumulative_store::InMemoryCumulativeStore;
+    use agentmonitor_rs::otel::parser::parse_otel_metrics;
+    use agentmonitor_rs::otel::protobuf::decode_export_metrics_request;
+
+    let resource_attrs = [
+        length_delimited(1, proto_string_kv("service.name", "claude_code")),
+        length_delimited(1, proto_string_kv("gen_ai.session.id", "otel-histogram-sess")),
+    ]
+    .concat();
+    let resource_field = length_delimited(1, resource_attrs);
+
+    let dp_attrs = length_delimited(9, proto_string_kv("model", "claude-sonnet-4-20250514"));
+    let bucket_counts = [1u64, 2u64, 2u64]
+        .iter()
+        .flat_map(|n| n.to_le_bytes())
+        .collect::<Vec<u8>>();
+    let explicit_bounds = [1.0f64, 5.0f64]
+        .iter()
+        .flat_map(|f| f.to_bits().to_le_bytes())
+        .collect::<Vec<u8>>();
+    let data_point = [
+        fixed64_field(4, 5u64),              // count
+        fixed64_field(5, 12.5f64.to_bits()), // sum
+        length_delimited(6, bucket_counts),
+        length_delimited(7, explicit_bounds),
+        dp_attrs,
+    ]
+    .concat();
+    let histogram_message = length_delimited(1, data_point); // Histogram.data_points
+    let metric_message = [
+        length_delimited(1, b"gen_ai.client.operation.duration".to_vec()), // Metric.name
+        length_delimited(9, histogram_message),                           // Metric.histogram
+    ]
+    .concat();
+    let metrics_field = length_delimited(2, metric_message); // ScopeMetrics.metrics
+    let resource_metrics_message = [resource_field, length_delimited(2, metrics_field)].concat();
+    let body = length_delimited(1, resource_metrics_message);
+
+    let payload = decode_export_metrics_request(&body);
+    let cumulative_state = InMemoryCumulativeStore::new();
+    let parsed = parse_otel_metrics(&payload, &cumulative_state);
+
+    assert_eq!(parsed.histograms.len(), 1);
+    let histogram = &parsed.histograms[0];
+    assert_eq!(histogram.session_id, "otel-histogram-sess");
+    assert_eq!(histogram.metric_name, "gen_ai.client.operation.duration");
+    assert_eq!(histogram.model.as_deref(), Some("claude-sonnet-4-20250514"));
+    assert_eq!(histogram.count_delta, 5);
+    assert_eq!(histogram.sum_delta, 12.5);
+    assert_eq!(histogram.buckets.len(), 3);
+    assert_eq!(histogram.buckets[0].upper_bound, Some(1.0));
+    assert_eq!(histogram.buckets[0].count_delta, 1);
+    assert_eq!(histogram.buckets[1].upper_bound, Some(5.0));
+    assert_eq!(histogram.buckets[1].count_delta, 2);
+    assert_eq!(histogram.buckets[2].upper_bound, None);
+    assert_eq!(histogram.buckets[2].count_delta, 2);
 }
 
 #[tokio::test]
@@ -280,6 +484,67 @@ async fn otel_metrics_ingests_synthetic_llm_response_rows() {
     assert!(events.iter().all(|e| e["source"] == "otel"));
 }
 
+#[tokio::test]
+async fn otel_metrics_accepts_histogram_data_points() {
+    let app = test_app();
+    let session_id = "otel-histogram-sess";
+    let payload = json!({
+      "resourceMetrics": [{
+        "resource": {
+          "attributes": [
+            { "key": "service.name", "value": { "stringValue": "claude_code" } },
+            { "key": "gen_ai.session.id", "value": { "stringValue": session_id } }
+          ]
+        },
+        "scopeMetrics": [{
+          "metrics": [{
+            "name": "gen_ai.client.operation.duration",
+            "histogram": {
+              "dataPoints": [{
+                "count": "4",
+                "sum": 12.5,
+                "bucketCounts": ["1", "2", "1"],
+                "explicitBounds": [1.0, 5.0],
+                "attributes": [
+                  { "key": "model", "value": { "stringValue": "claude-sonnet-4-20250514" } }
+                ]
+              }],
+              "aggregationTemporality": 1
+            }
+          }]
+        }]
+      }]
+    });
+
+    let (status, _) = post_json(&app, "/api/otel/v1/metrics", payload).await;
+    assert_eq!(status, 200);
+
+    let (session_status, body) =
+        get_json(&app, "/api/sessions/otel-histogram-sess?event_limit=20").await;
+    assert_eq!(session_status, 200);
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+
+    let event = &events[0];
+    assert_eq!(event["event_type"], "llm_response");
+    assert_eq!(event["model"], "claude-sonnet-4-20250514");
+    // duration_ms is the mean over the interval: sum_delta / count_delta.
+    assert_eq!(event["duration_ms"].as_i64(), Some((12.5_f64 / 4.0).round() as i64));
+
+    let metadata = &event["metadata"];
+    assert_eq!(metadata["metric_name"], "gen_ai.client.operation.duration");
+    assert_eq!(metadata["count_delta"], 4);
+    assert_eq!(metadata["sum_delta"], 12.5);
+    let buckets = metadata["buckets"].as_array().unwrap();
+    assert_eq!(buckets.len(), 3);
+    assert_eq!(buckets[0]["upper_bound"], 1.0);
+    assert_eq!(buckets[0]["count_delta"], 1);
+    assert_eq!(buckets[1]["upper_bound"], 5.0);
+    assert_eq!(buckets[1]["count_delta"], 2);
+    assert!(buckets[2]["upper_bound"].is_null());
+    assert_eq!(buckets[2]["count_delta"], 1);
+}
+
 #[tokio::test]
 async fn otel_metrics_cumulative_to_delta_conversion() {
     let app = test_app();
@@ -348,10 +613,294 @@ async fn otel_metrics_cumulative_to_delta_conversion() {
 }
 
 #[tokio::test]
-async fn otel_traces_stub_accepts_json() {
+async fn otel_metrics_cumulative_counter_reset_emits_full_value() {
+    let app = test_app();
+    let session_id = "otel-reset-sess";
+
+    let make_payload = |value: i64, start_time_nanos: &str| {
+        json!({
+          "resourceMetrics": [{
+            "resource": {
+              "attributes": [
+                { "key": "service.name", "value": { "stringValue": "claude_code" } },
+                { "key": "gen_ai.session.id", "value": { "stringValue": session_id } }
+              ]
+            },
+            "scopeMetrics": [{
+              "metrics": [{
+                "name": "claude_code.token.usage",
+                "sum": {
+                  "dataPoints": [{
+                    "asInt": value.to_string(),
+                    "startTimeUnixNano": start_time_nanos,
+                    "attributes": [
+                      { "key": "type", "value": { "stringValue": "input" } },
+                      { "key": "model", "value": { "stringValue": "claude-sonnet-4-20250514" } }
+                    ]
+                  }],
+                  "isMonotonic": true,
+                  "aggregationTemporality": 2
+                }
+              }]
+            }]
+          }]
+        })
+    };
+
+    // Same process lifetime (start time "100"): 1000, then 1500 -> delta 500.
+    assert_eq!(
+        post_json(&app, "/api/otel/v1/metrics", make_payload(1000, "100"))
+            .await
+            .0,
+        200
+    );
+    assert_eq!(
+        post_json(&app, "/api/otel/v1/metrics", make_payload(1500, "100"))
+            .await
+            .0,
+        200
+    );
+    // Process restarted (new start time "200"): counter resets to 300, which
+    // is lower than the prior 1500 and carries a different start time, so
+    // the full 300 should be emitted rather than a negative/zero delta.
+    assert_eq!(
+        post_json(&app, "/api/otel/v1/metrics", make_payload(300, "200"))
+            .await
+            .0,
+        200
+    );
+
+    let (_, body) = get_json(&app, "/api/sessions/otel-reset-sess?event_limit=20").await;
+    let values: Vec<i64> = body["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e["tokens_in"].as_i64())
+        .filter(|v| *v > 0)
+        .collect();
+
+    assert!(values.contains(&1000));
+    assert!(values.contains(&500));
+    assert!(values.contains(&300));
+    assert_eq!(values.len(), 3);
+}
+
+#[tokio::test]
+async fn otel_traces_accepts_empty_resource_spans() {
     let app = test_app();
     let (status, body) =
         post_json(&app, "/api/otel/v1/traces", json!({ "resourceSpans": [] })).await;
     assert_eq!(status, 200);
     assert_eq!(body, json!({}));
 }
+
+#[tokio::test]
+async fn otel_traces_ingests_protobuf_encoded_span() {
+    let app = test_app();
+    let session_id = "otel-traces-protobuf-sess";
+
+    // ExportTraceServiceRequest { resource_spans: [ ResourceSpans {
+    //   resource: Resource { attributes: [service.name, gen_ai.session.id] },
+    //   scope_spans: [ ScopeSpans { spans: [ Span {
+    //     trace_id, name, attributes: [gen_ai.tool.name], status: { code: 1 }
+    //   } ] } ]
+    // } ] }
+    let resource_attrs = [
+        length_delimited(1, proto_string_kv("service.name", "claude_code")),
+        length_delimited(1, proto_string_kv("gen_ai.session.id", session_id)),
+    ]
+    .concat();
+    let resource_field = length_delimited(1, resource_attrs);
+
+    let status_message = varint_field(3, 1); // Status.code = OK
+    let span_message = [
+        length_delimited(1, vec![0xab, 0xc1, 0x23]), // Span.trace_id
+        length_delimited(5, b"claude_code.tool_result".to_vec()), // Span.name
+        length_delimited(9, proto_string_kv("gen_ai.tool.name", "Bash")), // Span.attributes
+        length_delimited(15, status_message),        // Span.status
+    ]
+    .concat();
+    let spans_field = length_delimited(2, span_message); // ScopeSpans.spans
+    let resource_spans_message = [resource_field, length_delimited(2, spans_field)].concat();
+    let body = length_delimited(1, resource_spans_message);
+
+    let (status, _) = post_protobuf(&app, "/api/otel/v1/traces", body).await;
+    assert_eq!(status, 200);
+
+    let (session_status, session_body) =
+        get_json(&app, "/api/sessions/otel-traces-protobuf-sess?event_limit=10").await;
+    assert_eq!(session_status, 200);
+    let events = session_body["events"].as_array().unwrap();
+    assert!(events.iter().any(|e| {
+        e["event_type"] == "tool_use" && e["tool_name"] == "Bash" && e["source"] == "otel"
+    }));
+}
+
+#[tokio::test]
+async fn otel_traces_ingests_span_with_duration_and_ids() {
+    let app = test_app();
+    let session_id = "otel-traces-sess";
+    let payload = json!({
+      "resourceSpans": [{
+        "resource": {
+          "attributes": [
+            { "key": "service.name", "value": { "stringValue": "claude_code" } },
+            { "key": "gen_ai.session.id", "value": { "stringValue": session_id } }
+          ]
+        },
+        "scopeSpans": [{
+          "spans": [{
+            "traceId": "abc123",
+            "spanId": "span1",
+            "parentSpanId": "span0",
+            "name": "claude_code.tool_result",
+            "startTimeUnixNano": "1700000000000000000",
+            "endTimeUnixNano": "1700000000250000000",
+            "status": { "code": 1 },
+            "attributes": [
+              { "key": "gen_ai.tool.name", "value": { "stringValue": "Bash" } }
+            ],
+            "events": [{
+              "timeUnixNano": "1700000000100000000",
+              "name": "retry",
+              "attributes": [
+                { "key": "attempt", "value": { "intValue": 2 } }
+              ]
+            }]
+          }]
+        }]
+      }]
+    });
+
+    let (status, _) = post_json(&app, "/api/otel/v1/traces", payload).await;
+    assert_eq!(status, 200);
+
+    let (session_status, body) =
+        get_json(&app, "/api/sessions/otel-traces-sess?event_limit=10").await;
+    assert_eq!(session_status, 200);
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event["event_type"], "tool_use");
+    assert_eq!(event["tool_name"], "Bash");
+    assert_eq!(event["source"], "otel");
+    assert_eq!(event["duration_ms"], 250);
+
+    let metadata = parse_event_metadata(event);
+    assert_eq!(metadata["trace_id"], "abc123");
+    assert_eq!(metadata["span_id"], "span1");
+    assert_eq!(metadata["parent_span_id"], "span0");
+    assert_eq!(metadata["span_events"][0]["name"], "retry");
+    assert_eq!(metadata["span_events"][0]["attributes"]["attempt"], 2);
+}
+
+#[tokio::test]
+async fn otel_traces_falls_back_to_gen_ai_operation_name() {
+    let app = test_app();
+    let session_id = "otel-traces-gen-ai-op";
+    let payload = json!({
+      "resourceSpans": [{
+        "resource": {
+          "attributes": [
+            { "key": "service.name", "value": { "stringValue": "some_agent" } },
+            { "key": "gen_ai.session.id", "value": { "stringValue": session_id } }
+          ]
+        },
+        "scopeSpans": [{
+          "spans": [{
+            "traceId": "trace1",
+            "spanId": "span1",
+            // Span name doesn't match any known claude_code.*/codex.* event
+            // name, so the event type should fall back to the gen_ai
+            // semantic-convention `gen_ai.operation.name` attribute instead
+            // of the generic "tool_use" default.
+            "name": "chat claude-sonnet-4-20250514",
+            "startTimeUnixNano": "1700000000000000000",
+            "endTimeUnixNano": "1700000000500000000",
+            "attributes": [
+              { "key": "gen_ai.operation.name", "value": { "stringValue": "chat" } },
+              { "key": "gen_ai.usage.input_tokens", "value": { "intValue": 120 } },
+              { "key": "gen_ai.usage.output_tokens", "value": { "intValue": 45 } }
+            ]
+          }]
+        }]
+      }]
+    });
+
+    let (status, _) = post_json(&app, "/api/otel/v1/traces", payload).await;
+    assert_eq!(status, 200);
+
+    let (session_status, body) =
+        get_json(&app, "/api/sessions/otel-traces-gen-ai-op?event_limit=10").await;
+    assert_eq!(session_status, 200);
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event["event_type"], "llm_response");
+    assert_eq!(event["tokens_in"], 120);
+    assert_eq!(event["tokens_out"], 45);
+    assert_eq!(event["source"], "otel");
+}
+
+#[tokio::test]
+async fn otel_traces_links_parent_and_child_spans_via_metadata() {
+    let app = test_app();
+    let session_id = "otel-traces-parent-child";
+    let payload = json!({
+      "resourceSpans": [{
+        "resource": {
+          "attributes": [
+            { "key": "service.name", "value": { "stringValue": "claude_code" } },
+            { "key": "gen_ai.session.id", "value": { "stringValue": session_id } }
+          ]
+        },
+        "scopeSpans": [{
+          "spans": [
+            {
+              "traceId": "trace-parent-child",
+              "spanId": "span-parent",
+              "name": "claude_code.api_request",
+              "startTimeUnixNano": "1700000000000000000",
+              "endTimeUnixNano": "1700000000300000000"
+            },
+            {
+              "traceId": "trace-parent-child",
+              "spanId": "span-child",
+              "parentSpanId": "span-parent",
+              "name": "claude_code.tool_result",
+              "startTimeUnixNano": "1700000000050000000",
+              "endTimeUnixNano": "1700000000150000000",
+              "attributes": [
+                { "key": "gen_ai.tool.name", "value": { "stringValue": "Read" } }
+              ]
+            }
+          ]
+        }]
+      }]
+    });
+
+    let (status, _) = post_json(&app, "/api/otel/v1/traces", payload).await;
+    assert_eq!(status, 200);
+
+    let (session_status, body) =
+        get_json(&app, "/api/sessions/otel-traces-parent-child?event_limit=10").await;
+    assert_eq!(session_status, 200);
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 2);
+
+    let parent = events
+        .iter()
+        .find(|e| e["event_type"] == "llm_request")
+        .expect("parent span stored as llm_request");
+    let child = events
+        .iter()
+        .find(|e| e["event_type"] == "tool_use")
+        .expect("child span stored as tool_use");
+
+    let parent_meta = parse_event_metadata(parent);
+    let child_meta = parse_event_metadata(child);
+    assert_eq!(parent_meta["trace_id"], "trace-parent-child");
+    assert_eq!(parent_meta["span_id"], "span-parent");
+    assert_eq!(child_meta["trace_id"], parent_meta["trace_id"]);
+    assert_eq!(child_meta["parent_span_id"], parent_meta["span_id"]);
+}
@@ -1,16 +1,13 @@
 use rusqlite::Connection;
 
-// We can't import from the binary crate directly in integration tests,
-// so we replicate the schema init and test the SQL behavior.
+use agentmonitor_rs::db;
+use agentmonitor_rs::db::queries;
 
 fn init_db() -> Connection {
     let conn = Connection::open_in_memory().unwrap();
     conn.pragma_update(None, "journal_mode", "WAL").unwrap();
     conn.pragma_update(None, "busy_timeout", 5000).unwrap();
-    let schema_sql = include_str!("../src/db/schema.rs");
-    let start = schema_sql.find("r#\"\n").unwrap() + 4;
-    let end = schema_sql.rfind("\"#;").unwrap();
-    conn.execute_batch(&schema_sql[start..end]).unwrap();
+    db::schema::apply_schema(&conn).unwrap();
     conn
 }
 
@@ -178,3 +175,45 @@ fn idle_and_end_session_lifecycle() {
     ).unwrap();
     assert_eq!(status, "ended");
 }
+
+#[test]
+fn recompute_costs_backfills_missing_cost_and_is_idempotent() {
+    let conn = init_db();
+
+    // Row with no cost_usd at all: eligible for first-time pricing.
+    conn.execute(
+        "INSERT INTO events (session_id, agent_type, event_type, status, model, tokens_in, tokens_out, source)
+         VALUES ('sess-1', 'codex', 'llm_response', 'success', 'o3', 1000000, 500000, 'import')",
+        [],
+    ).unwrap();
+
+    // Row with a source-provided cost_usd: must be left alone.
+    conn.execute(
+        "INSERT INTO events (session_id, agent_type, event_type, status, model, tokens_in, tokens_out, cost_usd, source)
+         VALUES ('sess-2', 'claude_code', 'tool_use', 'success', 'claude-sonnet-4-5', 100, 50, 0.5, 'import')",
+        [],
+    ).unwrap();
+
+    let summary = queries::recompute_costs(&conn).unwrap();
+    assert_eq!(summary.rows_scanned, 1, "only the uncosted row is a candidate");
+    assert_eq!(summary.rows_updated, 1);
+    assert_eq!(summary.pricing_version, agentmonitor_rs::pricing::pricing_version());
+
+    let (cost, pricing_version): (f64, Option<String>) = conn.query_row(
+        "SELECT cost_usd, pricing_version FROM events WHERE session_id = 'sess-1'",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).unwrap();
+    assert!((cost - 6.0).abs() < 1e-9);
+    assert_eq!(pricing_version.as_deref(), Some(agentmonitor_rs::pricing::pricing_version()));
+
+    let untouched_cost: f64 = conn.query_row(
+        "SELECT cost_usd FROM events WHERE session_id = 'sess-2'", [], |r| r.get(0),
+    ).unwrap();
+    assert!((untouched_cost - 0.5).abs() < 1e-9, "source-provided cost must not be overwritten");
+
+    // Re-running with unchanged rates should be a no-op.
+    let second_pass = queries::recompute_costs(&conn).unwrap();
+    assert_eq!(second_pass.rows_scanned, 0);
+    assert_eq!(second_pass.rows_updated, 0);
+}
@@ -0,0 +1,59 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use agentmonitor_rs::config::Config;
+use agentmonitor_rs::db;
+use agentmonitor_rs::state::AppState;
+
+// There's no second agentmonitor instance available in this test
+// environment, so these cover the deterministic pieces of the integration
+// (config defaults, status accessors, and `/api/upstreams` with relay mode
+// dormant) rather than a live upstream connection — see `nats_integration.rs`
+// for the analogous NATS coverage.
+
+#[test]
+fn relay_is_dormant_by_default() {
+    let config = Config::from_env();
+    assert!(config.upstreams.is_empty());
+}
+
+#[tokio::test]
+async fn relay_status_is_empty_without_configured_upstreams() {
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let config = Config::from_env();
+    let state: Arc<AppState> = AppState::new(conn, config);
+
+    assert!(state.relay_status.is_empty());
+}
+
+#[tokio::test]
+async fn upstreams_endpoint_reports_each_configured_upstream() {
+    use axum::body::Body;
+    use http_body_util::BodyExt;
+    use hyper::Request;
+    use tower::ServiceExt;
+
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let mut config = Config::from_env();
+    config.upstreams = vec!["http://upstream-a:3142".to_string(), "http://upstream-b:3142".to_string()];
+    let state: Arc<AppState> = AppState::new(conn, config);
+    assert_eq!(state.relay_status.len(), 2);
+
+    let app = agentmonitor_rs::build_router(state);
+    let req = Request::builder()
+        .uri("/api/upstreams")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(req).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    let upstreams = body["upstreams"].as_array().unwrap();
+    assert_eq!(upstreams.len(), 2);
+    assert_eq!(upstreams[0]["url"], "http://upstream-a:3142");
+    // Nothing has actually dialed either upstream in this test, so both
+    // should still report disconnected.
+    assert_eq!(upstreams[0]["connected"], false);
+    assert_eq!(upstreams[1]["connected"], false);
+}
@@ -0,0 +1,125 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::body::Body;
+use http_body_util::BodyExt;
+use hyper::Request;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use agentmonitor_rs::config::Config;
+use agentmonitor_rs::db;
+use agentmonitor_rs::state::AppState;
+
+fn test_app_with_config(config: Config) -> axum::Router {
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let state: Arc<AppState> = AppState::new(conn, config);
+    agentmonitor_rs::build_router(state)
+}
+
+fn test_app() -> axum::Router {
+    test_app_with_config(Config::from_env())
+}
+
+async fn post_json(app: &axum::Router, uri: &str, body: Value) -> (u16, Value) {
+    let req = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    let status = response.status().as_u16();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let parsed: Value = serde_json::from_slice(&bytes).unwrap();
+    (status, parsed)
+}
+
+async fn get_status(app: &axum::Router, uri: &str) -> u16 {
+    let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    app.clone().oneshot(req).await.unwrap().status().as_u16()
+}
+
+// --- DefaultBodyLimit (Config::max_body_kb) ---
+
+#[tokio::test]
+async fn oversized_non_ingest_body_returns_413_with_json_error() {
+    let mut config = Config::from_env();
+    config.max_body_kb = 1; // 1KB, so any body bigger than that on a non-ingest route trips it
+    let app = test_app_with_config(config);
+
+    let oversized = json!({
+        "filter": "x".repeat(4096),
+    });
+    let req = Request::builder()
+        .method("POST")
+        .uri("/api/admin/keys")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&oversized).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(response.status().as_u16(), 413);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let parsed: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(parsed["error"], "request body too large");
+}
+
+#[tokio::test]
+async fn ingest_routes_keep_their_own_more_generous_body_limit() {
+    // max_body_kb is tiny, but the ingest-specific limit (layered closer to
+    // the route, before this merge) is untouched and stays generous, so a
+    // normal-sized event still goes through.
+    let mut config = Config::from_env();
+    config.max_body_kb = 1;
+    let app = test_app_with_config(config);
+
+    let event = json!({
+        "session_id": "sess-1",
+        "agent_type": "claude_code",
+        "event_type": "tool_use",
+        "tool_name": "Read",
+        "tool_input": "x".repeat(4096),
+    });
+    let (status, _body) = post_json(&app, "/api/events", event).await;
+    assert_eq!(status, 201);
+}
+
+// --- Request timeout (Config::request_timeout_ms) ---
+
+#[tokio::test]
+async fn slow_non_streaming_request_returns_408_with_json_error() {
+    let mut config = Config::from_env();
+    config.request_timeout_ms = 0;
+    let app = test_app_with_config(config);
+
+    // A long-poll on an empty database parks for `timeout_ms`, so it's
+    // still pending when the 0ms request timeout fires.
+    let req = Request::builder()
+        .uri("/api/events/poll?since=0&timeout_ms=5000")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(response.status().as_u16(), 408);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let parsed: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(parsed["error"], "request timed out");
+}
+
+#[tokio::test]
+async fn normal_requests_are_unaffected_by_the_default_timeout() {
+    let app = test_app();
+    assert_eq!(get_status(&app, "/api/health").await, 200);
+    assert_eq!(get_status(&app, "/api/stats").await, 200);
+}
+
+#[tokio::test]
+async fn streaming_routes_are_exempt_from_the_request_timeout() {
+    // Even with a timeout of 0ms, /api/stream must not be wrapped by the
+    // timeout layer — it's expected to stay open far longer than any normal
+    // request.
+    let mut config = Config::from_env();
+    config.request_timeout_ms = 0;
+    let app = test_app_with_config(config);
+
+    assert_eq!(get_status(&app, "/api/stream").await, 200);
+}
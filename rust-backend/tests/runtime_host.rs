@@ -25,6 +25,20 @@ async fn health_status(addr: SocketAddr) -> Option<u16> {
         .and_then(|code| code.parse::<u16>().ok())
 }
 
+async fn health_body(addr: SocketAddr) -> Option<String> {
+    let mut stream = TcpStream::connect(addr).await.ok()?;
+    let request = format!(
+        "GET /api/health HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        addr
+    );
+    stream.write_all(request.as_bytes()).await.ok()?;
+
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).await.ok()?;
+    let response = String::from_utf8_lossy(&buf).into_owned();
+    response.split("\r\n\r\n").nth(1).map(str::to_string)
+}
+
 async fn wait_for_health(addr: SocketAddr, timeout_ms: u64) {
     let deadline = std::time::Instant::now() + Duration::from_millis(timeout_ms);
     while std::time::Instant::now() < deadline {
@@ -69,6 +83,21 @@ async fn runtime_host_starts_serves_health_and_stops() {
     wait_for_unreachable(addr, 2_000).await;
 }
 
+#[tokio::test]
+async fn health_reports_ready_once_serving() {
+    let host = start_with_config(test_config())
+        .await
+        .expect("runtime host should start");
+    let addr = host.local_addr();
+    wait_for_health(addr, 2_000).await;
+
+    let body = health_body(addr).await.expect("health response body");
+    let json: serde_json::Value = serde_json::from_str(&body).expect("health body is JSON");
+    assert_eq!(json["ready"], serde_json::json!(true));
+
+    host.stop().await.expect("runtime host should stop cleanly");
+}
+
 #[tokio::test]
 async fn runtime_host_releases_port_for_restart() {
     let port = 36141;
@@ -3,7 +3,6 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use rusqlite::params;
-use serde_json::Value;
 
 use agentmonitor_rs::config::Config;
 use agentmonitor_rs::db;
@@ -12,24 +11,16 @@ use agentmonitor_rs::runtime_tasks::{run_idle_check_once, run_stats_broadcast_on
 use agentmonitor_rs::state::AppState;
 
 fn test_state() -> Arc<AppState> {
-    let conn = db::initialize(Path::new(":memory:")).expect("in-memory DB");
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
     let config = Config::from_env();
     AppState::new(conn, config)
 }
 
-fn parse_sse_message(raw: &str) -> Value {
-    let content = raw
-        .strip_prefix("data: ")
-        .and_then(|s| s.strip_suffix("\n\n"))
-        .unwrap_or(raw);
-    serde_json::from_str(content).expect("valid json payload")
-}
-
 #[tokio::test]
 async fn stats_broadcast_emits_stats_payload_when_clients_connected() {
     let state = test_state();
     {
-        let db = state.db.lock().await;
+        let db = state.write_conn().expect("checkout db connection");
         let params = InsertEventParams {
             event_id: Some("stats-event-1"),
             session_id: "stats-sess-1",
@@ -60,14 +51,13 @@ async fn stats_broadcast_emits_stats_payload_when_clients_connected() {
     let sent = run_stats_broadcast_once(Arc::clone(&state)).await;
     assert!(sent);
 
-    let raw = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+    let event = tokio::time::timeout(Duration::from_secs(1), rx.recv())
         .await
         .expect("expected message in timeout")
         .expect("recv failed");
-    let msg = parse_sse_message(&raw);
-    assert_eq!(msg["type"], "stats");
-    assert!(msg["payload"]["total_events"].as_i64().unwrap_or(0) >= 1);
-    assert!(msg["payload"]["usage_monitor"].is_array());
+    assert_eq!(event.kind, "stats");
+    assert!(event.payload["total_events"].as_i64().unwrap_or(0) >= 1);
+    assert!(event.payload["usage_monitor"].is_array());
 }
 
 #[tokio::test]
@@ -81,7 +71,7 @@ async fn stats_broadcast_skips_when_no_clients_connected() {
 async fn idle_check_broadcasts_session_update_when_sessions_idled() {
     let state = test_state();
     {
-        let db = state.db.lock().await;
+        let db = state.write_conn().expect("checkout db connection");
         db.execute(
             "INSERT INTO agents (id, agent_type) VALUES (?1, ?2)",
             params!["claude_code-default", "claude_code"],
@@ -101,12 +91,11 @@ async fn idle_check_broadcasts_session_update_when_sessions_idled() {
     let idled = run_idle_check_once(Arc::clone(&state)).await;
     assert_eq!(idled, 1);
 
-    let raw = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+    let event = tokio::time::timeout(Duration::from_secs(1), rx.recv())
         .await
         .expect("expected message in timeout")
         .expect("recv failed");
-    let msg = parse_sse_message(&raw);
-    assert_eq!(msg["type"], "session_update");
-    assert_eq!(msg["payload"]["type"], "idle_check");
-    assert_eq!(msg["payload"]["idled"], 1);
+    assert_eq!(event.kind, "session_update");
+    assert_eq!(event.payload["type"], "idle_check");
+    assert_eq!(event.payload["idled"], 1);
 }
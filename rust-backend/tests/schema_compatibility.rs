@@ -1,26 +1,63 @@
 use std::collections::HashSet;
-use std::path::PathBuf;
 
 use rusqlite::Connection;
 
+use agentmonitor_rs::db;
+
 fn init_db() -> Connection {
-    let _path = PathBuf::from(":memory:");
-    // Can't use initialize with :memory: since it takes a Path, so replicate inline
+    // db::schema::apply_schema only migrates; replicate the pool's pragma
+    // setup inline so this still exercises the same WAL/busy_timeout config.
     let conn = Connection::open_in_memory().unwrap();
     conn.pragma_update(None, "journal_mode", "WAL").unwrap();
     conn.pragma_update(None, "busy_timeout", 5000).unwrap();
-
-    // Load schema from the actual module via file-based init
-    // For in-memory, we execute the schema SQL directly
-    let schema_sql = include_str!("../src/db/schema.rs");
-    // Extract the SQL between r#" and "#
-    let start = schema_sql.find("r#\"\n").unwrap() + 4;
-    let end = schema_sql.rfind("\"#;").unwrap();
-    let sql = &schema_sql[start..end];
-    conn.execute_batch(sql).unwrap();
+    db::schema::apply_schema(&conn).unwrap();
     conn
 }
 
+#[test]
+fn migrate_records_applied_migrations() {
+    let conn = init_db();
+    let applied: Vec<(u32, String)> = conn
+        .prepare("SELECT version, name FROM schema_migrations ORDER BY version")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+
+    assert_eq!(
+        applied,
+        vec![
+            (1, "base_schema".to_string()),
+            (2, "import_state".to_string()),
+            (3, "event_pricing_version".to_string()),
+            (4, "session_stats_rollup".to_string()),
+            (5, "events_fts".to_string()),
+            (6, "event_fingerprints".to_string()),
+            (7, "payload_chunks".to_string()),
+            (8, "tenant_partitioning".to_string()),
+            (9, "settings_table".to_string()),
+            (10, "admin_key_scope".to_string()),
+            (11, "tenant_scoped_uniqueness".to_string()),
+        ]
+    );
+    assert_eq!(db::migrations::current_version(&conn).unwrap(), 11);
+}
+
+#[test]
+fn migrate_is_idempotent() {
+    let conn = init_db();
+    let version_again = db::migrations::migrate(&conn).unwrap();
+    assert_eq!(version_again, 11);
+
+    let applied_count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM schema_migrations", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(applied_count, 11, "re-running migrate must not reapply already-applied migrations");
+}
+
 fn get_table_names(conn: &Connection) -> HashSet<String> {
     let mut stmt = conn
         .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'")
@@ -68,6 +105,10 @@ fn import_state_columns_match_typescript() {
         "source",
         "events_imported",
         "imported_at",
+        "byte_offset",
+        "line_offset",
+        "cost_state",
+        "header_hash",
     ]
     .iter()
     .map(|s| s.to_string())
@@ -135,6 +176,8 @@ fn events_columns_match_typescript() {
         "cache_read_tokens",
         "cache_write_tokens",
         "source",
+        "pricing_version",
+        "tenant_id",
     ]
     .iter()
     .map(|s| s.to_string())
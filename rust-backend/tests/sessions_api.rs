@@ -12,7 +12,7 @@ use agentmonitor_rs::db;
 use agentmonitor_rs::state::AppState;
 
 fn test_app() -> axum::Router {
-    let conn = db::initialize(Path::new(":memory:")).expect("in-memory DB");
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
     let config = Config::from_env();
     let state: Arc<AppState> = AppState::new(conn, config);
     agentmonitor_rs::build_router(state)
@@ -166,6 +166,93 @@ async fn transcript_maps_entries_with_roles() {
     assert!(!entries.is_empty());
     assert!(entries.iter().any(|entry| entry["role"] == "user"));
     assert!(entries.iter().any(|entry| entry["role"] == "tool"));
+    assert!(body["next_cursor"].is_null());
+}
+
+#[tokio::test]
+async fn transcript_paginates_with_cursor() {
+    let app = test_app();
+
+    for i in 0..5 {
+        post_json(
+            &app,
+            "/api/events",
+            json!({
+                "session_id": "sess-transcript-page",
+                "agent_type": "claude_code",
+                "event_type": "tool_use",
+                "tool_name": format!("Tool{i}")
+            }),
+        )
+        .await;
+    }
+
+    let (status, first) =
+        get_json(&app, "/api/sessions/sess-transcript-page/transcript?limit=2").await;
+    assert_eq!(status, 200);
+    let first_entries = first["entries"].as_array().unwrap();
+    assert_eq!(first_entries.len(), 2);
+    let cursor = first["next_cursor"].as_str().expect("expected a next_cursor");
+
+    let (status, second) = get_json(
+        &app,
+        &format!("/api/sessions/sess-transcript-page/transcript?limit=2&cursor={cursor}"),
+    )
+    .await;
+    assert_eq!(status, 200);
+    let second_entries = second["entries"].as_array().unwrap();
+    assert_eq!(second_entries.len(), 2);
+    assert_ne!(first_entries[0]["tool_name"], second_entries[0]["tool_name"]);
+
+    let (status, third) = get_json(
+        &app,
+        &format!(
+            "/api/sessions/sess-transcript-page/transcript?limit=2&cursor={}",
+            second["next_cursor"].as_str().unwrap()
+        ),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(third["entries"].as_array().unwrap().len(), 1);
+    assert!(third["next_cursor"].is_null());
+}
+
+#[tokio::test]
+async fn transcript_filters_by_role() {
+    let app = test_app();
+
+    post_json(
+        &app,
+        "/api/events",
+        json!({
+            "session_id": "sess-transcript-role",
+            "agent_type": "claude_code",
+            "event_type": "user_prompt",
+            "metadata": { "message": "hi" }
+        }),
+    )
+    .await;
+    post_json(
+        &app,
+        "/api/events",
+        json!({
+            "session_id": "sess-transcript-role",
+            "agent_type": "claude_code",
+            "event_type": "tool_use",
+            "tool_name": "Read"
+        }),
+    )
+    .await;
+
+    let (status, body) = get_json(
+        &app,
+        "/api/sessions/sess-transcript-role/transcript?role=tool",
+    )
+    .await;
+    assert_eq!(status, 200);
+    let entries = body["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["role"], "tool");
 }
 
 #[tokio::test]
@@ -12,7 +12,7 @@ use agentmonitor_rs::db;
 use agentmonitor_rs::state::AppState;
 
 fn test_app() -> axum::Router {
-    let conn = db::initialize(Path::new(":memory:")).expect("in-memory DB");
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
     let config = Config::from_env();
     let state: Arc<AppState> = AppState::new(conn, config);
     agentmonitor_rs::build_router(state)
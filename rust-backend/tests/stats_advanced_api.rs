@@ -12,7 +12,7 @@ use agentmonitor_rs::db;
 use agentmonitor_rs::state::AppState;
 
 fn test_app() -> axum::Router {
-    let conn = db::initialize(Path::new(":memory:")).expect("in-memory DB");
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
     let config = Config::from_env();
     let state: Arc<AppState> = AppState::new(conn, config);
     agentmonitor_rs::build_router(state)
@@ -119,6 +119,54 @@ async fn stats_cost_returns_shape_and_breakdowns() {
     );
 }
 
+#[tokio::test]
+async fn analytics_returns_grouped_buckets() {
+    let app = test_app();
+
+    post_json(
+        &app,
+        "/api/events",
+        json!({
+            "session_id": "analytics-sess",
+            "agent_type": "claude_code",
+            "event_type": "llm_response",
+            "project": "proj-analytics",
+            "model": "model-analytics",
+            "tokens_in": 100,
+            "tokens_out": 50,
+            "cache_read_tokens": 10,
+            "cache_write_tokens": 5,
+            "cost_usd": 1.0,
+            "duration_ms": 200
+        }),
+    )
+    .await;
+
+    let (status, body) = get_json(
+        &app,
+        "/api/analytics?since=1970-01-01T00:00:00Z&interval=day&group_by=model",
+    )
+    .await;
+    assert_eq!(status, 200);
+
+    let buckets = body["buckets"].as_array().unwrap();
+    let row = buckets
+        .iter()
+        .find(|b| b["group"] == "model-analytics")
+        .expect("bucket for model-analytics");
+    assert_eq!(row["event_count"], 1);
+    assert_eq!(row["tokens_in"], 100);
+    assert_eq!(row["tokens_out"], 50);
+    assert_eq!(row["cache_read_tokens"], 10);
+    assert_eq!(row["cache_write_tokens"], 5);
+    assert_eq!(row["cost_usd"], 1.0);
+    assert_eq!(row["avg_duration_ms"], 200.0);
+
+    let (status, body) = get_json(&app, "/api/analytics?group_by=bogus").await;
+    assert_eq!(status, 200);
+    assert!(body["buckets"].as_array().unwrap().iter().all(|b| b["group"].is_null()));
+}
+
 #[tokio::test]
 async fn usage_monitor_returns_claude_and_codex_shapes() {
     let app = test_app();
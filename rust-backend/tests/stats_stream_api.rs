@@ -12,7 +12,7 @@ use agentmonitor_rs::db;
 use agentmonitor_rs::state::AppState;
 
 fn test_app() -> axum::Router {
-    let conn = db::initialize(Path::new(":memory:")).expect("in-memory DB");
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
     let config = Config::from_env();
     let state: Arc<AppState> = AppState::new(conn, config);
     agentmonitor_rs::build_router(state)
@@ -133,7 +133,7 @@ async fn stream_sends_connected_message() {
 #[tokio::test]
 async fn stream_max_clients_returns_503() {
     // Build app with max_sse_clients=1
-    let conn = db::initialize(Path::new(":memory:")).expect("in-memory DB");
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
     let mut config = Config::from_env();
     config.max_sse_clients = 1;
     let state: Arc<AppState> = AppState::new(conn, config);
@@ -207,6 +207,86 @@ async fn ingest_broadcasts_event_to_sse_clients() {
     assert!(msg.contains("\"type\":\"event\""), "expected event broadcast, got: {msg}");
 }
 
+#[tokio::test]
+async fn stream_session_id_filter_drops_non_matching_events() {
+    let app = test_app();
+
+    let req = Request::builder()
+        .uri("/api/stream?session_id=wanted-session")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    use futures_util::StreamExt;
+    let mut stream = response.into_body().into_data_stream();
+
+    let first = stream.next().await.unwrap().unwrap();
+    assert!(String::from_utf8(first.to_vec()).unwrap().contains("connected"));
+
+    // A different session's event should never reach this client.
+    post_json(&app, "/api/events", json!({
+        "session_id": "other-session",
+        "agent_type": "claude_code",
+        "event_type": "tool_use"
+    })).await;
+
+    // The requested session's event should still come through.
+    post_json(&app, "/api/events", json!({
+        "session_id": "wanted-session",
+        "agent_type": "claude_code",
+        "event_type": "tool_use"
+    })).await;
+
+    let next = tokio::time::timeout(std::time::Duration::from_secs(2), stream.next())
+        .await
+        .expect("expected a broadcast within 2 seconds")
+        .unwrap()
+        .unwrap();
+    let msg = String::from_utf8(next.to_vec()).unwrap();
+    assert!(msg.contains("\"session_id\":\"wanted-session\""), "got: {msg}");
+    assert!(!msg.contains("other-session"), "filtered client saw the other session's event: {msg}");
+}
+
+#[tokio::test]
+async fn stream_reports_gap_when_client_lags_past_broadcast_capacity() {
+    let app = test_app();
+
+    let req = Request::builder()
+        .uri("/api/stream")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    use futures_util::StreamExt;
+    let mut stream = response.into_body().into_data_stream();
+    let first = stream.next().await.unwrap().unwrap();
+    assert!(String::from_utf8(first.to_vec()).unwrap().contains("connected"));
+
+    // The hub's broadcast channel (`SseHub::new`) holds 256 messages — ingest
+    // past that without ever reading from `stream`, so this client falls
+    // behind the live channel entirely rather than just the replay buffer.
+    for i in 0..300 {
+        post_json(&app, "/api/events", json!({
+            "session_id": format!("lag-session-{i}"),
+            "agent_type": "claude_code",
+            "event_type": "tool_use"
+        })).await;
+    }
+
+    // The client should be told it missed frames instead of the connection
+    // silently stalling or resuming mid-stream with a hole in it.
+    let next = tokio::time::timeout(std::time::Duration::from_secs(2), stream.next())
+        .await
+        .expect("expected a gap frame within 2 seconds")
+        .unwrap()
+        .unwrap();
+    let msg = String::from_utf8(next.to_vec()).unwrap();
+    assert!(msg.contains("\"type\":\"gap\""), "expected a lag gap frame, got: {msg}");
+    assert!(msg.contains("\"skipped\""), "expected a skipped count, got: {msg}");
+}
+
 // ==================== Health endpoint reflects SSE client count ====================
 
 #[tokio::test]
@@ -231,3 +311,183 @@ async fn health_reflects_sse_client_count() {
     let (_, health2) = get_json(&app, "/api/health").await;
     assert_eq!(health2["sse_clients"], 1);
 }
+
+// ==================== Resumable streams (Last-Event-ID replay) ====================
+
+#[test]
+fn sse_hub_replay_since_returns_only_newer_broadcasts() {
+    use agentmonitor_rs::sse::hub::SseHub;
+
+    let hub = SseHub::new(10, 1000);
+    hub.broadcast("stats", &json!({"total_events": 1}));
+    hub.broadcast("session_update", &json!({"type": "idle_check", "idled": 1}));
+    hub.broadcast("stats", &json!({"total_events": 2}));
+
+    let all = hub.replay_since(0);
+    assert_eq!(all.len(), 3);
+    assert_eq!(all[0].id, 1);
+    assert_eq!(all[2].id, 3);
+
+    let since_first = hub.replay_since(all[0].id);
+    assert_eq!(since_first.len(), 2);
+    assert_eq!(since_first[0].kind, "session_update");
+
+    let since_last = hub.replay_since(all[2].id);
+    assert!(since_last.is_empty());
+}
+
+#[test]
+fn sse_hub_subscribe_never_admits_past_max_clients_under_contention() {
+    use agentmonitor_rs::sse::hub::SseHub;
+    use std::sync::Arc;
+    use std::thread;
+
+    let hub = Arc::new(SseHub::new(8, 10));
+    let attempts = 64;
+
+    let handles: Vec<_> = (0..attempts)
+        .map(|_| {
+            let hub = Arc::clone(&hub);
+            thread::spawn(move || hub.subscribe())
+        })
+        .collect();
+
+    let admitted: Vec<_> = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .filter_map(|client| client)
+        .collect();
+
+    assert_eq!(admitted.len(), 8, "max_clients must cap admission exactly, even with racing subscribers");
+    assert_eq!(hub.client_count(), 8);
+
+    drop(admitted);
+    assert_eq!(hub.client_count(), 0);
+}
+
+#[tokio::test]
+async fn stream_replays_missed_broadcasts_after_last_event_id() {
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let config = Config::from_env();
+    let state: Arc<AppState> = AppState::new(conn, config);
+    let app = agentmonitor_rs::build_router(Arc::clone(&state));
+
+    // Broadcast a couple of frames before any client is connected — they land
+    // in the hub's replay buffer, not in a live subscriber.
+    state.sse_hub.broadcast("stats", &json!({"total_events": 1}));
+    state
+        .sse_hub
+        .broadcast("session_update", &json!({"type": "idle_check", "idled": 2}));
+
+    // Reconnect with Last-Event-ID 0 — should replay both missed frames
+    // before the connected message's live stream takes over.
+    let req = Request::builder()
+        .uri("/api/stream")
+        .header("last-event-id", "0")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    use futures_util::StreamExt;
+    let mut stream = response.into_body().into_data_stream();
+
+    let first = stream.next().await.unwrap().unwrap();
+    assert!(String::from_utf8(first.to_vec()).unwrap().contains("connected"));
+
+    let second = stream.next().await.unwrap().unwrap();
+    let second_text = String::from_utf8(second.to_vec()).unwrap();
+    assert!(second_text.contains("\"type\":\"stats\""), "got: {second_text}");
+
+    let third = stream.next().await.unwrap().unwrap();
+    let third_text = String::from_utf8(third.to_vec()).unwrap();
+    assert!(third_text.contains("\"type\":\"session_update\""), "got: {third_text}");
+}
+
+#[tokio::test]
+async fn stream_accepts_from_id_query_param_as_since_seq_alias() {
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let config = Config::from_env();
+    let state: Arc<AppState> = AppState::new(conn, config);
+    let app = agentmonitor_rs::build_router(Arc::clone(&state));
+
+    state.sse_hub.broadcast("stats", &json!({"total_events": 1}));
+
+    let req = Request::builder()
+        .uri("/api/stream?from_id=0")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    use futures_util::StreamExt;
+    let mut stream = response.into_body().into_data_stream();
+    let _connected = stream.next().await.unwrap().unwrap();
+
+    let second = stream.next().await.unwrap().unwrap();
+    let second_text = String::from_utf8(second.to_vec()).unwrap();
+    assert!(second_text.contains("\"type\":\"stats\""), "got: {second_text}");
+}
+
+#[tokio::test]
+async fn stream_emits_gap_marker_when_resuming_past_evicted_history() {
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let config = Config::from_env();
+    let state: Arc<AppState> = AppState::new(conn, config);
+    let app = agentmonitor_rs::build_router(Arc::clone(&state));
+
+    // Push well past the replay buffer's capacity so the earliest ids get
+    // evicted, then ask to resume from one of those evicted ids.
+    for i in 0..1005 {
+        state.sse_hub.broadcast("stats", &json!({"total_events": i}));
+    }
+    let oldest = state.sse_hub.oldest_id().unwrap();
+    assert!(oldest > 1, "expected eviction to have happened");
+
+    let req = Request::builder()
+        .uri("/api/stream")
+        .header("last-event-id", "1")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    use futures_util::StreamExt;
+    let mut stream = response.into_body().into_data_stream();
+    let _connected = stream.next().await.unwrap().unwrap();
+
+    let second = stream.next().await.unwrap().unwrap();
+    let second_text = String::from_utf8(second.to_vec()).unwrap();
+    assert!(second_text.contains("\"type\":\"gap\""), "got: {second_text}");
+    assert!(second_text.contains(&format!("\"from\":{oldest}")), "got: {second_text}");
+}
+
+#[tokio::test]
+async fn stream_sends_keepalive_comment_on_idle_connection() {
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let mut config = Config::from_env();
+    config.sse_heartbeat_ms = 20;
+    let state: Arc<AppState> = AppState::new(conn, config);
+    let app = agentmonitor_rs::build_router(state);
+
+    let req = Request::builder()
+        .uri("/api/stream")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    use futures_util::StreamExt;
+    let mut stream = response.into_body().into_data_stream();
+    let _connected = stream.next().await.unwrap().unwrap();
+
+    // No broadcasts happen — the next frame on an idle connection must be
+    // the keep-alive comment, so proxies/browsers don't drop the stream.
+    let keepalive = tokio::time::timeout(std::time::Duration::from_millis(500), stream.next())
+        .await
+        .expect("expected a keep-alive frame within 500ms")
+        .unwrap()
+        .unwrap();
+    let text = String::from_utf8(keepalive.to_vec()).unwrap();
+    assert!(text.starts_with(':'), "expected an SSE comment frame, got: {text}");
+}
@@ -0,0 +1,167 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use agentmonitor_rs::config::Config;
+use agentmonitor_rs::runtime_host::start_with_config;
+
+fn test_config() -> Config {
+    let mut config = Config::from_env();
+    config.host = "127.0.0.1".into();
+    config.port = 0;
+    config.auto_import_interval_minutes = 0;
+    config.stats_interval_ms = 100;
+    config
+}
+
+async fn wait_for_unreachable(addr: SocketAddr, timeout_ms: u64) {
+    let deadline = std::time::Instant::now() + Duration::from_millis(timeout_ms);
+    while std::time::Instant::now() < deadline {
+        if tokio::net::TcpStream::connect(addr).await.is_err() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    panic!("server still reachable at {addr} after {timeout_ms}ms");
+}
+
+/// A cert verifier that accepts anything, so the test client can complete a
+/// handshake against the self-signed cert generated below without needing a
+/// real CA. Never used outside this test.
+#[derive(Debug)]
+struct AcceptAnyServerCert;
+
+impl rustls::client::danger::ServerCertVerifier for AcceptAnyServerCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::pki_types::CertificateDer<'_>,
+        _intermediates: &[rustls::pki_types::CertificateDer<'_>],
+        _server_name: &rustls::pki_types::ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: rustls::pki_types::UnixTime,
+    ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::danger::ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &rustls::pki_types::CertificateDer<'_>,
+        _dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        Ok(rustls::client::danger::HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &rustls::pki_types::CertificateDer<'_>,
+        _dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        Ok(rustls::client::danger::HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        rustls::crypto::ring::default_provider()
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+async fn https_health_status(addr: SocketAddr) -> u16 {
+    let client_config = rustls::ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyServerCert))
+        .with_no_client_auth();
+    let connector = tokio_rustls::TlsConnector::from(Arc::new(client_config));
+
+    let tcp = tokio::net::TcpStream::connect(addr).await.expect("connect over TCP");
+    let server_name = rustls::pki_types::ServerName::try_from("localhost").unwrap();
+    let mut tls = connector.connect(server_name, tcp).await.expect("TLS handshake");
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let request = format!(
+        "GET /api/health HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n"
+    );
+    tls.write_all(request.as_bytes()).await.expect("write request");
+
+    let mut buf = Vec::new();
+    tls.read_to_end(&mut buf).await.expect("read response");
+    let response = String::from_utf8_lossy(&buf);
+    let status_line = response.lines().next().expect("status line");
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .expect("status code")
+}
+
+#[test]
+fn tls_is_dormant_by_default() {
+    let config = Config::from_env();
+    assert!(config.tls_cert_path.is_none());
+    assert!(config.tls_key_path.is_none());
+}
+
+#[tokio::test]
+async fn tls_listener_serves_health_over_https() {
+    let tmp_dir = tempfile::tempdir().expect("create temp dir");
+    let cert_path = tmp_dir.path().join("cert.pem");
+    let key_path = tmp_dir.path().join("key.pem");
+
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+        .expect("generate self-signed cert");
+    std::fs::write(&cert_path, cert.cert.pem()).expect("write cert");
+    std::fs::write(&key_path, cert.signing_key.serialize_pem()).expect("write key");
+
+    let mut config = test_config();
+    config.tls_cert_path = Some(cert_path);
+    config.tls_key_path = Some(key_path);
+
+    let host = start_with_config(config)
+        .await
+        .expect("runtime host should start with TLS configured");
+    let addr = host.local_addr();
+
+    // Plain HTTP should not speak to a TLS listener.
+    let plain = tokio::net::TcpStream::connect(addr).await.expect("connect over TCP");
+    drop(plain);
+
+    let status = https_health_status(addr).await;
+    assert_eq!(status, 200);
+
+    host.stop().await.expect("runtime host should stop cleanly");
+    wait_for_unreachable(addr, 2_000).await;
+}
+
+#[tokio::test]
+async fn tls_stays_disabled_when_only_cert_path_is_set() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let tmp_dir = tempfile::tempdir().expect("create temp dir");
+    let cert_path = tmp_dir.path().join("cert.pem");
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+        .expect("generate self-signed cert");
+    std::fs::write(&cert_path, cert.cert.pem()).expect("write cert");
+
+    let mut config = test_config();
+    config.tls_cert_path = Some(cert_path);
+    // tls_key_path intentionally left unset.
+
+    let host = start_with_config(config)
+        .await
+        .expect("runtime host should start plaintext when only one TLS field is set");
+    let addr = host.local_addr();
+
+    let mut stream = tokio::net::TcpStream::connect(addr).await.expect("connect over TCP");
+    let request = format!(
+        "GET /api/health HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n"
+    );
+    stream.write_all(request.as_bytes()).await.expect("write request");
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).await.expect("read response");
+    let response = String::from_utf8_lossy(&buf);
+    assert!(response.starts_with("HTTP/1.1 200"), "expected plaintext HTTP, got: {response}");
+
+    host.stop().await.expect("runtime host should stop cleanly");
+}
@@ -0,0 +1,129 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+use agentmonitor_rs::config::Config;
+use agentmonitor_rs::db;
+use agentmonitor_rs::state::AppState;
+
+async fn spawn_app() -> (Arc<AppState>, String) {
+    let conn = db::pool::initialize(Path::new(":memory:"), 4, None).expect("in-memory DB pool");
+    let config = Config::from_env();
+    let state: Arc<AppState> = AppState::new(conn, config);
+    let app = agentmonitor_rs::build_router(Arc::clone(&state));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (state, format!("ws://{addr}/api/ws"))
+}
+
+async fn next_frame(
+    socket: &mut tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >,
+) -> Value {
+    loop {
+        match socket.next().await.unwrap().unwrap() {
+            WsMessage::Text(text) => return serde_json::from_str(&text).unwrap(),
+            WsMessage::Ping(_) | WsMessage::Pong(_) => continue,
+            other => panic!("unexpected websocket message: {other:?}"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn ws_sends_connected_message_on_upgrade() {
+    let (_state, url) = spawn_app().await;
+    let (mut socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+
+    let frame = next_frame(&mut socket).await;
+    assert_eq!(frame["type"], "connected");
+}
+
+#[tokio::test]
+async fn ws_relays_broadcast_events() {
+    let (state, url) = spawn_app().await;
+    let (mut socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    let _connected = next_frame(&mut socket).await;
+
+    state.sse_hub.broadcast("stats", &json!({"total_events": 1}));
+
+    let frame = next_frame(&mut socket).await;
+    assert_eq!(frame["type"], "stats");
+    assert_eq!(frame["payload"]["total_events"], 1);
+}
+
+#[tokio::test]
+async fn ws_subscribe_message_filters_the_stream() {
+    let (state, url) = spawn_app().await;
+    let (mut socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    let _connected = next_frame(&mut socket).await;
+
+    socket
+        .send(WsMessage::Text(
+            json!({"subscribe": {"agent_type": "codex"}}).to_string(),
+        ))
+        .await
+        .unwrap();
+
+    state
+        .sse_hub
+        .broadcast("event", &json!({"agent_type": "claude_code", "event_type": "tool_use"}));
+    state
+        .sse_hub
+        .broadcast("event", &json!({"agent_type": "codex", "event_type": "tool_use"}));
+
+    let frame = next_frame(&mut socket).await;
+    assert_eq!(frame["payload"]["agent_type"], "codex");
+}
+
+#[tokio::test]
+async fn ws_event_message_ingests_over_the_socket() {
+    let (state, url) = spawn_app().await;
+    let (mut socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    let _connected = next_frame(&mut socket).await;
+
+    socket
+        .send(WsMessage::Text(
+            json!({"event": {
+                "session_id": "ws-session",
+                "agent_type": "claude_code",
+                "event_type": "tool_use"
+            }})
+            .to_string(),
+        ))
+        .await
+        .unwrap();
+
+    // Give the handler a beat to process the inbound message and commit.
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let conn = state.read_conn().unwrap();
+    let count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM events WHERE session_id = 'ws-session'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[tokio::test]
+async fn health_reflects_websocket_client_count() {
+    let (state, url) = spawn_app().await;
+    assert_eq!(state.sse_hub.client_count(), 0);
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    let _connected = next_frame(&mut socket).await;
+
+    assert_eq!(state.sse_hub.client_count(), 1);
+}
@@ -11,16 +11,31 @@ use agentmonitor_rs::runtime_contract::{
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 
+/// Which transport `ipc::fetch_health` (and, eventually, other IPC commands)
+/// should dial to reach the embedded backend. `Unix` is preferred when
+/// available — no listening TCP port is needed for the round trip — with
+/// `Tcp` as the fallback on platforms `runtime_host::bind_ipc_socket` doesn't
+/// support, or if the socket failed to bind. The TCP listener behind
+/// `local_addr`/`base_url` always exists regardless of this choice: the
+/// webview itself still loads the app and talks to the API over HTTP.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BackendTransport {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
 pub struct EmbeddedBackend {
     runtime: Option<RuntimeContract>,
     local_addr: SocketAddr,
     base_url: String,
+    ipc_socket_path: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EmbeddedBackendSnapshot {
     pub local_addr: SocketAddr,
     pub base_url: String,
+    pub transport: BackendTransport,
 }
 
 impl EmbeddedBackend {
@@ -32,6 +47,13 @@ impl EmbeddedBackend {
         &self.base_url
     }
 
+    /// The embedded backend's shared application state, for in-process
+    /// callers that want to call a handler's logic directly rather than
+    /// round-tripping through HTTP — see `ipc::desktop_health_from_state`.
+    pub fn app_state(&self) -> Option<&std::sync::Arc<agentmonitor_rs::state::AppState>> {
+        self.runtime.as_ref().and_then(RuntimeContract::app_state)
+    }
+
     pub async fn shutdown(mut self) -> Result<(), BackendStartupError> {
         if let Some(runtime) = self.runtime.take() {
             runtime
@@ -43,9 +65,14 @@ impl EmbeddedBackend {
     }
 
     fn snapshot(&self) -> EmbeddedBackendSnapshot {
+        let transport = match &self.ipc_socket_path {
+            Some(path) => BackendTransport::Unix(path.clone()),
+            None => BackendTransport::Tcp(self.local_addr),
+        };
         EmbeddedBackendSnapshot {
             local_addr: self.local_addr,
             base_url: self.base_url.clone(),
+            transport,
         }
     }
 }
@@ -91,6 +118,26 @@ impl EmbeddedBackendState {
             .ok_or_else(|| "embedded backend not available".to_string())?;
         Ok(backend.snapshot())
     }
+
+    /// Run `f` against the embedded backend's shared application state
+    /// in-process, without a round trip through its HTTP listener. See
+    /// `ipc::desktop_health_from_state`.
+    pub fn with_app_state<T>(
+        &self,
+        f: impl FnOnce(&agentmonitor_rs::state::AppState) -> T,
+    ) -> Result<T, String> {
+        let guard = self
+            .backend
+            .lock()
+            .map_err(|_| "embedded backend state lock poisoned".to_string())?;
+        let backend = guard
+            .as_ref()
+            .ok_or_else(|| "embedded backend not available".to_string())?;
+        let state = backend
+            .app_state()
+            .ok_or_else(|| "embedded backend runtime not available".to_string())?;
+        Ok(f(state))
+    }
 }
 
 #[derive(Debug)]
@@ -156,11 +203,30 @@ pub fn apply_desktop_runtime_overrides(
                 ))
             })?;
         }
+        // Anchor the IPC socket to app data rather than a temp dir when one's
+        // available, so it survives alongside the database it's socketed
+        // next to. `AGENTMONITOR_IPC_SOCKET_PATH` still wins if set.
+        if config.ipc_socket_path.is_none() {
+            config.ipc_socket_path = Some(app_data_dir.join("agentmonitor.sock"));
+        }
     }
 
     Ok(config)
 }
 
+/// Default IPC socket path used when neither `AGENTMONITOR_IPC_SOCKET_PATH`
+/// nor an app data dir gave us one — e.g. in tests that build a `Config`
+/// directly. Unique per call so concurrently-running embedded backends in
+/// the same process (same pid) don't collide on one socket file.
+fn default_ipc_socket_path() -> PathBuf {
+    static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let suffix = COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    std::env::temp_dir().join(format!(
+        "agentmonitor-ipc-{}-{suffix}.sock",
+        std::process::id()
+    ))
+}
+
 fn desktop_runtime_config_from_env(app_data_dir: Option<&Path>) -> Result<Config, BackendStartupError> {
     let base = Config::from_env();
     let overrides = DesktopBindOverrides::from_env();
@@ -179,13 +245,21 @@ pub async fn start_embedded_backend_with_app_data_dir(
 }
 
 pub async fn start_embedded_backend_with_config(
-    config: Config,
+    mut config: Config,
 ) -> Result<EmbeddedBackend, BackendStartupError> {
+    if config.ipc_socket_path.is_none() {
+        config.ipc_socket_path = Some(default_ipc_socket_path());
+    }
+
     let runtime = start_runtime_with_config(config)
         .await
         .map_err(map_start_error)?;
     let local_addr = runtime.local_addr();
     let base_url = runtime.base_url().to_string();
+    // `runtime_host::bind_ipc_socket` falling back to TCP-only (unsupported
+    // platform, or the bind itself failed) means `ipc_socket_path()` comes
+    // back `None` here even though we asked for one above.
+    let ipc_socket_path = runtime.ipc_socket_path().map(Path::to_path_buf);
 
     if let Err(err) = wait_for_health(local_addr, Duration::from_secs(2)).await {
         // Ensure partially-started runtime does not leak on readiness failure.
@@ -197,6 +271,7 @@ pub async fn start_embedded_backend_with_config(
         runtime: Some(runtime),
         local_addr,
         base_url,
+        ipc_socket_path,
     })
 }
 
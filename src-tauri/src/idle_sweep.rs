@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use agentmonitor_rs::db::{queries, settings};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::backend::EmbeddedBackendState;
+
+const DEFAULT_POLL_MINUTES: u64 = 30;
+const DEFAULT_THRESHOLD_MINUTES: u64 = 30;
+
+const SETTING_POLL_MINUTES: &str = "idle_sweep_poll_minutes";
+const SETTING_THRESHOLD_MINUTES: &str = "idle_sweep_threshold_minutes";
+
+#[derive(Clone, Serialize)]
+struct SessionIdleClosed {
+    session_id: String,
+}
+
+/// Closes sessions the window never reaped itself. `runtime_tasks::run_idle_check_once`
+/// already ages sessions `active` -> `idle` -> `ended` on the embedded
+/// backend's own schedule (see `RuntimeSettings::idle_timeout_minutes`); this
+/// sweeper runs alongside it, not instead of it, because it exists for a
+/// different reason — it emits a `session-idle-closed` Tauri event the
+/// frontend can react to live, and its cutoff is a desktop-only preference
+/// read from the `settings` table rather than server config. Spawned once
+/// from `runtime_coordinator::initialize` after the embedded backend is up;
+/// both threshold and poll cadence can be changed at runtime by writing to
+/// `settings` — no restart needed, since each tick re-reads them.
+pub fn spawn(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let poll_minutes = read_setting(&app_handle, SETTING_POLL_MINUTES)
+                .unwrap_or(DEFAULT_POLL_MINUTES)
+                .max(1);
+            tokio::time::sleep(Duration::from_secs(poll_minutes * 60)).await;
+
+            for session_id in sweep_once(&app_handle) {
+                let _ = app_handle.emit("session-idle-closed", SessionIdleClosed { session_id });
+            }
+        }
+    });
+}
+
+fn read_setting(app_handle: &AppHandle, key: &str) -> Option<u64> {
+    let backend_state = app_handle.try_state::<EmbeddedBackendState>()?;
+    backend_state
+        .with_app_state(|app_state| {
+            let conn = app_state.db.writer.get().ok()?;
+            settings::get::<u64>(&conn, key).ok().flatten()
+        })
+        .ok()
+        .flatten()
+}
+
+/// Ages every tenant's sessions through `active` -> `idle` -> `ended` via
+/// `queries::update_idle_sessions_returning_ended` (the same staging
+/// `runtime_tasks::run_idle_check_once` uses), and returns the ids that
+/// reached `ended` this tick. Iterates `queries::list_tenants` rather than
+/// scanning the whole `sessions` table in one unscoped query, so a
+/// multi-tenant embedded backend never closes one tenant's sessions against
+/// another's threshold setting.
+fn sweep_once(app_handle: &AppHandle) -> Vec<String> {
+    let Some(backend_state) = app_handle.try_state::<EmbeddedBackendState>() else {
+        return Vec::new();
+    };
+
+    backend_state
+        .with_app_state(|app_state| {
+            let Ok(conn) = app_state.db.writer.get() else {
+                return Vec::new();
+            };
+            let threshold_minutes = settings::get::<u64>(&conn, SETTING_THRESHOLD_MINUTES)
+                .ok()
+                .flatten()
+                .unwrap_or(DEFAULT_THRESHOLD_MINUTES);
+
+            let tenants = queries::list_tenants(&conn).unwrap_or_default();
+            tenants
+                .iter()
+                .flat_map(|tenant_id| {
+                    queries::update_idle_sessions_returning_ended(&conn, threshold_minutes, tenant_id)
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
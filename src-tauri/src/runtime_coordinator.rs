@@ -1,8 +1,12 @@
 use std::fmt;
+use std::path::PathBuf;
+use std::sync::Mutex;
 
+use serde::{Deserialize, Serialize};
 use tauri::Manager;
 
 use crate::backend;
+use crate::idle_sweep;
 
 #[derive(Debug)]
 pub enum RuntimeCoordinatorError {
@@ -25,31 +29,141 @@ impl fmt::Display for RuntimeCoordinatorError {
 
 impl std::error::Error for RuntimeCoordinatorError {}
 
-pub fn initialize(app: &tauri::App) -> Result<(), RuntimeCoordinatorError> {
-    let app_data_dir = app.path().app_data_dir().ok();
-    let backend = tauri::async_runtime::block_on(backend::start_embedded_backend_with_app_data_dir(
-        app_data_dir,
-    ))
-        .map_err(|err| RuntimeCoordinatorError::BackendStart(err.to_string()))?;
-    let backend_url = backend.base_url().to_string();
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SetupErrorSeverity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SetupError {
+    pub severity: SetupErrorSeverity,
+    pub message: String,
+}
+
+/// Recoverable problems hit during `initialize`, recorded here instead of
+/// failing setup outright — following the pattern of surfacing setup errors
+/// on the home screen rather than a fatal popup. `lib::run` manages one of
+/// these so `get_setup_errors` can hand the list to the frontend. The one
+/// case that still aborts setup is `RuntimeCoordinatorError::MainWindowMissing`:
+/// with no window there's nowhere to show anything.
+#[derive(Default)]
+pub struct SetupErrorState {
+    errors: Mutex<Vec<SetupError>>,
+}
+
+impl SetupErrorState {
+    pub fn record(&self, severity: SetupErrorSeverity, message: impl Into<String>) {
+        self.errors
+            .lock()
+            .expect("setup error state lock poisoned")
+            .push(SetupError { severity, message: message.into() });
+    }
+
+    pub fn snapshot(&self) -> Vec<SetupError> {
+        self.errors
+            .lock()
+            .expect("setup error state lock poisoned")
+            .clone()
+    }
+}
+
+#[tauri::command]
+pub fn get_setup_errors(state: tauri::State<'_, SetupErrorState>) -> Vec<SetupError> {
+    state.snapshot()
+}
+
+/// Directory `tauri_plugin_log` writes rotated log files into, captured once
+/// during `initialize` (it already resolves `app_data_dir` the same way) so
+/// `get_last_log_file` doesn't need its own `AppHandle` path lookup. `None`
+/// when the platform's log directory can't be resolved.
+pub struct LogDirState(pub Option<PathBuf>);
 
-    let parsed_url = tauri::Url::parse(&backend_url).map_err(|err| {
-        RuntimeCoordinatorError::UrlParse(format!(
-            "Failed to parse embedded backend URL ({backend_url}): {err}"
-        ))
-    })?;
+/// Locates the newest file (by mtime) in the app's log directory, for a
+/// frontend crash/bug report to attach without the user hunting through
+/// filesystem paths — mirrors the same idea as `SetupErrorState`: surface
+/// diagnostics through a command instead of expecting the user to go
+/// spelunking. With `tail_lines` set, returns that many trailing lines of
+/// the file's contents instead of its path, so the UI can preview it inline.
+#[tauri::command]
+pub fn get_last_log_file(
+    state: tauri::State<'_, LogDirState>,
+    tail_lines: Option<usize>,
+) -> Option<String> {
+    let dir = state.0.as_ref()?;
+    let newest_path = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((entry.path(), modified))
+        })
+        .max_by_key(|(_, modified)| *modified)
+        .map(|(path, _)| path)?;
 
+    match tail_lines {
+        Some(n) => {
+            let contents = std::fs::read_to_string(&newest_path).ok()?;
+            let lines: Vec<&str> = contents.lines().collect();
+            let start = lines.len().saturating_sub(n);
+            Some(lines[start..].join("\n"))
+        }
+        None => Some(newest_path.display().to_string()),
+    }
+}
+
+/// Locates the main window (a hard failure if it's missing — there's
+/// nowhere to show anything without it), then starts the embedded backend
+/// and navigates to it. A backend that fails to start, or a URL that fails
+/// to parse or navigate to, is recorded on `SetupErrorState` instead of
+/// aborting: the window stays on its default content and the frontend can
+/// call `get_setup_errors` to show what went wrong.
+pub fn initialize(app: &tauri::App) -> Result<(), RuntimeCoordinatorError> {
     let window = app
         .get_webview_window("main")
         .ok_or(RuntimeCoordinatorError::MainWindowMissing)?;
-    window.navigate(parsed_url).map_err(|err| {
-        RuntimeCoordinatorError::Navigate(format!(
-            "Failed to navigate Tauri window to embedded backend: {err}"
-        ))
-    })?;
-
-    log::info!("embedded backend listening on {backend_url}");
-    app.manage(backend::EmbeddedBackendState::new(backend));
+
+    let setup_errors = SetupErrorState::default();
+    let app_data_dir = app.path().app_data_dir().ok();
+    app.manage(LogDirState(app.path().app_log_dir().ok()));
+
+    match tauri::async_runtime::block_on(backend::start_embedded_backend_with_app_data_dir(
+        app_data_dir,
+    )) {
+        Ok(backend) => {
+            let backend_url = backend.base_url().to_string();
+            match tauri::Url::parse(&backend_url) {
+                Ok(parsed_url) => match window.navigate(parsed_url) {
+                    Ok(()) => log::info!("embedded backend listening on {backend_url}"),
+                    Err(err) => setup_errors.record(
+                        SetupErrorSeverity::Error,
+                        RuntimeCoordinatorError::Navigate(format!(
+                            "Failed to navigate Tauri window to embedded backend: {err}"
+                        ))
+                        .to_string(),
+                    ),
+                },
+                Err(err) => setup_errors.record(
+                    SetupErrorSeverity::Error,
+                    RuntimeCoordinatorError::UrlParse(format!(
+                        "Failed to parse embedded backend URL ({backend_url}): {err}"
+                    ))
+                    .to_string(),
+                ),
+            }
+            app.manage(backend::EmbeddedBackendState::new(backend));
+            idle_sweep::spawn(app.handle().clone());
+        }
+        Err(err) => setup_errors.record(
+            SetupErrorSeverity::Error,
+            RuntimeCoordinatorError::BackendStart(err.to_string()).to_string(),
+        ),
+    }
+
+    app.manage(setup_errors);
     Ok(())
 }
 
@@ -29,7 +29,10 @@ async fn runtime_status_reports_live_backend_endpoint() {
     let state = EmbeddedBackendState::new(backend);
     let status = runtime_status_from_state(&state).expect("runtime status should succeed");
 
-    assert_eq!(status.backend_transport, "http");
+    // A Unix domain socket is the preferred transport wherever it's
+    // available (see backend::BackendTransport) — this test runs on Linux/
+    // macOS CI, so it should always come back "uds" here.
+    assert_eq!(status.backend_transport, "uds");
     assert!(status.ipc_enabled);
     assert_eq!(status.backend_base_url, expected_base_url);
     assert_eq!(status.backend_addr, expected_addr);